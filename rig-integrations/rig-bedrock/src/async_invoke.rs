@@ -0,0 +1,314 @@
+//! Asynchronous invocations, for long-running models such as video generation.
+//!
+//! An asynchronous invocation writes the model output to S3 instead of returning it, and is
+//! tracked by its invocation ARN:
+//!
+//! ```rust,ignore
+//! let jobs = client.async_invoke();
+//! let arn = jobs.start(AMAZON_NOVA_REEL, model_input, "s3://my-bucket/videos").await?;
+//! let job = jobs.wait(&arn, Some(Duration::from_secs(1800))).await?;
+//! println!("Output written to {:?}", job.output_uri);
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use aws_sdk_bedrockruntime::operation::get_async_invoke::GetAsyncInvokeOutput;
+use aws_sdk_bedrockruntime::types::{
+    self as aws_bedrock, AsyncInvokeOutputDataConfig, AsyncInvokeS3OutputDataConfig,
+    AsyncInvokeSummary,
+};
+use aws_smithy_types::DateTime;
+use tokio::time::Instant;
+
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+use crate::types::json::AwsDocument;
+
+/// The status of an asynchronous invocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AsyncInvokeStatus {
+    InProgress,
+    Completed,
+    Failed,
+    /// A status this crate doesn't know about yet.
+    Unknown(String),
+}
+
+impl From<&aws_bedrock::AsyncInvokeStatus> for AsyncInvokeStatus {
+    fn from(status: &aws_bedrock::AsyncInvokeStatus) -> Self {
+        match status {
+            aws_bedrock::AsyncInvokeStatus::InProgress => Self::InProgress,
+            aws_bedrock::AsyncInvokeStatus::Completed => Self::Completed,
+            aws_bedrock::AsyncInvokeStatus::Failed => Self::Failed,
+            other => Self::Unknown(other.as_str().to_string()),
+        }
+    }
+}
+
+/// An asynchronous invocation and its current status.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsyncInvokeJob {
+    pub invocation_arn: String,
+    pub model_arn: String,
+    pub status: AsyncInvokeStatus,
+    pub failure_message: Option<String>,
+    /// The S3 location the output is written to.
+    pub output_uri: Option<String>,
+    pub submit_time: DateTime,
+    pub end_time: Option<DateTime>,
+}
+
+fn output_uri(config: Option<&AsyncInvokeOutputDataConfig>) -> Option<String> {
+    match config {
+        Some(AsyncInvokeOutputDataConfig::S3OutputDataConfig(s3)) => Some(s3.s3_uri().to_string()),
+        _ => None,
+    }
+}
+
+impl From<GetAsyncInvokeOutput> for AsyncInvokeJob {
+    fn from(output: GetAsyncInvokeOutput) -> Self {
+        Self {
+            status: output.status().into(),
+            output_uri: output_uri(output.output_data_config()),
+            invocation_arn: output.invocation_arn,
+            model_arn: output.model_arn,
+            failure_message: output.failure_message,
+            submit_time: output.submit_time,
+            end_time: output.end_time,
+        }
+    }
+}
+
+impl From<AsyncInvokeSummary> for AsyncInvokeJob {
+    fn from(summary: AsyncInvokeSummary) -> Self {
+        Self {
+            status: summary
+                .status()
+                .map(AsyncInvokeStatus::from)
+                .unwrap_or(AsyncInvokeStatus::InProgress),
+            output_uri: output_uri(summary.output_data_config()),
+            invocation_arn: summary.invocation_arn,
+            model_arn: summary.model_arn,
+            failure_message: summary.failure_message,
+            submit_time: summary.submit_time,
+            end_time: summary.end_time,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AsyncInvokeError {
+    /// A Bedrock API call failed, or its input couldn't be built.
+    Request(Box<dyn std::error::Error + Send + Sync>),
+    /// The invocation failed.
+    Failed {
+        invocation_arn: String,
+        message: String,
+    },
+    /// The invocation didn't finish within the given timeout. It keeps running.
+    Timeout { invocation_arn: String },
+}
+
+impl fmt::Display for AsyncInvokeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(error) => write!(f, "Async invoke request failed: {error}"),
+            Self::Failed {
+                invocation_arn,
+                message,
+            } => write!(f, "Async invocation {invocation_arn} failed: {message}"),
+            Self::Timeout { invocation_arn } => {
+                write!(f, "Timed out waiting for async invocation {invocation_arn}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsyncInvokeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Starts and tracks asynchronous invocations. Created with [`Client::async_invoke`].
+#[derive(Clone, Debug)]
+pub struct AsyncInvoke {
+    client: Client,
+    poll_interval: Duration,
+}
+
+impl AsyncInvoke {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            poll_interval: Duration::from_secs(10),
+        }
+    }
+
+    /// Delay between two status checks in [`AsyncInvoke::wait`]. Defaults to 10 seconds.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Start an invocation of `model` with its native `model_input`, writing the output below
+    /// the `output_s3_uri` prefix. Returns the invocation ARN.
+    pub async fn start(
+        &self,
+        model: &str,
+        model_input: serde_json::Value,
+        output_s3_uri: &str,
+    ) -> Result<String, AsyncInvokeError> {
+        let output_config = AsyncInvokeS3OutputDataConfig::builder()
+            .s3_uri(output_s3_uri)
+            .build()
+            .map_err(|e| AsyncInvokeError::Request(e.into()))?;
+
+        let output = self
+            .client
+            .get_inner()
+            .await
+            .start_async_invoke()
+            .client_request_token(uuid::Uuid::new_v4().to_string())
+            .model_id(model)
+            .model_input(AwsDocument::from(model_input).0)
+            .output_data_config(AsyncInvokeOutputDataConfig::S3OutputDataConfig(
+                output_config,
+            ))
+            .send()
+            .await
+            .map_err(|e| AsyncInvokeError::Request(BedrockError::from(e).into()))?;
+
+        Ok(output.invocation_arn)
+    }
+
+    /// The current state of an invocation.
+    pub async fn get(&self, invocation_arn: &str) -> Result<AsyncInvokeJob, AsyncInvokeError> {
+        self.client
+            .get_inner()
+            .await
+            .get_async_invoke()
+            .invocation_arn(invocation_arn)
+            .send()
+            .await
+            .map(AsyncInvokeJob::from)
+            .map_err(|e| AsyncInvokeError::Request(BedrockError::from(e).into()))
+    }
+
+    /// All invocations of the account and region, most recent first, optionally only those
+    /// with the given status.
+    pub async fn list(
+        &self,
+        status: Option<AsyncInvokeStatus>,
+    ) -> Result<Vec<AsyncInvokeJob>, AsyncInvokeError> {
+        let status = match status {
+            Some(AsyncInvokeStatus::Unknown(status)) => {
+                Some(aws_bedrock::AsyncInvokeStatus::from(status.as_str()))
+            }
+            Some(AsyncInvokeStatus::InProgress) => Some(aws_bedrock::AsyncInvokeStatus::InProgress),
+            Some(AsyncInvokeStatus::Completed) => Some(aws_bedrock::AsyncInvokeStatus::Completed),
+            Some(AsyncInvokeStatus::Failed) => Some(aws_bedrock::AsyncInvokeStatus::Failed),
+            None => None,
+        };
+
+        let client = self.client.get_inner().await;
+        let mut jobs = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = client
+                .list_async_invokes()
+                .set_status_equals(status.clone())
+                .sort_by(aws_bedrock::SortAsyncInvocationBy::SubmissionTime)
+                .sort_order(aws_bedrock::SortOrder::Descending)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| AsyncInvokeError::Request(BedrockError::from(e).into()))?;
+
+            jobs.extend(
+                output
+                    .async_invoke_summaries
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(AsyncInvokeJob::from),
+            );
+
+            match output.next_token {
+                Some(token) => next_token = Some(token),
+                None => return Ok(jobs),
+            }
+        }
+    }
+
+    /// Wait for an invocation to complete, failing if it failed or `timeout` elapsed first.
+    pub async fn wait(
+        &self,
+        invocation_arn: &str,
+        timeout: Option<Duration>,
+    ) -> Result<AsyncInvokeJob, AsyncInvokeError> {
+        let started = Instant::now();
+        loop {
+            let job = self.get(invocation_arn).await?;
+            match job.status {
+                AsyncInvokeStatus::Completed => return Ok(job),
+                AsyncInvokeStatus::Failed => {
+                    return Err(AsyncInvokeError::Failed {
+                        invocation_arn: job.invocation_arn,
+                        message: job.failure_message.unwrap_or_default(),
+                    });
+                }
+                AsyncInvokeStatus::InProgress | AsyncInvokeStatus::Unknown(_) => {}
+            }
+
+            if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                return Err(AsyncInvokeError::Timeout {
+                    invocation_arn: invocation_arn.to_string(),
+                });
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_from_get_output() {
+        let output = GetAsyncInvokeOutput::builder()
+            .invocation_arn("arn:aws:bedrock:us-east-1:123456789012:async-invoke/abc")
+            .model_arn("arn:aws:bedrock:us-east-1::foundation-model/amazon.nova-reel-v1:1")
+            .status(aws_bedrock::AsyncInvokeStatus::Failed)
+            .failure_message("Content filtered")
+            .submit_time(DateTime::from_secs(1_700_000_000))
+            .output_data_config(AsyncInvokeOutputDataConfig::S3OutputDataConfig(
+                AsyncInvokeS3OutputDataConfig::builder()
+                    .s3_uri("s3://my-bucket/videos/abc")
+                    .build()
+                    .unwrap(),
+            ))
+            .build()
+            .unwrap();
+
+        let job = AsyncInvokeJob::from(output);
+
+        assert_eq!(job.status, AsyncInvokeStatus::Failed);
+        assert_eq!(job.failure_message.as_deref(), Some("Content filtered"));
+        assert_eq!(job.output_uri.as_deref(), Some("s3://my-bucket/videos/abc"));
+        assert_eq!(job.end_time, None);
+    }
+
+    #[test]
+    fn test_unknown_status() {
+        assert_eq!(
+            AsyncInvokeStatus::from(&aws_bedrock::AsyncInvokeStatus::from("Paused")),
+            AsyncInvokeStatus::Unknown("Paused".into())
+        );
+    }
+}
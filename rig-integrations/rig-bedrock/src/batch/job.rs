@@ -0,0 +1,115 @@
+use aws_sdk_bedrock::types::{
+    ModelInvocationJobInputDataConfig, ModelInvocationJobOutputDataConfig,
+    ModelInvocationJobS3InputDataConfig, ModelInvocationJobS3OutputDataConfig,
+    ModelInvocationJobStatus, S3InputFormat,
+};
+use aws_sdk_s3::primitives::ByteStream;
+use aws_smithy_types::error::display::DisplayErrorContext;
+use tokio::time::Instant;
+
+use super::{BatchConfig, BatchError, S3Uri, output_location};
+use crate::client::Client;
+
+/// Upload `jsonl` to S3, run a batch job for `model` on it and return the output file contents.
+pub(crate) async fn run_job(
+    client: &Client,
+    model: &str,
+    jsonl: String,
+    config: &BatchConfig,
+) -> Result<String, BatchError> {
+    let sdk_config = client.sdk_config().await;
+    let s3 = aws_sdk_s3::Client::new(sdk_config);
+    let bedrock = aws_sdk_bedrock::Client::new(sdk_config);
+
+    let job_name = format!("rig-{}", uuid::Uuid::new_v4());
+    let input = S3Uri::parse(&config.input_uri)?.join(&format!("{job_name}.jsonl"));
+    let output = S3Uri::parse(&config.output_uri)?;
+
+    s3.put_object()
+        .bucket(&input.bucket)
+        .key(&input.key)
+        .body(ByteStream::from(jsonl.into_bytes()))
+        .send()
+        .await
+        .map_err(|e| BatchError::S3(DisplayErrorContext(&e).to_string()))?;
+
+    let input_data_config = ModelInvocationJobS3InputDataConfig::builder()
+        .s3_input_format(S3InputFormat::Jsonl)
+        .s3_uri(input.to_string())
+        .build()
+        .map_err(|e| BatchError::Bedrock(e.to_string()))?;
+    let output_data_config = ModelInvocationJobS3OutputDataConfig::builder()
+        .s3_uri(output.to_string())
+        .build()
+        .map_err(|e| BatchError::Bedrock(e.to_string()))?;
+
+    let job = bedrock
+        .create_model_invocation_job()
+        .job_name(&job_name)
+        .role_arn(&config.role_arn)
+        .model_id(model)
+        .input_data_config(ModelInvocationJobInputDataConfig::S3InputDataConfig(
+            input_data_config,
+        ))
+        .output_data_config(ModelInvocationJobOutputDataConfig::S3OutputDataConfig(
+            output_data_config,
+        ))
+        .send()
+        .await
+        .map_err(|e| BatchError::Bedrock(DisplayErrorContext(&e).to_string()))?;
+    let job_arn = job.job_arn().to_string();
+    tracing::info!(target: "rig::bedrock", "Submitted batch job {job_arn}");
+
+    let started = Instant::now();
+    loop {
+        let job = bedrock
+            .get_model_invocation_job()
+            .job_identifier(&job_arn)
+            .send()
+            .await
+            .map_err(|e| BatchError::Bedrock(DisplayErrorContext(&e).to_string()))?;
+
+        match job.status() {
+            // Failed records of a partially completed job are reported in the output file
+            Some(ModelInvocationJobStatus::Completed)
+            | Some(ModelInvocationJobStatus::PartiallyCompleted) => break,
+            Some(
+                status @ (ModelInvocationJobStatus::Failed
+                | ModelInvocationJobStatus::Stopped
+                | ModelInvocationJobStatus::Expired),
+            ) => {
+                return Err(BatchError::JobFailed {
+                    status: status.as_str().to_string(),
+                    message: job.message().unwrap_or_default().to_string(),
+                });
+            }
+            _ => {}
+        }
+
+        if config
+            .timeout
+            .is_some_and(|timeout| started.elapsed() >= timeout)
+        {
+            return Err(BatchError::Timeout { job_arn });
+        }
+
+        tokio::time::sleep(config.poll_interval).await;
+    }
+
+    let output = output_location(&output, &job_arn, &input.key);
+    let object = s3
+        .get_object()
+        .bucket(&output.bucket)
+        .key(&output.key)
+        .send()
+        .await
+        .map_err(|e| BatchError::S3(DisplayErrorContext(&e).to_string()))?;
+    let bytes = object
+        .body
+        .collect()
+        .await
+        .map_err(|e| BatchError::S3(e.to_string()))?
+        .into_bytes();
+
+    String::from_utf8(bytes.to_vec()).map_err(|e| BatchError::S3(e.to_string()))
+}
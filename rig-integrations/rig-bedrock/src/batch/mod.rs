@@ -0,0 +1,343 @@
+//! Bedrock batch inference: JSONL records staged in S3 and processed by a model invocation job.
+//!
+//! Batch jobs are much cheaper than on-demand calls and not subject to on-demand quotas, which
+//! makes them a better fit for very large workloads such as embedding millions of documents.
+//! The record codecs in this module are always available; running jobs requires the `batch`
+//! feature.
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/batch-inference.html>
+
+#[cfg(feature = "batch")]
+mod job;
+
+#[cfg(feature = "batch")]
+pub(crate) use job::run_job;
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A line of a batch job input file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchInputRecord<T> {
+    pub record_id: String,
+    pub model_input: T,
+}
+
+/// A line of a batch job output file: the model output, or the error for this record.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOutputRecord<T> {
+    pub record_id: String,
+    pub model_output: Option<T>,
+    pub error: Option<BatchRecordError>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRecordError {
+    pub error_code: Option<i64>,
+    pub error_message: String,
+}
+
+/// The record id of the `index`-th record. Bedrock requires 11 character record ids.
+pub fn record_id(index: usize) -> String {
+    format!("{index:011}")
+}
+
+/// Serialize records as JSON Lines.
+pub fn to_jsonl<T: Serialize>(records: &[T]) -> Result<String, serde_json::Error> {
+    let mut jsonl = String::new();
+    for record in records {
+        jsonl.push_str(&serde_json::to_string(record)?);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+/// Deserialize JSON Lines, skipping blank lines.
+pub fn from_jsonl<T: DeserializeOwned>(jsonl: &str) -> Result<Vec<T>, serde_json::Error> {
+    jsonl
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+/// An `s3://bucket/prefix` location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct S3Uri {
+    pub bucket: String,
+    pub key: String,
+}
+
+impl S3Uri {
+    pub fn parse(uri: &str) -> Result<Self, BatchError> {
+        let (bucket, key) = uri
+            .strip_prefix("s3://")
+            .map(|rest| rest.split_once('/').unwrap_or((rest, "")))
+            .filter(|(bucket, _)| !bucket.is_empty())
+            .ok_or_else(|| BatchError::InvalidS3Uri(uri.to_string()))?;
+
+        Ok(Self {
+            bucket: bucket.to_string(),
+            key: key.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// The location of `name` below this prefix.
+    pub fn join(&self, name: &str) -> Self {
+        let key = if self.key.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{name}", self.key)
+        };
+        Self {
+            bucket: self.bucket.clone(),
+            key,
+        }
+    }
+}
+
+impl fmt::Display for S3Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "s3://{}/{}", self.bucket, self.key)
+    }
+}
+
+/// Where Bedrock writes the output of `input_file` for the job `job_arn`:
+/// `<output prefix>/<job id>/<input file name>.out`.
+pub fn output_location(output: &S3Uri, job_arn: &str, input_file: &str) -> S3Uri {
+    let job_id = job_arn.rsplit('/').next().unwrap_or(job_arn);
+    let file_name = input_file.rsplit('/').next().unwrap_or(input_file);
+    output.join(&format!("{job_id}/{file_name}.out"))
+}
+
+/// Where to stage a batch job and how to wait for it.
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    /// IAM role Bedrock assumes to read the input and write the output.
+    pub role_arn: String,
+    /// S3 prefix the input file is uploaded to.
+    pub input_uri: String,
+    /// S3 prefix Bedrock writes the output to.
+    pub output_uri: String,
+    /// Delay between two job status checks.
+    pub poll_interval: Duration,
+    /// Give up waiting after this long. Batch jobs can take up to 24 hours to be scheduled.
+    pub timeout: Option<Duration>,
+}
+
+impl BatchConfig {
+    pub fn new(
+        role_arn: impl Into<String>,
+        input_uri: impl Into<String>,
+        output_uri: impl Into<String>,
+    ) -> Self {
+        Self {
+            role_arn: role_arn.into(),
+            input_uri: input_uri.into(),
+            output_uri: output_uri.into(),
+            poll_interval: Duration::from_secs(60),
+            timeout: None,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    InvalidS3Uri(String),
+    Json(serde_json::Error),
+    /// Reading or writing S3 objects failed.
+    S3(String),
+    /// A Bedrock control plane call failed.
+    Bedrock(String),
+    /// The job ended without completing.
+    JobFailed {
+        status: String,
+        message: String,
+    },
+    /// The job didn't finish within [`BatchConfig::timeout`].
+    Timeout {
+        job_arn: String,
+    },
+    /// Some records of a completed job failed.
+    RecordFailed {
+        record_id: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidS3Uri(uri) => write!(f, "Invalid S3 URI: {uri}"),
+            Self::Json(e) => write!(f, "Invalid batch record: {e}"),
+            Self::S3(message) => write!(f, "S3 error: {message}"),
+            Self::Bedrock(message) => write!(f, "Bedrock error: {message}"),
+            Self::JobFailed { status, message } => {
+                write!(f, "Batch job ended with status {status}: {message}")
+            }
+            Self::Timeout { job_arn } => write!(f, "Timed out waiting for batch job {job_arn}"),
+            Self::RecordFailed { record_id, message } => {
+                write!(f, "Batch record {record_id} failed: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for BatchError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// Order the model outputs of `records` produced by [`record_id`], failing on the first record error.
+pub fn outputs_in_order<T>(
+    records: Vec<BatchOutputRecord<T>>,
+    expected: usize,
+) -> Result<Vec<T>, BatchError> {
+    let mut outputs: Vec<Option<T>> = std::iter::repeat_with(|| None).take(expected).collect();
+
+    for record in records {
+        let index = record
+            .record_id
+            .parse::<usize>()
+            .ok()
+            .filter(|index| *index < expected)
+            .ok_or_else(|| BatchError::RecordFailed {
+                record_id: record.record_id.clone(),
+                message: "Unknown record id".into(),
+            })?;
+
+        match (record.model_output, record.error) {
+            (Some(output), None) => outputs[index] = Some(output),
+            (_, error) => {
+                return Err(BatchError::RecordFailed {
+                    record_id: record.record_id,
+                    message: error
+                        .map(|error| error.error_message)
+                        .unwrap_or_else(|| "Missing model output".into()),
+                });
+            }
+        }
+    }
+
+    outputs
+        .into_iter()
+        .enumerate()
+        .map(|(index, output)| {
+            output.ok_or_else(|| BatchError::RecordFailed {
+                record_id: record_id(index),
+                message: "Missing from the job output".into(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let records = vec![
+            BatchInputRecord {
+                record_id: record_id(0),
+                model_input: json!({ "inputText": "hello" }),
+            },
+            BatchInputRecord {
+                record_id: record_id(1),
+                model_input: json!({ "inputText": "world" }),
+            },
+        ];
+
+        let jsonl = to_jsonl(&records).unwrap();
+        assert_eq!(
+            jsonl.lines().next().unwrap(),
+            r#"{"recordId":"00000000000","modelInput":{"inputText":"hello"}}"#
+        );
+
+        let parsed: Vec<BatchInputRecord<serde_json::Value>> =
+            from_jsonl(&format!("{jsonl}\n")).unwrap();
+        assert_eq!(parsed, records);
+    }
+
+    #[test]
+    fn test_s3_uri() {
+        let uri = S3Uri::parse("s3://my-bucket/batch/input/").unwrap();
+        assert_eq!(uri.bucket, "my-bucket");
+        assert_eq!(uri.key, "batch/input");
+        assert_eq!(
+            uri.join("job.jsonl").to_string(),
+            "s3://my-bucket/batch/input/job.jsonl"
+        );
+        assert_eq!(
+            S3Uri::parse("s3://my-bucket")
+                .unwrap()
+                .join("a")
+                .to_string(),
+            "s3://my-bucket/a"
+        );
+        assert!(S3Uri::parse("https://my-bucket/key").is_err());
+        assert!(S3Uri::parse("s3:///key").is_err());
+    }
+
+    #[test]
+    fn test_output_location() {
+        let output = S3Uri::parse("s3://my-bucket/output").unwrap();
+        assert_eq!(
+            output_location(
+                &output,
+                "arn:aws:bedrock:us-east-1:123456789012:model-invocation-job/abc123",
+                "input/rig-job.jsonl"
+            )
+            .to_string(),
+            "s3://my-bucket/output/abc123/rig-job.jsonl.out"
+        );
+    }
+
+    #[test]
+    fn test_outputs_in_order() {
+        let records: Vec<BatchOutputRecord<u32>> = from_jsonl(
+            r#"{"recordId":"00000000001","modelInput":{},"modelOutput":2}
+{"recordId":"00000000000","modelInput":{},"modelOutput":1}"#,
+        )
+        .unwrap();
+        assert_eq!(outputs_in_order(records, 2).unwrap(), vec![1, 2]);
+
+        let failed: Vec<BatchOutputRecord<u32>> = from_jsonl(
+            r#"{"recordId":"00000000000","modelInput":{},"error":{"errorCode":400,"errorMessage":"Malformed input"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            outputs_in_order(failed, 1),
+            Err(BatchError::RecordFailed { message, .. }) if message == "Malformed input"
+        ));
+
+        assert!(outputs_in_order(Vec::<BatchOutputRecord<u32>>::new(), 1).is_err());
+    }
+}
@@ -0,0 +1,336 @@
+use std::time::SystemTime;
+
+use async_stream::stream;
+use aws_sdk_bedrockagentruntime::operation::invoke_agent::InvokeAgentOutput;
+use aws_sdk_bedrockagentruntime::types::{
+    Memory, MemoryType, PayloadPart, ResponseStream, StreamingConfigurations,
+};
+use aws_smithy_types::error::display::DisplayErrorContext;
+use rig::OneOrMany;
+use rig::completion::{self, CompletionError, CompletionRequest, Usage};
+use rig::message::{AssistantContent, Message, UserContent};
+use rig::streaming::{RawStreamingChoice, StreamingCompletionResponse};
+
+use super::{AgentResponse, DRAFT_ALIAS_ID, SessionSummary};
+use crate::client::Client;
+use crate::knowledge_base::Citation;
+use crate::knowledge_base::convert::citation;
+use crate::types::errors::BedrockError;
+
+/// A Bedrock Agent alias, as a completion model. Created with
+/// [`Client::agent_completion_model`], or by [`completion::CompletionModel::make`] from an
+/// `agent_id/alias_id` model name.
+#[derive(Clone, Debug)]
+pub struct AgentCompletionModel {
+    client: Client,
+    pub agent_id: String,
+    pub agent_alias_id: String,
+    session_id: String,
+    memory_id: Option<String>,
+}
+
+impl AgentCompletionModel {
+    /// Invoke the alias `agent_alias_id` of the agent `agent_id`, in a new session.
+    pub fn new(
+        client: Client,
+        agent_id: impl Into<String>,
+        agent_alias_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            client,
+            agent_id: agent_id.into(),
+            agent_alias_id: agent_alias_id.into(),
+            session_id: uuid::Uuid::new_v4().to_string(),
+            memory_id: None,
+        }
+    }
+
+    /// Continue the session `session_id`, e.g. one per end user. Sessions expire after the
+    /// agent's idle session timeout.
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = session_id.into();
+        self
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Summarize the sessions into the memory `memory_id` when they end, and recall the
+    /// summaries of the previous sessions. Requires memory to be enabled for the agent.
+    pub fn with_memory_id(mut self, memory_id: impl Into<String>) -> Self {
+        self.memory_id = Some(memory_id.into());
+        self
+    }
+
+    pub fn memory_id(&self) -> Option<&str> {
+        self.memory_id.as_deref()
+    }
+
+    /// End the session, so it is summarized into the memory instead of waiting for the idle
+    /// session timeout. Later calls start the session anew.
+    pub async fn end_session(&self) -> Result<(), BedrockError> {
+        let sdk_config = self.client.sdk_config().await;
+        let mut output = aws_sdk_bedrockagentruntime::Client::new(sdk_config)
+            .invoke_agent()
+            .agent_id(&self.agent_id)
+            .agent_alias_id(&self.agent_alias_id)
+            .session_id(&self.session_id)
+            .set_memory_id(self.memory_id.clone())
+            .end_session(true)
+            .send()
+            .await?;
+
+        // The session only ends once the response is consumed
+        while let Ok(Some(_)) = output.completion.recv().await {}
+        Ok(())
+    }
+
+    /// The summaries of the sessions stored in the memory `memory_id`.
+    pub async fn memory(&self, memory_id: &str) -> Result<Vec<SessionSummary>, BedrockError> {
+        let sdk_config = self.client.sdk_config().await;
+        let client = aws_sdk_bedrockagentruntime::Client::new(sdk_config);
+
+        let mut summaries = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = client
+                .get_agent_memory()
+                .agent_id(&self.agent_id)
+                .agent_alias_id(&self.agent_alias_id)
+                .memory_id(memory_id)
+                .memory_type(MemoryType::SessionSummary)
+                .set_next_token(next_token)
+                .send()
+                .await?;
+
+            summaries.extend(output.memory_contents().iter().filter_map(|memory| {
+                let Memory::SessionSummary(summary) = memory else {
+                    return None;
+                };
+                let time = |time: Option<&aws_smithy_types::DateTime>| {
+                    time.and_then(|time| SystemTime::try_from(*time).ok())
+                };
+                Some(SessionSummary {
+                    memory_id: summary.memory_id().map(str::to_string),
+                    session_id: summary.session_id().map(str::to_string),
+                    session_start_time: time(summary.session_start_time()),
+                    session_expiry_time: time(summary.session_expiry_time()),
+                    summary_text: summary.summary_text().map(str::to_string),
+                })
+            }));
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(summaries);
+            }
+        }
+    }
+
+    /// Delete the summaries stored in the memory `memory_id`, e.g. when a user asks to be
+    /// forgotten.
+    pub async fn delete_memory(&self, memory_id: &str) -> Result<(), BedrockError> {
+        let sdk_config = self.client.sdk_config().await;
+        aws_sdk_bedrockagentruntime::Client::new(sdk_config)
+            .delete_agent_memory()
+            .agent_id(&self.agent_id)
+            .agent_alias_id(&self.agent_alias_id)
+            .memory_id(memory_id)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn invoke(
+        &self,
+        request: &CompletionRequest,
+        stream_final_response: bool,
+    ) -> Result<InvokeAgentOutput, CompletionError> {
+        let input_text = input_text(request)?;
+        let sdk_config = self.client.sdk_config().await;
+
+        let output = aws_sdk_bedrockagentruntime::Client::new(sdk_config)
+            .invoke_agent()
+            .agent_id(&self.agent_id)
+            .agent_alias_id(&self.agent_alias_id)
+            .session_id(&self.session_id)
+            .set_memory_id(self.memory_id.clone())
+            .input_text(input_text)
+            .streaming_configurations(
+                StreamingConfigurations::builder()
+                    .stream_final_response(stream_final_response)
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        Ok(output)
+    }
+}
+
+impl completion::CompletionModel for AgentCompletionModel {
+    type Response = AgentResponse;
+    type StreamingResponse = AgentResponse;
+
+    type Client = Client;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        let (agent_id, agent_alias_id) = parse_agent_model(&model.into());
+        Self::new(client.clone(), agent_id, agent_alias_id)
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<completion::CompletionResponse<AgentResponse>, CompletionError> {
+        let mut output = self.invoke(&request, false).await?;
+
+        let mut response = AgentResponse {
+            session_id: output.session_id().to_string(),
+            memory_id: output.memory_id().map(str::to_string),
+            ..Default::default()
+        };
+        while let Some(event) = output.completion.recv().await.map_err(stream_error)? {
+            if let ResponseStream::Chunk(part) = event {
+                let (text, citations) = chunk(&part);
+                response.text.push_str(&text);
+                response.citations.extend(citations);
+            }
+        }
+
+        Ok(completion::CompletionResponse {
+            choice: OneOrMany::one(AssistantContent::text(&response.text)),
+            usage: Usage::new(),
+            raw_response: response,
+        })
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<AgentResponse>, CompletionError> {
+        let mut output = self.invoke(&request, true).await?;
+
+        let stream = Box::pin(stream! {
+            let mut response = AgentResponse {
+                session_id: output.session_id().to_string(),
+                memory_id: output.memory_id().map(str::to_string),
+                ..Default::default()
+            };
+            loop {
+                match output.completion.recv().await {
+                    Ok(Some(ResponseStream::Chunk(part))) => {
+                        let (text, citations) = chunk(&part);
+                        response.text.push_str(&text);
+                        response.citations.extend(citations);
+                        if !text.is_empty() {
+                            yield Ok(RawStreamingChoice::Message(text));
+                        }
+                    }
+                    Ok(Some(_)) => {}
+                    Ok(None) => break,
+                    Err(e) => {
+                        yield Err(stream_error(e));
+                        return;
+                    }
+                }
+            }
+            yield Ok(RawStreamingChoice::FinalResponse(response));
+        });
+
+        Ok(StreamingCompletionResponse::stream(stream))
+    }
+}
+
+impl Client {
+    /// The alias `agent_alias_id` of the Bedrock Agent `agent_id`, as a completion model.
+    pub fn agent_completion_model(
+        &self,
+        agent_id: impl Into<String>,
+        agent_alias_id: impl Into<String>,
+    ) -> AgentCompletionModel {
+        AgentCompletionModel::new(self.clone(), agent_id, agent_alias_id)
+    }
+}
+
+/// Split an `agent_id/alias_id` model name, defaulting to the draft alias.
+fn parse_agent_model(model: &str) -> (String, String) {
+    match model.split_once('/') {
+        Some((agent_id, alias_id)) => (agent_id.to_string(), alias_id.to_string()),
+        None => (model.to_string(), DRAFT_ALIAS_ID.to_string()),
+    }
+}
+
+/// The text of the last message of `request`, which must be a user message.
+fn input_text(request: &CompletionRequest) -> Result<String, CompletionError> {
+    let Message::User { content } = request.chat_history.last_ref() else {
+        return Err(CompletionError::RequestError(
+            "Bedrock agents must be prompted with a user message".into(),
+        ));
+    };
+
+    let text = content
+        .iter()
+        .filter_map(|content| match content {
+            UserContent::Text(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if text.is_empty() {
+        return Err(CompletionError::RequestError(
+            "Bedrock agents only accept text input".into(),
+        ));
+    }
+    Ok(text)
+}
+
+/// The text of a completion chunk and the citations attributed to it.
+fn chunk(part: &PayloadPart) -> (String, Vec<Citation>) {
+    let text = part
+        .bytes()
+        .map(|bytes| String::from_utf8_lossy(bytes.as_ref()).into_owned())
+        .unwrap_or_default();
+    let citations = part
+        .attribution()
+        .map(|attribution| attribution.citations().iter().map(citation).collect())
+        .unwrap_or_default();
+    (text, citations)
+}
+
+fn stream_error(error: impl std::error::Error) -> CompletionError {
+    CompletionError::ProviderError(DisplayErrorContext(&error).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+
+    fn request(message: Message) -> CompletionRequest {
+        completion_request(message).build()
+    }
+
+    #[test]
+    fn test_parse_agent_model() {
+        assert_eq!(
+            parse_agent_model("AGENT12345/ALIAS12345"),
+            ("AGENT12345".to_string(), "ALIAS12345".to_string())
+        );
+        assert_eq!(
+            parse_agent_model("AGENT12345"),
+            ("AGENT12345".to_string(), DRAFT_ALIAS_ID.to_string())
+        );
+    }
+
+    #[test]
+    fn test_input_text() {
+        assert_eq!(
+            input_text(&request(Message::user("Hello"))).unwrap(),
+            "Hello"
+        );
+        assert!(input_text(&request(Message::assistant("Hello"))).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Managed Bedrock Agents, invoked through rig's completion interface.
+//!
+//! A Bedrock Agent is configured in the Bedrock console, with its own instructions, action
+//! groups and knowledge bases, and keeps the conversation in a session on the service side. Only
+//! the text of the last user message of a request is sent; its preamble, tools and history are
+//! ignored. The response types in this module are always available; invoking agents requires
+//! the `agents` feature.
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .agent_completion_model("AGENT12345", "ALIAS12345")
+//!     .with_session_id("user-42");
+//!
+//! let response = model.completion_request("Book a meeting room for 3pm").send().await?;
+//! for citation in &response.raw_response.citations {
+//!     println!("{:?}", citation.sources);
+//! }
+//! ```
+//!
+//! Agents with memory enabled summarize each session when it ends, and recall the summaries of
+//! the previous sessions sharing the same memory id, e.g. one per end user:
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .agent_completion_model("AGENT12345", "ALIAS12345")
+//!     .with_memory_id("user-42");
+//!
+//! model.completion_request("I prefer window seats").send().await?;
+//! model.end_session().await?;
+//!
+//! for summary in model.memory("user-42").await? {
+//!     println!("{:?}", summary.summary_text);
+//! }
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/agents-invoke-agent.html>
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/agents-memory.html>
+
+#[cfg(feature = "agents")]
+mod invoke;
+
+#[cfg(feature = "agents")]
+pub use invoke::AgentCompletionModel;
+
+use std::time::SystemTime;
+
+use rig::completion::{GetTokenUsage, Usage};
+use serde::{Deserialize, Serialize};
+
+use crate::knowledge_base::Citation;
+
+/// The alias of the working draft of an agent.
+pub const DRAFT_ALIAS_ID: &str = "TSTALIASID";
+
+/// The final response of an agent.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentResponse {
+    pub text: String,
+    pub session_id: String,
+    /// The memory the session is summarized into, if memory is enabled for the agent.
+    #[serde(default)]
+    pub memory_id: Option<String>,
+    /// Citations of the knowledge base chunks the response is grounded in.
+    pub citations: Vec<Citation>,
+}
+
+impl GetTokenUsage for AgentResponse {
+    /// InvokeAgent doesn't report token usage.
+    fn token_usage(&self) -> Option<Usage> {
+        None
+    }
+}
+
+/// The summary of an ended agent session, recalled in the following sessions sharing its
+/// memory id.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub memory_id: Option<String>,
+    pub session_id: Option<String>,
+    pub session_start_time: Option<SystemTime>,
+    /// When the summary is deleted, after the agent's memory retention period.
+    pub session_expiry_time: Option<SystemTime>,
+    pub summary_text: Option<String>,
+}
@@ -0,0 +1,291 @@
+//! Spending limits, to stop sending requests once the estimated cost of a client, agent or
+//! tenant reaches a budget.
+//!
+//! A guard attached to a [`Client`](crate::client::Client) limits every completion model created
+//! from it afterwards, and one attached to a model limits that model and the agents built on it:
+//!
+//! ```rust,ignore
+//! let budget = BudgetGuard::new(25.0)
+//!     .with_alert_at(0.8)
+//!     .on_alert(|alert| tracing::warn!("Tenant spent ${:.2} of ${:.2}", alert.spent, alert.limit));
+//! let client = Client::from_env().with_budget_guard(budget.clone());
+//!
+//! let agent = client.agent(AMAZON_NOVA_PRO).build();
+//! match agent.prompt("Summarize the quarter").await {
+//!     Err(PromptError::CompletionError(error)) if BudgetExceededError::from_completion_error(&error).is_some() => {
+//!         // Tell the tenant to upgrade their plan
+//!     }
+//!     result => { /* ... */ }
+//! }
+//! ```
+//!
+//! Spend is the cost estimated from the usage of each call and the pricing of its model, see
+//! [`crate::pricing`], so calls to models without a known pricing aren't counted. The budget is
+//! checked before sending each request: requests already in flight complete, and may take the
+//! spend over the limit.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::Notify;
+
+use crate::pricing::CostEstimate;
+use crate::types::errors::BudgetExceededError;
+
+/// What a [`BudgetGuard`] does with requests once its budget is exceeded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BudgetAction {
+    /// Fail them with a [`BudgetExceededError`].
+    #[default]
+    Reject,
+    /// Hold them until the budget is raised with [`BudgetGuard::set_limit`] or the spend is
+    /// reset with [`BudgetGuard::reset`], e.g. at the start of the next billing period.
+    Pause,
+}
+
+/// Notification that the spend of a [`BudgetGuard`] reached one of its alert thresholds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BudgetAlert {
+    /// The fraction of the budget that was reached, 1 when it is exceeded.
+    pub threshold: f64,
+    /// Estimated spend, in US dollars.
+    pub spent: f64,
+    /// The budget, in US dollars.
+    pub limit: f64,
+}
+
+type AlertHook = Arc<dyn Fn(BudgetAlert) + Send + Sync>;
+
+/// A spending limit shared by all the clients and models it is attached to, and by its clones.
+#[derive(Clone)]
+pub struct BudgetGuard {
+    inner: Arc<Mutex<Budget>>,
+    resumed: Arc<Notify>,
+}
+
+struct Budget {
+    limit: f64,
+    spent: f64,
+    action: BudgetAction,
+    /// Fractions of the limit to alert at, in increasing order, with whether they were reached.
+    thresholds: Vec<(f64, bool)>,
+    hooks: Vec<AlertHook>,
+}
+
+impl BudgetGuard {
+    /// A guard rejecting requests once `limit` US dollars were spent, alerting when the limit is
+    /// exceeded.
+    pub fn new(limit: f64) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Budget {
+                limit,
+                spent: 0.0,
+                action: BudgetAction::default(),
+                thresholds: vec![(1.0, false)],
+                hooks: Vec::new(),
+            })),
+            resumed: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn with_action(self, action: BudgetAction) -> Self {
+        self.lock().action = action;
+        self
+    }
+
+    /// Also alert when the spend reaches `fraction` of the limit, e.g. 0.8 to warn at 80%.
+    pub fn with_alert_at(self, fraction: f64) -> Self {
+        let mut budget = self.lock();
+        if !budget
+            .thresholds
+            .iter()
+            .any(|(threshold, _)| *threshold == fraction)
+        {
+            budget.thresholds.push((fraction, false));
+            budget.thresholds.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        drop(budget);
+        self
+    }
+
+    /// Call `hook` when the spend reaches an alert threshold, once per threshold until the spend
+    /// is reset. The hook runs on the task that completed the call, so it shouldn't block.
+    pub fn on_alert(self, hook: impl Fn(BudgetAlert) + Send + Sync + 'static) -> Self {
+        self.lock().hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// The estimated spend, in US dollars.
+    pub fn spent(&self) -> f64 {
+        self.lock().spent
+    }
+
+    pub fn limit(&self) -> f64 {
+        self.lock().limit
+    }
+
+    pub fn remaining(&self) -> f64 {
+        let budget = self.lock();
+        (budget.limit - budget.spent).max(0.0)
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.lock().is_exceeded()
+    }
+
+    /// Change the limit, resuming paused requests if the new limit isn't exceeded.
+    pub fn set_limit(&self, limit: f64) {
+        let mut budget = self.lock();
+        budget.limit = limit;
+        let spent = budget.spent;
+        for (threshold, reached) in budget.thresholds.iter_mut() {
+            *reached = spent >= *threshold * limit;
+        }
+        drop(budget);
+        self.resumed.notify_waiters();
+    }
+
+    /// Forget the spend, e.g. at the start of a billing period, resuming paused requests.
+    pub fn reset(&self) {
+        let mut budget = self.lock();
+        budget.spent = 0.0;
+        for (_, reached) in budget.thresholds.iter_mut() {
+            *reached = false;
+        }
+        drop(budget);
+        self.resumed.notify_waiters();
+    }
+
+    /// Check whether a request may be sent, before sending it, waiting while the budget is
+    /// exceeded if the guard pauses requests.
+    pub(crate) async fn acquire(&self) -> Result<(), BudgetExceededError> {
+        loop {
+            // Registered before checking, so a reset in between isn't missed
+            let resumed = self.resumed.notified();
+            {
+                let budget = self.lock();
+                if !budget.is_exceeded() {
+                    return Ok(());
+                }
+                if budget.action == BudgetAction::Reject {
+                    return Err(BudgetExceededError {
+                        spent: budget.spent,
+                        limit: budget.limit,
+                    });
+                }
+            }
+            tracing::debug!(target: "rig::bedrock", "Budget exceeded, pausing request");
+            resumed.await;
+        }
+    }
+
+    /// Add the estimated cost of a call to the spend.
+    pub(crate) fn record(&self, cost: CostEstimate) {
+        let mut budget = self.lock();
+        budget.spent += cost.total();
+        let (spent, limit) = (budget.spent, budget.limit);
+        let mut alerts = Vec::new();
+        for (threshold, reached) in budget.thresholds.iter_mut() {
+            if !*reached && spent >= *threshold * limit {
+                *reached = true;
+                alerts.push(BudgetAlert {
+                    threshold: *threshold,
+                    spent,
+                    limit,
+                });
+            }
+        }
+        let hooks = budget.hooks.clone();
+        drop(budget);
+
+        for alert in alerts {
+            if alert.threshold >= 1.0 {
+                tracing::warn!(
+                    target: "rig::bedrock",
+                    "Budget of ${:.2} exceeded, ${:.2} spent",
+                    alert.limit,
+                    alert.spent
+                );
+            }
+            for hook in &hooks {
+                hook(alert);
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Budget> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Budget {
+    fn is_exceeded(&self) -> bool {
+        self.spent >= self.limit
+    }
+}
+
+impl fmt::Debug for BudgetGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let budget = self.lock();
+        f.debug_struct("BudgetGuard")
+            .field("limit", &budget.limit)
+            .field("spent", &budget.spent)
+            .field("action", &budget.action)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn cost(total: f64) -> CostEstimate {
+        CostEstimate {
+            input: total,
+            output: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_once_exceeded_and_alerts() {
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let recorded = alerts.clone();
+        let guard = BudgetGuard::new(1.0)
+            .with_alert_at(0.5)
+            .on_alert(move |alert| recorded.lock().unwrap().push(alert.threshold));
+
+        guard.record(cost(0.6));
+        assert!(guard.acquire().await.is_ok());
+        guard.record(cost(0.3));
+        guard.record(cost(0.2));
+
+        let error = guard.acquire().await.unwrap_err();
+        assert!((error.spent - 1.1).abs() < 1e-9);
+        assert_eq!(guard.remaining(), 0.0);
+        assert_eq!(*alerts.lock().unwrap(), vec![0.5, 1.0]);
+
+        guard.reset();
+        assert!(guard.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_paused_requests_resume_when_limit_is_raised() {
+        let guard = BudgetGuard::new(1.0).with_action(BudgetAction::Pause);
+        guard.record(cost(1.5));
+
+        let paused = tokio::spawn({
+            let guard = guard.clone();
+            async move { guard.acquire().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!paused.is_finished());
+
+        guard.set_limit(2.0);
+        tokio::time::timeout(Duration::from_secs(1), paused)
+            .await
+            .expect("request should resume")
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! Caching of embeddings, so re-indexing unchanged documents doesn't call Bedrock again.
+//!
+//! ```rust,ignore
+//! let cache = Arc::new(InMemoryEmbeddingCache::new());
+//! let model = EmbeddingModel::builder(client, AMAZON_TITAN_EMBED_TEXT_V2_0)
+//!     .cache(cache.clone())
+//!     .build()?;
+//! ```
+//!
+//! Persistent backends (files, Redis, a database table, ...) implement [`EmbeddingCache`].
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+
+/// A store of embeddings keyed by [`cache_key`].
+///
+/// A backend that fails to read or write an entry should log the failure and behave as if the
+/// entry was missing: the embedding is then requested from Bedrock again.
+pub trait EmbeddingCache: Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<f64>>>;
+
+    fn put<'a>(&'a self, key: &'a str, embedding: &'a [f64]) -> BoxFuture<'a, ()>;
+}
+
+/// An unbounded [`EmbeddingCache`] living in memory for the lifetime of the process.
+#[derive(Debug, Default)]
+pub struct InMemoryEmbeddingCache {
+    entries: Mutex<HashMap<String, Vec<f64>>>,
+}
+
+impl InMemoryEmbeddingCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("cache lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().expect("cache lock poisoned").clear();
+    }
+}
+
+impl EmbeddingCache for InMemoryEmbeddingCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<Vec<f64>>> {
+        let embedding = self
+            .entries
+            .lock()
+            .expect("cache lock poisoned")
+            .get(key)
+            .cloned();
+        Box::pin(async move { embedding })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, embedding: &'a [f64]) -> BoxFuture<'a, ()> {
+        self.entries
+            .lock()
+            .expect("cache lock poisoned")
+            .insert(key.to_string(), embedding.to_vec());
+        Box::pin(async {})
+    }
+}
+
+/// The cache key of the embedding of `text`: a hex encoded SHA-256 hash of the model, the
+/// dimensions, the model specific input options and the text.
+///
+/// The key is stable across processes and crate versions, so it can be used by persistent caches.
+pub fn cache_key(model: &str, ndims: usize, options: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [model, &ndims.to_string(), options, text] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        let key = cache_key("amazon.titan-embed-text-v2:0", 256, "", "hello");
+        assert_eq!(key.len(), 64);
+        assert_eq!(
+            key,
+            cache_key("amazon.titan-embed-text-v2:0", 256, "", "hello")
+        );
+        assert_ne!(
+            key,
+            cache_key("amazon.titan-embed-text-v2:0", 512, "", "hello")
+        );
+        assert_ne!(
+            key,
+            cache_key("amazon.titan-embed-text-v2:0", 256, "", "hello!")
+        );
+        // Parts are length prefixed, so moving characters between them changes the key
+        assert_ne!(cache_key("a", 1, "b", "c"), cache_key("a", 1, "", "bc"));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_cache() {
+        let cache = InMemoryEmbeddingCache::new();
+        assert_eq!(cache.get("key").await, None);
+
+        cache.put("key", &[0.1, 0.2]).await;
+        assert_eq!(cache.get("key").await, Some(vec![0.1, 0.2]));
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
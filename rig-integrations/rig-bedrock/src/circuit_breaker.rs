@@ -0,0 +1,408 @@
+//! Circuit breaking, to fail fast instead of piling up calls to a model or region that keeps
+//! failing.
+//!
+//! A breaker is closed until the rate of failed calls within a sliding window exceeds a
+//! threshold. It then opens and rejects every call with a [`CircuitOpenError`] until a cooldown
+//! elapses, after which it is half-open: a single probe call is let through, closing the breaker
+//! again if it succeeds or reopening it if it fails.
+//!
+//! ```rust,ignore
+//! let breaker = CircuitBreaker::new()
+//!     .with_failure_rate_threshold(0.5)
+//!     .with_cooldown(Duration::from_secs(60));
+//!
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_LITE)
+//!     .with_circuit_breaker(breaker.clone());
+//! ```
+//!
+//! Only errors that point at the model or the service count as failures: throttling,
+//! timeouts, model errors and unavailability. Invalid requests don't.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::types::errors::{BedrockError, BedrockErrorKind, CircuitOpenError};
+
+/// The state of a [`CircuitBreaker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls are rejected until the cooldown elapses.
+    Open,
+    /// A single probe call goes through to decide whether to close the breaker.
+    HalfOpen,
+}
+
+/// A circuit breaker shared by all the models it is attached to, and by its clones.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    inner: Arc<Mutex<Breaker>>,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    failure_rate_threshold: f64,
+    minimum_calls: usize,
+    window: Duration,
+    cooldown: Duration,
+    /// Completion time and success of the calls within the window, oldest first.
+    outcomes: VecDeque<(Instant, bool)>,
+    state: State,
+    /// The number of probes let through so far, identifying the next one.
+    probes: u64,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed,
+    Open {
+        until: Instant,
+    },
+    /// `probe` is in flight, if any.
+    HalfOpen {
+        probe: Option<Probe>,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Probe {
+    id: u64,
+    started: Instant,
+}
+
+/// A call let through by a [`CircuitBreaker`], whose outcome is recorded with
+/// [`CircuitPermit::record`]. Only the outcome of the probe of a half-open breaker decides
+/// whether it closes.
+#[derive(Debug)]
+pub(crate) struct CircuitPermit {
+    breaker: CircuitBreaker,
+    probe: Option<u64>,
+}
+
+impl CircuitPermit {
+    /// Record the outcome of the call.
+    pub(crate) fn record(self, error: Option<&BedrockError>) {
+        let success = !error.is_some_and(|error| trips_breaker(error.kind()));
+        self.breaker
+            .lock()
+            .record(Instant::now(), self.probe, success);
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Breaker {
+                failure_rate_threshold: 0.5,
+                minimum_calls: 10,
+                window: Duration::from_secs(60),
+                cooldown: Duration::from_secs(30),
+                outcomes: VecDeque::new(),
+                state: State::Closed,
+                probes: 0,
+            })),
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// A breaker opening when half of at least 10 calls within a minute failed, for 30 seconds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open when the rate of failed calls within the window exceeds `threshold`, between 0
+    /// and 1. Defaults to 0.5.
+    pub fn with_failure_rate_threshold(self, threshold: f64) -> Self {
+        self.lock().failure_rate_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Don't open before at least `minimum_calls` calls completed within the window, so a
+    /// couple of early failures don't open the breaker. Defaults to 10.
+    pub fn with_minimum_calls(self, minimum_calls: usize) -> Self {
+        self.lock().minimum_calls = minimum_calls.max(1);
+        self
+    }
+
+    /// The sliding window over which the failure rate is computed. Defaults to a minute.
+    pub fn with_window(self, window: Duration) -> Self {
+        self.lock().window = window;
+        self
+    }
+
+    /// How long the breaker stays open before letting a probe call through. Defaults to 30
+    /// seconds.
+    pub fn with_cooldown(self, cooldown: Duration) -> Self {
+        self.lock().cooldown = cooldown;
+        self
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let mut breaker = self.lock();
+        breaker.expire_cooldown(Instant::now());
+        match breaker.state {
+            State::Closed => CircuitState::Closed,
+            State::Open { .. } => CircuitState::Open,
+            State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Close the breaker and forget the recorded calls.
+    pub fn reset(&self) {
+        let mut breaker = self.lock();
+        breaker.state = State::Closed;
+        breaker.outcomes.clear();
+    }
+
+    /// Check whether a call may be made, before making it.
+    pub(crate) fn acquire(&self) -> Result<CircuitPermit, CircuitOpenError> {
+        let mut breaker = self.lock();
+        let now = Instant::now();
+        breaker.expire_cooldown(now);
+        let cooldown = breaker.cooldown;
+
+        let probe = match breaker.state {
+            State::Closed => None,
+            State::Open { until } => {
+                return Err(CircuitOpenError {
+                    retry_after: until.saturating_duration_since(now),
+                });
+            }
+            // A probe that never completed, e.g. because it was cancelled, is given up on
+            State::HalfOpen { probe: Some(probe) } if now - probe.started < cooldown => {
+                return Err(CircuitOpenError {
+                    retry_after: Duration::ZERO,
+                });
+            }
+            State::HalfOpen { .. } => {
+                breaker.probes += 1;
+                let probe = Probe {
+                    id: breaker.probes,
+                    started: now,
+                };
+                breaker.state = State::HalfOpen { probe: Some(probe) };
+                Some(probe.id)
+            }
+        };
+
+        Ok(CircuitPermit {
+            breaker: self.clone(),
+            probe,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Breaker> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Breaker {
+    fn expire_cooldown(&mut self, now: Instant) {
+        if let State::Open { until } = self.state
+            && now >= until
+        {
+            self.state = State::HalfOpen { probe: None };
+        }
+    }
+
+    /// Record the outcome of a call, `probe` being the id of the probe it was let through as.
+    fn record(&mut self, now: Instant, probe: Option<u64>, success: bool) {
+        match self.state {
+            State::HalfOpen {
+                probe: Some(current),
+            } if probe == Some(current.id) => {
+                if success {
+                    tracing::info!(target: "rig::bedrock", "Circuit breaker closed");
+                    self.state = State::Closed;
+                    self.outcomes.clear();
+                } else {
+                    self.open(now);
+                }
+            }
+            // Calls started before the breaker opened, and probes that were given up on
+            State::Open { .. } | State::HalfOpen { .. } => {}
+            State::Closed => {
+                self.outcomes.push_back((now, success));
+                while self
+                    .outcomes
+                    .front()
+                    .is_some_and(|(completed, _)| now - *completed > self.window)
+                {
+                    self.outcomes.pop_front();
+                }
+
+                let failures = self.outcomes.iter().filter(|(_, success)| !success).count();
+                if self.outcomes.len() >= self.minimum_calls
+                    && failures as f64 / self.outcomes.len() as f64 > self.failure_rate_threshold
+                {
+                    self.open(now);
+                }
+            }
+        }
+    }
+
+    fn open(&mut self, now: Instant) {
+        tracing::warn!(
+            target: "rig::bedrock",
+            "Circuit breaker opened, rejecting calls for {:?}",
+            self.cooldown
+        );
+        self.state = State::Open {
+            until: now + self.cooldown,
+        };
+        self.outcomes.clear();
+    }
+}
+
+/// Whether an error of this kind points at the model or the service rather than the request.
+fn trips_breaker(kind: BedrockErrorKind) -> bool {
+    kind.is_retryable()
+        || matches!(
+            kind,
+            BedrockErrorKind::ModelError | BedrockErrorKind::ModelStreamError
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{AMAZON_NOVA_LITE, CompletionModel};
+    use crate::request_limits::RequestLimits;
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::CompletionModel as _;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new()
+            .with_minimum_calls(4)
+            .with_failure_rate_threshold(0.5)
+            .with_cooldown(Duration::from_secs(30))
+    }
+
+    fn record(breaker: &CircuitBreaker, now: Instant, success: bool) {
+        breaker.lock().record(now, None, success);
+    }
+
+    fn half_open(breaker: &CircuitBreaker) {
+        breaker.lock().state = State::HalfOpen { probe: None };
+    }
+
+    #[test]
+    fn test_opens_above_failure_rate() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        record(&breaker, now, false);
+        record(&breaker, now, false);
+        record(&breaker, now, false);
+        // Below the minimum number of calls
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        record(&breaker, now, true);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.acquire().is_err());
+    }
+
+    #[test]
+    fn test_stays_closed_at_threshold() {
+        let breaker = breaker();
+        let now = Instant::now();
+
+        for success in [true, false, true, false] {
+            record(&breaker, now, success);
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.acquire().is_ok());
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = breaker().with_cooldown(Duration::ZERO);
+        breaker.lock().open(Instant::now());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // A single probe is let through
+        breaker.lock().cooldown = Duration::from_secs(30);
+        let probe = breaker.acquire().unwrap();
+        assert!(breaker.acquire().is_err());
+
+        probe.record(None);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let breaker = breaker();
+        half_open(&breaker);
+        let probe = breaker.acquire().unwrap();
+
+        breaker.lock().record(Instant::now(), probe.probe, false);
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_only_the_probe_decides_when_half_open() {
+        let breaker = breaker();
+        let started_before_opening = breaker.acquire().unwrap();
+        half_open(&breaker);
+        let probe = breaker.acquire().unwrap();
+
+        started_before_opening.record(None);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // A probe that was given up on doesn't decide for the next one either
+        breaker.lock().cooldown = Duration::ZERO;
+        let next_probe = breaker.acquire().unwrap();
+        probe.record(None);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        next_probe.record(None);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_poisoned_lock_is_recovered() {
+        let breaker = breaker();
+        let poisoned = breaker.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoned.lock();
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert!(breaker.inner.is_poisoned());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.acquire().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_probe_failing_before_bedrock_is_released() {
+        let breaker = breaker();
+        half_open(&breaker);
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello!"));
+        let model = CompletionModel::new(mock.client(), AMAZON_NOVA_LITE)
+            .with_circuit_breaker(breaker.clone());
+
+        let limited = model
+            .clone()
+            .with_request_limits(RequestLimits::default().with_max_payload_bytes(1));
+        assert!(limited.completion_request("Hi").send().await.is_err());
+        assert!(mock.requests().is_empty());
+
+        model.completion_request("Hi").send().await.unwrap();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_invalid_requests_dont_trip() {
+        assert!(trips_breaker(BedrockErrorKind::Throttled));
+        assert!(trips_breaker(BedrockErrorKind::ModelError));
+        assert!(!trips_breaker(BedrockErrorKind::Validation));
+        assert!(!trips_breaker(BedrockErrorKind::AccessDenied));
+    }
+}
@@ -1,16 +1,62 @@
+use crate::async_invoke::AsyncInvoke;
+use crate::budget::BudgetGuard;
+use crate::concurrency::{ConcurrencyLimiter, ConcurrencyLimits};
 use crate::image::ImageGenerationModel;
-use crate::{completion::CompletionModel, embedding::EmbeddingModel};
-use aws_config::{BehaviorVersion, Region};
+use crate::rate_limit::RateLimiter;
+use crate::types::errors::{InvalidDimensionsError, ModelAccessError};
+use crate::usage::UsageTracker;
+use crate::{
+    completion::CompletionModel,
+    embedding::{CohereInputType, EmbeddingModel},
+};
+use aws_config::{BehaviorVersion, ConfigLoader, Region, SdkConfig};
+use aws_sdk_bedrockruntime::config::{Intercept, SharedInterceptor};
 use rig::client::Nothing;
 use rig::prelude::*;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::OnceCell;
 
 pub const DEFAULT_AWS_REGION: &str = "us-east-1";
 
+/// Endpoint variants to resolve against when building the underlying AWS config.
+/// Unset values fall back to the standard AWS configuration chain
+/// (e.g. `AWS_USE_FIPS_ENDPOINT` and `AWS_USE_DUALSTACK_ENDPOINT`).
+#[derive(Clone, Debug, Default)]
+struct EndpointOptions {
+    use_fips: Option<bool>,
+    use_dual_stack: Option<bool>,
+}
+
+impl EndpointOptions {
+    fn apply(&self, mut loader: ConfigLoader) -> ConfigLoader {
+        if let Some(use_fips) = self.use_fips {
+            loader = loader.use_fips(use_fips);
+        }
+        if let Some(use_dual_stack) = self.use_dual_stack {
+            loader = loader.use_dual_stack(use_dual_stack);
+        }
+        loader
+    }
+}
+
+/// The Bedrock runtime client of `sdk_config`, running `interceptors` on every operation.
+fn runtime_client(
+    sdk_config: &SdkConfig,
+    interceptors: &[SharedInterceptor],
+) -> aws_sdk_bedrockruntime::Client {
+    let mut config = aws_sdk_bedrockruntime::config::Builder::from(sdk_config);
+    for interceptor in interceptors {
+        config.push_interceptor(interceptor.clone());
+    }
+    aws_sdk_bedrockruntime::Client::from_conf(config.build())
+}
+
 #[derive(Clone)]
 pub struct ClientBuilder<'a> {
     region: &'a str,
+    endpoint_options: EndpointOptions,
+    interceptors: Vec<SharedInterceptor>,
 }
 
 impl<'a> ClientBuilder<'a> {
@@ -21,6 +67,8 @@ impl<'a> ClientBuilder<'a> {
     pub fn new() -> Self {
         Self {
             region: DEFAULT_AWS_REGION,
+            endpoint_options: EndpointOptions::default(),
+            interceptors: Vec::new(),
         }
     }
 
@@ -32,17 +80,49 @@ impl<'a> ClientBuilder<'a> {
         self
     }
 
+    /// Resolve FIPS 140-2 validated endpoints, required for some government workloads.
+    ///
+    /// Make sure FIPS endpoints are [available] in the selected region.
+    ///
+    /// [available]: https://aws.amazon.com/compliance/fips/
+    pub fn use_fips(mut self, use_fips: bool) -> Self {
+        self.endpoint_options.use_fips = Some(use_fips);
+        self
+    }
+
+    /// Resolve dual-stack endpoints, which accept both IPv4 and IPv6 traffic.
+    pub fn use_dual_stack(mut self, use_dual_stack: bool) -> Self {
+        self.endpoint_options.use_dual_stack = Some(use_dual_stack);
+        self
+    }
+
+    /// Run `interceptor` on every operation of the Bedrock runtime client, e.g. to add headers,
+    /// tweak requests before signing or audit responses. Interceptors run in the order they were
+    /// added, after the interceptors of the SDK.
+    pub fn interceptor(mut self, interceptor: impl Intercept + 'static) -> Self {
+        self.interceptors.push(SharedInterceptor::new(interceptor));
+        self
+    }
+
     /// Make sure you have permissions to access [Amazon Bedrock foundation model]
     ///
     /// [ Amazon Bedrock foundation model]: <https://docs.aws.amazon.com/bedrock/latest/userguide/model-access-modify.html>
     pub async fn build(self) -> Client {
-        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
-            .region(Region::new(String::from(self.region)))
-            .load()
-            .await;
-        let client = aws_sdk_bedrockruntime::Client::new(&sdk_config);
+        let loader = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(String::from(self.region)));
+        let sdk_config = self.endpoint_options.apply(loader).load().await;
+        let client = runtime_client(&sdk_config, &self.interceptors);
         Client {
             profile_name: None,
+            region: None,
+            endpoint_options: self.endpoint_options,
+            interceptors: self.interceptors,
+            request_metadata: HashMap::new(),
+            usage_tracker: None,
+            budget_guard: None,
+            concurrency: ConcurrencyLimits::default(),
+            completion_rate_limits: HashMap::new(),
+            sdk_config: Arc::new(OnceCell::from(sdk_config)),
             aws_client: Arc::new(OnceCell::from(client)),
         }
     }
@@ -58,6 +138,15 @@ impl Default for ClientBuilder<'_> {
 #[derive(Clone, Debug)]
 pub struct Client {
     profile_name: Option<String>,
+    region: Option<String>,
+    endpoint_options: EndpointOptions,
+    interceptors: Vec<SharedInterceptor>,
+    pub(crate) request_metadata: HashMap<String, String>,
+    pub(crate) usage_tracker: Option<UsageTracker>,
+    pub(crate) budget_guard: Option<BudgetGuard>,
+    pub(crate) concurrency: ConcurrencyLimits,
+    pub(crate) completion_rate_limits: HashMap<String, RateLimiter>,
+    sdk_config: Arc<OnceCell<SdkConfig>>,
     pub(crate) aws_client: Arc<OnceCell<aws_sdk_bedrockruntime::Client>>,
 }
 
@@ -65,6 +154,15 @@ impl From<aws_sdk_bedrockruntime::Client> for Client {
     fn from(aws_client: aws_sdk_bedrockruntime::Client) -> Self {
         Client {
             profile_name: None,
+            region: None,
+            endpoint_options: EndpointOptions::default(),
+            interceptors: Vec::new(),
+            request_metadata: HashMap::new(),
+            usage_tracker: None,
+            budget_guard: None,
+            concurrency: ConcurrencyLimits::default(),
+            completion_rate_limits: HashMap::new(),
+            sdk_config: Arc::new(OnceCell::new()),
             aws_client: Arc::new(OnceCell::from(aws_client)),
         }
     }
@@ -74,6 +172,15 @@ impl Client {
     fn new() -> Self {
         Self {
             profile_name: None,
+            region: None,
+            endpoint_options: EndpointOptions::default(),
+            interceptors: Vec::new(),
+            request_metadata: HashMap::new(),
+            usage_tracker: None,
+            budget_guard: None,
+            concurrency: ConcurrencyLimits::default(),
+            completion_rate_limits: HashMap::new(),
+            sdk_config: Arc::new(OnceCell::new()),
             aws_client: Arc::new(OnceCell::new()),
         }
     }
@@ -82,25 +189,230 @@ impl Client {
     pub fn with_profile_name(profile_name: &str) -> Self {
         Self {
             profile_name: Some(profile_name.into()),
+            region: None,
+            endpoint_options: EndpointOptions::default(),
+            interceptors: Vec::new(),
+            request_metadata: HashMap::new(),
+            usage_tracker: None,
+            budget_guard: None,
+            concurrency: ConcurrencyLimits::default(),
+            completion_rate_limits: HashMap::new(),
+            sdk_config: Arc::new(OnceCell::new()),
             aws_client: Arc::new(OnceCell::new()),
         }
     }
 
-    pub async fn get_inner(&self) -> &aws_sdk_bedrockruntime::Client {
-        self.aws_client
+    /// Resolve FIPS 140-2 validated endpoints.
+    pub fn with_fips_endpoints(mut self, use_fips: bool) -> Self {
+        self.endpoint_options.use_fips = Some(use_fips);
+        self.reconfigure(|config| {
+            config.set_use_fips(Some(use_fips));
+        })
+    }
+
+    /// Resolve dual-stack (IPv4 and IPv6) endpoints.
+    pub fn with_dual_stack_endpoints(mut self, use_dual_stack: bool) -> Self {
+        self.endpoint_options.use_dual_stack = Some(use_dual_stack);
+        self.reconfigure(|config| {
+            config.set_use_dual_stack(Some(use_dual_stack));
+        })
+    }
+
+    /// Run `interceptor` on every operation of the Bedrock runtime client, see
+    /// [`ClientBuilder::interceptor`]. Clients created from an existing
+    /// `aws_sdk_bedrockruntime::Client` keep its configuration and interceptors.
+    pub fn with_interceptor(mut self, interceptor: impl Intercept + 'static) -> Self {
+        let interceptor = SharedInterceptor::new(interceptor);
+        self.interceptors.push(interceptor.clone());
+        self.reconfigure(|config| {
+            config.push_interceptor(interceptor);
+        })
+    }
+
+    /// Apply `configure` to the runtime client if it was already created, keeping its
+    /// credentials and HTTP client, and load the AWS configuration again on next use.
+    fn reconfigure(
+        mut self,
+        configure: impl FnOnce(&mut aws_sdk_bedrockruntime::config::Builder),
+    ) -> Self {
+        if let Some(aws_client) = self.aws_client.get() {
+            let mut config = aws_client.config().to_builder();
+            configure(&mut config);
+            self.aws_client = Arc::new(OnceCell::from(aws_sdk_bedrockruntime::Client::from_conf(
+                config.build(),
+            )));
+        }
+        self.sdk_config = Arc::new(OnceCell::new());
+        self
+    }
+
+    /// A copy of this client calling Bedrock in `region`, with the same profile, endpoint
+    /// options, interceptors, request metadata and usage tracker. The AWS configuration is loaded again from
+    /// the environment, so a client created from an existing `aws_sdk_bedrockruntime::Client`
+    /// doesn't keep its credentials or HTTP client.
+    pub fn in_region(&self, region: impl Into<String>) -> Self {
+        Self {
+            region: Some(region.into()),
+            sdk_config: Arc::new(OnceCell::new()),
+            aws_client: Arc::new(OnceCell::new()),
+            ..self.clone()
+        }
+    }
+
+    /// Attach a `requestMetadata` key/value pair to every Converse call made through this client.
+    /// The pairs are recorded in CloudTrail and model invocation logs, so they can be used to
+    /// tag invocations with tenant ids, trace ids or feature names.
+    pub fn with_request_metadata(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.request_metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Record the token usage and estimated cost of the calls of all the completion and
+    /// embedding models created from this client afterwards.
+    pub fn with_usage_tracker(mut self, usage_tracker: UsageTracker) -> Self {
+        self.usage_tracker = Some(usage_tracker);
+        self
+    }
+
+    /// Limit the estimated spend of all the completion models created from this client
+    /// afterwards, see [`crate::budget`].
+    pub fn with_budget_guard(mut self, budget_guard: BudgetGuard) -> Self {
+        self.budget_guard = Some(budget_guard);
+        self
+    }
+
+    /// Limit the number of in-flight completion requests of all the completion models created
+    /// from this client afterwards, see [`crate::concurrency`].
+    pub fn with_completion_concurrency(mut self, limiter: ConcurrencyLimiter) -> Self {
+        self.concurrency.completions = Some(limiter);
+        self
+    }
+
+    /// Limit the number of in-flight embedding requests of all the embedding models created
+    /// from this client afterwards.
+    pub fn with_embedding_concurrency(mut self, limiter: ConcurrencyLimiter) -> Self {
+        self.concurrency.embeddings = Some(limiter);
+        self
+    }
+
+    /// Limit the requests and tokens per minute of the completion requests sent to `model` by
+    /// the completion models created from this client afterwards, including when `model` is a
+    /// fallback, to stay under its account quotas, see [`crate::rate_limit`].
+    pub fn with_completion_rate_limit(
+        mut self,
+        model: impl Into<String>,
+        limiter: RateLimiter,
+    ) -> Self {
+        self.completion_rate_limits.insert(model.into(), limiter);
+        self
+    }
+
+    /// Check that `model` can be invoked with the current credentials and region.
+    ///
+    /// This sends a minimal Converse request (a single-token completion), so it may incur a
+    /// negligible charge. The returned [`ModelAccessError`] distinguishes models that haven't been
+    /// enabled in the account from missing IAM permissions and unknown model ids.
+    pub async fn verify_model_access(&self, model: &str) -> Result<(), ModelAccessError> {
+        let message = aws_sdk_bedrockruntime::types::Message::builder()
+            .role(aws_sdk_bedrockruntime::types::ConversationRole::User)
+            .content(aws_sdk_bedrockruntime::types::ContentBlock::Text(
+                "ping".into(),
+            ))
+            .build()
+            .map_err(|e| ModelAccessError::Other(e.to_string()))?;
+
+        self.get_inner()
+            .await
+            .converse()
+            .model_id(model)
+            .messages(message)
+            .inference_config(
+                aws_sdk_bedrockruntime::types::InferenceConfiguration::builder()
+                    .max_tokens(1)
+                    .build(),
+            )
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|sdk_error| {
+                ModelAccessError::from_converse_error(model, sdk_error.into_service_error())
+            })
+    }
+
+    /// Create an embedding model that sends `input_type` to Cohere Embed models, so queries and
+    /// documents can be embedded differently for retrieval.
+    pub fn embedding_model_with_input_type(
+        &self,
+        model: impl Into<String>,
+        input_type: CohereInputType,
+    ) -> EmbeddingModel {
+        EmbeddingModel::builder(self.clone(), model)
+            .input_type(input_type)
+            .build_unchecked()
+    }
+
+    /// Create an embedding model producing `ndims` dimensional embeddings, failing if the model is
+    /// known not to support that dimension.
+    pub fn try_embedding_model_with_ndims(
+        &self,
+        model: impl Into<String>,
+        ndims: usize,
+    ) -> Result<EmbeddingModel, InvalidDimensionsError> {
+        let model = EmbeddingModel::builder(self.clone(), model)
+            .ndims(ndims)
+            .build_unchecked();
+        model.validate_dimensions()?;
+        Ok(model)
+    }
+
+    /// An agent builder for `model` placing prompt cache points after its preamble and static
+    /// context documents on every request, see [`CompletionModel::with_prompt_cache_points`].
+    pub fn agent_with_prompt_caching(
+        &self,
+        model: impl Into<String>,
+    ) -> rig::agent::AgentBuilder<CompletionModel> {
+        rig::agent::AgentBuilder::new(self.completion_model(model).with_prompt_cache_points())
+    }
+
+    /// Start and track asynchronous invocations of long-running models.
+    pub fn async_invoke(&self) -> AsyncInvoke {
+        AsyncInvoke::new(self.clone())
+    }
+
+    /// The AWS configuration shared by the Bedrock runtime client and the clients of other AWS
+    /// services used by this crate (e.g. S3 for batch jobs).
+    pub async fn sdk_config(&self) -> &SdkConfig {
+        self.sdk_config
             .get_or_init(|| async {
-                let config = if let Some(profile_name) = &self.profile_name {
-                    aws_config::defaults(BehaviorVersion::latest())
-                        .profile_name(profile_name)
-                        .load()
-                        .await
-                } else {
-                    aws_config::load_from_env().await
-                };
-                aws_sdk_bedrockruntime::Client::new(&config)
+                let mut loader = aws_config::defaults(BehaviorVersion::latest());
+                if let Some(profile_name) = &self.profile_name {
+                    loader = loader.profile_name(profile_name);
+                }
+                // A client wrapping an existing runtime client keeps that client's region
+                if let Some(region) = self
+                    .aws_client
+                    .get()
+                    .and_then(|client| client.config().region().cloned())
+                {
+                    loader = loader.region(region);
+                }
+                if let Some(region) = &self.region {
+                    loader = loader.region(Region::new(region.clone()));
+                }
+                self.endpoint_options.apply(loader).load().await
             })
             .await
     }
+
+    pub async fn get_inner(&self) -> &aws_sdk_bedrockruntime::Client {
+        self.aws_client
+            .get_or_init(|| async { runtime_client(self.sdk_config().await, &self.interceptors) })
+            .await
+    }
 }
 
 impl ProviderClient for Client {
@@ -135,15 +447,25 @@ impl EmbeddingsClient for Client {
     type EmbeddingModel = EmbeddingModel;
 
     fn embedding_model(&self, model: impl Into<String>) -> Self::EmbeddingModel {
-        EmbeddingModel::new(self.clone(), model, None)
+        EmbeddingModel::builder(self.clone(), model).build_unchecked()
     }
 
+    /// Create an embedding model producing `ndims` dimensional embeddings. `ndims` is checked
+    /// like in [`Client::try_embedding_model_with_ndims`], but as this can't fail, an unsupported
+    /// dimension is only logged and Bedrock rejects the requests. Use the `try_*` variant or
+    /// [`EmbeddingModel::builder`] to get the error instead.
     fn embedding_model_with_ndims(
         &self,
         model: impl Into<String>,
         ndims: usize,
     ) -> Self::EmbeddingModel {
-        EmbeddingModel::new(self.clone(), model, Some(ndims))
+        let model = EmbeddingModel::builder(self.clone(), model)
+            .ndims(ndims)
+            .build_unchecked();
+        if let Err(error) = model.validate_dimensions() {
+            tracing::warn!(target: "rig::bedrock", "{error}, requests will be rejected");
+        }
+        model
     }
 }
 
@@ -161,3 +483,100 @@ impl VerifyClient for Client {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_endpoint_options_are_applied_to_sdk_config() {
+        let options = EndpointOptions {
+            use_fips: Some(true),
+            use_dual_stack: Some(true),
+        };
+        let loader = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(DEFAULT_AWS_REGION))
+            .credentials_provider(aws_sdk_bedrockruntime::config::Credentials::for_tests());
+
+        let config = options.apply(loader).load().await;
+
+        assert_eq!(config.use_fips(), Some(true));
+        assert_eq!(config.use_dual_stack(), Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_options_apply_to_created_clients() {
+        use crate::completion::AMAZON_NOVA_LITE;
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello"));
+        let client = mock.client();
+        // Create the runtime client before changing the endpoint options
+        client.get_inner().await;
+        let model = client
+            .with_fips_endpoints(true)
+            .completion_model(AMAZON_NOVA_LITE);
+
+        model
+            .completion(model.completion_request("Hi").build())
+            .await
+            .unwrap();
+
+        assert!(mock.requests()[0].uri.contains("bedrock-runtime-fips."));
+    }
+
+    #[derive(Debug)]
+    struct AuditHeader(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl Intercept for AuditHeader {
+        fn name(&self) -> &'static str {
+            "AuditHeader"
+        }
+
+        fn modify_before_signing(
+            &self,
+            context: &mut aws_sdk_bedrockruntime::config::interceptors::BeforeTransmitInterceptorContextMut<'_>,
+            _runtime_components: &aws_sdk_bedrockruntime::config::RuntimeComponents,
+            _cfg: &mut aws_sdk_bedrockruntime::config::ConfigBag,
+        ) -> Result<(), aws_sdk_bedrockruntime::error::BoxError> {
+            context
+                .request_mut()
+                .headers_mut()
+                .insert("x-audit-id", "abc-123");
+            Ok(())
+        }
+
+        fn read_after_transmit(
+            &self,
+            context: &aws_sdk_bedrockruntime::config::interceptors::BeforeDeserializationInterceptorContextRef<'_>,
+            _runtime_components: &aws_sdk_bedrockruntime::config::RuntimeComponents,
+            _cfg: &mut aws_sdk_bedrockruntime::config::ConfigBag,
+        ) -> Result<(), aws_sdk_bedrockruntime::error::BoxError> {
+            assert!(context.response().status().is_success());
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interceptors_run_on_operations() {
+        use crate::completion::AMAZON_NOVA_LITE;
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello"));
+        let responses = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = mock
+            .client()
+            .with_interceptor(AuditHeader(responses.clone()));
+        let model = client.completion_model(AMAZON_NOVA_LITE);
+
+        model
+            .completion(model.completion_request("Hi").build())
+            .await
+            .unwrap();
+
+        assert_eq!(responses.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
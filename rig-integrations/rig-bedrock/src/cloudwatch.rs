@@ -0,0 +1,344 @@
+//! CloudWatch metrics of completion and embedding calls, published directly with
+//! `PutMetricData` when the `cloudwatch-metrics` feature is enabled, for teams without an
+//! OpenTelemetry pipeline.
+//!
+//! ```rust,ignore
+//! let config = aws_config::load_from_env().await;
+//! CloudWatchMetrics::new(aws_sdk_cloudwatch::Client::new(&config))
+//!     .with_dimension("Service", "support-bot")
+//!     .install()
+//!     .expect("CloudWatch metrics are installed once");
+//!
+//! // ... on shutdown, publish the metrics of the last calls
+//! cloudwatch::flush().await?;
+//! ```
+//!
+//! Once installed, every call records, in the [`DEFAULT_NAMESPACE`] namespace by default:
+//! - `Invocations`: count of calls
+//! - `Errors`: count of failed calls, also with an `ErrorType` dimension
+//! - `InputTokens` and `OutputTokens`: token usage of successful calls
+//! - `Latency`: call duration, in milliseconds
+//!
+//! All of them have the `Operation` (`chat`, `chat_streaming`, `embeddings`) and `ModelId`
+//! dimensions, plus the ones configured with [`CloudWatchMetrics::with_dimension`]. Metrics are
+//! buffered and published every minute by default from a background task, so installing requires
+//! a Tokio runtime. Metrics that fail to be published stay buffered until the next flush.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use aws_sdk_cloudwatch::config::http::HttpResponse;
+use aws_sdk_cloudwatch::error::{DisplayErrorContext, SdkError};
+use aws_sdk_cloudwatch::operation::put_metric_data::PutMetricDataError;
+use aws_sdk_cloudwatch::primitives::DateTime;
+use aws_sdk_cloudwatch::types::{Dimension, MetricDatum, StandardUnit};
+use tokio::time::Instant;
+
+pub const DEFAULT_NAMESPACE: &str = "Rig/Bedrock";
+
+/// The most metrics `PutMetricData` accepts in a single call.
+const MAX_DATUMS_PER_CALL: usize = 1000;
+
+static PUBLISHER: OnceLock<CloudWatchMetrics> = OnceLock::new();
+
+/// Publishes the metrics of the calls made by this crate to CloudWatch, see
+/// [`crate::cloudwatch`].
+#[derive(Clone, Debug)]
+pub struct CloudWatchMetrics {
+    client: aws_sdk_cloudwatch::Client,
+    namespace: String,
+    dimensions: Vec<(String, String)>,
+    flush_interval: Duration,
+    buffer: Arc<Mutex<Vec<MetricDatum>>>,
+}
+
+impl CloudWatchMetrics {
+    pub fn new(client: aws_sdk_cloudwatch::Client) -> Self {
+        Self {
+            client,
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            dimensions: Vec::new(),
+            flush_interval: Duration::from_secs(60),
+            buffer: Default::default(),
+        }
+    }
+
+    /// Publish the metrics in `namespace`. Defaults to [`DEFAULT_NAMESPACE`].
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Add a `name` dimension with `value` to all the metrics, e.g. the service or the
+    /// environment. CloudWatch allows up to 30 dimensions per metric.
+    pub fn with_dimension(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.dimensions.push((name.into(), value.into()));
+        self
+    }
+
+    /// How often the buffered metrics are published. Defaults to a minute, the resolution of
+    /// standard CloudWatch metrics.
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Record the metrics of all the calls made afterwards with this publisher, and publish them
+    /// periodically. Fails with the publisher if one was already installed.
+    pub fn install(self) -> Result<(), Self> {
+        PUBLISHER.set(self.clone())?;
+
+        let flush_interval = self.flush_interval;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(flush_interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(error) = self.flush().await {
+                    tracing::warn!(target: "rig::bedrock", "Failed to publish CloudWatch metrics: {error}");
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Publish the buffered metrics now. On failure, the metrics that weren't published are put
+    /// back in the buffer, ahead of the ones recorded in the meantime.
+    pub async fn flush(&self) -> Result<(), MetricsPublishError> {
+        let mut unpublished = std::mem::take(&mut *self.lock());
+
+        while !unpublished.is_empty() {
+            let chunk = unpublished.len().min(MAX_DATUMS_PER_CALL);
+            let result = self
+                .client
+                .put_metric_data()
+                .namespace(&self.namespace)
+                .set_metric_data(Some(unpublished[..chunk].to_vec()))
+                .send()
+                .await;
+            if let Err(source) = result {
+                let error = MetricsPublishError {
+                    unpublished: unpublished.len(),
+                    source,
+                };
+                let mut buffer = self.lock();
+                unpublished.append(&mut buffer);
+                *buffer = unpublished;
+                return Err(error);
+            }
+            unpublished.drain(..chunk);
+        }
+        Ok(())
+    }
+
+    /// The metrics of a call to `model`.
+    fn datums(
+        &self,
+        operation: &str,
+        model: &str,
+        latency: Duration,
+        input_tokens: Option<u64>,
+        output_tokens: Option<u64>,
+        error_type: Option<&str>,
+    ) -> Vec<MetricDatum> {
+        let timestamp = DateTime::from(SystemTime::now());
+        let mut dimensions = vec![
+            dimension("Operation", operation),
+            dimension("ModelId", model),
+        ];
+        dimensions.extend(
+            self.dimensions
+                .iter()
+                .map(|(name, value)| dimension(name, value)),
+        );
+        let datum = |name: &str, value: f64, unit: StandardUnit, dimensions: &[Dimension]| {
+            MetricDatum::builder()
+                .metric_name(name)
+                .value(value)
+                .unit(unit)
+                .timestamp(timestamp)
+                .set_dimensions(Some(dimensions.to_vec()))
+                .build()
+        };
+
+        let mut datums = vec![
+            datum("Invocations", 1.0, StandardUnit::Count, &dimensions),
+            datum(
+                "Latency",
+                latency.as_secs_f64() * 1000.0,
+                StandardUnit::Milliseconds,
+                &dimensions,
+            ),
+        ];
+        for (name, tokens) in [
+            ("InputTokens", input_tokens),
+            ("OutputTokens", output_tokens),
+        ] {
+            if let Some(tokens) = tokens {
+                datums.push(datum(name, tokens as f64, StandardUnit::Count, &dimensions));
+            }
+        }
+        if let Some(error_type) = error_type {
+            datums.push(datum("Errors", 1.0, StandardUnit::Count, &dimensions));
+            let mut dimensions = dimensions.clone();
+            dimensions.push(dimension("ErrorType", error_type));
+            datums.push(datum("Errors", 1.0, StandardUnit::Count, &dimensions));
+        }
+        datums
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<MetricDatum>> {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Publish the metrics buffered by the installed publisher now, e.g. before the process exits.
+pub async fn flush() -> Result<(), MetricsPublishError> {
+    match PUBLISHER.get() {
+        Some(publisher) => publisher.flush().await,
+        None => Ok(()),
+    }
+}
+
+/// A failed `PutMetricData` call. The metrics that weren't published are kept in the buffer and
+/// published with the next flush.
+#[derive(Debug)]
+pub struct MetricsPublishError {
+    /// The number of buffered metrics that weren't published.
+    pub unpublished: usize,
+    source: SdkError<PutMetricDataError, HttpResponse>,
+}
+
+impl MetricsPublishError {
+    /// The original AWS SDK error.
+    pub fn aws_error(&self) -> &SdkError<PutMetricDataError, HttpResponse> {
+        &self.source
+    }
+}
+
+impl fmt::Display for MetricsPublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to publish {} CloudWatch metrics: {}",
+            self.unpublished,
+            DisplayErrorContext(&self.source)
+        )
+    }
+}
+
+impl std::error::Error for MetricsPublishError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn dimension(name: &str, value: &str) -> Dimension {
+    Dimension::builder().name(name).value(value).build()
+}
+
+/// Records the metrics of a single call with the installed publisher, if any.
+pub(crate) struct Invocation {
+    operation: &'static str,
+    model: String,
+    started: Instant,
+}
+
+impl Invocation {
+    pub(crate) fn start(operation: &'static str, model: &str) -> Option<Self> {
+        PUBLISHER.get()?;
+        Some(Self {
+            operation,
+            model: model.to_string(),
+            started: Instant::now(),
+        })
+    }
+
+    pub(crate) fn finish(
+        &self,
+        input_tokens: Option<u64>,
+        output_tokens: Option<u64>,
+        error_type: Option<&str>,
+    ) {
+        let Some(publisher) = PUBLISHER.get() else {
+            return;
+        };
+        let datums = publisher.datums(
+            self.operation,
+            &self.model,
+            self.started.elapsed(),
+            input_tokens,
+            output_tokens,
+            error_type,
+        );
+        publisher.lock().extend(datums);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A publisher whose client has no region, so its calls fail without reaching AWS.
+    fn publisher() -> CloudWatchMetrics {
+        let config = aws_sdk_cloudwatch::Config::builder()
+            .behavior_version(aws_sdk_cloudwatch::config::BehaviorVersion::latest())
+            .build();
+        CloudWatchMetrics::new(aws_sdk_cloudwatch::Client::from_conf(config))
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_keeps_the_metrics() {
+        let publisher = publisher();
+        let datums = publisher.datums(
+            "chat",
+            "amazon.nova-lite-v1:0",
+            Duration::from_millis(250),
+            Some(10),
+            Some(20),
+            None,
+        );
+        publisher.lock().extend(datums.clone());
+
+        let error = publisher.flush().await.unwrap_err();
+
+        assert_eq!(error.unpublished, datums.len());
+        assert_eq!(*publisher.lock(), datums);
+    }
+
+    #[test]
+    fn test_datums_of_failed_call() {
+        let publisher = publisher().with_dimension("Service", "support-bot");
+
+        let datums = publisher.datums(
+            "chat",
+            "amazon.nova-lite-v1:0",
+            Duration::from_millis(250),
+            None,
+            None,
+            Some("Throttled"),
+        );
+
+        let names = datums
+            .iter()
+            .filter_map(MetricDatum::metric_name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Invocations", "Latency", "Errors", "Errors"]);
+        assert_eq!(datums[1].value(), Some(250.0));
+        let dimensions = datums[3]
+            .dimensions()
+            .iter()
+            .filter_map(|dimension| Some((dimension.name()?, dimension.value()?)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dimensions,
+            vec![
+                ("Operation", "chat"),
+                ("ModelId", "amazon.nova-lite-v1:0"),
+                ("Service", "support-bot"),
+                ("ErrorType", "Throttled"),
+            ]
+        );
+    }
+}
@@ -1,15 +1,43 @@
 //! All supported models <https://docs.aws.amazon.com/bedrock/latest/userguide/models-supported.html>
 
+#[cfg(feature = "image-preprocessing")]
+use crate::image_preprocessing::ImagePreprocessing;
 use crate::{
+    budget::BudgetGuard,
+    circuit_breaker::{CircuitBreaker, CircuitPermit},
     client::Client,
+    debug_logging::DebugLogging,
+    guardrails::{GuardrailConfig, GuardrailInterventionError},
+    hedging::HedgedCompletionModel,
+    image_fetch::ImageFetch,
+    metrics::InvocationMetrics,
+    model_params,
+    param_ranges::ParamRanges,
+    prefill::Prefill,
+    pricing::{CostEstimate, CostTracker, ModelPricing},
+    rate_limit::estimate_request_tokens,
+    request_limits::RequestLimits,
+    retry::{RetryPolicy, RetryingCompletionModel},
+    streaming::{BedrockUsage, StreamCancellation},
+    telemetry, think_tags,
+    tool_hooks::{ToolHook, ToolHookCompletionModel},
+    tool_names::ToolNames,
+    tool_validation::{ToolCallValidation, repair_results},
     types::{
-        assistant_content::AwsConverseOutput, completion_request::AwsCompletionRequest,
-        converse_output::InternalConverseOutput, errors::AwsSdkConverseError,
+        assistant_content::AwsConverseOutput,
+        completion_request::AwsCompletionRequest,
+        converse_output::{ContentBlock, ConverseOutput, InternalConverseOutput, StopReason},
+        errors::{AwsSdkConverseError, BedrockError, BedrockErrorKind},
     },
+    usage::UsageTracker,
 };
 
 use rig::completion::{self, CompletionError, CompletionRequest};
+use rig::message::{Message, UserContent};
 use rig::streaming::StreamingCompletionResponse;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::Instrument;
 
 /// `ai21.jamba-1-5-large-v1:0`
 pub const AI21_JAMBA_1_5_LARGE: &str = "ai21.jamba-1-5-large-v1:0";
@@ -156,36 +184,461 @@ pub const STABILITY_STABLE_IMAGE_CORE_1_0_V1_0: &str = "stability.stable-image-c
 /// `stability.stable-image-ultra-v1:0`
 pub const STABILITY_STABLE_IMAGE_ULTRA_1_0_V1_0: &str = "stability.stable-image-ultra-v1:0";
 
+/// The Bedrock API completions are sent through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompletionApi {
+    /// The model-agnostic Converse API.
+    #[default]
+    Converse,
+    /// `InvokeModel` with the native request format of the model family, for parameters and
+    /// beta features Converse doesn't expose yet (e.g. `anthropic_beta` for Anthropic models),
+    /// passed through the request's `additional_params`. Amazon Titan Text, Anthropic, Meta
+    /// Llama and Mistral models are supported. Requests fail with models using guardrails or
+    /// cache points, which only Converse supports.
+    Native,
+}
+
 #[derive(Clone)]
 pub struct CompletionModel {
     pub(crate) client: Client,
     pub model: String,
+    request_metadata: HashMap<String, String>,
+    pub(crate) stream_cancellation: Option<StreamCancellation>,
+    pub(crate) image_fetch: ImageFetch,
+    #[cfg(feature = "image-preprocessing")]
+    pub(crate) image_preprocessing: Option<ImagePreprocessing>,
+    pub(crate) request_limits: RequestLimits,
+    /// Overrides the ranges of the model family, see [`CompletionModel::with_param_ranges`].
+    param_ranges: Option<ParamRanges>,
+    pub(crate) tool_cache_point: bool,
+    pub(crate) prompt_cache_points: bool,
+    pub(crate) circuit_breaker: Option<CircuitBreaker>,
+    pub(crate) cost_tracker: CostTracker,
+    pub(crate) debug_logging: Option<DebugLogging>,
+    pub(crate) api: CompletionApi,
+    pub(crate) think_tags: bool,
+    pub(crate) guardrail: Option<GuardrailConfig>,
+    pub(crate) tool_call_validation: Option<ToolCallValidation>,
+    tool_call_repairs: usize,
+    /// Ids of the tool calls whose results are sent with an error status, see
+    /// [`CompletionModel::with_tool_call_repair`].
+    failed_tool_calls: Vec<String>,
+    fallback_models: Vec<String>,
 }
 
 impl CompletionModel {
     pub fn new(client: Client, model: impl Into<String>) -> Self {
+        let model = model.into();
+        let mut cost_tracker = CostTracker::new(&model, client.usage_tracker.clone());
+        if let Some(budget_guard) = &client.budget_guard {
+            cost_tracker = cost_tracker.with_budget_guard(budget_guard.clone());
+        }
         Self {
+            think_tags: think_tags::extracted_by_default(&model),
+            cost_tracker,
             client,
-            model: model.into(),
+            model,
+            request_metadata: HashMap::new(),
+            stream_cancellation: None,
+            image_fetch: ImageFetch::default(),
+            #[cfg(feature = "image-preprocessing")]
+            image_preprocessing: None,
+            request_limits: RequestLimits::default(),
+            param_ranges: None,
+            tool_cache_point: false,
+            prompt_cache_points: false,
+            circuit_breaker: None,
+            debug_logging: None,
+            api: CompletionApi::default(),
+            guardrail: None,
+            tool_call_validation: None,
+            tool_call_repairs: 0,
+            failed_tool_calls: Vec::new(),
+            fallback_models: Vec::new(),
         }
     }
-}
 
-impl completion::CompletionModel for CompletionModel {
-    type Response = AwsConverseOutput;
-    type StreamingResponse = crate::streaming::BedrockStreamingResponse;
+    /// Apply `guardrail` to the prompts and responses of this model. Completions the guardrail
+    /// intervenes in fail with a [`GuardrailInterventionError`], see [`crate::guardrails`].
+    pub fn with_guardrail(mut self, guardrail: GuardrailConfig) -> Self {
+        self.guardrail = Some(guardrail);
+        self
+    }
 
-    type Client = Client;
+    /// Validate the arguments of the tool calls made by the model against the schema of the
+    /// called tool, see [`crate::tool_validation`].
+    pub fn with_tool_call_validation(mut self, validation: ToolCallValidation) -> Self {
+        self.tool_call_validation = Some(validation);
+        self
+    }
 
-    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
-        Self::new(client.clone(), model)
+    /// When the model calls tools with invalid arguments, send the schema violations back to it
+    /// as tool results with an error status and ask it again, up to `max_repairs` times before
+    /// failing with an [`InvalidToolCallError`]. Turns on [`ToolCallValidation::Reject`].
+    ///
+    /// Only completions are repaired: streamed tool calls were already sent when they're found
+    /// invalid, so they fail right away. The usage of the repaired completion includes the
+    /// usage of its failed attempts.
+    ///
+    /// [`InvalidToolCallError`]: crate::tool_validation::InvalidToolCallError
+    pub fn with_tool_call_repair(mut self, max_repairs: usize) -> Self {
+        self.tool_call_validation = Some(ToolCallValidation::Reject);
+        self.tool_call_repairs = max_repairs;
+        self
     }
 
-    async fn completion(
+    /// Insert a prompt cache point after the tool definitions, so large tool schemas that don't
+    /// change between turns are cached. Only models supporting prompt caching accept it.
+    pub fn with_tool_cache_point(mut self) -> Self {
+        self.tool_cache_point = true;
+        self
+    }
+
+    /// The tool configuration of `request`, with a cache point if enabled.
+    pub(crate) fn tools_config(
+        &self,
+        request: &AwsCompletionRequest,
+    ) -> Result<Option<aws_sdk_bedrockruntime::types::ToolConfiguration>, CompletionError> {
+        if self.tool_cache_point {
+            request.tools_config_with_cache_point()
+        } else {
+            request.tools_config()
+        }
+    }
+
+    /// Insert prompt cache points after the preamble and after the documents of every request,
+    /// so the static prefix of agent requests is cached without placing cache points manually.
+    /// Only models supporting prompt caching accept it.
+    pub fn with_prompt_cache_points(mut self) -> Self {
+        self.prompt_cache_points = true;
+        self
+    }
+
+    /// The system prompt of `request`, with a cache point if enabled.
+    pub(crate) fn system_prompt(
+        &self,
+        request: &AwsCompletionRequest,
+    ) -> Result<Option<Vec<aws_sdk_bedrockruntime::types::SystemContentBlock>>, CompletionError>
+    {
+        if self.prompt_cache_points {
+            request.system_prompt_with_cache_point()
+        } else {
+            Ok(request.system_prompt())
+        }
+    }
+
+    /// The messages of `request`, with a cache point after its documents if enabled.
+    pub(crate) fn messages(
+        &self,
+        request: AwsCompletionRequest,
+    ) -> Result<Vec<aws_sdk_bedrockruntime::types::Message>, CompletionError> {
+        let mut messages = if self.prompt_cache_points {
+            request.into_messages_with_cache_point()
+        } else {
+            request.into_messages()
+        }?;
+
+        if !self.failed_tool_calls.is_empty() {
+            for content in messages
+                .iter_mut()
+                .flat_map(|message| message.content.iter_mut())
+            {
+                if let aws_sdk_bedrockruntime::types::ContentBlock::ToolResult(result) = content
+                    && self.failed_tool_calls.contains(&result.tool_use_id)
+                {
+                    result.status = Some(aws_sdk_bedrockruntime::types::ToolResultStatus::Error);
+                }
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Download images given by URL before sending them to Bedrock, e.g. with [`ImageFetch::new`].
+    /// Off by default.
+    pub fn with_image_fetch(mut self, image_fetch: ImageFetch) -> Self {
+        self.image_fetch = image_fetch;
+        self
+    }
+
+    /// Downscale and recompress images exceeding the Converse limits before sending them, see
+    /// [`crate::image_preprocessing`].
+    #[cfg(feature = "image-preprocessing")]
+    pub fn with_image_preprocessing(mut self, image_preprocessing: ImagePreprocessing) -> Self {
+        self.image_preprocessing = Some(image_preprocessing);
+        self
+    }
+
+    /// Relax the parameters of `request` conflicting with extended thinking, see
+    /// [`AnthropicThinking`], fetch its images given by URL, and preprocess its images if
+    /// enabled.
+    ///
+    /// [`AnthropicThinking`]: crate::model_params::AnthropicThinking
+    pub(crate) async fn prepare_request(
+        &self,
+        mut request: completion::CompletionRequest,
+    ) -> Result<completion::CompletionRequest, CompletionError> {
+        model_params::relax_for_thinking(&mut request);
+        match &self.param_ranges {
+            Some(ranges) => ranges.apply(&self.model, &mut request)?,
+            None => ParamRanges::for_model(&self.model).apply(&self.model, &mut request)?,
+        }
+        self.image_fetch.resolve(&mut request).await?;
+        #[cfg(feature = "image-preprocessing")]
+        if let Some(image_preprocessing) = &self.image_preprocessing {
+            request = image_preprocessing.apply(request).await?;
+        }
+        Ok(request)
+    }
+
+    /// Check requests against `request_limits` before sending them, or not at all with
+    /// [`RequestLimits::disabled`]. See [`crate::request_limits`].
+    pub fn with_request_limits(mut self, request_limits: RequestLimits) -> Self {
+        self.request_limits = request_limits;
+        self
+    }
+
+    /// Check the temperature, top p and max tokens of requests against `param_ranges` instead of
+    /// the ranges of the model family, including when falling back to other models. See
+    /// [`crate::param_ranges`].
+    pub fn with_param_ranges(mut self, param_ranges: ParamRanges) -> Self {
+        self.param_ranges = Some(param_ranges);
+        self
+    }
+
+    /// Send completions through `api`, Converse by default. See [`CompletionApi`].
+    pub fn with_api(mut self, api: CompletionApi) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// Move the `<think>` sections of the text output into reasoning content, see
+    /// [`crate::think_tags`]. Enabled by default for DeepSeek models; enable it for other models
+    /// writing think tags, e.g. imported DeepSeek-R1 distills.
+    pub fn with_think_tags(mut self, enabled: bool) -> Self {
+        self.think_tags = enabled;
+        self
+    }
+
+    /// Attach a [`StreamCancellation`] handle so in-flight streaming completions made with this
+    /// model can be stopped from elsewhere.
+    pub fn with_stream_cancellation(mut self, cancellation: StreamCancellation) -> Self {
+        self.stream_cancellation = Some(cancellation);
+        self
+    }
+
+    /// Attach a `requestMetadata` key/value pair to every Converse call made with this model.
+    /// Entries set here take precedence over the ones configured on the [`Client`].
+    pub fn with_request_metadata(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.request_metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Reject requests without calling Bedrock while `circuit_breaker` is open. The breaker can
+    /// be shared with other completion and embedding models.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Fail fast if the budget is exceeded, or wait for it to be raised if it pauses requests.
+    pub(crate) async fn acquire_budget(&self) -> Result<(), CompletionError> {
+        Ok(self.cost_tracker.acquire_budget().await?)
+    }
+
+    /// Fail fast if the circuit breaker is open.
+    pub(crate) fn acquire_circuit(&self) -> Result<Option<CircuitPermit>, CompletionError> {
+        match &self.circuit_breaker {
+            Some(circuit_breaker) => Ok(Some(circuit_breaker.acquire()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Wait until `request` fits the client's rate limit for this model, if it has one.
+    pub(crate) async fn acquire_rate_limit(&self, request: &CompletionRequest) {
+        if let Some(limiter) = self.client.completion_rate_limits.get(&self.model) {
+            limiter.acquire(estimate_request_tokens(request)).await;
+        }
+    }
+
+    /// Run the steps shared by the Converse and native APIs before building the call of
+    /// `request`: check the budget, prepare the request, sanitize its tool names and prefill,
+    /// check it against the request limits and wait for the rate limit.
+    pub(crate) async fn prepare_call(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<PreparedCall, CompletionError> {
+        self.acquire_budget().await?;
+        let mut request = self.prepare_request(request).await?;
+        let tool_names = ToolNames::sanitize_request(&mut request);
+        let prefill = Prefill::prepare_request(&mut request);
+        self.request_limits.check(&request)?;
+        self.acquire_rate_limit(&request).await;
+        Ok(PreparedCall {
+            request,
+            tool_names,
+            prefill,
+        })
+    }
+
+    /// Make a Bedrock `call` if the circuit breaker lets it through, recording its outcome with
+    /// the breaker and its errors on `span` and `metrics`. The circuit is acquired last, so a
+    /// half-open circuit's probe isn't taken by requests failing before reaching Bedrock.
+    pub(crate) async fn send_call<T>(
+        &self,
+        span: &tracing::Span,
+        metrics: &InvocationMetrics,
+        call: impl Future<Output = Result<T, CompletionError>>,
+    ) -> Result<T, CompletionError> {
+        let permit = self.acquire_circuit()?;
+        let result = call.instrument(span.clone()).await;
+        let bedrock_error = result
+            .as_ref()
+            .err()
+            .and_then(BedrockError::from_completion_error);
+        if result.is_err() {
+            if let Some(bedrock_error) = bedrock_error {
+                telemetry::record_error(span, bedrock_error);
+            }
+            metrics.failure(bedrock_error);
+        }
+        if let Some(permit) = permit {
+            permit.record(bedrock_error);
+        }
+        result
+    }
+
+    /// Give `response` back in the terms of the request prepared by [`Self::prepare_call`]: with
+    /// its original tool names and prefill, and its think sections as reasoning if enabled.
+    pub(crate) fn restore_response(
+        &self,
+        mut response: completion::CompletionResponse<AwsConverseOutput>,
+        tool_names: &ToolNames,
+        prefill: &Prefill,
+    ) -> Result<completion::CompletionResponse<AwsConverseOutput>, CompletionError> {
+        tool_names.restore_choice(&mut response.choice);
+        prefill.restore_choice(&mut response.choice);
+        if self.think_tags {
+            think_tags::extract(response)
+        } else {
+            Ok(response)
+        }
+    }
+
+    /// Log the request and response bodies of the Converse calls made with this model, see
+    /// [`crate::debug_logging`].
+    pub fn with_debug_logging(mut self, debug_logging: DebugLogging) -> Self {
+        self.debug_logging = Some(debug_logging);
+        self
+    }
+
+    /// Estimate costs with `pricing` rather than the on-demand pricing of the model, e.g. for
+    /// models missing from [`crate::pricing`] or with negotiated prices.
+    pub fn with_pricing(mut self, pricing: ModelPricing) -> Self {
+        self.cost_tracker = self.cost_tracker.with_pricing(pricing);
+        self
+    }
+
+    /// The estimated cost of all the calls made with this model and its clones, see
+    /// [`crate::pricing`].
+    pub fn estimated_cost(&self) -> CostEstimate {
+        self.cost_tracker.total()
+    }
+
+    pub fn reset_estimated_cost(&self) {
+        self.cost_tracker.reset();
+    }
+
+    /// Record the usage of the calls made with this model with `usage_tracker`, instead of the
+    /// client's tracker.
+    pub fn with_usage_tracker(mut self, usage_tracker: UsageTracker) -> Self {
+        self.cost_tracker = self.cost_tracker.with_usage_tracker(usage_tracker);
+        self
+    }
+
+    /// Limit the estimated spend of this model and its clones with `budget_guard`, instead of
+    /// the client's guard, see [`crate::budget`].
+    pub fn with_budget_guard(mut self, budget_guard: BudgetGuard) -> Self {
+        self.cost_tracker = self.cost_tracker.with_budget_guard(budget_guard);
+        self
+    }
+
+    /// Retry requests throttled by Bedrock, or sent while the model or the service is
+    /// unavailable, with `policy`.
+    pub fn with_retries(self, policy: RetryPolicy) -> RetryingCompletionModel<Self> {
+        RetryingCompletionModel::new(self, policy)
+    }
+
+    /// When a request is throttled or times out, or the model isn't ready, send it to the next
+    /// of `models` in order, e.g. Nova Lite then Nova Micro after Nova Pro. The fallback models
+    /// are called with the settings of this model, and the model that served a request is
+    /// reported in the `model` of its response.
+    pub fn with_fallback_models(
+        mut self,
+        models: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.fallback_models = models.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The fallback `model`, with the settings of this model.
+    fn fallback(&self, model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            cost_tracker: self.cost_tracker.for_model(model),
+            fallback_models: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Send a duplicate request to `hedge` when this model didn't respond within `delay`, and
+    /// return the first response, see [`crate::hedging`].
+    pub fn with_hedging(
+        self,
+        hedge: CompletionModel,
+        delay: Duration,
+    ) -> HedgedCompletionModel<Self> {
+        HedgedCompletionModel::new(self, hedge, delay)
+    }
+
+    /// Run `hook` on the tool calls of this model and on the tool results sent to it, see
+    /// [`crate::tool_hooks`].
+    pub fn with_tool_hook(self, hook: impl ToolHook) -> ToolHookCompletionModel<Self> {
+        ToolHookCompletionModel::new(self).with_tool_hook(hook)
+    }
+
+    /// The merged client and model level `requestMetadata`, if any was configured.
+    pub(crate) fn request_metadata(&self) -> Option<HashMap<String, String>> {
+        let mut metadata = self.client.request_metadata.clone();
+        metadata.extend(self.request_metadata.clone());
+
+        if metadata.is_empty() {
+            None
+        } else {
+            Some(metadata)
+        }
+    }
+}
+
+impl CompletionModel {
+    /// Complete a request with this model only, without falling back.
+    async fn complete(
         &self,
         completion_request: completion::CompletionRequest,
     ) -> Result<completion::CompletionResponse<AwsConverseOutput>, CompletionError> {
-        let request = AwsCompletionRequest(completion_request);
+        if self.api == CompletionApi::Native {
+            return self.completion_native(completion_request).await;
+        }
+
+        let PreparedCall {
+            request,
+            tool_names,
+            prefill,
+        } = self.prepare_call(completion_request).await?;
+        let request = AwsCompletionRequest(request);
 
         let mut converse_builder = self
             .client
@@ -194,31 +647,497 @@ impl completion::CompletionModel for CompletionModel {
             .converse()
             .model_id(self.model.as_str());
 
-        let tool_config = request.tools_config()?;
-        let messages = request.messages()?;
+        let tool_config = self.tools_config(&request)?;
         converse_builder = converse_builder
             .set_additional_model_request_fields(request.additional_params())
             .set_inference_config(request.inference_config())
             .set_tool_config(tool_config)
-            .set_system(request.system_prompt())
-            .set_messages(Some(messages));
+            .set_system(self.system_prompt(&request)?)
+            .set_messages(Some(self.messages(request)?))
+            .set_request_metadata(self.request_metadata())
+            .set_guardrail_config(
+                self.guardrail
+                    .as_ref()
+                    .map(GuardrailConfig::converse)
+                    .transpose()?,
+            );
 
-        let response = converse_builder
-            .send()
-            .await
-            .map_err(|sdk_error| Into::<CompletionError>::into(AwsSdkConverseError(sdk_error)))?;
+        let _permit = self.client.concurrency.acquire_completion().await;
+        let span = telemetry::chat_span(&self.model);
+        let metrics = InvocationMetrics::start("chat", &self.model);
+        let mut operation = converse_builder.customize();
+        if let Some(debug_logging) = &self.debug_logging {
+            operation = operation.interceptor(debug_logging.clone());
+        }
+        let response = self
+            .send_call(&span, &metrics, async {
+                operation
+                    .send()
+                    .await
+                    .map_err(|sdk_error| AwsSdkConverseError(sdk_error).into())
+            })
+            .await?;
+
+        let mut response: InternalConverseOutput = response.try_into().map_err(|x| {
+            metrics.failure(None);
+            CompletionError::ProviderError(format!("Type conversion error: {x}"))
+        })?;
+
+        telemetry::record_finish_reason(&span, &response.stop_reason);
+        let usage = response.usage.clone().map(BedrockUsage::from);
+        response.estimated_cost = record_usage(&span, &metrics, &self.cost_tracker, usage.as_ref());
+        if let Some(metrics) = &response.metrics {
+            telemetry::record_latency(&span, metrics.latency_ms);
+        }
+        response.model = Some(self.model.clone());
+
+        if response.stop_reason == StopReason::GuardrailIntervened {
+            return Err(guardrail_intervention(&response).into());
+        }
+        self.restore_response(
+            AwsConverseOutput(response).try_into()?,
+            &tool_names,
+            &prefill,
+        )
+    }
+
+    /// Complete a request with this model, then with the fallback models.
+    async fn complete_with_fallbacks(
+        &self,
+        request: completion::CompletionRequest,
+    ) -> Result<completion::CompletionResponse<AwsConverseOutput>, CompletionError> {
+        let mut result = self.complete(request.clone()).await;
+        for model in &self.fallback_models {
+            match &result {
+                Err(error) if is_fallback_error(error) => tracing::warn!(
+                    target: "rig::bedrock",
+                    "Bedrock request failed, falling back to model {model}: {error}"
+                ),
+                _ => break,
+            }
+            result = self.fallback(model).complete(request.clone()).await;
+        }
+        result
+    }
+}
+
+impl completion::CompletionModel for CompletionModel {
+    type Response = AwsConverseOutput;
+    type StreamingResponse = crate::streaming::BedrockStreamingResponse;
+
+    type Client = Client;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self::new(client.clone(), model)
+    }
 
-        let response: InternalConverseOutput = response
-            .try_into()
-            .map_err(|x| CompletionError::ProviderError(format!("Type conversion error: {x}")))?;
+    async fn completion(
+        &self,
+        mut request: completion::CompletionRequest,
+    ) -> Result<completion::CompletionResponse<AwsConverseOutput>, CompletionError> {
+        let Some(validation) = self.tool_call_validation else {
+            return self.complete_with_fallbacks(request).await;
+        };
+
+        let mut model = self.clone();
+        let mut usage = completion::Usage::new();
+        for _ in 0..self.tool_call_repairs {
+            let mut response = model.complete_with_fallbacks(request.clone()).await?;
+            usage += response.usage;
+            let Some(results) = repair_results(&request.tools, &response.choice) else {
+                response.usage = usage;
+                return Ok(response);
+            };
+
+            tracing::warn!(
+                target: "rig::bedrock",
+                "The model called tools with invalid arguments, asking it to repair them"
+            );
+            model
+                .failed_tool_calls
+                .extend(results.iter().filter_map(|content| match content {
+                    UserContent::ToolResult(result) => Some(result.id.clone()),
+                    _ => None,
+                }));
+            request.chat_history.push(Message::Assistant {
+                id: None,
+                content: response.choice,
+            });
+            request
+                .chat_history
+                .push(Message::User { content: results });
+        }
 
-        AwsConverseOutput(response).try_into()
+        let mut response = model.complete_with_fallbacks(request.clone()).await?;
+        validation.check(&request.tools, &response.choice)?;
+        response.usage += usage;
+        Ok(response)
     }
 
     async fn stream(
         &self,
         request: CompletionRequest,
     ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
-        CompletionModel::stream(self, request).await
+        let mut result = CompletionModel::stream(self, request.clone()).await;
+        for model in &self.fallback_models {
+            match &result {
+                Err(error) if is_fallback_error(error) => tracing::warn!(
+                    target: "rig::bedrock",
+                    "Bedrock request failed, falling back to model {model}: {error}"
+                ),
+                _ => break,
+            }
+            result = CompletionModel::stream(&self.fallback(model), request.clone()).await;
+        }
+        result
+    }
+}
+
+/// A request ready to be sent, see [`CompletionModel::prepare_call`].
+pub(crate) struct PreparedCall {
+    pub request: CompletionRequest,
+    pub tool_names: ToolNames,
+    pub prefill: Prefill,
+}
+
+/// Record the usage of a successful call on its span and metrics, returning its estimated cost.
+pub(crate) fn record_usage(
+    span: &tracing::Span,
+    metrics: &InvocationMetrics,
+    cost_tracker: &CostTracker,
+    usage: Option<&BedrockUsage>,
+) -> Option<CostEstimate> {
+    if let Some(usage) = usage {
+        telemetry::record_usage(
+            span,
+            usage.input_tokens as u64,
+            Some(usage.output_tokens as u64),
+        );
+    }
+    metrics.success(
+        usage.map(|usage| usage.input_tokens as u64),
+        usage.map(|usage| usage.output_tokens as u64),
+    );
+    usage.and_then(|usage| {
+        cost_tracker.record(
+            usage.input_tokens as u64,
+            usage.output_tokens as u64,
+            usage.cache_read_input_tokens.unwrap_or_default() as u64,
+            usage.cache_write_input_tokens.unwrap_or_default() as u64,
+        )
+    })
+}
+
+/// The intervention of a guardrail in `response`, with its text and the guardrail assessment.
+fn guardrail_intervention(response: &InternalConverseOutput) -> GuardrailInterventionError {
+    let output = match &response.output {
+        Some(ConverseOutput::Message(message)) => message
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => String::new(),
+    };
+    let assessment = response
+        .trace
+        .as_ref()
+        .and_then(|trace| trace.guardrail.clone());
+    GuardrailInterventionError::new(output, assessment)
+}
+
+/// Whether a request may succeed with a fallback model: it was throttled or timed out, or the
+/// model wasn't ready.
+fn is_fallback_error(error: &CompletionError) -> bool {
+    BedrockError::from_completion_error(error).is_some_and(|error| {
+        matches!(
+            error.kind(),
+            BedrockErrorKind::Throttled
+                | BedrockErrorKind::Timeout
+                | BedrockErrorKind::ModelTimeout
+                | BedrockErrorKind::ModelNotReady
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_metadata_merges_client_and_model_entries() {
+        let client = Client::from(aws_sdk_bedrockruntime::Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version_latest()
+                .build(),
+        ))
+        .with_request_metadata("tenant", "acme")
+        .with_request_metadata("feature", "search");
+
+        let model = CompletionModel::new(client, AMAZON_NOVA_LITE)
+            .with_request_metadata("feature", "summarize")
+            .with_request_metadata("trace_id", "abc-123");
+
+        let metadata = model.request_metadata().expect("metadata should be set");
+        assert_eq!(metadata.len(), 3);
+        assert_eq!(metadata["tenant"], "acme");
+        assert_eq!(metadata["feature"], "summarize");
+        assert_eq!(metadata["trace_id"], "abc-123");
+    }
+
+    #[test]
+    fn test_request_metadata_is_omitted_when_empty() {
+        let client = Client::from(aws_sdk_bedrockruntime::Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version_latest()
+                .build(),
+        ));
+        let model = CompletionModel::new(client, AMAZON_NOVA_LITE);
+
+        assert!(model.request_metadata().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_next_model() {
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::error(
+                429,
+                "ThrottlingException",
+                "Too many requests, please wait before trying again.",
+            ))
+            .with_response(MockResponse::error(
+                429,
+                "ModelNotReadyException",
+                "Model is not ready for inference.",
+            ))
+            .with_response(MockResponse::text("Hello"));
+        let model = CompletionModel::new(mock.client(), AMAZON_NOVA_PRO)
+            .with_fallback_models([AMAZON_NOVA_LITE, AMAZON_NOVA_MICRO]);
+
+        let response = model
+            .completion(model.completion_request("Hi").build())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.raw_response.0.model.as_deref(),
+            Some(AMAZON_NOVA_MICRO)
+        );
+        let model_ids = mock
+            .requests()
+            .iter()
+            .filter_map(|request| request.model_id())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            model_ids,
+            vec![AMAZON_NOVA_PRO, AMAZON_NOVA_LITE, AMAZON_NOVA_MICRO]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_completion_rate_limit_delays_requests() {
+        use crate::rate_limit::RateLimiter;
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::text("Hello"))
+            .with_response(MockResponse::text("Hello"));
+        let client = mock.client().with_completion_rate_limit(
+            AMAZON_NOVA_LITE,
+            RateLimiter::new().with_requests_per_minute(1),
+        );
+        let model = CompletionModel::new(client.clone(), AMAZON_NOVA_LITE);
+        let request = model.completion_request("Hi").build();
+
+        model.completion(request.clone()).await.unwrap();
+        // The second request waits for the next minute
+        let second = tokio::time::timeout(Duration::from_millis(100), model.completion(request));
+        assert!(second.await.is_err());
+        assert_eq!(mock.requests().len(), 1);
+
+        // Other models aren't limited
+        let other = CompletionModel::new(client, AMAZON_NOVA_MICRO);
+        other
+            .completion(other.completion_request("Hi").build())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_budget_guard_rejects_requests_over_budget() {
+        use crate::budget::BudgetGuard;
+        use crate::testing::{MockBedrock, MockResponse};
+        use crate::types::errors::BudgetExceededError;
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::text("Hello"))
+            .with_response(MockResponse::text("Hello"));
+        let budget = BudgetGuard::new(0.000_001);
+        let model = CompletionModel::new(
+            mock.client().with_budget_guard(budget.clone()),
+            AMAZON_NOVA_LITE,
+        );
+        let request = model.completion_request("Hi").build();
+
+        model.completion(request.clone()).await.unwrap();
+        assert!(budget.is_exceeded());
+
+        let Err(error) = model.completion(request).await else {
+            panic!("expected the request to be rejected");
+        };
+        assert!(BudgetExceededError::from_completion_error(&error).is_some());
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_repairs_invalid_tool_calls() {
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::{CompletionModel as _, ToolDefinition};
+
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::tool_use(
+                "tool_1",
+                "get_weather",
+                serde_json::json!({ "town": "Lisbon" }),
+            ))
+            .with_response(MockResponse::tool_use(
+                "tool_2",
+                "get_weather",
+                serde_json::json!({ "city": "Lisbon" }),
+            ));
+        let model = CompletionModel::new(mock.client(), AMAZON_NOVA_PRO).with_tool_call_repair(2);
+        let request = model
+            .completion_request("What's the weather in Lisbon?")
+            .tool(ToolDefinition {
+                name: "get_weather".into(),
+                description: "Get the weather of a city".into(),
+                parameters: serde_json::json!({
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                    "required": ["city"]
+                }),
+            })
+            .build();
+
+        let response = model.completion(request).await.unwrap();
+
+        let rig::message::AssistantContent::ToolCall(tool_call) = response.choice.first() else {
+            panic!("expected a tool call");
+        };
+        assert_eq!(tool_call.id, "tool_2");
+        assert_eq!(response.usage.input_tokens, 20);
+
+        let repair = mock.requests()[1].json().unwrap();
+        let result = &repair["messages"][2]["content"][0]["toolResult"];
+        assert_eq!(result["toolUseId"], "tool_1");
+        assert_eq!(result["status"], "error");
+        assert!(
+            result["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("missing required property `city`")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_guardrail_intervention_fails_the_completion() {
+        use crate::guardrails::{GuardrailFinding, GuardrailPolicy, GuardrailSource};
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new().with_response(MockResponse::json(
+            200,
+            serde_json::json!({
+                "output": { "message": {
+                    "role": "assistant",
+                    "content": [{ "text": "Sorry, I can't help with that." }]
+                } },
+                "stopReason": "guardrail_intervened",
+                "usage": { "inputTokens": 10, "outputTokens": 0, "totalTokens": 10 },
+                "metrics": { "latencyMs": 1 },
+                "trace": { "guardrail": {
+                    "inputAssessment": { "gr-1": { "topicPolicy": { "topics": [{
+                        "name": "Investment advice",
+                        "type": "DENY",
+                        "action": "BLOCKED",
+                        "detected": true
+                    }] } } },
+                    "actionReason": "Guardrail blocked."
+                } }
+            }),
+        ));
+        let model = CompletionModel::new(mock.client(), AMAZON_NOVA_PRO)
+            .with_guardrail(GuardrailConfig::new("gr-1", "2"));
+
+        let Err(error) = model
+            .completion(
+                model
+                    .completion_request("Which stocks should I buy?")
+                    .build(),
+            )
+            .await
+        else {
+            panic!("expected a guardrail intervention");
+        };
+
+        let intervention = GuardrailInterventionError::from_completion_error(&error).unwrap();
+        assert_eq!(intervention.output, "Sorry, I can't help with that.");
+        assert_eq!(
+            intervention.action_reason.as_deref(),
+            Some("Guardrail blocked.")
+        );
+        assert_eq!(
+            intervention.findings,
+            vec![GuardrailFinding {
+                source: GuardrailSource::Input,
+                policy: GuardrailPolicy::Topic,
+                name: "Investment advice".into(),
+                blocked: true,
+            }]
+        );
+        assert!(intervention.is_input_blocked());
+        let request = mock.requests()[0].json().unwrap();
+        assert_eq!(
+            request["guardrailConfig"],
+            serde_json::json!({
+                "guardrailIdentifier": "gr-1",
+                "guardrailVersion": "2",
+                "trace": "enabled"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_agent_with_prompt_caching() {
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::Prompt;
+
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello"));
+        let agent = mock
+            .client()
+            .agent_with_prompt_caching(ANTHROPIC_CLAUDE_3_5_HAIKU)
+            .preamble("You are a helpful assistant")
+            .context("Rust is a systems programming language")
+            .build();
+
+        agent.prompt("Hi").await.unwrap();
+
+        let request = mock.requests()[0].json().unwrap();
+        assert_eq!(
+            request["system"][1],
+            serde_json::json!({ "cachePoint": { "type": "default" } })
+        );
+        let documents = request["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(
+            documents.last().unwrap(),
+            &serde_json::json!({ "cachePoint": { "type": "default" } })
+        );
     }
 }
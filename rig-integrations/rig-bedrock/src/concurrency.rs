@@ -0,0 +1,117 @@
+//! Client-side limits on the number of in-flight requests, to cap concurrency to the Bedrock
+//! account quotas instead of sending bursts of requests that get throttled all at once.
+//!
+//! ```rust,ignore
+//! let client = Client::from_env()
+//!     .with_completion_concurrency(ConcurrencyLimiter::new(16))
+//!     .with_embedding_concurrency(ConcurrencyLimiter::new(64));
+//! ```
+//!
+//! Requests over the limit wait for an earlier one to finish, in order. Streaming completions
+//! count as in flight until their stream ends or is dropped, and retried embedding requests don't
+//! hold a slot while waiting to retry.
+
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Limits the number of concurrent requests sent to Bedrock.
+///
+/// A limiter can be cloned and shared between clients, in which case they share the same slots.
+#[derive(Clone, Debug)]
+pub struct ConcurrencyLimiter {
+    semaphore: Arc<Semaphore>,
+    max_in_flight: usize,
+}
+
+impl ConcurrencyLimiter {
+    /// Allow at most `max_in_flight` requests at once, at least one.
+    pub fn new(max_in_flight: usize) -> Self {
+        let max_in_flight = max_in_flight.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_in_flight)),
+            max_in_flight,
+        }
+    }
+
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    /// The number of requests currently holding a slot.
+    pub fn in_flight(&self) -> usize {
+        self.max_in_flight - self.semaphore.available_permits()
+    }
+
+    /// Wait for a free slot, which is released when the permit is dropped.
+    pub(crate) async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("concurrency limiter semaphore is never closed")
+    }
+}
+
+/// The concurrency limits of a [`Client`](crate::client::Client).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ConcurrencyLimits {
+    pub(crate) completions: Option<ConcurrencyLimiter>,
+    pub(crate) embeddings: Option<ConcurrencyLimiter>,
+}
+
+impl ConcurrencyLimits {
+    pub(crate) async fn acquire_completion(&self) -> Option<OwnedSemaphorePermit> {
+        match &self.completions {
+            Some(limiter) => Some(limiter.acquire().await),
+            None => None,
+        }
+    }
+
+    pub(crate) async fn acquire_embedding(&self) -> Option<OwnedSemaphorePermit> {
+        match &self.embeddings {
+            Some(limiter) => Some(limiter.acquire().await),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::client::CompletionClient;
+    use rig::completion::CompletionModel as _;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_waits_for_a_free_slot() {
+        let limiter = ConcurrencyLimiter::new(1);
+        let permit = limiter.acquire().await;
+        assert_eq!(limiter.in_flight(), 1);
+
+        let waiting = tokio::time::timeout(Duration::from_millis(20), limiter.acquire()).await;
+        assert!(waiting.is_err());
+
+        drop(permit);
+        let _permit = limiter.acquire().await;
+        assert_eq!(limiter.in_flight(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_streams_hold_a_slot_until_dropped() {
+        let limiter = ConcurrencyLimiter::new(2);
+        let mock = MockBedrock::new().with_response(MockResponse::text_stream(["Hello"]));
+        let model = mock
+            .client()
+            .with_completion_concurrency(limiter.clone())
+            .completion_model("amazon.nova-lite-v1:0");
+
+        let request = model.completion_request("Hi").build();
+        let stream = model.stream(request).await.unwrap();
+        assert_eq!(limiter.in_flight(), 1);
+
+        drop(stream);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+}
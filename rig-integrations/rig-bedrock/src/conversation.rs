@@ -0,0 +1,419 @@
+//! Serializable conversation state, to persist the history of long-running agents and resume it
+//! after a restart.
+//!
+//! A [`Conversation`] holds rig messages, and Bedrock messages for turns whose content rig
+//! messages can't represent, such as prompt cache points or redacted reasoning.
+//!
+//! ```rust,ignore
+//! let mut conversation = Conversation::load("session.json")
+//!     .await
+//!     .unwrap_or_else(|_| Conversation::new().with_model(AMAZON_NOVA_PRO));
+//!
+//! let mut history = conversation.chat_history()?;
+//! let answer = agent.prompt(prompt).with_history(&mut history).await?;
+//! conversation.replace_history(history);
+//!
+//! conversation.save("session.json").await?;
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use aws_sdk_bedrockruntime::types as aws_bedrock;
+use rig::completion::{CompletionError, CompletionResponse};
+use rig::message::Message;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::converse_json;
+use crate::types::assistant_content::AwsConverseOutput;
+use crate::types::converse_output::{
+    self, CachePointBlock, CachePointType, ContentBlock, ReasoningContentBlock,
+};
+use crate::types::errors::TypeConversionError;
+use crate::types::message::RigMessage;
+
+/// The version of the serialized [`Conversation`] format.
+pub const CONVERSATION_FORMAT_VERSION: u32 = 1;
+
+/// A message of a [`Conversation`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "format", content = "message", rename_all = "snake_case")]
+pub enum ConversationMessage {
+    Rig(Message),
+    /// A message in the Bedrock Converse format, keeping the blocks rig messages can't
+    /// represent.
+    Bedrock(converse_output::Message),
+}
+
+impl ConversationMessage {
+    /// The message as a rig message. Prompt cache points and redacted reasoning are dropped.
+    pub fn to_rig(&self) -> Result<Message, CompletionError> {
+        match self {
+            Self::Rig(message) => Ok(message.clone()),
+            Self::Bedrock(message) => {
+                let message = converse_output::Message {
+                    role: message.role.clone(),
+                    content: message
+                        .content
+                        .iter()
+                        .filter(|block| {
+                            !matches!(
+                                block,
+                                ContentBlock::CachePoint(_)
+                                    | ContentBlock::ReasoningContent(
+                                        ReasoningContentBlock::RedactedContent(_)
+                                    )
+                            )
+                        })
+                        .cloned()
+                        .collect(),
+                };
+                RigMessage::try_from(message).map(|message| message.0)
+            }
+        }
+    }
+
+    /// The message in the Bedrock Converse format.
+    pub fn to_bedrock(&self) -> Result<converse_output::Message, CompletionError> {
+        match self {
+            Self::Rig(message) => {
+                let message = aws_bedrock::Message::try_from(RigMessage(message.clone()))?;
+                converse_output::Message::try_from(message).map_err(|e| {
+                    CompletionError::RequestError(format!("Type conversion error: {e}").into())
+                })
+            }
+            Self::Bedrock(message) => Ok(message.clone()),
+        }
+    }
+}
+
+impl From<Message> for ConversationMessage {
+    fn from(message: Message) -> Self {
+        Self::Rig(message)
+    }
+}
+
+impl From<converse_output::Message> for ConversationMessage {
+    fn from(message: converse_output::Message) -> Self {
+        Self::Bedrock(message)
+    }
+}
+
+/// The state of a conversation, serializable with serde, see [`crate::conversation`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Conversation {
+    #[serde(default = "format_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub preamble: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<ConversationMessage>,
+}
+
+impl Default for Conversation {
+    fn default() -> Self {
+        Self {
+            version: CONVERSATION_FORMAT_VERSION,
+            model: None,
+            preamble: None,
+            messages: Vec::new(),
+        }
+    }
+}
+
+impl Conversation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_preamble(mut self, preamble: impl Into<String>) -> Self {
+        self.preamble = Some(preamble.into());
+        self
+    }
+
+    pub fn push(&mut self, message: impl Into<ConversationMessage>) {
+        self.messages.push(message.into());
+    }
+
+    /// Append the message of a Converse completion response, as returned by Bedrock, so its
+    /// reasoning signatures and redacted reasoning are sent back on the next turn.
+    pub fn push_response(&mut self, response: &CompletionResponse<AwsConverseOutput>) {
+        let message = response
+            .raw_response
+            .0
+            .output
+            .as_ref()
+            .and_then(|output| output.as_message().ok());
+
+        match message {
+            Some(message) => self.push(message.clone()),
+            None => self.push(Message::Assistant {
+                id: None,
+                content: response.choice.clone(),
+            }),
+        }
+    }
+
+    /// Append a prompt cache point to the last message, so the conversation up to it is cached.
+    /// Does nothing if the conversation is empty.
+    pub fn push_cache_point(&mut self) -> Result<(), CompletionError> {
+        let Some(last) = self.messages.last_mut() else {
+            return Ok(());
+        };
+
+        let mut message = last.to_bedrock()?;
+        message
+            .content
+            .push(ContentBlock::CachePoint(CachePointBlock {
+                kind: CachePointType::Default,
+            }));
+        *last = ConversationMessage::Bedrock(message);
+        Ok(())
+    }
+
+    /// Replace the messages with `history`, e.g. after a multi-turn agent prompt extended it.
+    /// Bedrock messages that are still at the start of `history` are kept as is.
+    pub fn replace_history(&mut self, history: Vec<Message>) {
+        let kept = self
+            .messages
+            .iter()
+            .zip(&history)
+            .take_while(|(message, rig)| message.to_rig().is_ok_and(|message| message == **rig))
+            .count();
+
+        self.messages.truncate(kept);
+        self.messages
+            .extend(history.into_iter().skip(kept).map(ConversationMessage::Rig));
+    }
+
+    /// The messages as rig messages, e.g. the chat history of an agent. Prompt cache points
+    /// and redacted reasoning are dropped.
+    pub fn chat_history(&self) -> Result<Vec<Message>, CompletionError> {
+        self.messages
+            .iter()
+            .map(ConversationMessage::to_rig)
+            .collect()
+    }
+
+    /// The messages as Bedrock SDK messages, including every Bedrock-specific block, for
+    /// requests sent with the SDK client.
+    pub fn bedrock_messages(&self) -> Result<Vec<aws_bedrock::Message>, CompletionError> {
+        self.messages
+            .iter()
+            .map(|message| {
+                aws_bedrock::Message::try_from(message.to_bedrock()?).map_err(|e| {
+                    CompletionError::RequestError(format!("Type conversion error: {e}").into())
+                })
+            })
+            .collect()
+    }
+
+    /// A conversation from Converse JSON, keeping every Bedrock-specific block. The text of the
+    /// `system` blocks of a request body becomes the preamble. See [`crate::converse_json`].
+    pub fn from_converse_json(json: &Value) -> Result<Self, TypeConversionError> {
+        let preamble = json
+            .get("system")
+            .and_then(Value::as_array)
+            .map(|system| {
+                system
+                    .iter()
+                    .filter_map(|block| block.get("text").and_then(Value::as_str))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .filter(|preamble| !preamble.is_empty());
+
+        let messages = converse_json::bedrock_messages_from_converse_json(json)?
+            .into_iter()
+            .map(|message| converse_output::Message::try_from(message).map(Into::into))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            model: None,
+            preamble,
+            messages,
+            ..Self::default()
+        })
+    }
+
+    /// The conversation as a Converse request body with its `system` and `messages`.
+    pub fn to_converse_json(&self) -> Result<Value, TypeConversionError> {
+        let messages = self
+            .messages
+            .iter()
+            .map(|message| {
+                message
+                    .to_bedrock()
+                    .map_err(|e| TypeConversionError::new(&e.to_string()))
+                    .and_then(aws_bedrock::Message::try_from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut json = serde_json::json!({
+            "messages": converse_json::bedrock_messages_to_converse_json(&messages)?,
+        });
+        if let Some(preamble) = &self.preamble {
+            json["system"] = serde_json::json!([{ "text": preamble }]);
+        }
+        Ok(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, ConversationError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ConversationError> {
+        let conversation: Self = serde_json::from_str(json)?;
+        if conversation.version > CONVERSATION_FORMAT_VERSION {
+            return Err(ConversationError::UnsupportedVersion(conversation.version));
+        }
+        Ok(conversation)
+    }
+
+    /// Save the conversation as JSON to `path`, replacing the file if it exists.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), ConversationError> {
+        tokio::fs::write(path, self.to_json()?).await?;
+        Ok(())
+    }
+
+    /// Load a conversation saved with [`Conversation::save`].
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, ConversationError> {
+        Self::from_json(&tokio::fs::read_to_string(path).await?)
+    }
+}
+
+fn format_version() -> u32 {
+    CONVERSATION_FORMAT_VERSION
+}
+
+/// Failure to save or load a [`Conversation`].
+#[derive(Debug)]
+pub enum ConversationError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The conversation was saved by a newer version of this crate.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ConversationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Failed to read or write the conversation: {error}"),
+            Self::Json(error) => write!(f, "Invalid conversation JSON: {error}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported conversation format version {version}, the latest supported is \
+                 {CONVERSATION_FORMAT_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConversationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Json(error) => Some(error),
+            Self::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConversationError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ConversationError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::converse_output::{Blob, ConversationRole, ReasoningTextBlock};
+
+    fn reasoning_message() -> converse_output::Message {
+        converse_output::Message {
+            role: ConversationRole::Assistant,
+            content: vec![
+                ContentBlock::ReasoningContent(ReasoningContentBlock::ReasoningText(
+                    ReasoningTextBlock {
+                        text: "The user greets me".into(),
+                        signature: Some("sig".into()),
+                    },
+                )),
+                ContentBlock::ReasoningContent(ReasoningContentBlock::RedactedContent(Blob {
+                    inner: vec![1, 2, 3],
+                })),
+                ContentBlock::Text("Hello!".into()),
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let mut conversation = Conversation::new()
+            .with_model("anthropic.claude-3-7-sonnet-20250219-v1:0")
+            .with_preamble("You are a helpful assistant");
+        conversation.push(Message::user("Hi"));
+        conversation.push_cache_point().unwrap();
+        conversation.push(reasoning_message());
+
+        let path = std::env::temp_dir().join(format!("conversation-{}.json", uuid::Uuid::new_v4()));
+        conversation.save(&path).await.unwrap();
+        let loaded = Conversation::load(&path).await.unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(loaded, conversation);
+
+        let messages = loaded.bedrock_messages().unwrap();
+        assert!(matches!(
+            messages[0].content().last(),
+            Some(aws_bedrock::ContentBlock::CachePoint(_))
+        ));
+        assert_eq!(messages[1].content().len(), 3);
+    }
+
+    #[test]
+    fn test_chat_history_drops_bedrock_blocks() {
+        let mut conversation = Conversation::new();
+        conversation.push(Message::user("Hi"));
+        conversation.push_cache_point().unwrap();
+        conversation.push(reasoning_message());
+
+        let history = conversation.chat_history().unwrap();
+        assert_eq!(history[0], Message::user("Hi"));
+        let Message::Assistant { content, .. } = &history[1] else {
+            panic!("expected an assistant message");
+        };
+        assert_eq!(content.len(), 2);
+
+        // Extending the history keeps the Bedrock messages it starts with
+        let mut extended = history;
+        extended.push(Message::user("How are you?"));
+        conversation.replace_history(extended);
+        assert_eq!(conversation.messages.len(), 3);
+        assert!(matches!(
+            conversation.messages[1],
+            ConversationMessage::Bedrock(_)
+        ));
+    }
+
+    #[test]
+    fn test_rejects_newer_versions() {
+        let error = Conversation::from_json(r#"{ "version": 2, "messages": [] }"#).unwrap_err();
+        assert!(matches!(error, ConversationError::UnsupportedVersion(2)));
+    }
+}
@@ -0,0 +1,515 @@
+//! Conversion between messages and the JSON wire format of the Converse API, e.g. to replay
+//! transcripts captured from Bedrock model invocation logs or sent with another SDK through rig
+//! agents.
+//!
+//! ```rust,ignore
+//! // The `input.inputBodyJson` of an invocation log record
+//! let history = messages_from_converse_json(&record["input"]["inputBodyJson"])?;
+//! let answer = agent.chat("And in Python?", history).await?;
+//! ```
+//!
+//! The JSON is either a `messages` array or a Converse request body containing one. Binary
+//! content (images, documents, videos and redacted reasoning) is base64-encoded, as in the
+//! wire format.
+
+use aws_sdk_bedrockruntime::types as aws_bedrock;
+use aws_smithy_types::Blob;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use rig::message::Message;
+use serde_json::{Map, Value, json};
+
+use crate::conversation::ConversationMessage;
+use crate::types::converse_output;
+use crate::types::errors::TypeConversionError;
+use crate::types::json::AwsDocument;
+use crate::types::message::RigMessage;
+
+/// Parse the rig messages of Converse JSON. Blocks rig messages can't represent, such as
+/// prompt cache points and redacted reasoning, are dropped.
+pub fn messages_from_converse_json(json: &Value) -> Result<Vec<Message>, TypeConversionError> {
+    bedrock_messages_from_converse_json(json)?
+        .into_iter()
+        .map(|message| {
+            ConversationMessage::Bedrock(converse_output::Message::try_from(message)?)
+                .to_rig()
+                .map_err(|e| TypeConversionError::new(&e.to_string()))
+        })
+        .collect()
+}
+
+/// Serialize rig messages as a Converse `messages` array.
+pub fn messages_to_converse_json(messages: &[Message]) -> Result<Value, TypeConversionError> {
+    let messages = messages
+        .iter()
+        .map(|message| {
+            aws_bedrock::Message::try_from(RigMessage(message.clone()))
+                .map_err(|e| TypeConversionError::new(&e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    bedrock_messages_to_converse_json(&messages)
+}
+
+/// Parse the Bedrock SDK messages of Converse JSON, keeping every block.
+pub fn bedrock_messages_from_converse_json(
+    json: &Value,
+) -> Result<Vec<aws_bedrock::Message>, TypeConversionError> {
+    let messages = match json {
+        Value::Object(request) => request.get("messages").unwrap_or(&Value::Null),
+        messages => messages,
+    };
+    let Value::Array(messages) = messages else {
+        return Err(TypeConversionError::new(
+            "Expected a Converse `messages` array or a request body containing one",
+        ));
+    };
+
+    messages.iter().map(message_from_json).collect()
+}
+
+/// Serialize Bedrock SDK messages as a Converse `messages` array.
+pub fn bedrock_messages_to_converse_json(
+    messages: &[aws_bedrock::Message],
+) -> Result<Value, TypeConversionError> {
+    messages
+        .iter()
+        .map(message_to_json)
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+fn message_from_json(json: &Value) -> Result<aws_bedrock::Message, TypeConversionError> {
+    let content = array_field(json, "content")?
+        .iter()
+        .map(content_block_from_json)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(aws_bedrock::Message::builder()
+        .role(aws_bedrock::ConversationRole::from(str_field(
+            json, "role",
+        )?))
+        .set_content(Some(content))
+        .build()?)
+}
+
+fn message_to_json(message: &aws_bedrock::Message) -> Result<Value, TypeConversionError> {
+    let content = message
+        .content()
+        .iter()
+        .map(content_block_to_json)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(json!({ "role": message.role().as_str(), "content": content }))
+}
+
+fn content_block_from_json(json: &Value) -> Result<aws_bedrock::ContentBlock, TypeConversionError> {
+    let (kind, value) = single_entry(json)?;
+    let block = match kind {
+        "text" => aws_bedrock::ContentBlock::Text(as_str(value, "text")?.to_string()),
+        "image" => aws_bedrock::ContentBlock::Image(image_from_json(value)?),
+        "document" => aws_bedrock::ContentBlock::Document(document_from_json(value)?),
+        "video" => aws_bedrock::ContentBlock::Video(video_from_json(value)?),
+        "toolUse" => aws_bedrock::ContentBlock::ToolUse(
+            aws_bedrock::ToolUseBlock::builder()
+                .tool_use_id(str_field(value, "toolUseId")?)
+                .name(str_field(value, "name")?)
+                .input(AwsDocument::from(field(value, "input")?.clone()).0)
+                .build()?,
+        ),
+        "toolResult" => {
+            let content = array_field(value, "content")?
+                .iter()
+                .map(tool_result_content_from_json)
+                .collect::<Result<Vec<_>, _>>()?;
+            aws_bedrock::ContentBlock::ToolResult(
+                aws_bedrock::ToolResultBlock::builder()
+                    .tool_use_id(str_field(value, "toolUseId")?)
+                    .set_content(Some(content))
+                    .set_status(
+                        value
+                            .get("status")
+                            .and_then(Value::as_str)
+                            .map(aws_bedrock::ToolResultStatus::from),
+                    )
+                    .build()?,
+            )
+        }
+        "reasoningContent" => {
+            let (kind, value) = single_entry(value)?;
+            aws_bedrock::ContentBlock::ReasoningContent(match kind {
+                "reasoningText" => aws_bedrock::ReasoningContentBlock::ReasoningText(
+                    aws_bedrock::ReasoningTextBlock::builder()
+                        .text(str_field(value, "text")?)
+                        .set_signature(
+                            value
+                                .get("signature")
+                                .and_then(Value::as_str)
+                                .map(str::to_string),
+                        )
+                        .build()?,
+                ),
+                "redactedContent" => {
+                    aws_bedrock::ReasoningContentBlock::RedactedContent(bytes_from_json(value)?)
+                }
+                kind => return Err(unsupported("reasoningContent", kind)),
+            })
+        }
+        "cachePoint" => aws_bedrock::ContentBlock::CachePoint(
+            aws_bedrock::CachePointBlock::builder()
+                .r#type(aws_bedrock::CachePointType::from(str_field(value, "type")?))
+                .build()?,
+        ),
+        kind => return Err(unsupported("content", kind)),
+    };
+    Ok(block)
+}
+
+fn content_block_to_json(block: &aws_bedrock::ContentBlock) -> Result<Value, TypeConversionError> {
+    let json = match block {
+        aws_bedrock::ContentBlock::Text(text) => json!({ "text": text }),
+        aws_bedrock::ContentBlock::Image(image) => json!({ "image": image_to_json(image)? }),
+        aws_bedrock::ContentBlock::Document(document) => {
+            json!({ "document": document_to_json(document)? })
+        }
+        aws_bedrock::ContentBlock::Video(video) => json!({ "video": video_to_json(video)? }),
+        aws_bedrock::ContentBlock::ToolUse(tool_use) => json!({
+            "toolUse": {
+                "toolUseId": tool_use.tool_use_id(),
+                "name": tool_use.name(),
+                "input": Value::from(AwsDocument(tool_use.input().clone())),
+            }
+        }),
+        aws_bedrock::ContentBlock::ToolResult(tool_result) => {
+            let content = tool_result
+                .content()
+                .iter()
+                .map(tool_result_content_to_json)
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut json = json!({
+                "toolUseId": tool_result.tool_use_id(),
+                "content": content,
+            });
+            if let Some(status) = tool_result.status() {
+                json["status"] = status.as_str().into();
+            }
+            json!({ "toolResult": json })
+        }
+        aws_bedrock::ContentBlock::ReasoningContent(
+            aws_bedrock::ReasoningContentBlock::ReasoningText(reasoning),
+        ) => {
+            let mut json = json!({ "text": reasoning.text() });
+            if let Some(signature) = reasoning.signature() {
+                json["signature"] = signature.into();
+            }
+            json!({ "reasoningContent": { "reasoningText": json } })
+        }
+        aws_bedrock::ContentBlock::ReasoningContent(
+            aws_bedrock::ReasoningContentBlock::RedactedContent(content),
+        ) => json!({ "reasoningContent": { "redactedContent": bytes_to_json(content) } }),
+        aws_bedrock::ContentBlock::CachePoint(cache_point) => {
+            json!({ "cachePoint": { "type": cache_point.r#type().as_str() } })
+        }
+        block => {
+            return Err(TypeConversionError::new(&format!(
+                "Unsupported Converse content block: {block:?}"
+            )));
+        }
+    };
+    Ok(json)
+}
+
+fn tool_result_content_from_json(
+    json: &Value,
+) -> Result<aws_bedrock::ToolResultContentBlock, TypeConversionError> {
+    let (kind, value) = single_entry(json)?;
+    let content = match kind {
+        "text" => aws_bedrock::ToolResultContentBlock::Text(as_str(value, "text")?.to_string()),
+        "json" => aws_bedrock::ToolResultContentBlock::Json(AwsDocument::from(value.clone()).0),
+        "image" => aws_bedrock::ToolResultContentBlock::Image(image_from_json(value)?),
+        "document" => aws_bedrock::ToolResultContentBlock::Document(document_from_json(value)?),
+        "video" => aws_bedrock::ToolResultContentBlock::Video(video_from_json(value)?),
+        kind => return Err(unsupported("toolResult content", kind)),
+    };
+    Ok(content)
+}
+
+fn tool_result_content_to_json(
+    content: &aws_bedrock::ToolResultContentBlock,
+) -> Result<Value, TypeConversionError> {
+    let json = match content {
+        aws_bedrock::ToolResultContentBlock::Text(text) => json!({ "text": text }),
+        aws_bedrock::ToolResultContentBlock::Json(document) => {
+            json!({ "json": Value::from(AwsDocument(document.clone())) })
+        }
+        aws_bedrock::ToolResultContentBlock::Image(image) => {
+            json!({ "image": image_to_json(image)? })
+        }
+        aws_bedrock::ToolResultContentBlock::Document(document) => {
+            json!({ "document": document_to_json(document)? })
+        }
+        aws_bedrock::ToolResultContentBlock::Video(video) => {
+            json!({ "video": video_to_json(video)? })
+        }
+        content => {
+            return Err(TypeConversionError::new(&format!(
+                "Unsupported Converse tool result content: {content:?}"
+            )));
+        }
+    };
+    Ok(json)
+}
+
+fn image_from_json(json: &Value) -> Result<aws_bedrock::ImageBlock, TypeConversionError> {
+    let (kind, source) = single_entry(field(json, "source")?)?;
+    let source = match kind {
+        "bytes" => aws_bedrock::ImageSource::Bytes(bytes_from_json(source)?),
+        "s3Location" => aws_bedrock::ImageSource::S3Location(s3_location_from_json(source)?),
+        kind => return Err(unsupported("image source", kind)),
+    };
+
+    Ok(aws_bedrock::ImageBlock::builder()
+        .format(aws_bedrock::ImageFormat::from(str_field(json, "format")?))
+        .source(source)
+        .build()?)
+}
+
+fn image_to_json(image: &aws_bedrock::ImageBlock) -> Result<Value, TypeConversionError> {
+    let source = match image.source() {
+        Some(aws_bedrock::ImageSource::Bytes(bytes)) => json!({ "bytes": bytes_to_json(bytes) }),
+        Some(aws_bedrock::ImageSource::S3Location(location)) => {
+            json!({ "s3Location": s3_location_to_json(location) })
+        }
+        source => {
+            return Err(TypeConversionError::new(&format!(
+                "Unsupported Converse image source: {source:?}"
+            )));
+        }
+    };
+    Ok(json!({ "format": image.format().as_str(), "source": source }))
+}
+
+fn document_from_json(json: &Value) -> Result<aws_bedrock::DocumentBlock, TypeConversionError> {
+    let (kind, source) = single_entry(field(json, "source")?)?;
+    let source = match kind {
+        "bytes" => aws_bedrock::DocumentSource::Bytes(bytes_from_json(source)?),
+        "text" => aws_bedrock::DocumentSource::Text(as_str(source, "text")?.to_string()),
+        "s3Location" => aws_bedrock::DocumentSource::S3Location(s3_location_from_json(source)?),
+        kind => return Err(unsupported("document source", kind)),
+    };
+
+    Ok(aws_bedrock::DocumentBlock::builder()
+        .set_format(
+            json.get("format")
+                .and_then(Value::as_str)
+                .map(aws_bedrock::DocumentFormat::from),
+        )
+        .name(str_field(json, "name")?)
+        .source(source)
+        .build()?)
+}
+
+fn document_to_json(document: &aws_bedrock::DocumentBlock) -> Result<Value, TypeConversionError> {
+    let source = match document.source() {
+        Some(aws_bedrock::DocumentSource::Bytes(bytes)) => {
+            json!({ "bytes": bytes_to_json(bytes) })
+        }
+        Some(aws_bedrock::DocumentSource::Text(text)) => json!({ "text": text }),
+        Some(aws_bedrock::DocumentSource::S3Location(location)) => {
+            json!({ "s3Location": s3_location_to_json(location) })
+        }
+        source => {
+            return Err(TypeConversionError::new(&format!(
+                "Unsupported Converse document source: {source:?}"
+            )));
+        }
+    };
+
+    Ok(json!({
+        "format": document.format().as_str(),
+        "name": document.name(),
+        "source": source,
+    }))
+}
+
+fn video_from_json(json: &Value) -> Result<aws_bedrock::VideoBlock, TypeConversionError> {
+    let (kind, source) = single_entry(field(json, "source")?)?;
+    let source = match kind {
+        "bytes" => aws_bedrock::VideoSource::Bytes(bytes_from_json(source)?),
+        "s3Location" => aws_bedrock::VideoSource::S3Location(s3_location_from_json(source)?),
+        kind => return Err(unsupported("video source", kind)),
+    };
+
+    Ok(aws_bedrock::VideoBlock::builder()
+        .format(aws_bedrock::VideoFormat::from(str_field(json, "format")?))
+        .source(source)
+        .build()?)
+}
+
+fn video_to_json(video: &aws_bedrock::VideoBlock) -> Result<Value, TypeConversionError> {
+    let source = match video.source() {
+        Some(aws_bedrock::VideoSource::Bytes(bytes)) => json!({ "bytes": bytes_to_json(bytes) }),
+        Some(aws_bedrock::VideoSource::S3Location(location)) => {
+            json!({ "s3Location": s3_location_to_json(location) })
+        }
+        source => {
+            return Err(TypeConversionError::new(&format!(
+                "Unsupported Converse video source: {source:?}"
+            )));
+        }
+    };
+    Ok(json!({ "format": video.format().as_str(), "source": source }))
+}
+
+fn s3_location_from_json(json: &Value) -> Result<aws_bedrock::S3Location, TypeConversionError> {
+    Ok(aws_bedrock::S3Location::builder()
+        .uri(str_field(json, "uri")?)
+        .set_bucket_owner(
+            json.get("bucketOwner")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        )
+        .build()?)
+}
+
+fn s3_location_to_json(location: &aws_bedrock::S3Location) -> Value {
+    let mut json = json!({ "uri": location.uri() });
+    if let Some(bucket_owner) = location.bucket_owner() {
+        json["bucketOwner"] = bucket_owner.into();
+    }
+    json
+}
+
+fn bytes_from_json(json: &Value) -> Result<Blob, TypeConversionError> {
+    BASE64_STANDARD
+        .decode(as_str(json, "bytes")?)
+        .map(Blob::new)
+        .map_err(|e| TypeConversionError::new(&format!("Invalid base64 content: {e}")))
+}
+
+fn bytes_to_json(bytes: &Blob) -> Value {
+    BASE64_STANDARD.encode(bytes.as_ref()).into()
+}
+
+/// The key and value of an object with a single entry, the way the Converse JSON encodes union
+/// types.
+fn single_entry(json: &Value) -> Result<(&str, &Value), TypeConversionError> {
+    match json.as_object().map(Map::iter).map(|mut entries| {
+        let entry = entries.next();
+        (entry, entries.next())
+    }) {
+        Some((Some((key, value)), None)) => Ok((key.as_str(), value)),
+        _ => Err(TypeConversionError::new(&format!(
+            "Expected an object with a single member, got {json}"
+        ))),
+    }
+}
+
+fn field<'a>(json: &'a Value, name: &str) -> Result<&'a Value, TypeConversionError> {
+    json.get(name)
+        .ok_or_else(|| TypeConversionError::new(&format!("Missing `{name}` in {json}")))
+}
+
+fn str_field<'a>(json: &'a Value, name: &str) -> Result<&'a str, TypeConversionError> {
+    as_str(field(json, name)?, name)
+}
+
+fn array_field<'a>(json: &'a Value, name: &str) -> Result<&'a Vec<Value>, TypeConversionError> {
+    field(json, name)?
+        .as_array()
+        .ok_or_else(|| TypeConversionError::new(&format!("Expected `{name}` to be an array")))
+}
+
+fn as_str<'a>(json: &'a Value, name: &str) -> Result<&'a str, TypeConversionError> {
+    json.as_str()
+        .ok_or_else(|| TypeConversionError::new(&format!("Expected `{name}` to be a string")))
+}
+
+fn unsupported(kind: &str, name: &str) -> TypeConversionError {
+    TypeConversionError::new(&format!("Unsupported Converse {kind} block `{name}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rig::message::{AssistantContent, UserContent};
+
+    fn transcript() -> Value {
+        json!({
+            "messages": [
+                {
+                    "role": "user",
+                    "content": [
+                        { "text": "What's the weather in Paris?" },
+                        { "image": { "format": "png", "source": { "bytes": "iVBORw0KGgo=" } } },
+                        { "cachePoint": { "type": "default" } }
+                    ]
+                },
+                {
+                    "role": "assistant",
+                    "content": [
+                        { "reasoningContent": { "reasoningText": { "text": "Use the tool", "signature": "sig" } } },
+                        { "reasoningContent": { "redactedContent": "AQID" } },
+                        { "toolUse": { "toolUseId": "tool_1", "name": "weather", "input": { "city": "Paris" } } }
+                    ]
+                },
+                {
+                    "role": "user",
+                    "content": [
+                        {
+                            "toolResult": {
+                                "toolUseId": "tool_1",
+                                "content": [{ "json": { "celsius": 21 } }],
+                                "status": "success"
+                            }
+                        }
+                    ]
+                }
+            ],
+            "inferenceConfig": { "maxTokens": 512 }
+        })
+    }
+
+    #[test]
+    fn test_round_trips_bedrock_messages() {
+        let json = transcript();
+        let messages = bedrock_messages_from_converse_json(&json).unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            bedrock_messages_to_converse_json(&messages).unwrap(),
+            json["messages"]
+        );
+    }
+
+    #[test]
+    fn test_rig_messages() {
+        let messages = messages_from_converse_json(&transcript()).unwrap();
+
+        let Message::User { content } = &messages[0] else {
+            panic!("expected a user message");
+        };
+        assert_eq!(content.len(), 2);
+        assert!(matches!(content.iter().nth(1), Some(UserContent::Image(_))));
+
+        let Message::Assistant { content, .. } = &messages[1] else {
+            panic!("expected an assistant message");
+        };
+        assert_eq!(content.len(), 2);
+        assert!(matches!(content.first(), AssistantContent::Reasoning(_)));
+
+        let json = messages_to_converse_json(&messages).unwrap();
+        assert_eq!(
+            json[1]["content"][1],
+            json!({ "toolUse": { "toolUseId": "tool_1", "name": "weather", "input": { "city": "Paris" } } })
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_blocks() {
+        let error = bedrock_messages_from_converse_json(&json!([
+            { "role": "user", "content": [{ "audio": {} }] }
+        ]))
+        .unwrap_err();
+
+        assert!(error.to_string().contains("audio"));
+    }
+}
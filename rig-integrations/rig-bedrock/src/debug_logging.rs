@@ -0,0 +1,213 @@
+//! Opt-in logging of the raw Converse request and response bodies, to debug prompt construction.
+//!
+//! Bodies are logged at the `DEBUG` level with the `rig::bedrock::debug` target, after redaction:
+//! image, document and video bytes are replaced by their length, and values at the configured
+//! JSON paths are masked. Paths are dot-separated keys, where `*` matches any array index or
+//! object key:
+//!
+//! ```rust,ignore
+//! let model = client.completion_model(AMAZON_NOVA_LITE).with_debug_logging(
+//!     DebugLogging::new()
+//!         .mask_path("system.*.text")
+//!         .mask_path("requestMetadata.tenant"),
+//! );
+//! ```
+//!
+//! Streamed responses aren't buffered, so only the requests of streaming completions are logged.
+
+use aws_sdk_bedrockruntime::config::interceptors::{
+    AfterDeserializationInterceptorContextRef, BeforeTransmitInterceptorContextRef,
+};
+use aws_sdk_bedrockruntime::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_sdk_bedrockruntime::error::BoxError;
+use serde_json::Value;
+
+const MASK: &str = "***";
+
+/// How Converse bodies are redacted before being logged.
+#[derive(Clone, Debug)]
+pub struct DebugLogging {
+    strip_binary: bool,
+    masked_paths: Vec<Vec<String>>,
+}
+
+impl Default for DebugLogging {
+    fn default() -> Self {
+        Self {
+            strip_binary: true,
+            masked_paths: Vec::new(),
+        }
+    }
+}
+
+impl DebugLogging {
+    /// Log bodies without image, document and video bytes, and without masking anything else.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to replace image, document and video bytes by their length. Defaults to `true`.
+    pub fn strip_binary(mut self, strip_binary: bool) -> Self {
+        self.strip_binary = strip_binary;
+        self
+    }
+
+    /// Mask the values at `path`, e.g. `messages.*.content.*.text`, in requests and responses.
+    pub fn mask_path(mut self, path: &str) -> Self {
+        self.masked_paths
+            .push(path.split('.').map(str::to_string).collect());
+        self
+    }
+
+    /// The redacted JSON of a body, or a placeholder if it isn't JSON.
+    fn redact(&self, body: &[u8]) -> String {
+        let Ok(mut json) = serde_json::from_slice::<Value>(body) else {
+            return format!("<{} bytes of non-JSON body>", body.len());
+        };
+        if self.strip_binary {
+            strip_binary(&mut json);
+        }
+        for path in &self.masked_paths {
+            mask(&mut json, path);
+        }
+        json.to_string()
+    }
+}
+
+impl Intercept for DebugLogging {
+    fn name(&self) -> &'static str {
+        "DebugLogging"
+    }
+
+    fn read_before_transmit(
+        &self,
+        context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let request = context.request();
+        if let Some(body) = request.body().bytes() {
+            tracing::debug!(
+                target: "rig::bedrock::debug",
+                uri = request.uri(),
+                body = self.redact(body),
+                "Bedrock request"
+            );
+        }
+        Ok(())
+    }
+
+    fn read_after_deserialization(
+        &self,
+        context: &AfterDeserializationInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let response = context.response();
+        if let Some(body) = response.body().bytes() {
+            tracing::debug!(
+                target: "rig::bedrock::debug",
+                status = response.status().as_u16(),
+                body = self.redact(body),
+                "Bedrock response"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Replace the base64 `bytes` of image, document and video sources by their decoded length.
+fn strip_binary(json: &mut Value) {
+    match json {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(bytes) if key == "bytes" => {
+                        *value = Value::String(format!("<{} bytes>", bytes.len() / 4 * 3));
+                    }
+                    value => strip_binary(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(strip_binary),
+        _ => {}
+    }
+}
+
+fn mask(json: &mut Value, path: &[String]) {
+    let Some((key, rest)) = path.split_first() else {
+        *json = Value::String(MASK.to_string());
+        return;
+    };
+
+    match json {
+        Value::Object(object) if key == "*" => {
+            object.values_mut().for_each(|value| mask(value, rest));
+        }
+        Value::Object(object) => {
+            if let Some(value) = object.get_mut(key) {
+                mask(value, rest);
+            }
+        }
+        Value::Array(values) if key == "*" => {
+            values.iter_mut().for_each(|value| mask(value, rest));
+        }
+        Value::Array(values) => {
+            if let Some(value) = key
+                .parse()
+                .ok()
+                .and_then(|index: usize| values.get_mut(index))
+            {
+                mask(value, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact() {
+        let body = json!({
+            "messages": [{
+                "role": "user",
+                "content": [
+                    { "text": "My card number is 4111 1111 1111 1111" },
+                    { "image": { "format": "png", "source": { "bytes": "aGVsbG8gd29ybGQh" } } }
+                ]
+            }],
+            "system": [{ "text": "You are a helpful assistant" }]
+        });
+
+        let redacted = DebugLogging::new()
+            .mask_path("messages.*.content.0.text")
+            .redact(body.to_string().as_bytes());
+        let redacted: Value = serde_json::from_str(&redacted).unwrap();
+
+        assert_eq!(
+            redacted,
+            json!({
+                "messages": [{
+                    "role": "user",
+                    "content": [
+                        { "text": "***" },
+                        { "image": { "format": "png", "source": { "bytes": "<12 bytes>" } } }
+                    ]
+                }],
+                "system": [{ "text": "You are a helpful assistant" }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_redact_non_json() {
+        assert_eq!(
+            DebugLogging::new().redact(b"\x00\x01"),
+            "<2 bytes of non-JSON body>"
+        );
+    }
+}
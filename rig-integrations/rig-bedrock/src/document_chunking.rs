@@ -0,0 +1,241 @@
+//! Splitting of large text documents to fit the Converse limits on the size of a document and
+//! the number of documents in a request.
+//!
+//! ```rust,ignore
+//! let chunker = DocumentChunker::default();
+//! let mut content = chunker.user_content([Document {
+//!     data: DocumentSourceKind::String(std::fs::read_to_string("handbook.md")?),
+//!     media_type: Some(DocumentMediaType::MARKDOWN),
+//!     additional_params: None,
+//! }])?;
+//! content.push(UserContent::text("Summarize the vacation policy."));
+//!
+//! let answer = agent.prompt(Message::User { content: OneOrMany::many(content)? }).await?;
+//! ```
+//!
+//! Text documents (plain text, Markdown, HTML, CSV, code, ...) are split at paragraph, line or
+//! word boundaries. Binary documents such as PDF or Office files can't be split without parsing
+//! them, and fail with [`RequestLimitError::DocumentTooLarge`] when too large.
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use rig::completion::CompletionError;
+use rig::message::{Document, DocumentMediaType, DocumentSourceKind, UserContent};
+
+use crate::request_limits::{MAX_DOCUMENT_BYTES, MAX_DOCUMENTS};
+use crate::types::errors::RequestLimitError;
+use crate::types::media_types::DOCUMENT_FORMAT_KEY;
+
+/// Splits documents exceeding the Converse limits, see [`crate::document_chunking`].
+#[derive(Clone, Debug)]
+pub struct DocumentChunker {
+    max_document_bytes: usize,
+    max_documents: usize,
+}
+
+impl Default for DocumentChunker {
+    fn default() -> Self {
+        Self {
+            max_document_bytes: MAX_DOCUMENT_BYTES,
+            max_documents: MAX_DOCUMENTS,
+        }
+    }
+}
+
+impl DocumentChunker {
+    /// Split documents into chunks of at most `max_document_bytes`. Defaults to
+    /// [`MAX_DOCUMENT_BYTES`].
+    pub fn with_max_document_bytes(mut self, max_document_bytes: usize) -> Self {
+        self.max_document_bytes = max_document_bytes.max(1);
+        self
+    }
+
+    /// Send at most `max_documents` chunks as documents in [`DocumentChunker::user_content`].
+    /// Defaults to [`MAX_DOCUMENTS`].
+    pub fn with_max_documents(mut self, max_documents: usize) -> Self {
+        self.max_documents = max_documents;
+        self
+    }
+
+    /// Split `document` into documents of at most the maximum size. Documents within the limit
+    /// are returned as is.
+    pub fn chunk(&self, document: Document) -> Result<Vec<Document>, CompletionError> {
+        let Some(text) = self.oversized_text(&document)? else {
+            return Ok(vec![document]);
+        };
+
+        Ok(split(&text, self.max_document_bytes)
+            .into_iter()
+            .map(|chunk| Document {
+                data: DocumentSourceKind::String(chunk.to_string()),
+                media_type: document.media_type.clone(),
+                additional_params: document.additional_params.clone(),
+            })
+            .collect())
+    }
+
+    /// The content of a user message attaching `documents`, split into chunks. Once the maximum
+    /// number of documents is reached, the remaining chunks are attached as text, which isn't
+    /// subject to the document limits.
+    pub fn user_content(
+        &self,
+        documents: impl IntoIterator<Item = Document>,
+    ) -> Result<Vec<UserContent>, CompletionError> {
+        let mut chunks = Vec::new();
+        for document in documents {
+            chunks.extend(self.chunk(document)?);
+        }
+
+        let total = chunks.len();
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                if index < self.max_documents {
+                    return Ok(UserContent::Document(chunk));
+                }
+                let text = text(&chunk)?.ok_or(RequestLimitError::TooManyDocuments {
+                    count: total,
+                    max: self.max_documents,
+                })?;
+                Ok(UserContent::text(format!(
+                    "<document part=\"{}\" of=\"{total}\">\n{text}\n</document>",
+                    index + 1
+                )))
+            })
+            .collect()
+    }
+
+    /// The text of `document` if it's larger than the maximum size, failing for binary
+    /// documents that can't be split.
+    fn oversized_text(&self, document: &Document) -> Result<Option<String>, CompletionError> {
+        let bytes = match &document.data {
+            DocumentSourceKind::String(text) => text.len(),
+            DocumentSourceKind::Raw(bytes) => bytes.len(),
+            // The decoded size of the base64 data
+            DocumentSourceKind::Base64(data) => data.len() / 4 * 3,
+            _ => return Ok(None),
+        };
+        if bytes <= self.max_document_bytes {
+            return Ok(None);
+        }
+
+        text(document)?.map(Some).ok_or_else(|| {
+            RequestLimitError::DocumentTooLarge {
+                bytes,
+                max: self.max_document_bytes,
+            }
+            .into()
+        })
+    }
+}
+
+/// The text of a text document, or `None` for binary documents.
+fn text(document: &Document) -> Result<Option<String>, CompletionError> {
+    let binary = matches!(
+        document.media_type,
+        Some(DocumentMediaType::PDF | DocumentMediaType::RTF)
+    ) || document
+        .additional_params
+        .as_ref()
+        .is_some_and(|params| params.get(DOCUMENT_FORMAT_KEY).is_some());
+    if binary {
+        return Ok(None);
+    }
+
+    let bytes = match &document.data {
+        DocumentSourceKind::String(text) => return Ok(Some(text.clone())),
+        DocumentSourceKind::Raw(bytes) => bytes.clone(),
+        DocumentSourceKind::Base64(data) => BASE64_STANDARD
+            .decode(data)
+            .map_err(|e| CompletionError::RequestError(e.into()))?,
+        _ => return Ok(None),
+    };
+    Ok(String::from_utf8(bytes).ok())
+}
+
+/// Split `text` into chunks of at most `max_bytes`, preferably after a blank line, a line or a
+/// word.
+fn split(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+
+    while rest.len() > max_bytes {
+        let mut end = max_bytes;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let window = &rest[..end];
+        let end = ["\n\n", "\n", " "]
+            .iter()
+            .find_map(|separator| window.rfind(separator).map(|index| index + separator.len()))
+            .unwrap_or(end)
+            // A character larger than the maximum size is kept whole
+            .max(rest.chars().next().map_or(1, char::len_utf8));
+
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(text: &str) -> Document {
+        Document {
+            data: DocumentSourceKind::String(text.to_string()),
+            media_type: Some(DocumentMediaType::MARKDOWN),
+            additional_params: None,
+        }
+    }
+
+    #[test]
+    fn test_split_at_boundaries() {
+        assert_eq!(
+            split("first paragraph\n\nsecond one\nthird line", 18),
+            vec!["first paragraph\n\n", "second one\n", "third line"]
+        );
+        assert_eq!(split("abcdef", 4), vec!["abcd", "ef"]);
+        assert_eq!(split("héé", 2), vec!["h", "é", "é"]);
+    }
+
+    #[test]
+    fn test_chunks_overflow_into_text() {
+        let chunker = DocumentChunker::default()
+            .with_max_document_bytes(8)
+            .with_max_documents(2);
+
+        let content = chunker
+            .user_content([document("one two three four")])
+            .unwrap();
+
+        assert_eq!(content.len(), 3);
+        assert!(matches!(&content[0], UserContent::Document(_)));
+        assert!(matches!(&content[1], UserContent::Document(_)));
+        let UserContent::Text(text) = &content[2] else {
+            panic!("expected a text part");
+        };
+        assert_eq!(
+            text.text,
+            "<document part=\"3\" of=\"3\">\nfour\n</document>"
+        );
+    }
+
+    #[test]
+    fn test_large_binary_documents_fail() {
+        let chunker = DocumentChunker::default().with_max_document_bytes(4);
+        let pdf = Document {
+            data: DocumentSourceKind::Raw(b"%PDF-1.7".to_vec()),
+            media_type: Some(DocumentMediaType::PDF),
+            additional_params: None,
+        };
+
+        let error = chunker.chunk(pdf).unwrap_err();
+        assert!(error.to_string().contains("8 bytes"));
+    }
+}
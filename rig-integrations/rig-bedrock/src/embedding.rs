@@ -1,22 +1,190 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use aws_smithy_types::Blob;
+use futures::{StreamExt, TryStreamExt, stream};
 use rig::embeddings::{self, Embedding, EmbeddingError};
 use serde::{Deserialize, Serialize};
+use tracing::Instrument;
 
-use crate::{client::Client, types::errors::AwsSdkInvokeModelError};
+use crate::{
+    batch::{
+        BatchError, BatchInputRecord, BatchOutputRecord, from_jsonl, outputs_in_order, record_id,
+    },
+    cache::{EmbeddingCache, cache_key},
+    circuit_breaker::CircuitBreaker,
+    client::Client,
+    metrics::InvocationMetrics,
+    native::base_model_id,
+    pricing::{CostEstimate, CostTracker, ModelPricing},
+    rate_limit::{RateLimiter, estimate_tokens},
+    retry::RetryPolicy,
+    telemetry,
+    types::errors::{
+        BedrockError, EmbeddingOptionsError, InvalidDimensionsError,
+        is_transient_invoke_model_error, retry_after,
+    },
+    usage::UsageTracker,
+};
 
+/// The request body of Titan embedding models, see [`EmbeddingModel::document_to_embeddings`].
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EmbeddingRequest {
     pub input_text: String,
     pub dimensions: usize,
     pub normalize: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_types: Option<Vec<EmbeddingOutputType>>,
+}
+
+impl EmbeddingRequest {
+    /// A request for the normalized float embedding of `input_text`, with `dimensions`
+    /// dimensions.
+    pub fn new(input_text: impl Into<String>, dimensions: usize) -> Self {
+        Self {
+            input_text: input_text.into(),
+            dimensions,
+            normalize: true,
+            embedding_types: None,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct EmbeddingResponse {
+    /// The float embedding, missing when only other types were requested.
+    #[serde(default)]
     pub embedding: Vec<f64>,
     pub input_text_token_count: usize,
+    #[serde(default)]
+    pub embeddings_by_type: HashMap<EmbeddingOutputType, Vec<f64>>,
+}
+
+impl EmbeddingResponse {
+    /// The embedding of type `output_type`, if returned.
+    pub fn into_vector(mut self, output_type: EmbeddingOutputType) -> Option<Vec<f64>> {
+        match output_type {
+            EmbeddingOutputType::Float if !self.embedding.is_empty() => Some(self.embedding),
+            output_type => self.embeddings_by_type.remove(&output_type),
+        }
+    }
+}
+
+/// The numeric type of the returned embeddings. Quantized embeddings are much smaller to store,
+/// at some cost in retrieval quality.
+///
+/// Titan Text Embeddings V2 supports [`Float`](Self::Float) and [`Binary`](Self::Binary), Cohere
+/// Embed v3 supports all types, and other models only [`Float`](Self::Float).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingOutputType {
+    #[default]
+    Float,
+    Int8,
+    Uint8,
+    /// Bit-packed signed binary embeddings for Cohere, one value per dimension for Titan.
+    Binary,
+    /// Bit-packed unsigned binary embeddings.
+    Ubinary,
+}
+
+impl EmbeddingOutputType {
+    /// The output types supported by `model`.
+    pub fn supported_by(model: &str) -> &'static [EmbeddingOutputType] {
+        match base_model_id(model) {
+            AMAZON_TITAN_EMBED_TEXT_V2_0 => &[Self::Float, Self::Binary],
+            COHERE_EMBED_ENGLISH_V3 | COHERE_EMBED_MULTILINGUAL_V3 => &[
+                Self::Float,
+                Self::Int8,
+                Self::Uint8,
+                Self::Binary,
+                Self::Ubinary,
+            ],
+            _ => &[Self::Float],
+        }
+    }
+}
+
+/// Maximum number of texts Cohere Embed models accept in a single request.
+pub const COHERE_MAX_TEXTS_PER_REQUEST: usize = 96;
+/// Cohere Embed v3 models always return 1024 dimensional embeddings.
+const COHERE_EMBED_V3_NDIMS: usize = 1024;
+
+/// Request body of Cohere Embed models, which embed many texts per call.
+#[derive(Serialize)]
+pub struct CohereEmbeddingRequest {
+    pub texts: Vec<String>,
+    pub input_type: CohereInputType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_types: Option<Vec<EmbeddingOutputType>>,
+}
+
+/// How the embedded texts will be used, which Cohere uses to optimise the embeddings.
+///
+/// For retrieval, documents and queries must be embedded differently: index documents with a
+/// model using [`CohereInputType::SearchDocument`] and search the vector store with one using
+/// [`CohereInputType::SearchQuery`].
+///
+/// ```rust,ignore
+/// let documents_model = client.embedding_model_with_input_type(COHERE_EMBED_ENGLISH_V3, CohereInputType::SearchDocument);
+/// let query_model = client.embedding_model_with_input_type(COHERE_EMBED_ENGLISH_V3, CohereInputType::SearchQuery);
+///
+/// let embeddings = EmbeddingsBuilder::new(documents_model).documents(documents)?.build().await?;
+/// let index = InMemoryVectorStore::from_documents(embeddings).index(query_model);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CohereInputType {
+    /// Documents stored in a vector database for search.
+    #[default]
+    SearchDocument,
+    /// Search queries run against embedded documents.
+    SearchQuery,
+    Classification,
+    Clustering,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CohereEmbeddingResponse {
+    pub embeddings: CohereEmbeddings,
+}
+
+/// Cohere returns a plain list of float embeddings, or embeddings keyed by type when
+/// `embedding_types` is requested.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum CohereEmbeddings {
+    Floats(Vec<Vec<f64>>),
+    ByType(HashMap<EmbeddingOutputType, Vec<Vec<f64>>>),
+}
+
+impl CohereEmbeddings {
+    /// The embeddings of type `output_type`, empty if none were returned.
+    pub fn into_vectors(self, output_type: EmbeddingOutputType) -> Vec<Vec<f64>> {
+        match (self, output_type) {
+            (Self::Floats(floats), EmbeddingOutputType::Float) => floats,
+            (Self::Floats(_), _) => Vec::new(),
+            (Self::ByType(mut by_type), output_type) => {
+                by_type.remove(&output_type).unwrap_or_default()
+            }
+        }
+    }
+}
+
+impl From<CohereEmbeddings> for Vec<Vec<f64>> {
+    fn from(embeddings: CohereEmbeddings) -> Self {
+        embeddings.into_vectors(EmbeddingOutputType::Float)
+    }
+}
+
+/// The `modelOutput` of a batch embedding record.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum BatchEmbeddingOutput {
+    Titan(EmbeddingResponse),
+    Cohere(CohereEmbeddingResponse),
 }
 
 /// `amazon.titan-embed-text-v1`
@@ -30,19 +198,326 @@ pub const COHERE_EMBED_ENGLISH_V3: &str = "cohere.embed-english-v3";
 /// `cohere.embed-multilingual-v3`
 pub const COHERE_EMBED_MULTILINGUAL_V3: &str = "cohere.embed-multilingual-v3";
 
+/// The output dimensions supported by a known embedding model, or `None` for other models.
+pub fn supported_dimensions(model: &str) -> Option<&'static [usize]> {
+    match base_model_id(model) {
+        AMAZON_TITAN_EMBED_TEXT_V1 => Some(&[1536]),
+        AMAZON_TITAN_EMBED_TEXT_V2_0 => Some(&[256, 512, 1024]),
+        COHERE_EMBED_ENGLISH_V3 | COHERE_EMBED_MULTILINGUAL_V3 => Some(&[COHERE_EMBED_V3_NDIMS]),
+        _ => None,
+    }
+}
+
 #[derive(Clone)]
 pub struct EmbeddingModel {
     client: Client,
     model: String,
     ndims: Option<usize>,
+    supported_dimensions: Option<Vec<usize>>,
+    input_type: CohereInputType,
+    normalize: bool,
+    output_type: EmbeddingOutputType,
+    concurrency: usize,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cache: Option<Arc<dyn EmbeddingCache>>,
+    circuit_breaker: Option<CircuitBreaker>,
+    cost_tracker: CostTracker,
+}
+
+/// Builder for an [`EmbeddingModel`] with non-default options, the way to configure embedding
+/// models.
+///
+/// ```rust,ignore
+/// let model = EmbeddingModel::builder(client, AMAZON_TITAN_EMBED_TEXT_V2_0)
+///     .ndims(512)
+///     .output_type(EmbeddingOutputType::Binary)
+///     .concurrency(8)
+///     .retry_policy(RetryPolicy::new(10))
+///     .build()?;
+/// ```
+#[derive(Clone)]
+pub struct EmbeddingModelBuilder {
+    model: EmbeddingModel,
+}
+
+impl EmbeddingModelBuilder {
+    /// Number of dimensions of the embeddings, for models that support several.
+    pub fn ndims(mut self, ndims: usize) -> Self {
+        self.model.ndims = Some(ndims);
+        self
+    }
+
+    /// Dimensions supported by a custom or imported model.
+    pub fn supported_dimensions(mut self, supported_dimensions: impl Into<Vec<usize>>) -> Self {
+        self.model.supported_dimensions = Some(supported_dimensions.into());
+        self
+    }
+
+    /// Whether Titan models return unit length embeddings. Defaults to `true`.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.model.normalize = normalize;
+        self
+    }
+
+    /// Numeric type of the returned embeddings. Defaults to [`EmbeddingOutputType::Float`].
+    pub fn output_type(mut self, output_type: EmbeddingOutputType) -> Self {
+        self.model.output_type = output_type;
+        self
+    }
+
+    /// Maximum number of requests in flight for a single `embed_texts` call. Defaults to 1.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.model.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// `input_type` sent to Cohere Embed models.
+    pub fn input_type(mut self, input_type: CohereInputType) -> Self {
+        self.model.input_type = input_type;
+        self
+    }
+
+    /// How requests failing with throttling or service unavailable errors are retried.
+    /// Defaults to [`RetryPolicy::default`]; use [`RetryPolicy::none`] to disable retries.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.model.retry_policy = retry_policy;
+        self
+    }
+
+    /// Throttle requests client-side so bulk embedding stays under the account quotas.
+    /// Share a clone of the same limiter between models to give them a common budget.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.model.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Look up embeddings in `cache` before calling Bedrock, and store new ones in it.
+    /// Entries are keyed by model, dimensions, request options and text.
+    pub fn cache(mut self, cache: Arc<dyn EmbeddingCache>) -> Self {
+        self.model.cache = Some(cache);
+        self
+    }
+
+    /// Reject calls without calling Bedrock while `circuit_breaker` is open. The breaker can be
+    /// shared with other embedding and completion models.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.model.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Estimate costs with `pricing` rather than the on-demand pricing of the model, e.g. for
+    /// models missing from [`crate::pricing`] or with negotiated prices.
+    pub fn pricing(mut self, pricing: ModelPricing) -> Self {
+        self.model.cost_tracker = self.model.cost_tracker.with_pricing(pricing);
+        self
+    }
+
+    /// Record the usage of the calls made with the model with `usage_tracker`, instead of the
+    /// client's tracker.
+    pub fn usage_tracker(mut self, usage_tracker: UsageTracker) -> Self {
+        self.model.cost_tracker = self.model.cost_tracker.with_usage_tracker(usage_tracker);
+        self
+    }
+
+    /// Build the model, checking the options are supported by it.
+    pub fn build(self) -> Result<EmbeddingModel, EmbeddingOptionsError> {
+        let model = self.model;
+        model.validate_dimensions()?;
+
+        if !EmbeddingOutputType::supported_by(&model.model).contains(&model.output_type) {
+            return Err(EmbeddingOptionsError::UnsupportedOutputType {
+                model: model.model,
+                output_type: model.output_type,
+            });
+        }
+
+        Ok(model)
+    }
+
+    /// The model, without checking the options that [`Self::build`] checks.
+    pub(crate) fn build_unchecked(self) -> EmbeddingModel {
+        self.model
+    }
 }
 
 impl EmbeddingModel {
+    #[deprecated(note = "use `EmbeddingModel::builder` instead")]
     pub fn new(client: Client, model: impl Into<String>, ndims: Option<usize>) -> Self {
+        Self {
+            ndims,
+            ..Self::with_defaults(client, model)
+        }
+    }
+
+    fn with_defaults(client: Client, model: impl Into<String>) -> Self {
+        let model = model.into();
+        let cost_tracker = CostTracker::new(&model, client.usage_tracker.clone());
         Self {
             client,
-            model: model.into(),
+            supported_dimensions: supported_dimensions(&model).map(<[usize]>::to_vec),
+            cost_tracker,
+            model,
+            ndims: None,
+            input_type: CohereInputType::default(),
+            normalize: true,
+            output_type: EmbeddingOutputType::default(),
+            concurrency: 1,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            cache: None,
+            circuit_breaker: None,
+        }
+    }
+
+    /// Start building a model with non-default options.
+    pub fn builder(client: Client, model: impl Into<String>) -> EmbeddingModelBuilder {
+        EmbeddingModelBuilder {
+            model: Self::with_defaults(client, model),
+        }
+    }
+
+    /// Like [`EmbeddingModel::new`], but fails if `ndims` isn't supported by a known model such
+    /// as Titan Text Embeddings V2, which only produces 256, 512 or 1024 dimensional embeddings.
+    #[deprecated(note = "use `EmbeddingModel::builder` instead")]
+    pub fn try_new(
+        client: Client,
+        model: impl Into<String>,
+        ndims: Option<usize>,
+    ) -> Result<Self, InvalidDimensionsError> {
+        let model = Self {
             ndims,
+            ..Self::with_defaults(client, model)
+        };
+        model.validate_dimensions()?;
+        Ok(model)
+    }
+
+    /// Declare the dimensions supported by a custom or imported model, validating the configured
+    /// dimensions against them.
+    #[deprecated(note = "use `EmbeddingModelBuilder::supported_dimensions` instead")]
+    pub fn with_supported_dimensions(
+        mut self,
+        supported_dimensions: impl Into<Vec<usize>>,
+    ) -> Result<Self, InvalidDimensionsError> {
+        self.supported_dimensions = Some(supported_dimensions.into());
+        self.validate_dimensions()?;
+        Ok(self)
+    }
+
+    /// Check the configured dimensions against the ones supported by the model, if known.
+    pub fn validate_dimensions(&self) -> Result<(), InvalidDimensionsError> {
+        match (self.ndims, &self.supported_dimensions) {
+            (Some(ndims), Some(supported)) if !supported.contains(&ndims) => {
+                Err(InvalidDimensionsError {
+                    model: self.model.clone(),
+                    ndims,
+                    supported: supported.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Set how requests failing with throttling or service unavailable errors are retried.
+    /// Defaults to [`RetryPolicy::default`]; use [`RetryPolicy::none`] to disable retries.
+    #[deprecated(note = "use `EmbeddingModelBuilder::retry_policy` instead")]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Throttle requests client-side so bulk embedding stays under the account quotas.
+    /// Share a clone of the same limiter between models to give them a common budget.
+    #[deprecated(note = "use `EmbeddingModelBuilder::rate_limiter` instead")]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Look up embeddings in `cache` before calling Bedrock, and store new ones in it.
+    /// Entries are keyed by model, dimensions, request options and text.
+    #[deprecated(note = "use `EmbeddingModelBuilder::cache` instead")]
+    pub fn with_cache(mut self, cache: Arc<dyn EmbeddingCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Reject calls without calling Bedrock while `circuit_breaker` is open. The breaker can be
+    /// shared with other embedding and completion models.
+    #[deprecated(note = "use `EmbeddingModelBuilder::circuit_breaker` instead")]
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Estimate costs with `pricing` rather than the on-demand pricing of the model, e.g. for
+    /// models missing from [`crate::pricing`] or with negotiated prices.
+    #[deprecated(note = "use `EmbeddingModelBuilder::pricing` instead")]
+    pub fn with_pricing(mut self, pricing: ModelPricing) -> Self {
+        self.cost_tracker = self.cost_tracker.with_pricing(pricing);
+        self
+    }
+
+    /// The estimated cost of all the calls made with this model and its clones. Cohere Embed
+    /// doesn't report token usage, so its cost is estimated from the length of the texts.
+    pub fn estimated_cost(&self) -> CostEstimate {
+        self.cost_tracker.total()
+    }
+
+    pub fn reset_estimated_cost(&self) {
+        self.cost_tracker.reset();
+    }
+
+    /// Record the usage of the calls made with this model with `usage_tracker`, instead of the
+    /// client's tracker.
+    #[deprecated(note = "use `EmbeddingModelBuilder::usage_tracker` instead")]
+    pub fn with_usage_tracker(mut self, usage_tracker: UsageTracker) -> Self {
+        self.cost_tracker = self.cost_tracker.with_usage_tracker(usage_tracker);
+        self
+    }
+
+    /// Set the `input_type` sent to Cohere Embed models. Ignored by other models.
+    #[deprecated(note = "use `EmbeddingModelBuilder::input_type` instead")]
+    pub fn with_input_type(mut self, input_type: CohereInputType) -> Self {
+        self.input_type = input_type;
+        self
+    }
+
+    /// The `input_type` sent to Cohere Embed models.
+    pub fn input_type(&self) -> CohereInputType {
+        self.input_type
+    }
+
+    /// Whether the model is a Cohere Embed v3 model, taking the Cohere v3 request body. Later
+    /// Cohere models such as Embed v4 have other dimensions and aren't supported by this codec.
+    fn is_cohere_v3(&self) -> bool {
+        matches!(
+            base_model_id(&self.model),
+            COHERE_EMBED_ENGLISH_V3 | COHERE_EMBED_MULTILINGUAL_V3
+        )
+    }
+
+    /// The `embedding_types` to request, omitted for the default float embeddings.
+    fn embedding_types(&self) -> Option<Vec<EmbeddingOutputType>> {
+        (self.output_type != EmbeddingOutputType::Float).then(|| vec![self.output_type])
+    }
+
+    fn titan_request(&self, input_text: String) -> EmbeddingRequest {
+        use rig::embeddings::EmbeddingModel as _;
+
+        EmbeddingRequest {
+            normalize: self.normalize,
+            embedding_types: self.embedding_types(),
+            ..EmbeddingRequest::new(input_text, self.ndims())
+        }
+    }
+
+    fn cohere_request(&self, texts: Vec<String>) -> CohereEmbeddingRequest {
+        CohereEmbeddingRequest {
+            texts,
+            input_type: self.input_type,
+            embedding_types: self.embedding_types(),
         }
     }
 
@@ -51,30 +526,323 @@ impl EmbeddingModel {
         request: EmbeddingRequest,
     ) -> Result<EmbeddingResponse, EmbeddingError> {
         let input_document = serde_json::to_string(&request).map_err(EmbeddingError::JsonError)?;
+        let tokens = estimate_tokens(&request.input_text);
+        let span = telemetry::embeddings_span(&self.model);
+        let metrics = InvocationMetrics::start("embeddings", &self.model);
+        let response_str = self
+            .invoke(input_document, tokens, &metrics)
+            .instrument(span.clone())
+            .await?;
+
+        let result: EmbeddingResponse = serde_json::from_str(&response_str).map_err(|e| {
+            metrics.failure(None);
+            EmbeddingError::JsonError(e)
+        })?;
+        telemetry::record_usage(&span, result.input_text_token_count as u64, None);
+        metrics.success(Some(result.input_text_token_count as u64), None);
+        self.cost_tracker
+            .record(result.input_text_token_count as u64, 0, 0, 0);
+
+        Ok(result)
+    }
 
+    /// Embed up to [`COHERE_MAX_TEXTS_PER_REQUEST`] texts with a single Cohere Embed call.
+    pub async fn cohere_embeddings(
+        &self,
+        request: CohereEmbeddingRequest,
+    ) -> Result<Vec<Vec<f64>>, EmbeddingError> {
+        let input_document = serde_json::to_string(&request).map_err(EmbeddingError::JsonError)?;
+        let tokens = request.texts.iter().map(|text| estimate_tokens(text)).sum();
+        let metrics = InvocationMetrics::start("embeddings", &self.model);
+        let response_str = self
+            .invoke(input_document, tokens, &metrics)
+            .instrument(telemetry::embeddings_span(&self.model))
+            .await?;
+
+        let result: CohereEmbeddingResponse = serde_json::from_str(&response_str).map_err(|e| {
+            metrics.failure(None);
+            EmbeddingError::JsonError(e)
+        })?;
+        // Cohere Embed doesn't report token usage
+        metrics.success(None, None);
+        self.cost_tracker.record(tokens as u64, 0, 0, 0);
+
+        Ok(result.embeddings.into_vectors(self.output_type))
+    }
+
+    async fn invoke(
+        &self,
+        input_document: String,
+        tokens: u32,
+        metrics: &InvocationMetrics,
+    ) -> Result<String, EmbeddingError> {
+        let permit = self
+            .circuit_breaker
+            .as_ref()
+            .map(CircuitBreaker::acquire)
+            .transpose()?;
+        let client = self.client.get_inner().await;
         let model_response = self
-            .client
-            .get_inner()
-            .await
-            .invoke_model()
-            .model_id(self.model.as_str())
-            .content_type("application/json")
-            .accept("application/json")
-            .body(Blob::new(input_document))
-            .send()
+            .retry_policy
+            .retry(
+                is_transient_invoke_model_error,
+                |error| error.raw_response().and_then(retry_after),
+                || async {
+                    // Retries count against the limits as well
+                    if let Some(rate_limiter) = &self.rate_limiter {
+                        rate_limiter.acquire(tokens).await;
+                    }
+                    let _permit = self.client.concurrency.acquire_embedding().await;
+                    client
+                        .invoke_model()
+                        .model_id(self.model.as_str())
+                        .content_type("application/json")
+                        .accept("application/json")
+                        .body(Blob::new(input_document.as_str()))
+                        .send()
+                        .await
+                },
+            )
             .await;
 
-        let response = model_response
-            .map_err(|sdk_error| AwsSdkInvokeModelError(sdk_error).into())
-            .map_err(|e: EmbeddingError| e)?;
+        let response = match model_response {
+            Ok(response) => {
+                if let Some(permit) = permit {
+                    permit.record(None);
+                }
+                response
+            }
+            Err(sdk_error) => {
+                // Called within the span of the embedding request
+                let error = BedrockError::from(sdk_error);
+                telemetry::record_error(&tracing::Span::current(), &error);
+                metrics.failure(Some(&error));
+                if let Some(permit) = permit {
+                    permit.record(Some(&error));
+                }
+                return Err(EmbeddingError::from(error));
+            }
+        };
 
-        let response_str = String::from_utf8(response.body.into_inner())
-            .map_err(|e| EmbeddingError::ResponseError(e.to_string()))?;
+        String::from_utf8(response.body.into_inner()).map_err(|e| {
+            metrics.failure(None);
+            EmbeddingError::ResponseError(e.to_string())
+        })
+    }
 
-        let result: EmbeddingResponse =
-            serde_json::from_str(&response_str).map_err(EmbeddingError::JsonError)?;
+    /// The batch inference input records embedding `documents` with this model, one per document.
+    pub fn batch_records(&self, documents: &[String]) -> Vec<BatchInputRecord<serde_json::Value>> {
+        documents
+            .iter()
+            .enumerate()
+            .map(|(index, document)| {
+                let model_input = if self.is_cohere_v3() {
+                    serde_json::json!(self.cohere_request(vec![document.to_owned()]))
+                } else {
+                    serde_json::json!(self.titan_request(document.to_owned()))
+                };
+                BatchInputRecord {
+                    record_id: record_id(index),
+                    model_input,
+                }
+            })
+            .collect()
+    }
 
-        Ok(result)
+    /// Parse the output file of a batch job created from [`EmbeddingModel::batch_records`].
+    pub fn parse_batch_output(
+        &self,
+        documents: Vec<String>,
+        output: &str,
+    ) -> Result<Vec<Embedding>, BatchError> {
+        let records: Vec<BatchOutputRecord<BatchEmbeddingOutput>> = from_jsonl(output)?;
+
+        outputs_in_order(records, documents.len())?
+            .into_iter()
+            .zip(documents)
+            .map(|(output, document)| {
+                let vec = match output {
+                    BatchEmbeddingOutput::Titan(response) => response.into_vector(self.output_type),
+                    BatchEmbeddingOutput::Cohere(response) => response
+                        .embeddings
+                        .into_vectors(self.output_type)
+                        .into_iter()
+                        .next(),
+                };
+                vec.map(|vec| Embedding { document, vec })
+                    .ok_or_else(|| BatchError::Json(serde::de::Error::custom("Empty embedding")))
+            })
+            .collect()
+    }
+
+    /// Embed `documents` with a Bedrock batch inference job instead of on-demand calls.
+    ///
+    /// The documents are written as JSONL to [`BatchConfig::input_uri`], and the call returns
+    /// once the job completed and its output was parsed. Batch jobs require at least 100 records
+    /// and may take hours, so this is meant for very large corpora.
+    #[cfg(feature = "batch")]
+    pub async fn embed_texts_batch(
+        &self,
+        documents: Vec<String>,
+        config: &crate::batch::BatchConfig,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        let jsonl = crate::batch::to_jsonl(&self.batch_records(&documents))?;
+        let output = crate::batch::run_job(&self.client, &self.model, jsonl, config)
+            .await
+            .map_err(|e| EmbeddingError::ProviderError(e.to_string()))?;
+
+        self.parse_batch_output(documents, &output)
+            .map_err(|e| EmbeddingError::ResponseError(e.to_string()))
+    }
+
+    /// The [`cache_key`] of the embedding of `text` with this model.
+    pub fn cache_key(&self, text: &str) -> String {
+        use rig::embeddings::EmbeddingModel as _;
+
+        // The request options besides the text that change the embedding
+        let options = if self.is_cohere_v3() {
+            serde_json::json!({
+                "input_type": self.input_type,
+                "embedding_types": self.embedding_types(),
+            })
+        } else {
+            serde_json::json!({
+                "normalize": self.normalize,
+                "embedding_types": self.embedding_types(),
+            })
+        };
+        cache_key(
+            base_model_id(&self.model),
+            self.ndims(),
+            &options.to_string(),
+            text,
+        )
+    }
+
+    /// Embed the documents missing from `cache`, then store their embeddings.
+    async fn embed_texts_cached(
+        &self,
+        cache: &dyn EmbeddingCache,
+        documents: Vec<String>,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        let keys: Vec<String> = documents.iter().map(|doc| self.cache_key(doc)).collect();
+
+        let mut cached = Vec::with_capacity(documents.len());
+        for key in &keys {
+            cached.push(cache.get(key).await);
+        }
+
+        let misses: Vec<String> = documents
+            .iter()
+            .zip(&cached)
+            .filter(|(_, vec)| vec.is_none())
+            .map(|(doc, _)| doc.to_owned())
+            .collect();
+        tracing::debug!(
+            target: "rig::bedrock",
+            "Embedding cache: {} hits, {} misses",
+            documents.len() - misses.len(),
+            misses.len()
+        );
+
+        let mut embedded = if misses.is_empty() {
+            Vec::new()
+        } else {
+            self.embed_texts_uncached(misses).await?
+        }
+        .into_iter();
+
+        let mut results = Vec::with_capacity(documents.len());
+        for ((document, key), vec) in documents.into_iter().zip(keys).zip(cached) {
+            let vec = match vec {
+                Some(vec) => vec,
+                None => {
+                    let embedding = embedded
+                        .next()
+                        .ok_or_else(|| EmbeddingError::ResponseError("Missing embedding".into()))?;
+                    cache.put(&key, &embedding.vec).await;
+                    embedding.vec
+                }
+            };
+            results.push(Embedding { document, vec });
+        }
+
+        Ok(results)
+    }
+
+    async fn embed_texts_uncached(
+        &self,
+        documents: Vec<String>,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        if self.is_cohere_v3() {
+            return self.embed_texts_cohere(documents).await;
+        }
+
+        // Collecting the futures first keeps the returned future `Send`
+        let requests: Vec<_> = documents
+            .into_iter()
+            .map(|document| self.embed_text_titan(document))
+            .collect();
+
+        stream::iter(requests)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await
+    }
+
+    async fn embed_text_titan(&self, document: String) -> Result<Embedding, EmbeddingError> {
+        let response = self
+            .document_to_embeddings(self.titan_request(document.clone()))
+            .await?;
+        let vec = response.into_vector(self.output_type).ok_or_else(|| {
+            EmbeddingError::ResponseError(format!(
+                "Missing {:?} embedding in the response",
+                self.output_type
+            ))
+        })?;
+
+        Ok(Embedding { document, vec })
+    }
+
+    async fn embed_texts_cohere(
+        &self,
+        documents: Vec<String>,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        let requests: Vec<_> = documents
+            .chunks(COHERE_MAX_TEXTS_PER_REQUEST)
+            .map(|batch| self.embed_batch_cohere(batch.to_vec()))
+            .collect();
+
+        let batches: Vec<Vec<Embedding>> = stream::iter(requests)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+
+        Ok(batches.into_iter().flatten().collect())
+    }
+
+    /// Embed up to [`COHERE_MAX_TEXTS_PER_REQUEST`] documents with a single request.
+    async fn embed_batch_cohere(
+        &self,
+        batch: Vec<String>,
+    ) -> Result<Vec<Embedding>, EmbeddingError> {
+        let vectors = self
+            .cohere_embeddings(self.cohere_request(batch.clone()))
+            .await?;
+
+        if vectors.len() != batch.len() {
+            return Err(EmbeddingError::ResponseError(format!(
+                "Expected {} embeddings, got {}",
+                batch.len(),
+                vectors.len()
+            )));
+        }
+
+        Ok(batch
+            .into_iter()
+            .zip(vectors)
+            .map(|(document, vec)| Embedding { document, vec })
+            .collect())
     }
 }
 
@@ -84,11 +852,18 @@ impl embeddings::EmbeddingModel for EmbeddingModel {
     type Client = Client;
 
     fn make(client: &Self::Client, model: impl Into<String>, dims: Option<usize>) -> Self {
-        Self::new(client.clone(), model, dims)
+        Self {
+            ndims: dims,
+            ..Self::with_defaults(client.clone(), model)
+        }
     }
 
     fn ndims(&self) -> usize {
-        self.ndims.unwrap_or_default()
+        match self.ndims {
+            Some(ndims) => ndims,
+            None if self.is_cohere_v3() => COHERE_EMBED_V3_NDIMS,
+            None => 0,
+        }
     }
 
     async fn embed_texts(
@@ -96,33 +871,363 @@ impl embeddings::EmbeddingModel for EmbeddingModel {
         documents: impl IntoIterator<Item = String> + Send,
     ) -> Result<Vec<Embedding>, EmbeddingError> {
         let documents: Vec<_> = documents.into_iter().collect();
+        self.validate_dimensions()?;
 
-        let mut results = Vec::new();
-        let mut errors = Vec::new();
-
-        let mut iterator = documents.into_iter();
-        while let Some(embedding) = iterator.next().map(|doc| async move {
-            let request = EmbeddingRequest {
-                input_text: doc.to_owned(),
-                dimensions: self.ndims(),
-                normalize: true,
-            };
-            self.document_to_embeddings(request)
-                .await
-                .map(|embeddings| Embedding {
-                    document: doc.to_owned(),
-                    vec: embeddings.embedding,
-                })
-        }) {
-            match embedding.await {
-                Ok(embedding) => results.push(embedding),
-                Err(err) => errors.push(err),
-            }
+        match &self.cache {
+            Some(cache) => self.embed_texts_cached(cache.as_ref(), documents).await,
+            None => self.embed_texts_uncached(documents).await,
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rig::embeddings::EmbeddingModel as _;
+    use std::time::Duration;
+
+    fn builder(model: &str) -> EmbeddingModelBuilder {
+        let client = Client::from(aws_sdk_bedrockruntime::Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version_latest()
+                .build(),
+        ));
+        EmbeddingModel::builder(client, model)
+    }
+
+    fn model(model: &str) -> EmbeddingModel {
+        builder(model).build_unchecked()
+    }
+
+    #[test]
+    fn test_cohere_request_serialization() {
+        let request = CohereEmbeddingRequest {
+            texts: vec!["hello".into(), "world".into()],
+            input_type: CohereInputType::SearchQuery,
+            embedding_types: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({ "texts": ["hello", "world"], "input_type": "search_query" })
+        );
+    }
+
+    #[test]
+    fn test_cohere_response_deserialization() {
+        let floats: CohereEmbeddingResponse = serde_json::from_str(
+            r#"{"id":"1","response_type":"embeddings_floats","texts":["a","b"],"embeddings":[[0.1,0.2],[0.3,0.4]]}"#,
+        )
+        .unwrap();
+        let floats: Vec<Vec<f64>> = floats.embeddings.into();
+        assert_eq!(floats, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+
+        let by_type: CohereEmbeddingResponse = serde_json::from_str(
+            r#"{"id":"1","response_type":"embeddings_by_type","texts":["a"],"embeddings":{"float":[[0.5]]}}"#,
+        )
+        .unwrap();
+        let by_type: Vec<Vec<f64>> = by_type.embeddings.into();
+        assert_eq!(by_type, vec![vec![0.5]]);
+    }
+
+    #[test]
+    fn test_ndims_defaults() {
+        assert!(model(COHERE_EMBED_ENGLISH_V3).is_cohere_v3());
+        assert_eq!(model(COHERE_EMBED_MULTILINGUAL_V3).ndims(), 1024);
+        assert!(!model(AMAZON_TITAN_EMBED_TEXT_V2_0).is_cohere_v3());
+        assert!(model("us.cohere.embed-english-v3").is_cohere_v3());
+        assert!(!model("cohere.embed-v4:0").is_cohere_v3());
+        assert_eq!(model("cohere.embed-v4:0").ndims(), 0);
+        assert_eq!(model(AMAZON_TITAN_EMBED_TEXT_V2_0).ndims(), 0);
+    }
+
+    #[test]
+    fn test_dimension_validation() {
+        assert!(
+            builder(AMAZON_TITAN_EMBED_TEXT_V2_0)
+                .ndims(512)
+                .build()
+                .is_ok()
+        );
+        assert!(builder(AMAZON_TITAN_EMBED_TEXT_V2_0).build().is_ok());
+        assert_eq!(
+            builder(&format!("us.{AMAZON_TITAN_EMBED_TEXT_V2_0}"))
+                .ndims(768)
+                .build()
+                .err(),
+            Some(EmbeddingOptionsError::Dimensions(InvalidDimensionsError {
+                model: format!("us.{AMAZON_TITAN_EMBED_TEXT_V2_0}"),
+                ndims: 768,
+                supported: vec![256, 512, 1024],
+            }))
+        );
+        assert!(builder(COHERE_EMBED_ENGLISH_V3).ndims(512).build().is_err());
+
+        let custom = builder("my-imported-model").ndims(384);
+        assert!(custom.clone().build().is_ok());
+        assert!(
+            custom
+                .clone()
+                .supported_dimensions([384, 768])
+                .build()
+                .is_ok()
+        );
+        assert!(custom.supported_dimensions([768]).build().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cached_embeddings_skip_bedrock() {
+        let cache = Arc::new(crate::cache::InMemoryEmbeddingCache::new());
+        let model = builder(AMAZON_TITAN_EMBED_TEXT_V2_0)
+            .cache(cache.clone())
+            .build_unchecked();
+        cache.put(&model.cache_key("hello"), &[0.1, 0.2]).await;
+        cache.put(&model.cache_key("world"), &[0.3]).await;
+
+        // The client has no credentials, so this only succeeds without calling Bedrock
+        let embeddings = model
+            .embed_texts(vec!["hello".to_string(), "world".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(embeddings[0].vec, vec![0.1, 0.2]);
+        assert_eq!(embeddings[1].document, "world");
+        assert_eq!(embeddings[1].vec, vec![0.3]);
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_model_options() {
+        let query = builder(COHERE_EMBED_ENGLISH_V3)
+            .input_type(CohereInputType::SearchQuery)
+            .build_unchecked();
+        let document = model(COHERE_EMBED_ENGLISH_V3);
+        assert_ne!(query.cache_key("hello"), document.cache_key("hello"));
+        assert_eq!(
+            document.cache_key("hello"),
+            model(&format!("us.{COHERE_EMBED_ENGLISH_V3}")).cache_key("hello")
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let client = model(AMAZON_TITAN_EMBED_TEXT_V2_0).client;
+
+        let titan = EmbeddingModel::builder(client.clone(), AMAZON_TITAN_EMBED_TEXT_V2_0)
+            .ndims(256)
+            .normalize(false)
+            .output_type(EmbeddingOutputType::Binary)
+            .concurrency(0)
+            .build()
+            .unwrap();
+        assert_eq!(titan.ndims(), 256);
+        assert_eq!(titan.concurrency, 1);
+        assert_eq!(
+            serde_json::to_value(titan.titan_request("hello".into())).unwrap(),
+            serde_json::json!({
+                "inputText": "hello",
+                "dimensions": 256,
+                "normalize": false,
+                "embeddingTypes": ["binary"]
+            })
+        );
+
+        assert!(matches!(
+            EmbeddingModel::builder(client.clone(), AMAZON_TITAN_EMBED_TEXT_V2_0)
+                .ndims(100)
+                .build(),
+            Err(EmbeddingOptionsError::Dimensions(_))
+        ));
+        assert!(matches!(
+            EmbeddingModel::builder(client.clone(), AMAZON_TITAN_EMBED_TEXT_V2_0)
+                .output_type(EmbeddingOutputType::Int8)
+                .build(),
+            Err(EmbeddingOptionsError::UnsupportedOutputType { .. })
+        ));
+
+        let cohere = EmbeddingModel::builder(client, COHERE_EMBED_ENGLISH_V3)
+            .output_type(EmbeddingOutputType::Int8)
+            .input_type(CohereInputType::SearchQuery)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(cohere.cohere_request(vec!["hello".into()])).unwrap(),
+            serde_json::json!({
+                "texts": ["hello"],
+                "input_type": "search_query",
+                "embedding_types": ["int8"]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_document_to_embeddings() {
+        use crate::testing::{MockBedrock, MockResponse};
 
-        match errors.as_slice() {
-            [] => Ok(results),
-            [err, ..] => Err(EmbeddingError::ResponseError(err.to_string())),
+        let mock = MockBedrock::new().with_response(MockResponse::json(
+            200,
+            serde_json::json!({ "embedding": [0.5, 0.5], "inputTextTokenCount": 2 }),
+        ));
+        let model =
+            EmbeddingModel::builder(mock.client(), AMAZON_TITAN_EMBED_TEXT_V2_0).build_unchecked();
+
+        let request = EmbeddingRequest {
+            normalize: false,
+            ..EmbeddingRequest::new("Hello", 256)
+        };
+        let response = model.document_to_embeddings(request).await.unwrap();
+
+        assert_eq!(response.embedding, vec![0.5, 0.5]);
+        assert_eq!(
+            mock.requests()[0].json().unwrap(),
+            serde_json::json!({ "inputText": "Hello", "dimensions": 256, "normalize": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_throttled_requests_are_retried() {
+        use crate::testing::{MockBedrock, MockResponse};
+
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::error(
+                429,
+                "ThrottlingException",
+                "Too many requests",
+            ))
+            .with_response(MockResponse::json(
+                200,
+                serde_json::json!({ "embedding": [0.5, 0.5], "inputTextTokenCount": 2 }),
+            ));
+        let model = EmbeddingModel::builder(mock.client(), AMAZON_TITAN_EMBED_TEXT_V2_0)
+            .retry_policy(RetryPolicy::new(1).with_initial_delay(Duration::from_millis(1)))
+            .build_unchecked();
+
+        let embedding = model.embed_text("Hello").await.unwrap();
+
+        assert_eq!(embedding.vec, vec![0.5, 0.5]);
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_stop_after_max_retries() {
+        use crate::testing::{MockBedrock, MockResponse};
+
+        let throttled = || MockResponse::error(429, "ThrottlingException", "Too many requests");
+        let mock = MockBedrock::new()
+            .with_response(throttled())
+            .with_response(throttled())
+            .with_response(throttled());
+        let model = EmbeddingModel::builder(mock.client(), AMAZON_TITAN_EMBED_TEXT_V2_0)
+            .retry_policy(RetryPolicy::new(1).with_initial_delay(Duration::from_millis(1)))
+            .build_unchecked();
+
+        assert!(model.embed_text("Hello").await.is_err());
+        assert_eq!(mock.requests().len(), 2);
+        assert_eq!(mock.remaining_responses(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_delays_requests() {
+        use crate::testing::{MockBedrock, MockResponse};
+
+        let embedding = || {
+            MockResponse::json(
+                200,
+                serde_json::json!({ "embedding": [0.5, 0.5], "inputTextTokenCount": 2 }),
+            )
+        };
+        let mock = MockBedrock::new()
+            .with_response(embedding())
+            .with_response(embedding())
+            .with_response(embedding());
+        // Bursts of two requests, then one request every half second
+        let model = EmbeddingModel::builder(mock.client(), AMAZON_TITAN_EMBED_TEXT_V2_0)
+            .rate_limiter(RateLimiter::new().with_requests_per_second(2.0))
+            .build_unchecked();
+
+        let started = tokio::time::Instant::now();
+        for _ in 0..2 {
+            model.embed_text("Hello").await.unwrap();
         }
+        assert!(started.elapsed() < Duration::from_millis(250));
+
+        model.embed_text("Hello").await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(450));
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_typed_embeddings_deserialization() {
+        let titan: EmbeddingResponse = serde_json::from_str(
+            r#"{"inputTextTokenCount":1,"embeddingsByType":{"binary":[1,0,1]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            titan.into_vector(EmbeddingOutputType::Binary),
+            Some(vec![1.0, 0.0, 1.0])
+        );
+
+        let cohere: CohereEmbeddingResponse =
+            serde_json::from_str(r#"{"embeddings":{"int8":[[-3,7]]}}"#).unwrap();
+        assert_eq!(
+            cohere.embeddings.into_vectors(EmbeddingOutputType::Int8),
+            vec![vec![-3.0, 7.0]]
+        );
+    }
+
+    #[test]
+    fn test_batch_records() {
+        let documents = vec!["hello".to_string()];
+
+        let titan = builder(AMAZON_TITAN_EMBED_TEXT_V2_0)
+            .ndims(256)
+            .build_unchecked()
+            .batch_records(&documents);
+        assert_eq!(
+            serde_json::to_value(&titan[0]).unwrap(),
+            serde_json::json!({
+                "recordId": "00000000000",
+                "modelInput": { "inputText": "hello", "dimensions": 256, "normalize": true }
+            })
+        );
+
+        let cohere = model(COHERE_EMBED_ENGLISH_V3).batch_records(&documents);
+        assert_eq!(
+            cohere[0].model_input,
+            serde_json::json!({ "texts": ["hello"], "input_type": "search_document" })
+        );
+    }
+
+    #[test]
+    fn test_parse_batch_output() {
+        let output = r#"{"recordId":"00000000001","modelInput":{},"modelOutput":{"embeddings":[[0.3,0.4]]}}
+{"recordId":"00000000000","modelInput":{},"modelOutput":{"embedding":[0.1,0.2],"inputTextTokenCount":2}}
+"#;
+
+        let embeddings = model(AMAZON_TITAN_EMBED_TEXT_V2_0)
+            .parse_batch_output(vec!["a".into(), "b".into()], output)
+            .unwrap();
+
+        assert_eq!(embeddings[0].document, "a");
+        assert_eq!(embeddings[0].vec, vec![0.1, 0.2]);
+        assert_eq!(embeddings[1].document, "b");
+        assert_eq!(embeddings[1].vec, vec![0.3, 0.4]);
+    }
+
+    #[test]
+    fn test_input_type() {
+        assert_eq!(
+            model(COHERE_EMBED_ENGLISH_V3).input_type(),
+            CohereInputType::SearchDocument
+        );
+
+        let query_model = builder(COHERE_EMBED_ENGLISH_V3)
+            .input_type(CohereInputType::SearchQuery)
+            .build_unchecked();
+        assert_eq!(query_model.input_type(), CohereInputType::SearchQuery);
+
+        let clustering_model = model(COHERE_EMBED_ENGLISH_V3)
+            .client
+            .embedding_model_with_input_type(COHERE_EMBED_ENGLISH_V3, CohereInputType::Clustering);
+        assert_eq!(clustering_model.input_type(), CohereInputType::Clustering);
     }
 }
@@ -0,0 +1,228 @@
+use std::time::{Duration, SystemTime};
+
+use aws_sdk_bedrock::error::BuildError;
+use aws_sdk_bedrock::types as aws;
+use tokio::time::Instant;
+
+use super::{
+    EvaluationError, EvaluationJob, EvaluationJobConfig, EvaluationJobStatus, EvaluationKind,
+    EvaluationTask,
+};
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+
+impl Client {
+    /// Start the evaluation job `config` and return its ARN.
+    pub async fn create_evaluation_job(
+        &self,
+        config: &EvaluationJobConfig,
+    ) -> Result<String, EvaluationError> {
+        let evaluation_config = evaluation_config(config).map_err(invalid_config)?;
+        let models = config
+            .models
+            .iter()
+            .map(|model| {
+                aws::EvaluationBedrockModel::builder()
+                    .model_identifier(&model.model_id)
+                    .set_inference_params(model.inference_params.clone())
+                    .build()
+                    .map(aws::EvaluationModelConfig::BedrockModel)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid_config)?;
+        let output_data_config = aws::EvaluationOutputDataConfig::builder()
+            .s3_uri(&config.output_uri)
+            .build()
+            .map_err(invalid_config)?;
+
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .create_evaluation_job()
+            .job_name(&config.job_name)
+            .set_job_description(config.description.clone())
+            .role_arn(&config.role_arn)
+            .evaluation_config(evaluation_config)
+            .inference_config(aws::EvaluationInferenceConfig::Models(models))
+            .output_data_config(output_data_config)
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        let job_arn = output.job_arn().to_string();
+        tracing::info!(target: "rig::bedrock", "Started evaluation job {job_arn}");
+        Ok(job_arn)
+    }
+
+    /// The evaluation job `job`, its name or ARN.
+    pub async fn get_evaluation_job(&self, job: &str) -> Result<EvaluationJob, EvaluationError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .get_evaluation_job()
+            .job_identifier(job)
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        Ok(EvaluationJob {
+            job_arn: output.job_arn().to_string(),
+            job_name: output.job_name().to_string(),
+            status: EvaluationJobStatus::from(output.status().as_str()),
+            human: *output.job_type() == aws::EvaluationJobType::Human,
+            creation_time: SystemTime::try_from(*output.creation_time()).ok(),
+            failure_messages: output.failure_messages().to_vec(),
+            output_uri: output
+                .output_data_config()
+                .map(|output| output.s3_uri().to_string()),
+        })
+    }
+
+    /// The evaluation jobs of the account, only the ones with `status` if set.
+    pub async fn list_evaluation_jobs(
+        &self,
+        status: Option<EvaluationJobStatus>,
+    ) -> Result<Vec<EvaluationJob>, EvaluationError> {
+        let sdk_config = self.sdk_config().await;
+        let bedrock = aws_sdk_bedrock::Client::new(sdk_config);
+
+        let mut jobs = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = bedrock
+                .list_evaluation_jobs()
+                .set_status_equals(
+                    status
+                        .as_ref()
+                        .map(|status| aws::EvaluationJobStatus::from(status.as_str())),
+                )
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(BedrockError::from)?;
+
+            jobs.extend(output.job_summaries().iter().map(|summary| EvaluationJob {
+                job_arn: summary.job_arn().to_string(),
+                job_name: summary.job_name().to_string(),
+                status: EvaluationJobStatus::from(summary.status().as_str()),
+                human: *summary.job_type() == aws::EvaluationJobType::Human,
+                creation_time: SystemTime::try_from(*summary.creation_time()).ok(),
+                failure_messages: Vec::new(),
+                output_uri: None,
+            }));
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(jobs);
+            }
+        }
+    }
+
+    /// Stop the evaluation job `job`, its name or ARN.
+    pub async fn stop_evaluation_job(&self, job: &str) -> Result<(), EvaluationError> {
+        let sdk_config = self.sdk_config().await;
+        aws_sdk_bedrock::Client::new(sdk_config)
+            .stop_evaluation_job()
+            .job_identifier(job)
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+        Ok(())
+    }
+
+    /// Check the evaluation job `job` every `poll_interval` until it completes, failing if it
+    /// fails, is stopped or doesn't finish within `timeout`. Automatic evaluations usually take
+    /// minutes to hours; human evaluations as long as the workers need.
+    pub async fn wait_for_evaluation_job(
+        &self,
+        job: &str,
+        poll_interval: Duration,
+        timeout: Option<Duration>,
+    ) -> Result<EvaluationJob, EvaluationError> {
+        let started = Instant::now();
+        loop {
+            let evaluation = self.get_evaluation_job(job).await?;
+            match evaluation.status {
+                EvaluationJobStatus::Completed => return Ok(evaluation),
+                status if status.is_terminal() => {
+                    return Err(EvaluationError::JobFailed {
+                        status,
+                        messages: evaluation.failure_messages,
+                    });
+                }
+                _ => {}
+            }
+
+            if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                return Err(EvaluationError::Timeout {
+                    job_arn: evaluation.job_arn,
+                });
+            }
+
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+fn evaluation_config(config: &EvaluationJobConfig) -> Result<aws::EvaluationConfig, BuildError> {
+    let tasks = config
+        .tasks
+        .iter()
+        .map(dataset_metric_config)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match &config.evaluation {
+        EvaluationKind::Automated => aws::AutomatedEvaluationConfig::builder()
+            .set_dataset_metric_configs(Some(tasks))
+            .build()
+            .map(aws::EvaluationConfig::Automated),
+        EvaluationKind::Human {
+            flow_definition_arn,
+            instructions,
+            custom_metrics,
+        } => {
+            let workflow = aws::HumanWorkflowConfig::builder()
+                .flow_definition_arn(flow_definition_arn)
+                .set_instructions(instructions.clone())
+                .build()?;
+            let custom_metrics = custom_metrics
+                .iter()
+                .map(|metric| {
+                    aws::HumanEvaluationCustomMetric::builder()
+                        .name(&metric.name)
+                        .set_description(metric.description.clone())
+                        .rating_method(&metric.rating_method)
+                        .build()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            aws::HumanEvaluationConfig::builder()
+                .human_workflow_config(workflow)
+                .set_custom_metrics(Some(custom_metrics).filter(|metrics| !metrics.is_empty()))
+                .set_dataset_metric_configs(Some(tasks))
+                .build()
+                .map(aws::EvaluationConfig::Human)
+        }
+    }
+}
+
+fn dataset_metric_config(
+    task: &EvaluationTask,
+) -> Result<aws::EvaluationDatasetMetricConfig, BuildError> {
+    let dataset = aws::EvaluationDataset::builder()
+        .name(&task.dataset_name)
+        .set_dataset_location(
+            task.dataset_uri
+                .clone()
+                .map(aws::EvaluationDatasetLocation::S3Uri),
+        )
+        .build()?;
+
+    aws::EvaluationDatasetMetricConfig::builder()
+        .task_type(aws::EvaluationTaskType::from(task.task_type.as_str()))
+        .dataset(dataset)
+        .set_metric_names(Some(task.metrics.clone()))
+        .build()
+}
+
+fn invalid_config(error: BuildError) -> EvaluationError {
+    EvaluationError::InvalidConfig(error.into())
+}
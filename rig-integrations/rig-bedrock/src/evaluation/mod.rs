@@ -0,0 +1,309 @@
+//! Bedrock model evaluation jobs, to run regression evaluations of prompts and models against
+//! datasets in S3.
+//!
+//! Automatic evaluations score the responses with built-in metrics such as `Builtin.Accuracy`
+//! or `Builtin.Robustness`; human evaluations send them to a work team for rating. The types in
+//! this module are always available; running jobs requires the `control-plane` feature.
+//!
+//! ```rust,ignore
+//! let config = EvaluationJobConfig::automated(
+//!     "nova-lite-regression",
+//!     ROLE_ARN,
+//!     "s3://my-bucket/evaluations/",
+//!     vec![EvaluationModel::new(AMAZON_NOVA_LITE)],
+//!     vec![EvaluationTask::new(
+//!         EvaluationTaskType::QuestionAndAnswer,
+//!         "support-questions",
+//!         "s3://my-bucket/datasets/support.jsonl",
+//!         ["Builtin.Accuracy", "Builtin.Robustness"],
+//!     )],
+//! );
+//!
+//! let job_arn = client.create_evaluation_job(&config).await?;
+//! let job = client
+//!     .wait_for_evaluation_job(&job_arn, Duration::from_secs(60), None)
+//!     .await?;
+//! println!("Results in {:?}", job.output_uri);
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/evaluation.html>
+
+#[cfg(feature = "control-plane")]
+mod job;
+
+use std::fmt;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::converse_output::{UnknownVariantValue, api_values};
+use crate::types::errors::BedrockError;
+
+/// The definition of an evaluation job.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationJobConfig {
+    pub job_name: String,
+    pub description: Option<String>,
+    /// IAM role Bedrock assumes to invoke the models and access the datasets and output.
+    pub role_arn: String,
+    /// S3 prefix Bedrock writes the results to.
+    pub output_uri: String,
+    /// The models to evaluate, one for automatic jobs and up to two for human jobs.
+    pub models: Vec<EvaluationModel>,
+    pub tasks: Vec<EvaluationTask>,
+    pub evaluation: EvaluationKind,
+}
+
+impl EvaluationJobConfig {
+    /// An automatic evaluation of `models` on `tasks`.
+    pub fn automated(
+        job_name: impl Into<String>,
+        role_arn: impl Into<String>,
+        output_uri: impl Into<String>,
+        models: Vec<EvaluationModel>,
+        tasks: Vec<EvaluationTask>,
+    ) -> Self {
+        Self {
+            job_name: job_name.into(),
+            description: None,
+            role_arn: role_arn.into(),
+            output_uri: output_uri.into(),
+            models,
+            tasks,
+            evaluation: EvaluationKind::Automated,
+        }
+    }
+
+    /// A human evaluation of `models` on `tasks`, by the work team of the SageMaker flow
+    /// definition `flow_definition_arn`.
+    pub fn human(
+        job_name: impl Into<String>,
+        role_arn: impl Into<String>,
+        output_uri: impl Into<String>,
+        models: Vec<EvaluationModel>,
+        tasks: Vec<EvaluationTask>,
+        flow_definition_arn: impl Into<String>,
+    ) -> Self {
+        Self {
+            evaluation: EvaluationKind::Human {
+                flow_definition_arn: flow_definition_arn.into(),
+                instructions: None,
+                custom_metrics: Vec::new(),
+            },
+            ..Self::automated(job_name, role_arn, output_uri, models, tasks)
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EvaluationKind {
+    Automated,
+    Human {
+        flow_definition_arn: String,
+        /// Instructions shown to the workers.
+        instructions: Option<String>,
+        /// Metrics rated by the workers, referenced by name in the tasks.
+        custom_metrics: Vec<HumanMetric>,
+    },
+}
+
+/// A metric rated by the workers of a human evaluation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HumanMetric {
+    pub name: String,
+    pub description: Option<String>,
+    /// How the workers rate responses, e.g. `ThumbsUpDown` or `IndividualLikertScale`.
+    pub rating_method: String,
+}
+
+/// A model to evaluate.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationModel {
+    /// A model id, inference profile id or ARN.
+    pub model_id: String,
+    /// The inference parameters, as the JSON of the model's native request format.
+    pub inference_params: Option<String>,
+}
+
+impl EvaluationModel {
+    pub fn new(model_id: impl Into<String>) -> Self {
+        Self {
+            model_id: model_id.into(),
+            inference_params: None,
+        }
+    }
+
+    pub fn with_inference_params(mut self, inference_params: serde_json::Value) -> Self {
+        self.inference_params = Some(inference_params.to_string());
+        self
+    }
+}
+
+/// A dataset and the metrics the responses to its prompts are evaluated on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationTask {
+    pub task_type: EvaluationTaskType,
+    pub dataset_name: String,
+    /// The JSONL prompt dataset, or `None` for the built-in dataset `dataset_name`, e.g.
+    /// `Builtin.BoolQ`.
+    pub dataset_uri: Option<String>,
+    pub metrics: Vec<String>,
+}
+
+impl EvaluationTask {
+    pub fn new(
+        task_type: EvaluationTaskType,
+        dataset_name: impl Into<String>,
+        dataset_uri: impl Into<String>,
+        metrics: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            task_type,
+            dataset_name: dataset_name.into(),
+            dataset_uri: Some(dataset_uri.into()),
+            metrics: metrics.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EvaluationTaskType {
+    Classification,
+    Custom,
+    Generation,
+    QuestionAndAnswer,
+    Summarization,
+    Unknown(UnknownVariantValue),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EvaluationJobStatus {
+    InProgress,
+    Completed,
+    Failed,
+    Stopping,
+    Stopped,
+    Deleting,
+    Unknown(UnknownVariantValue),
+}
+
+api_values!(EvaluationTaskType {
+    Classification => "Classification",
+    Custom => "Custom",
+    Generation => "Generation",
+    QuestionAndAnswer => "QuestionAndAnswer",
+    Summarization => "Summarization",
+});
+
+api_values!(EvaluationJobStatus {
+    InProgress => "InProgress",
+    Completed => "Completed",
+    Failed => "Failed",
+    Stopping => "Stopping",
+    Stopped => "Stopped",
+    Deleting => "Deleting",
+});
+
+impl EvaluationJobStatus {
+    /// Whether the job won't change status anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Stopped)
+    }
+}
+
+/// The state of an evaluation job.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationJob {
+    pub job_arn: String,
+    pub job_name: String,
+    pub status: EvaluationJobStatus,
+    /// Whether the job is a human evaluation.
+    pub human: bool,
+    pub creation_time: Option<SystemTime>,
+    /// Why the job failed, only available from `Client::get_evaluation_job`.
+    pub failure_messages: Vec<String>,
+    /// Where the results are written, only available from `Client::get_evaluation_job`.
+    pub output_uri: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum EvaluationError {
+    /// The configuration couldn't be converted to a request, e.g. a task without metrics.
+    InvalidConfig(Box<dyn std::error::Error + Send + Sync>),
+    Request(BedrockError),
+    /// The job ended without completing.
+    JobFailed {
+        status: EvaluationJobStatus,
+        messages: Vec<String>,
+    },
+    /// The job didn't finish within the timeout.
+    Timeout {
+        job_arn: String,
+    },
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidConfig(error) => write!(f, "Invalid evaluation job: {error}"),
+            Self::Request(error) => write!(f, "Evaluation request failed: {error}"),
+            Self::JobFailed { status, messages } => write!(
+                f,
+                "Evaluation job ended with status {}: {}",
+                status.as_str(),
+                messages.join("; ")
+            ),
+            Self::Timeout { job_arn } => {
+                write!(f, "Timed out waiting for evaluation job {job_arn}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvaluationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidConfig(error) => Some(error.as_ref()),
+            Self::Request(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<BedrockError> for EvaluationError {
+    fn from(error: BedrockError) -> Self {
+        Self::Request(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_config() {
+        let config = EvaluationJobConfig::human(
+            "tone-review",
+            "arn:aws:iam::123456789012:role/Evaluation",
+            "s3://my-bucket/evaluations/",
+            vec![EvaluationModel::new("amazon.nova-lite-v1:0")],
+            vec![EvaluationTask::new(
+                EvaluationTaskType::Generation,
+                "prompts",
+                "s3://my-bucket/prompts.jsonl",
+                ["Friendliness"],
+            )],
+            "arn:aws:sagemaker:us-east-1:123456789012:flow-definition/reviewers",
+        );
+
+        assert_eq!(config.tasks[0].metrics, vec!["Friendliness".to_string()]);
+        assert!(matches!(config.evaluation, EvaluationKind::Human { .. }));
+        assert!(EvaluationJobStatus::from("Stopped").is_terminal());
+        assert!(!EvaluationJobStatus::InProgress.is_terminal());
+    }
+}
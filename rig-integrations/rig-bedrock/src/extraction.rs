@@ -0,0 +1,335 @@
+//! Streaming structured extraction, surfacing the object a model extracts while its tool input
+//! streams, e.g. to progressively render a form.
+//!
+//! Like rig's [`Extractor`](rig::extractor::Extractor), the model is asked to call a `submit`
+//! tool whose parameters are the JSON schema of the extracted type. The streamed tool input is
+//! parsed as it arrives, closing unterminated strings, arrays and objects and dropping incomplete
+//! members, and the fully deserialized object is yielded once the tool call is complete.
+//!
+//! ```rust,ignore
+//! #[derive(Deserialize, JsonSchema)]
+//! struct Invoice {
+//!     customer: String,
+//!     lines: Vec<InvoiceLine>,
+//! }
+//!
+//! let extractor = client.streaming_extractor::<Invoice>(AMAZON_NOVA_PRO);
+//! let mut stream = extractor.extract_stream(document).await?;
+//! while let Some(event) = stream.next().await {
+//!     match event? {
+//!         ExtractionEvent::Partial(partial) => render_draft(&partial),
+//!         ExtractionEvent::Complete(invoice) => render(&invoice),
+//!     }
+//! }
+//! ```
+
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use rig::OneOrMany;
+use rig::completion::{CompletionRequest, ToolDefinition};
+use rig::extractor::ExtractionError;
+use rig::message::{Message, ToolChoice};
+use rig::streaming::StreamedAssistantContent;
+use schemars::{JsonSchema, schema_for};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::client::Client;
+use crate::completion::CompletionModel;
+
+const SUBMIT_TOOL_NAME: &str = "submit";
+
+const PREAMBLE: &str = "\
+You are an AI assistant whose purpose is to extract structured data from the provided text.
+You will have access to a `submit` function that defines the structure of the data to extract from the provided text.
+Use the `submit` function to submit the structured data.
+Be sure to fill out every field and ALWAYS CALL THE `submit` function, even with default values!!!.";
+
+/// An item of an [`ExtractionStream`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExtractionEvent<T> {
+    /// The object extracted so far, parsed from the incomplete tool input. It isn't validated
+    /// against the extracted type, and its last string or number may be cut off.
+    Partial(Value),
+    /// The complete object.
+    Complete(T),
+}
+
+pub type ExtractionStream<T> =
+    Pin<Box<dyn Stream<Item = Result<ExtractionEvent<T>, ExtractionError>> + Send>>;
+
+/// Extracts objects of type `T` from text, streaming the partial object as it is generated, see
+/// [`crate::extraction`].
+#[derive(Clone)]
+pub struct StreamingExtractor<T> {
+    model: CompletionModel,
+    preamble: String,
+    max_tokens: Option<u64>,
+    additional_params: Option<Value>,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T> StreamingExtractor<T>
+where
+    T: JsonSchema + DeserializeOwned + Send + 'static,
+{
+    pub fn new(model: CompletionModel) -> Self {
+        Self {
+            model,
+            preamble: PREAMBLE.to_string(),
+            max_tokens: None,
+            additional_params: None,
+            _t: PhantomData,
+        }
+    }
+
+    /// Add instructions to the preamble of the extractor.
+    pub fn with_preamble(mut self, preamble: &str) -> Self {
+        self.preamble.push_str(&format!(
+            "\n=============== ADDITIONAL INSTRUCTIONS ===============\n{preamble}"
+        ));
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_additional_params(mut self, additional_params: Value) -> Self {
+        self.additional_params = Some(additional_params);
+        self
+    }
+
+    /// Extract an object from `text`. The stream yields the partial object each time it changes,
+    /// and ends with the complete object, or with [`ExtractionError::NoData`] if the model didn't
+    /// call the `submit` tool.
+    pub async fn extract_stream(
+        &self,
+        text: impl Into<Message>,
+    ) -> Result<ExtractionStream<T>, ExtractionError> {
+        let request = CompletionRequest {
+            preamble: Some(self.preamble.clone()),
+            chat_history: OneOrMany::one(text.into()),
+            documents: vec![],
+            tools: vec![ToolDefinition {
+                name: SUBMIT_TOOL_NAME.to_string(),
+                description: "Submit the structured data you extracted from the provided text."
+                    .to_string(),
+                parameters: serde_json::json!(schema_for!(T)),
+            }],
+            temperature: None,
+            max_tokens: self.max_tokens,
+            tool_choice: Some(ToolChoice::Required),
+            additional_params: self.additional_params.clone(),
+        };
+        let mut response = self.model.stream(request).await?;
+
+        Ok(Box::pin(stream! {
+            let mut tool_call_id = None;
+            let mut input = String::new();
+            let mut partial = None;
+
+            while let Some(content) = response.next().await {
+                match content {
+                    Ok(StreamedAssistantContent::ToolCallDelta { id, delta }) => {
+                        if tool_call_id.as_ref() != Some(&id) {
+                            tool_call_id = Some(id);
+                            input.clear();
+                        }
+                        input.push_str(&delta);
+
+                        if let Some(parsed) = parse_partial_json(&input)
+                            && partial.as_ref() != Some(&parsed)
+                        {
+                            partial = Some(parsed.clone());
+                            yield Ok(ExtractionEvent::Partial(parsed));
+                        }
+                    }
+                    Ok(StreamedAssistantContent::ToolCall(tool_call))
+                        if tool_call.function.name == SUBMIT_TOOL_NAME =>
+                    {
+                        yield serde_json::from_value(tool_call.function.arguments)
+                            .map(ExtractionEvent::Complete)
+                            .map_err(ExtractionError::from);
+                        return;
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        yield Err(error.into());
+                        return;
+                    }
+                }
+            }
+
+            yield Err(ExtractionError::NoData);
+        }))
+    }
+}
+
+impl Client {
+    /// A streaming extractor of objects of type `T` using `model`.
+    pub fn streaming_extractor<T>(&self, model: impl Into<String>) -> StreamingExtractor<T>
+    where
+        T: JsonSchema + DeserializeOwned + Send + 'static,
+    {
+        StreamingExtractor::new(CompletionModel::new(self.clone(), model))
+    }
+}
+
+/// Parse the JSON text `input` was cut off from, as far as it is complete: unterminated strings,
+/// arrays and objects are closed, and members that can't be completed, such as a key without
+/// its value or a partial `true`, are dropped.
+pub fn parse_partial_json(input: &str) -> Option<Value> {
+    let mut containers = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    // Where the input can be cut to drop its last, incomplete member, and the containers left
+    // open there
+    let mut cuts = Vec::new();
+
+    for (index, c) in input.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                containers.push(c);
+                cuts.push((index + 1, containers.clone()));
+            }
+            '}' | ']' => {
+                containers.pop();
+            }
+            ',' => cuts.push((index, containers.clone())),
+            _ => {}
+        }
+    }
+
+    let mut closed = input.to_string();
+    if in_string {
+        if escaped {
+            closed.pop();
+        }
+        closed.push('"');
+    }
+
+    close(&closed, &containers).or_else(|| {
+        cuts.iter()
+            .rev()
+            .find_map(|(index, containers)| close(&input[..*index], containers))
+    })
+}
+
+fn close(input: &str, containers: &[char]) -> Option<Value> {
+    let mut closed = input.trim_end().to_string();
+    for container in containers.iter().rev() {
+        closed.push(if *container == '{' { '}' } else { ']' });
+    }
+    serde_json::from_str(&closed).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockBedrock, MockResponse};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_partial_json() {
+        assert_eq!(parse_partial_json(""), None);
+        assert_eq!(parse_partial_json("{\"na"), Some(json!({})));
+        assert_eq!(
+            parse_partial_json("{\"name\": \"Jo"),
+            Some(json!({ "name": "Jo" }))
+        );
+        assert_eq!(
+            parse_partial_json("{\"name\": \"Jo\", \"age\": 3"),
+            Some(json!({ "name": "Jo", "age": 3 }))
+        );
+        assert_eq!(
+            parse_partial_json("{\"name\": \"Jo\", \"tags\": [\"a\", tr"),
+            Some(json!({ "name": "Jo", "tags": ["a"] }))
+        );
+        assert_eq!(
+            parse_partial_json("{\"quote\": \"say \\"),
+            Some(json!({ "quote": "say " }))
+        );
+        assert_eq!(
+            parse_partial_json("{\"a\": {\"b\": 1}, \"c\":"),
+            Some(json!({ "a": { "b": 1 } }))
+        );
+    }
+
+    #[derive(Debug, Deserialize, JsonSchema, PartialEq)]
+    struct Person {
+        name: String,
+        age: u8,
+    }
+
+    #[tokio::test]
+    async fn test_extract_stream() {
+        let deltas = ["{\"name\": \"Jo", "hn\", \"ag", "e\": 30}"].map(|delta| {
+            (
+                "contentBlockDelta",
+                json!({ "contentBlockIndex": 0, "delta": { "toolUse": { "input": delta } } }),
+            )
+        });
+        let mock = MockBedrock::new().with_response(MockResponse::event_stream(
+            [
+                ("messageStart", json!({ "role": "assistant" })),
+                (
+                    "contentBlockStart",
+                    json!({
+                        "contentBlockIndex": 0,
+                        "start": { "toolUse": { "toolUseId": "tool_1", "name": "submit" } }
+                    }),
+                ),
+            ]
+            .into_iter()
+            .chain(deltas)
+            .chain([
+                ("contentBlockStop", json!({ "contentBlockIndex": 0 })),
+                ("messageStop", json!({ "stopReason": "tool_use" })),
+            ]),
+        ));
+
+        let extractor = mock
+            .client()
+            .streaming_extractor::<Person>("amazon.nova-pro-v1:0");
+        let events = extractor
+            .extract_stream("John is 30 years old")
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            events,
+            vec![
+                ExtractionEvent::Partial(json!({ "name": "Jo" })),
+                ExtractionEvent::Partial(json!({ "name": "John" })),
+                ExtractionEvent::Partial(json!({ "name": "John", "age": 30 })),
+                ExtractionEvent::Complete(Person {
+                    name: "John".into(),
+                    age: 30
+                }),
+            ]
+        );
+
+        let request = mock.requests()[0].json().unwrap();
+        assert_eq!(request["toolConfig"]["toolChoice"], json!({ "any": {} }));
+    }
+}
@@ -0,0 +1,344 @@
+//! Multi-region failover, to keep serving completions when Bedrock throttles requests or is
+//! unavailable in a region.
+//!
+//! A [`FailoverCompletionModel`] holds a completion model per region and sends each request to
+//! the first healthy region. Requests failing with a transient error are sent to the next
+//! region, and the region that failed is considered unhealthy for a cooldown, during which it is
+//! only tried after the healthy ones.
+//!
+//! ```rust,ignore
+//! let model = FailoverCompletionModel::new("us-east-1", client.completion_model(AMAZON_NOVA_LITE))
+//!     .with_fallback_region("us-west-2")
+//!     .with_fallback_region("eu-central-1")
+//!     .with_cooldown(Duration::from_secs(60));
+//! ```
+//!
+//! Models that aren't available in every region can be invoked through an inference profile
+//! instead. Each region can also retry before failing over, by wrapping its model in a
+//! [`RetryingCompletionModel`](crate::retry::RetryingCompletionModel) and adding it with
+//! [`FailoverCompletionModel::with_region`].
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rig::completion::{self, CompletionError, CompletionRequest, CompletionResponse};
+use rig::streaming::StreamingCompletionResponse;
+use tokio::time::Instant;
+
+use crate::completion::CompletionModel;
+use crate::retry::is_retryable_completion_error;
+use crate::types::errors::CircuitOpenError;
+
+/// The order in which the healthy regions of a [`FailoverCompletionModel`] are tried.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailoverOrder {
+    /// In the order they were added, so other regions only serve requests while the first one
+    /// is unhealthy.
+    #[default]
+    Priority,
+    /// Starting from a different region for each request, to spread the load across regions.
+    RoundRobin,
+}
+
+/// The health of a region of a [`FailoverCompletionModel`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionHealth {
+    pub region: String,
+    /// Requests that failed over from this region since its last success.
+    pub consecutive_failures: u32,
+    /// How long until the region is healthy again, if it is unhealthy.
+    pub unhealthy_for: Option<Duration>,
+}
+
+impl RegionHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.unhealthy_for.is_none()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+/// A completion model failing over to other regions when Bedrock throttles requests, or the
+/// model or the service is unavailable, see [`crate::failover`].
+///
+/// Streaming requests only fail over until the stream starts. Clones share the health of the
+/// regions.
+#[derive(Clone, Debug)]
+pub struct FailoverCompletionModel<M = CompletionModel> {
+    regions: Vec<(String, M)>,
+    order: FailoverOrder,
+    cooldown: Duration,
+    health: Arc<Mutex<Vec<Health>>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl<M> FailoverCompletionModel<M> {
+    /// A model sending requests to `model` in `primary_region`, until other regions are added.
+    pub fn new(primary_region: impl Into<String>, model: M) -> Self {
+        Self {
+            regions: vec![(primary_region.into(), model)],
+            order: FailoverOrder::default(),
+            cooldown: Duration::from_secs(30),
+            health: Arc::new(Mutex::new(vec![Health::default()])),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Fail over to `model`, which calls Bedrock in `region`, after the regions added before.
+    pub fn with_region(mut self, region: impl Into<String>, model: M) -> Self {
+        self.regions.push((region.into(), model));
+        self.lock().push(Health::default());
+        self
+    }
+
+    /// Defaults to [`FailoverOrder::Priority`].
+    pub fn with_order(mut self, order: FailoverOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// How long a region that failed is only tried after the healthy ones. Defaults to 30
+    /// seconds.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// The regions and their models, in failover order.
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &M)> {
+        self.regions
+            .iter()
+            .map(|(region, model)| (region.as_str(), model))
+    }
+
+    pub fn health(&self) -> Vec<RegionHealth> {
+        let now = Instant::now();
+        self.regions
+            .iter()
+            .zip(self.lock().iter())
+            .map(|((region, _), health)| RegionHealth {
+                region: region.clone(),
+                consecutive_failures: health.consecutive_failures,
+                unhealthy_for: health
+                    .unhealthy_until
+                    .filter(|until| *until > now)
+                    .map(|until| until - now),
+            })
+            .collect()
+    }
+
+    /// Consider every region healthy again.
+    pub fn reset_health(&self) {
+        self.lock().fill_with(Health::default);
+    }
+
+    /// The indices of the regions to try for a request, healthy regions first.
+    fn attempt_order(&self) -> Vec<usize> {
+        let count = self.regions.len();
+        let start = match self.order {
+            FailoverOrder::Priority => 0,
+            FailoverOrder::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % count,
+        };
+        let mut indices = (0..count)
+            .map(|offset| (start + offset) % count)
+            .collect::<Vec<_>>();
+
+        let now = Instant::now();
+        let health = self.lock();
+        // Stable, so unhealthy regions keep their relative order
+        indices.sort_by_key(|index| {
+            health[*index]
+                .unhealthy_until
+                .is_some_and(|until| until > now)
+        });
+        indices
+    }
+
+    fn record(&self, index: usize, failed: bool) {
+        let mut health = self.lock();
+        let health = &mut health[index];
+        if failed {
+            health.consecutive_failures += 1;
+            health.unhealthy_until = Some(Instant::now() + self.cooldown);
+        } else {
+            *health = Health::default();
+        }
+    }
+
+    /// Send the request to the regions in turn, until one doesn't fail with a transient error.
+    async fn failover<'a, T, F, Fut>(&'a self, mut operation: F) -> Result<T, CompletionError>
+    where
+        F: FnMut(&'a M) -> Fut,
+        Fut: Future<Output = Result<T, CompletionError>>,
+    {
+        let mut last_error = None;
+        for index in self.attempt_order() {
+            let (region, model) = &self.regions[index];
+            match operation(model).await {
+                Err(error) if is_failover_error(&error) => {
+                    tracing::warn!(
+                        target: "rig::bedrock",
+                        "Bedrock request failed in region {region}, failing over: {error}"
+                    );
+                    self.record(index, true);
+                    last_error = Some(error);
+                }
+                result => {
+                    if result.is_ok() {
+                        self.record(index, false);
+                    }
+                    return result;
+                }
+            }
+        }
+
+        Err(last_error.expect("a failover model has at least one region"))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Health>> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl FailoverCompletionModel<CompletionModel> {
+    /// Fail over to the primary model, called in `region` with a copy of its client, see
+    /// [`crate::client::Client::in_region`].
+    pub fn with_fallback_region(self, region: impl Into<String>) -> Self {
+        let region = region.into();
+        let mut model = self.regions[0].1.clone();
+        model.client = model.client.in_region(region.clone());
+        self.with_region(region, model)
+    }
+}
+
+impl<M> completion::CompletionModel for FailoverCompletionModel<M>
+where
+    M: completion::CompletionModel,
+{
+    type Response = M::Response;
+    type StreamingResponse = M::StreamingResponse;
+
+    type Client = M::Client;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self::new("default", M::make(client, model))
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
+        self.failover(|model| model.completion(request.clone()))
+            .await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
+        self.failover(|model| model.stream(request.clone())).await
+    }
+}
+
+/// Whether a request may succeed in another region: it was throttled, the model or the service
+/// was unavailable, or the region's circuit breaker is open.
+fn is_failover_error(error: &CompletionError) -> bool {
+    is_retryable_completion_error(error)
+        || matches!(
+            error,
+            CompletionError::RequestError(error) if error.is::<CircuitOpenError>()
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockBedrock, MockResponse, completion_request};
+    use rig::client::CompletionClient;
+    use rig::completion::CompletionModel as _;
+
+    fn request() -> CompletionRequest {
+        completion_request("Hi").build()
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_healthy_region() {
+        let east = MockBedrock::new().with_response(MockResponse::error(
+            429,
+            "ThrottlingException",
+            "Too many requests, please wait before trying again.",
+        ));
+        let west = MockBedrock::new()
+            .with_response(MockResponse::text("Hello from the west"))
+            .with_response(MockResponse::text("Hello again"));
+
+        let model = FailoverCompletionModel::new(
+            "us-east-1",
+            east.client().completion_model("amazon.nova-lite-v1:0"),
+        )
+        .with_region(
+            "us-west-2",
+            west.client().completion_model("amazon.nova-lite-v1:0"),
+        );
+
+        let response = model.completion(request()).await.unwrap();
+        assert!(matches!(
+            response.choice.first(),
+            rig::message::AssistantContent::Text(text) if text.text == "Hello from the west"
+        ));
+
+        let health = model.health();
+        assert!(!health[0].is_healthy());
+        assert_eq!(health[0].consecutive_failures, 1);
+        assert!(health[1].is_healthy());
+
+        // The unhealthy primary region is skipped while cooling down
+        model.completion(request()).await.unwrap();
+        assert_eq!(east.requests().len(), 1);
+        assert_eq!(west.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_fail_over_invalid_requests() {
+        let east = MockBedrock::new().with_response(MockResponse::error(
+            400,
+            "ValidationException",
+            "The provided request is not valid",
+        ));
+        let west = MockBedrock::new();
+
+        let model = FailoverCompletionModel::new(
+            "us-east-1",
+            east.client().completion_model("amazon.nova-lite-v1:0"),
+        )
+        .with_region(
+            "us-west-2",
+            west.client().completion_model("amazon.nova-lite-v1:0"),
+        );
+
+        assert!(model.completion(request()).await.is_err());
+        assert!(west.requests().is_empty());
+        assert!(model.health().iter().all(RegionHealth::is_healthy));
+    }
+
+    #[test]
+    fn test_round_robin_order() {
+        let model = FailoverCompletionModel::new("a", ())
+            .with_region("b", ())
+            .with_region("c", ())
+            .with_order(FailoverOrder::RoundRobin);
+
+        assert_eq!(model.attempt_order(), vec![0, 1, 2]);
+        assert_eq!(model.attempt_order(), vec![1, 2, 0]);
+
+        model.record(2, true);
+        assert_eq!(model.attempt_order(), vec![0, 1, 2]);
+        assert_eq!(model.attempt_order(), vec![0, 1, 2]);
+        assert_eq!(model.attempt_order(), vec![1, 0, 2]);
+    }
+}
@@ -0,0 +1,125 @@
+use aws_sdk_bedrockagentruntime::types::{
+    self as aws, FlowInputContent, FlowMultiTurnInputContent, FlowOutputContent, FlowResponseStream,
+};
+use aws_smithy_types::error::display::DisplayErrorContext;
+
+use super::{FlowCompletionReason, FlowError, FlowInput, FlowInputRequest, FlowOutput, FlowResult};
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+use crate::types::json::AwsDocument;
+
+/// An alias of a Bedrock flow. Created with [`Client::flow`].
+#[derive(Clone, Debug)]
+pub struct Flow {
+    client: Client,
+    flow_id: String,
+    flow_alias_id: String,
+}
+
+impl Flow {
+    pub(crate) fn new(client: Client, flow_id: String, flow_alias_id: String) -> Self {
+        Self {
+            client,
+            flow_id,
+            flow_alias_id,
+        }
+    }
+
+    /// Run the flow with `inputs`, until it completes or asks for more input.
+    pub async fn invoke(&self, inputs: Vec<FlowInput>) -> Result<FlowResult, FlowError> {
+        self.send(inputs, None).await
+    }
+
+    /// Resume the execution `execution_id` of a flow that asked for more input, with the
+    /// answers to its [`FlowInputRequest`].
+    pub async fn continue_execution(
+        &self,
+        execution_id: &str,
+        inputs: Vec<FlowInput>,
+    ) -> Result<FlowResult, FlowError> {
+        self.send(inputs, Some(execution_id)).await
+    }
+
+    async fn send(
+        &self,
+        inputs: Vec<FlowInput>,
+        execution_id: Option<&str>,
+    ) -> Result<FlowResult, FlowError> {
+        let inputs = inputs
+            .into_iter()
+            .map(|input| {
+                aws::FlowInput::builder()
+                    .node_name(input.node_name)
+                    .set_node_output_name(input.node_output_name)
+                    .set_node_input_name(input.node_input_name)
+                    .content(FlowInputContent::Document(
+                        AwsDocument::from(input.content).0,
+                    ))
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| FlowError::Request(e.into()))?;
+
+        let sdk_config = self.client.sdk_config().await;
+        let mut output = aws_sdk_bedrockagentruntime::Client::new(sdk_config)
+            .invoke_flow()
+            .flow_identifier(&self.flow_id)
+            .flow_alias_identifier(&self.flow_alias_id)
+            .set_inputs(Some(inputs))
+            .set_execution_id(execution_id.map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| FlowError::Request(BedrockError::from(e).into()))?;
+
+        let mut result = FlowResult {
+            outputs: Vec::new(),
+            completion_reason: None,
+            execution_id: output.execution_id().map(str::to_string),
+            input_request: None,
+        };
+        while let Some(event) = output
+            .response_stream
+            .recv()
+            .await
+            .map_err(|e| FlowError::Request(DisplayErrorContext(&e).to_string().into()))?
+        {
+            match event {
+                FlowResponseStream::FlowOutputEvent(event) => {
+                    if let Some(FlowOutputContent::Document(document)) = event.content() {
+                        result.outputs.push(FlowOutput {
+                            node_name: event.node_name().to_string(),
+                            content: AwsDocument(document.clone()).into(),
+                        });
+                    }
+                }
+                FlowResponseStream::FlowMultiTurnInputRequestEvent(event) => {
+                    if let Some(FlowMultiTurnInputContent::Document(document)) = event.content() {
+                        result.input_request = Some(FlowInputRequest {
+                            node_name: event.node_name().to_string(),
+                            content: AwsDocument(document.clone()).into(),
+                        });
+                    }
+                }
+                FlowResponseStream::FlowCompletionEvent(event) => {
+                    result.completion_reason = Some(match event.completion_reason() {
+                        aws::FlowCompletionReason::Success => FlowCompletionReason::Success,
+                        aws::FlowCompletionReason::InputRequired => {
+                            FlowCompletionReason::InputRequired
+                        }
+                        other => FlowCompletionReason::Other(other.as_str().to_string()),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl Client {
+    /// The alias `flow_alias_id` of the flow `flow_id`.
+    pub fn flow(&self, flow_id: impl Into<String>, flow_alias_id: impl Into<String>) -> Flow {
+        Flow::new(self.clone(), flow_id.into(), flow_alias_id.into())
+    }
+}
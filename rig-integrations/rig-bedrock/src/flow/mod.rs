@@ -0,0 +1,231 @@
+//! Bedrock Flows, to run Prompt Flow pipelines defined in Bedrock.
+//!
+//! `InvokeFlow` sends documents to the input nodes of a flow and streams back the documents
+//! reaching its output nodes. The result types in this module are always available; invoking
+//! flows requires the `flows` feature.
+//!
+//! ```rust,ignore
+//! #[derive(Deserialize)]
+//! struct Summary {
+//!     title: String,
+//!     bullet_points: Vec<String>,
+//! }
+//!
+//! let result = client
+//!     .flow("FLOW123456", "ALIAS12345")
+//!     .invoke(vec![FlowInput::document(json!({ "article": article }))])
+//!     .await?;
+//!
+//! let summary: Summary = result.output_as("FlowOutputNode")?;
+//! ```
+//!
+//! Flows with an agent node may pause to ask for more input, in which case the result's
+//! [`FlowResult::input_request`] is set and the execution is resumed with
+//! `Flow::continue_execution`:
+//!
+//! ```rust,ignore
+//! if let (Some(execution_id), Some(request)) = (&result.execution_id, &result.input_request) {
+//!     let answer = request.answer(json!("Next Tuesday"));
+//!     let result = flow.continue_execution(execution_id, vec![answer]).await?;
+//! }
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/flows-test.html>
+
+#[cfg(feature = "flows")]
+mod invoke;
+
+#[cfg(feature = "flows")]
+pub use invoke::Flow;
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The name of the input node of flows created in the console.
+pub const DEFAULT_INPUT_NODE: &str = "FlowInputNode";
+
+/// The name of the output of input nodes.
+pub const DEFAULT_INPUT_NODE_OUTPUT: &str = "document";
+
+/// The name of the input of agent nodes, to answer their requests for more input.
+pub const AGENT_NODE_INPUT: &str = "agentInputText";
+
+/// A document sent to a node of a flow.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FlowInput {
+    pub node_name: String,
+    /// The output of the input node the document is sent through.
+    pub node_output_name: Option<String>,
+    /// The input of the node the document is sent to, when answering a [`FlowInputRequest`].
+    pub node_input_name: Option<String>,
+    pub content: Value,
+}
+
+impl FlowInput {
+    /// A document sent to the input node `node_name`.
+    pub fn new(node_name: impl Into<String>, content: Value) -> Self {
+        Self {
+            node_name: node_name.into(),
+            node_output_name: Some(DEFAULT_INPUT_NODE_OUTPUT.to_string()),
+            node_input_name: None,
+            content,
+        }
+    }
+
+    /// A document sent to the default input node, [`DEFAULT_INPUT_NODE`].
+    pub fn document(content: Value) -> Self {
+        Self::new(DEFAULT_INPUT_NODE, content)
+    }
+
+    pub fn with_node_output_name(mut self, node_output_name: impl Into<String>) -> Self {
+        self.node_output_name = Some(node_output_name.into());
+        self
+    }
+}
+
+/// A document that reached an output node of a flow.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FlowOutput {
+    pub node_name: String,
+    pub content: Value,
+}
+
+/// Why a flow execution ended.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowCompletionReason {
+    Success,
+    /// The flow is waiting for the input described by [`FlowResult::input_request`].
+    InputRequired,
+    Other(String),
+}
+
+/// A request of a flow for more input, to be answered with `Flow::continue_execution`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FlowInputRequest {
+    /// The node asking for input, to send the answer to.
+    pub node_name: String,
+    pub content: Value,
+}
+
+impl FlowInputRequest {
+    /// The answer `content` to this request.
+    pub fn answer(&self, content: Value) -> FlowInput {
+        FlowInput {
+            node_name: self.node_name.clone(),
+            node_output_name: None,
+            node_input_name: Some(AGENT_NODE_INPUT.to_string()),
+            content,
+        }
+    }
+}
+
+/// The outcome of a flow execution.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FlowResult {
+    /// The documents that reached the output nodes, in order.
+    pub outputs: Vec<FlowOutput>,
+    /// `None` if the stream ended without a completion event.
+    pub completion_reason: Option<FlowCompletionReason>,
+    /// The execution to continue when the flow asks for more input.
+    pub execution_id: Option<String>,
+    pub input_request: Option<FlowInputRequest>,
+}
+
+impl FlowResult {
+    /// The last document that reached the output node `node_name`.
+    pub fn output(&self, node_name: &str) -> Option<&Value> {
+        self.outputs
+            .iter()
+            .rev()
+            .find(|output| output.node_name == node_name)
+            .map(|output| &output.content)
+    }
+
+    /// The last document that reached the output node `node_name`, deserialized.
+    pub fn output_as<T: DeserializeOwned>(&self, node_name: &str) -> Result<T, FlowError> {
+        let content = self
+            .output(node_name)
+            .ok_or_else(|| FlowError::MissingOutput(node_name.to_string()))?;
+        Ok(T::deserialize(content)?)
+    }
+}
+
+#[derive(Debug)]
+pub enum FlowError {
+    /// The Bedrock call failed, or its input couldn't be built.
+    Request(Box<dyn std::error::Error + Send + Sync>),
+    /// No document reached the output node.
+    MissingOutput(String),
+    /// An output document doesn't have the expected shape.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for FlowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(error) => write!(f, "Flow request failed: {error}"),
+            Self::MissingOutput(node_name) => write!(f, "No output from flow node {node_name}"),
+            Self::Json(error) => write!(f, "Invalid flow output: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for FlowError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(error) => Some(error.as_ref()),
+            Self::MissingOutput(_) => None,
+            Self::Json(error) => Some(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for FlowError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_output_as() {
+        #[derive(Deserialize)]
+        struct Summary {
+            title: String,
+        }
+
+        let result = FlowResult {
+            outputs: vec![
+                FlowOutput {
+                    node_name: "FlowOutputNode".into(),
+                    content: json!({ "title": "Draft" }),
+                },
+                FlowOutput {
+                    node_name: "FlowOutputNode".into(),
+                    content: json!({ "title": "Final" }),
+                },
+            ],
+            completion_reason: Some(FlowCompletionReason::Success),
+            execution_id: None,
+            input_request: None,
+        };
+
+        let summary: Summary = result.output_as("FlowOutputNode").unwrap();
+        assert_eq!(summary.title, "Final");
+        assert!(matches!(
+            result.output_as::<Summary>("OtherNode"),
+            Err(FlowError::MissingOutput(node)) if node == "OtherNode"
+        ));
+        assert!(matches!(
+            result.output_as::<Vec<String>>("FlowOutputNode"),
+            Err(FlowError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+
+use aws_sdk_bedrockruntime::types as aws_bedrock;
+use rig::completion::CompletionError;
+use serde::{Deserialize, Serialize};
+
+use crate::types::converse_output::{
+    GuardrailAssessment, GuardrailContentPolicyAction, GuardrailContextualGroundingPolicyAction,
+    GuardrailSensitiveInformationPolicyAction, GuardrailTopicPolicyAction,
+    GuardrailTraceAssessment, GuardrailWordPolicyAction,
+};
+
+/// A guardrail applied to the requests of a completion model, see
+/// [`CompletionModel::with_guardrail`](crate::completion::CompletionModel::with_guardrail).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardrailConfig {
+    /// The id or ARN of the guardrail.
+    pub identifier: String,
+    /// A version number, or `DRAFT`.
+    pub version: String,
+}
+
+impl GuardrailConfig {
+    pub fn new(identifier: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            identifier: identifier.into(),
+            version: version.into(),
+        }
+    }
+
+    /// The configuration of Converse requests, with the trace enabled to report the assessment of
+    /// interventions.
+    pub(crate) fn converse(&self) -> Result<aws_bedrock::GuardrailConfiguration, CompletionError> {
+        aws_bedrock::GuardrailConfiguration::builder()
+            .guardrail_identifier(&self.identifier)
+            .guardrail_version(&self.version)
+            .trace(aws_bedrock::GuardrailTrace::Enabled)
+            .build()
+            .map_err(|e| CompletionError::RequestError(e.into()))
+    }
+
+    pub(crate) fn converse_stream(
+        &self,
+    ) -> Result<aws_bedrock::GuardrailStreamConfiguration, CompletionError> {
+        aws_bedrock::GuardrailStreamConfiguration::builder()
+            .guardrail_identifier(&self.identifier)
+            .guardrail_version(&self.version)
+            .trace(aws_bedrock::GuardrailTrace::Enabled)
+            .build()
+            .map_err(|e| CompletionError::RequestError(e.into()))
+    }
+}
+
+/// Whether a guardrail assessed the prompt or the model response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuardrailSource {
+    Input,
+    Output,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuardrailPolicy {
+    Topic,
+    ContentFilter,
+    Word,
+    SensitiveInformation,
+    ContextualGrounding,
+}
+
+/// Content a guardrail blocked or masked.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardrailFinding {
+    pub source: GuardrailSource,
+    pub policy: GuardrailPolicy,
+    /// What was found, e.g. the denied topic, the content filter type or the PII entity type.
+    pub name: String,
+    /// Whether the content was blocked, or masked otherwise.
+    pub blocked: bool,
+}
+
+/// A response in which a guardrail intervened, instead of the model's answer.
+///
+/// Completions stopped by a guardrail fail with this error, so applications can handle policy
+/// blocks explicitly, and streams yield it after their final response. The findings and the
+/// assessment are only available for requests with a guardrail trace, e.g. with a guardrail set
+/// by [`CompletionModel::with_guardrail`](crate::completion::CompletionModel::with_guardrail).
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuardrailInterventionError {
+    /// The text returned instead of the model response: the blocked messaging of the guardrail,
+    /// or the model response with the sensitive information masked.
+    pub output: String,
+    pub action_reason: Option<String>,
+    pub findings: Vec<GuardrailFinding>,
+    pub assessment: Option<GuardrailTraceAssessment>,
+}
+
+impl GuardrailInterventionError {
+    pub(crate) fn new(output: String, assessment: Option<GuardrailTraceAssessment>) -> Self {
+        let mut findings = Vec::new();
+        if let Some(assessment) = &assessment {
+            for input in assessment
+                .input_assessment
+                .iter()
+                .flat_map(|map| map.values())
+            {
+                push_findings(GuardrailSource::Input, input, &mut findings);
+            }
+            for output in assessment
+                .output_assessments
+                .iter()
+                .flat_map(|map| map.values())
+                .flatten()
+            {
+                push_findings(GuardrailSource::Output, output, &mut findings);
+            }
+        }
+        Self {
+            output,
+            action_reason: assessment
+                .as_ref()
+                .and_then(|assessment| assessment.action_reason.clone()),
+            findings,
+            assessment,
+        }
+    }
+
+    /// The guardrail intervention a completion failed with, if any.
+    pub fn from_completion_error(error: &CompletionError) -> Option<&Self> {
+        match error {
+            CompletionError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the prompt was blocked, before the model was invoked.
+    pub fn is_input_blocked(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.source == GuardrailSource::Input && finding.blocked)
+    }
+}
+
+/// The blocked or masked content of `assessment`.
+fn push_findings(
+    source: GuardrailSource,
+    assessment: &GuardrailAssessment,
+    findings: &mut Vec<GuardrailFinding>,
+) {
+    let mut push = |policy, name: String, blocked| {
+        findings.push(GuardrailFinding {
+            source,
+            policy,
+            name,
+            blocked,
+        })
+    };
+
+    if let Some(topics) = &assessment.topic_policy {
+        for topic in &topics.topics {
+            if topic.action == GuardrailTopicPolicyAction::Blocked {
+                push(GuardrailPolicy::Topic, topic.name.clone(), true);
+            }
+        }
+    }
+    if let Some(content) = &assessment.content_policy {
+        for filter in &content.filters {
+            if filter.action == GuardrailContentPolicyAction::Blocked {
+                push(
+                    GuardrailPolicy::ContentFilter,
+                    variant_name(&filter.kind),
+                    true,
+                );
+            }
+        }
+    }
+    if let Some(words) = &assessment.word_policy {
+        let custom = words
+            .custom_words
+            .iter()
+            .map(|word| (&word.matches_on, &word.action));
+        let managed = words
+            .managed_word_lists
+            .iter()
+            .map(|word| (&word.matches_on, &word.action));
+        for (word, action) in custom.chain(managed) {
+            if *action == GuardrailWordPolicyAction::Blocked {
+                push(GuardrailPolicy::Word, word.clone(), true);
+            }
+        }
+    }
+    if let Some(sensitive) = &assessment.sensitive_information_policy {
+        let entities = sensitive
+            .pii_entities
+            .iter()
+            .map(|entity| (variant_name(&entity.kind), &entity.action));
+        let regexes = sensitive.regexes.iter().map(|regex| {
+            let name = regex.name.clone().or_else(|| regex.regex.clone());
+            (name.unwrap_or_default(), &regex.action)
+        });
+        for (name, action) in entities.chain(regexes) {
+            match action {
+                GuardrailSensitiveInformationPolicyAction::Blocked => {
+                    push(GuardrailPolicy::SensitiveInformation, name, true)
+                }
+                GuardrailSensitiveInformationPolicyAction::Anonymized => {
+                    push(GuardrailPolicy::SensitiveInformation, name, false)
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(grounding) = &assessment.contextual_grounding_policy {
+        for filter in grounding.filters.iter().flatten() {
+            if filter.action == GuardrailContextualGroundingPolicyAction::Blocked {
+                push(
+                    GuardrailPolicy::ContextualGrounding,
+                    variant_name(&filter.kind),
+                    true,
+                );
+            }
+        }
+    }
+}
+
+/// The name of a variant of the trace enums, or the API value of their `Unknown` variant.
+fn variant_name(value: &impl fmt::Debug) -> String {
+    let name = format!("{value:?}");
+    match name.strip_prefix("Unknown(UnknownVariantValue(\"") {
+        Some(unknown) => unknown.trim_end_matches("\"))").to_string(),
+        None => name,
+    }
+}
+
+impl fmt::Display for GuardrailInterventionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Guardrail intervened")?;
+        if let Some(reason) = &self.action_reason {
+            write!(f, ": {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for GuardrailInterventionError {}
+
+impl From<GuardrailInterventionError> for CompletionError {
+    fn from(error: GuardrailInterventionError) -> Self {
+        CompletionError::RequestError(Box::new(error))
+    }
+}
@@ -0,0 +1,283 @@
+use aws_sdk_bedrock::error::BuildError;
+use aws_sdk_bedrock::operation::get_guardrail::GetGuardrailOutput;
+use aws_sdk_bedrock::types as aws;
+
+use super::{
+    ContentFilter, ContentFilterType, DeniedTopic, FilterStrength, Guardrail, GuardrailDefinition,
+    GuardrailError, GuardrailId, GuardrailStatus, PiiAction, PiiEntity,
+};
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+
+impl Client {
+    /// Create a guardrail from `definition`.
+    pub async fn create_guardrail(
+        &self,
+        definition: &GuardrailDefinition,
+    ) -> Result<GuardrailId, GuardrailError> {
+        let policies = Policies::new(definition).map_err(invalid_definition)?;
+
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .create_guardrail()
+            .name(&definition.name)
+            .set_description(definition.description.clone())
+            .blocked_input_messaging(&definition.blocked_input_messaging)
+            .blocked_outputs_messaging(&definition.blocked_outputs_messaging)
+            .set_topic_policy_config(policies.topics)
+            .set_content_policy_config(policies.content)
+            .set_word_policy_config(policies.words)
+            .set_sensitive_information_policy_config(policies.sensitive_information)
+            .set_kms_key_id(definition.kms_key_id.clone())
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        Ok(GuardrailId {
+            id: output.guardrail_id().to_string(),
+            arn: output.guardrail_arn().to_string(),
+            version: output.version().to_string(),
+        })
+    }
+
+    /// The guardrail `id`, its id or ARN, at `version`. Defaults to the `DRAFT` version.
+    pub async fn get_guardrail(
+        &self,
+        id: &str,
+        version: Option<&str>,
+    ) -> Result<Guardrail, GuardrailError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .get_guardrail()
+            .guardrail_identifier(id)
+            .set_guardrail_version(version.map(str::to_string))
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        Ok(guardrail(&output))
+    }
+
+    /// Replace the policies of the `DRAFT` version of the guardrail `id` by `definition`.
+    pub async fn update_guardrail(
+        &self,
+        id: &str,
+        definition: &GuardrailDefinition,
+    ) -> Result<GuardrailId, GuardrailError> {
+        let policies = Policies::new(definition).map_err(invalid_definition)?;
+
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .update_guardrail()
+            .guardrail_identifier(id)
+            .name(&definition.name)
+            .set_description(definition.description.clone())
+            .blocked_input_messaging(&definition.blocked_input_messaging)
+            .blocked_outputs_messaging(&definition.blocked_outputs_messaging)
+            .set_topic_policy_config(policies.topics)
+            .set_content_policy_config(policies.content)
+            .set_word_policy_config(policies.words)
+            .set_sensitive_information_policy_config(policies.sensitive_information)
+            .set_kms_key_id(definition.kms_key_id.clone())
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        Ok(GuardrailId {
+            id: output.guardrail_id().to_string(),
+            arn: output.guardrail_arn().to_string(),
+            version: output.version().to_string(),
+        })
+    }
+
+    /// Snapshot the `DRAFT` version of the guardrail `id` into a new numbered version, and
+    /// return that version.
+    pub async fn create_guardrail_version(
+        &self,
+        id: &str,
+        description: Option<&str>,
+    ) -> Result<String, GuardrailError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .create_guardrail_version()
+            .guardrail_identifier(id)
+            .set_description(description.map(str::to_string))
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        Ok(output.version().to_string())
+    }
+
+    /// Delete `version` of the guardrail `id`, or the whole guardrail without a version.
+    pub async fn delete_guardrail(
+        &self,
+        id: &str,
+        version: Option<&str>,
+    ) -> Result<(), GuardrailError> {
+        let sdk_config = self.sdk_config().await;
+        aws_sdk_bedrock::Client::new(sdk_config)
+            .delete_guardrail()
+            .guardrail_identifier(id)
+            .set_guardrail_version(version.map(str::to_string))
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+        Ok(())
+    }
+}
+
+/// The policy configurations of a definition, `None` for policies without any entry.
+struct Policies {
+    topics: Option<aws::GuardrailTopicPolicyConfig>,
+    content: Option<aws::GuardrailContentPolicyConfig>,
+    words: Option<aws::GuardrailWordPolicyConfig>,
+    sensitive_information: Option<aws::GuardrailSensitiveInformationPolicyConfig>,
+}
+
+impl Policies {
+    fn new(definition: &GuardrailDefinition) -> Result<Self, BuildError> {
+        let topics = definition
+            .denied_topics
+            .iter()
+            .map(|topic| {
+                aws::GuardrailTopicConfig::builder()
+                    .name(&topic.name)
+                    .definition(&topic.definition)
+                    .set_examples(Some(topic.examples.clone()))
+                    .r#type(aws::GuardrailTopicType::Deny)
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let filters = definition
+            .content_filters
+            .iter()
+            .map(|filter| {
+                aws::GuardrailContentFilterConfig::builder()
+                    .r#type(filter.filter_type.as_str().into())
+                    .input_strength(filter.input_strength.as_str().into())
+                    .output_strength(filter.output_strength.as_str().into())
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let words = definition
+            .denied_words
+            .iter()
+            .map(|word| aws::GuardrailWordConfig::builder().text(word).build())
+            .collect::<Result<Vec<_>, _>>()?;
+        let managed_word_lists = if definition.profanity_filter {
+            vec![
+                aws::GuardrailManagedWordsConfig::builder()
+                    .r#type(aws::GuardrailManagedWordsType::Profanity)
+                    .build()?,
+            ]
+        } else {
+            Vec::new()
+        };
+        let pii_entities = definition
+            .pii_entities
+            .iter()
+            .map(|entity| {
+                aws::GuardrailPiiEntityConfig::builder()
+                    .r#type(entity.entity_type.as_str().into())
+                    .action(entity.action.as_str().into())
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            topics: (!topics.is_empty())
+                .then(|| {
+                    aws::GuardrailTopicPolicyConfig::builder()
+                        .set_topics_config(Some(topics))
+                        .build()
+                })
+                .transpose()?,
+            content: (!filters.is_empty())
+                .then(|| {
+                    aws::GuardrailContentPolicyConfig::builder()
+                        .set_filters_config(Some(filters))
+                        .build()
+                })
+                .transpose()?,
+            words: (!words.is_empty() || !managed_word_lists.is_empty()).then(|| {
+                aws::GuardrailWordPolicyConfig::builder()
+                    .set_words_config(Some(words))
+                    .set_managed_word_lists_config(Some(managed_word_lists))
+                    .build()
+            }),
+            sensitive_information: (!pii_entities.is_empty()).then(|| {
+                aws::GuardrailSensitiveInformationPolicyConfig::builder()
+                    .set_pii_entities_config(Some(pii_entities))
+                    .build()
+            }),
+        })
+    }
+}
+
+fn invalid_definition(error: BuildError) -> GuardrailError {
+    GuardrailError::InvalidDefinition(error.into())
+}
+
+fn guardrail(output: &GetGuardrailOutput) -> Guardrail {
+    let definition = GuardrailDefinition {
+        name: output.name().to_string(),
+        description: output.description().map(str::to_string),
+        blocked_input_messaging: output.blocked_input_messaging().to_string(),
+        blocked_outputs_messaging: output.blocked_outputs_messaging().to_string(),
+        denied_topics: output
+            .topic_policy()
+            .map(|policy| policy.topics())
+            .unwrap_or_default()
+            .iter()
+            .map(|topic| DeniedTopic {
+                name: topic.name().to_string(),
+                definition: topic.definition().to_string(),
+                examples: topic.examples().to_vec(),
+            })
+            .collect(),
+        content_filters: output
+            .content_policy()
+            .map(|policy| policy.filters())
+            .unwrap_or_default()
+            .iter()
+            .map(|filter| ContentFilter {
+                filter_type: ContentFilterType::from(filter.r#type().as_str()),
+                input_strength: FilterStrength::from(filter.input_strength().as_str()),
+                output_strength: FilterStrength::from(filter.output_strength().as_str()),
+            })
+            .collect(),
+        denied_words: output
+            .word_policy()
+            .map(|policy| policy.words())
+            .unwrap_or_default()
+            .iter()
+            .map(|word| word.text().to_string())
+            .collect(),
+        profanity_filter: output.word_policy().is_some_and(|policy| {
+            policy
+                .managed_word_lists()
+                .iter()
+                .any(|list| *list.r#type() == aws::GuardrailManagedWordsType::Profanity)
+        }),
+        pii_entities: output
+            .sensitive_information_policy()
+            .map(|policy| policy.pii_entities())
+            .unwrap_or_default()
+            .iter()
+            .map(|entity| PiiEntity {
+                entity_type: entity.r#type().as_str().to_string(),
+                action: PiiAction::from(entity.action().as_str()),
+            })
+            .collect(),
+        kms_key_id: output.kms_key_arn().map(str::to_string),
+    };
+
+    Guardrail {
+        id: output.guardrail_id().to_string(),
+        arn: output.guardrail_arn().to_string(),
+        version: output.version().to_string(),
+        status: GuardrailStatus::from(output.status().as_str()),
+        definition,
+    }
+}
@@ -0,0 +1,329 @@
+//! Management of Bedrock Guardrails, to keep guardrails as code next to the agents using them.
+//!
+//! A [`GuardrailDefinition`] describes the policies of a guardrail: denied topics, content
+//! filters, denied words and PII entities. Creating or updating a guardrail changes its `DRAFT`
+//! version; numbered versions are immutable snapshots to reference from production. The types in
+//! this module are always available; calling Bedrock requires the `control-plane` feature.
+//!
+//! ```rust,ignore
+//! let definition = GuardrailDefinition::new(
+//!     "support-bot",
+//!     "Sorry, I can't help with that.",
+//!     "Sorry, I can't answer that.",
+//! )
+//! .with_denied_topic(
+//!     "Investment advice",
+//!     "Recommendations about investing in financial products.",
+//!     vec!["Which stocks should I buy?".into()],
+//! )
+//! .with_content_filter(ContentFilterType::PromptAttack, FilterStrength::High, FilterStrength::None)
+//! .with_pii_entity("EMAIL", PiiAction::Anonymize);
+//!
+//! let created = client.create_guardrail(&definition).await?;
+//! let version = client.create_guardrail_version(&created.id, None).await?;
+//! ```
+//!
+//! A guardrail is applied to the requests of a completion model with
+//! [`CompletionModel::with_guardrail`](crate::completion::CompletionModel::with_guardrail), and
+//! completions it intervenes in fail with a [`GuardrailInterventionError`]:
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_PRO)
+//!     .with_guardrail(GuardrailConfig::new(&created.id, &version));
+//! match model.completion(request).await {
+//!     Err(error) => match GuardrailInterventionError::from_completion_error(&error) {
+//!         Some(intervention) => println!("{} ({:?})", intervention.output, intervention.findings),
+//!         None => return Err(error.into()),
+//!     },
+//!     Ok(response) => { /* ... */ }
+//! }
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/guardrails.html>
+
+mod intervention;
+#[cfg(feature = "control-plane")]
+mod manage;
+
+pub use intervention::{
+    GuardrailConfig, GuardrailFinding, GuardrailInterventionError, GuardrailPolicy, GuardrailSource,
+};
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::converse_output::{UnknownVariantValue, api_values};
+use crate::types::errors::BedrockError;
+
+/// The policies of a guardrail.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GuardrailDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    /// The message returned instead of the model response when the input is blocked.
+    pub blocked_input_messaging: String,
+    /// The message returned instead of the model response when the output is blocked.
+    pub blocked_outputs_messaging: String,
+    pub denied_topics: Vec<DeniedTopic>,
+    pub content_filters: Vec<ContentFilter>,
+    pub denied_words: Vec<String>,
+    /// Whether to block profanity, with the word list managed by AWS.
+    pub profanity_filter: bool,
+    pub pii_entities: Vec<PiiEntity>,
+    /// The KMS key encrypting the guardrail, instead of an AWS managed key.
+    pub kms_key_id: Option<String>,
+}
+
+impl GuardrailDefinition {
+    /// A guardrail without any policy.
+    pub fn new(
+        name: impl Into<String>,
+        blocked_input_messaging: impl Into<String>,
+        blocked_outputs_messaging: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            blocked_input_messaging: blocked_input_messaging.into(),
+            blocked_outputs_messaging: blocked_outputs_messaging.into(),
+            denied_topics: Vec::new(),
+            content_filters: Vec::new(),
+            denied_words: Vec::new(),
+            profanity_filter: false,
+            pii_entities: Vec::new(),
+            kms_key_id: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Deny the topic `name`, described by `definition` and sample prompts about it.
+    pub fn with_denied_topic(
+        mut self,
+        name: impl Into<String>,
+        definition: impl Into<String>,
+        examples: Vec<String>,
+    ) -> Self {
+        self.denied_topics.push(DeniedTopic {
+            name: name.into(),
+            definition: definition.into(),
+            examples,
+        });
+        self
+    }
+
+    pub fn with_content_filter(
+        mut self,
+        filter_type: ContentFilterType,
+        input_strength: FilterStrength,
+        output_strength: FilterStrength,
+    ) -> Self {
+        self.content_filters.push(ContentFilter {
+            filter_type,
+            input_strength,
+            output_strength,
+        });
+        self
+    }
+
+    pub fn with_denied_word(mut self, word: impl Into<String>) -> Self {
+        self.denied_words.push(word.into());
+        self
+    }
+
+    pub fn with_profanity_filter(mut self) -> Self {
+        self.profanity_filter = true;
+        self
+    }
+
+    /// Block or anonymize the PII entities of `entity_type`, e.g. `EMAIL` or `PHONE`.
+    pub fn with_pii_entity(mut self, entity_type: impl Into<String>, action: PiiAction) -> Self {
+        self.pii_entities.push(PiiEntity {
+            entity_type: entity_type.into(),
+            action,
+        });
+        self
+    }
+
+    pub fn with_kms_key_id(mut self, kms_key_id: impl Into<String>) -> Self {
+        self.kms_key_id = Some(kms_key_id.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeniedTopic {
+    pub name: String,
+    pub definition: String,
+    pub examples: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContentFilter {
+    pub filter_type: ContentFilterType,
+    pub input_strength: FilterStrength,
+    pub output_strength: FilterStrength,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PiiEntity {
+    /// The type of entity, e.g. `EMAIL`, `PHONE` or `US_SOCIAL_SECURITY_NUMBER`.
+    pub entity_type: String,
+    pub action: PiiAction,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ContentFilterType {
+    Hate,
+    Insults,
+    Misconduct,
+    PromptAttack,
+    Sexual,
+    Violence,
+    Unknown(UnknownVariantValue),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FilterStrength {
+    None,
+    Low,
+    Medium,
+    High,
+    Unknown(UnknownVariantValue),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PiiAction {
+    Block,
+    Anonymize,
+    Unknown(UnknownVariantValue),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GuardrailStatus {
+    Creating,
+    Updating,
+    Versioning,
+    Ready,
+    Failed,
+    Deleting,
+    Unknown(UnknownVariantValue),
+}
+
+api_values!(ContentFilterType {
+    Hate => "HATE",
+    Insults => "INSULTS",
+    Misconduct => "MISCONDUCT",
+    PromptAttack => "PROMPT_ATTACK",
+    Sexual => "SEXUAL",
+    Violence => "VIOLENCE",
+});
+
+api_values!(FilterStrength {
+    None => "NONE",
+    Low => "LOW",
+    Medium => "MEDIUM",
+    High => "HIGH",
+});
+
+api_values!(PiiAction {
+    Block => "BLOCK",
+    Anonymize => "ANONYMIZE",
+});
+
+api_values!(GuardrailStatus {
+    Creating => "CREATING",
+    Updating => "UPDATING",
+    Versioning => "VERSIONING",
+    Ready => "READY",
+    Failed => "FAILED",
+    Deleting => "DELETING",
+});
+
+/// The identifiers of a created or updated guardrail.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardrailId {
+    pub id: String,
+    pub arn: String,
+    /// `DRAFT`, the version changed by creations and updates.
+    pub version: String,
+}
+
+/// A version of a guardrail, as stored in Bedrock.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Guardrail {
+    pub id: String,
+    pub arn: String,
+    pub version: String,
+    pub status: GuardrailStatus,
+    pub definition: GuardrailDefinition,
+}
+
+#[derive(Debug)]
+pub enum GuardrailError {
+    /// The definition couldn't be converted to a request, e.g. a denied topic without definition.
+    InvalidDefinition(Box<dyn std::error::Error + Send + Sync>),
+    Request(BedrockError),
+}
+
+impl fmt::Display for GuardrailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDefinition(error) => write!(f, "Invalid guardrail definition: {error}"),
+            Self::Request(error) => write!(f, "Guardrail request failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for GuardrailError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidDefinition(error) => Some(error.as_ref()),
+            Self::Request(error) => Some(error),
+        }
+    }
+}
+
+impl From<BedrockError> for GuardrailError {
+    fn from(error: BedrockError) -> Self {
+        Self::Request(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_definition_round_trip() {
+        let definition = GuardrailDefinition::new("support-bot", "Blocked input", "Blocked output")
+            .with_denied_topic(
+                "Investment advice",
+                "Recommendations about investing in financial products.",
+                vec!["Which stocks should I buy?".into()],
+            )
+            .with_content_filter(
+                ContentFilterType::PromptAttack,
+                FilterStrength::High,
+                FilterStrength::None,
+            )
+            .with_denied_word("competitor")
+            .with_profanity_filter()
+            .with_pii_entity("EMAIL", PiiAction::Anonymize);
+
+        assert_eq!(
+            definition.content_filters[0].filter_type.as_str(),
+            "PROMPT_ATTACK"
+        );
+        assert_eq!(PiiAction::from("ANONYMIZE"), PiiAction::Anonymize);
+
+        let json = serde_json::to_string(&definition).unwrap();
+        let parsed: GuardrailDefinition = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, definition);
+    }
+}
@@ -0,0 +1,252 @@
+//! Health checks of Bedrock and a model, e.g. for service readiness probes.
+//!
+//! ```rust,ignore
+//! let report = client.health_check(AMAZON_NOVA_LITE).await;
+//! if !report.is_healthy() {
+//!     tracing::warn!("Bedrock isn't ready: {report}");
+//! }
+//! ```
+//!
+//! By default the check sends a single-token Converse request, which may incur a negligible
+//! charge. Models supporting the CountTokens API, such as Anthropic Claude models, can be checked
+//! for free with [`HealthProbe::CountTokens`].
+
+use std::fmt;
+use std::time::Duration;
+
+use aws_sdk_bedrockruntime::config::http::HttpResponse;
+use aws_sdk_bedrockruntime::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_bedrockruntime::types::{
+    ContentBlock, ConversationRole, ConverseTokensRequest, CountTokensInput,
+    InferenceConfiguration, Message,
+};
+use tokio::time::Instant;
+
+use crate::client::Client;
+use crate::types::errors::{BedrockError, BedrockErrorKind};
+
+/// The request sent to check the health of a model.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HealthProbe {
+    /// A Converse request generating a single token.
+    #[default]
+    Converse,
+    /// A CountTokens request, which is free but only supported by some models.
+    CountTokens,
+}
+
+/// The outcome of a health check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy,
+    /// Bedrock couldn't be reached, e.g. because of a network failure or a timeout.
+    Unreachable,
+    /// The credentials are missing, invalid or expired.
+    Unauthenticated,
+    /// The credentials are valid, but their IAM identity isn't allowed to invoke the model.
+    AccessDenied,
+    /// The model can't serve requests, e.g. because it isn't enabled, doesn't exist in the
+    /// region or isn't ready yet, or the service is unavailable.
+    ModelUnavailable,
+    /// The request exceeded the account quotas. The service and the model are up, so other
+    /// requests may succeed.
+    Throttled,
+}
+
+/// The health of Bedrock and a model, as reported by [`Client::health_check`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthReport {
+    pub model: String,
+    pub status: HealthStatus,
+    /// How long the check took.
+    pub latency: Duration,
+    /// The error the check failed with, if any.
+    pub error: Option<String>,
+}
+
+impl HealthReport {
+    /// Whether the model can serve requests. Throttled models are considered healthy.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self.status, HealthStatus::Healthy | HealthStatus::Throttled)
+    }
+
+    /// Whether Bedrock could be reached.
+    pub fn is_reachable(&self) -> bool {
+        self.status != HealthStatus::Unreachable
+    }
+
+    /// Whether the credentials were accepted.
+    pub fn is_authenticated(&self) -> bool {
+        !matches!(
+            self.status,
+            HealthStatus::Unreachable | HealthStatus::Unauthenticated
+        )
+    }
+
+    /// Whether the model is ready to serve requests.
+    pub fn is_model_ready(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Model `{}` is {:?} ({:?})",
+            self.model, self.status, self.latency
+        )?;
+        if let Some(error) = &self.error {
+            write!(f, ": {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Check whether Bedrock can be reached, the credentials are valid and `model` is ready to
+    /// serve requests, with a single-token Converse request.
+    pub async fn health_check(&self, model: &str) -> HealthReport {
+        self.health_check_with_probe(model, HealthProbe::default())
+            .await
+    }
+
+    /// Check the health of `model` with `probe`.
+    pub async fn health_check_with_probe(&self, model: &str, probe: HealthProbe) -> HealthReport {
+        let client = self.get_inner().await;
+        let started = Instant::now();
+        let message = Message::builder()
+            .role(ConversationRole::User)
+            .content(ContentBlock::Text("ping".into()))
+            .build()
+            .expect("message has a role and content");
+
+        let error = match probe {
+            HealthProbe::Converse => client
+                .converse()
+                .model_id(model)
+                .messages(message)
+                .inference_config(InferenceConfiguration::builder().max_tokens(1).build())
+                .send()
+                .await
+                .err()
+                .map(classify),
+            HealthProbe::CountTokens => client
+                .count_tokens()
+                .model_id(model)
+                .input(CountTokensInput::Converse(
+                    ConverseTokensRequest::builder().messages(message).build(),
+                ))
+                .send()
+                .await
+                .err()
+                .map(classify),
+        };
+
+        let (status, error) = error.unwrap_or((HealthStatus::Healthy, None));
+        HealthReport {
+            model: model.to_string(),
+            status,
+            latency: started.elapsed(),
+            error,
+        }
+    }
+}
+
+fn classify<E>(error: SdkError<E, HttpResponse>) -> (HealthStatus, Option<String>)
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    let unauthenticated = matches!(
+        error.code(),
+        Some(
+            "UnrecognizedClientException"
+                | "InvalidSignatureException"
+                | "ExpiredTokenException"
+                | "IncompleteSignature"
+                | "MissingAuthenticationTokenException"
+        )
+    );
+    let error = BedrockError::from(error);
+
+    let status = match error.kind() {
+        _ if unauthenticated => HealthStatus::Unauthenticated,
+        BedrockErrorKind::Throttled => HealthStatus::Throttled,
+        BedrockErrorKind::AccessDenied
+            if error
+                .message()
+                .to_lowercase()
+                .contains("access to the model") =>
+        {
+            HealthStatus::ModelUnavailable
+        }
+        BedrockErrorKind::AccessDenied => HealthStatus::AccessDenied,
+        BedrockErrorKind::Timeout => HealthStatus::Unreachable,
+        // Credentials are resolved before the request is sent
+        BedrockErrorKind::Dispatch | BedrockErrorKind::Other
+            if error.message().to_lowercase().contains("credentials") =>
+        {
+            HealthStatus::Unauthenticated
+        }
+        BedrockErrorKind::Dispatch => HealthStatus::Unreachable,
+        _ => HealthStatus::ModelUnavailable,
+    };
+
+    (status, Some(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockBedrock, MockResponse};
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::text("p"))
+            .with_response(MockResponse::error(
+                403,
+                "UnrecognizedClientException",
+                "The security token included in the request is invalid.",
+            ))
+            .with_response(MockResponse::error(
+                429,
+                "ModelNotReadyException",
+                "Model is not ready for inference.",
+            ));
+        let client = mock.client();
+
+        let report = client.health_check("amazon.nova-lite-v1:0").await;
+        assert!(report.is_healthy());
+        assert_eq!(report.error, None);
+
+        let report = client.health_check("amazon.nova-lite-v1:0").await;
+        assert_eq!(report.status, HealthStatus::Unauthenticated);
+        assert!(report.is_reachable());
+        assert!(!report.is_authenticated());
+
+        let report = client.health_check("amazon.nova-lite-v1:0").await;
+        assert_eq!(report.status, HealthStatus::ModelUnavailable);
+        assert!(report.is_authenticated());
+        assert!(!report.is_model_ready());
+    }
+
+    #[tokio::test]
+    async fn test_count_tokens_probe() {
+        let mock = MockBedrock::new().with_response(MockResponse::json(
+            200,
+            serde_json::json!({ "inputTokens": 8 }),
+        ));
+
+        let report = mock
+            .client()
+            .health_check_with_probe(
+                "anthropic.claude-3-5-haiku-20241022-v1:0",
+                HealthProbe::CountTokens,
+            )
+            .await;
+
+        assert_eq!(report.status, HealthStatus::Healthy);
+        assert_eq!(mock.requests()[0].operation(), Some("count-tokens"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Request hedging, to cut the tail latency of completions.
+//!
+//! A [`HedgedCompletionModel`] sends each request to its primary model, and when no response
+//! arrived after a delay, sends a duplicate request to its hedge model, e.g. the same model in
+//! another region or through another inference profile. The first response is returned and the
+//! other request is canceled.
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model("us.anthropic.claude-3-5-haiku-20241022-v1:0")
+//!     .with_hedging(
+//!         client.completion_model("eu.anthropic.claude-3-5-haiku-20241022-v1:0"),
+//!         Duration::from_secs(2),
+//!     );
+//! ```
+//!
+//! Hedged requests are billed twice when both models start generating, so the delay is usually
+//! set around the P95 or P99 latency of the primary model.
+
+use std::pin::pin;
+use std::time::Duration;
+
+use rig::completion::{self, CompletionError, CompletionRequest, CompletionResponse};
+use rig::streaming::StreamingCompletionResponse;
+
+use crate::completion::CompletionModel;
+
+/// A completion model sending a duplicate request to a hedge model when the primary model is
+/// slow to respond, see [`crate::hedging`].
+///
+/// Streaming requests are hedged until the stream starts.
+#[derive(Clone, Debug)]
+pub struct HedgedCompletionModel<M = CompletionModel> {
+    primary: M,
+    hedge: M,
+    delay: Duration,
+}
+
+impl<M> HedgedCompletionModel<M> {
+    pub fn new(primary: M, hedge: M, delay: Duration) -> Self {
+        Self {
+            primary,
+            hedge,
+            delay,
+        }
+    }
+
+    pub fn primary(&self) -> &M {
+        &self.primary
+    }
+
+    pub fn hedge(&self) -> &M {
+        &self.hedge
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+impl<M> completion::CompletionModel for HedgedCompletionModel<M>
+where
+    M: completion::CompletionModel,
+{
+    type Response = M::Response;
+    type StreamingResponse = M::StreamingResponse;
+
+    type Client = M::Client;
+
+    /// A model hedging requests with a duplicate request to the same model after two seconds.
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        let model = M::make(client, model);
+        Self::new(model.clone(), model, Duration::from_secs(2))
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
+        hedge(self.delay, self.primary.completion(request.clone()), || {
+            self.hedge.completion(request)
+        })
+        .await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
+        hedge(self.delay, self.primary.stream(request.clone()), || {
+            self.hedge.stream(request)
+        })
+        .await
+    }
+}
+
+/// Run `primary`, and `hedge` as well if `primary` didn't complete within `delay`. Returns the
+/// first successful result, or the error of the primary if both failed. A primary failing before
+/// the delay isn't hedged.
+async fn hedge<T, E, P, H>(delay: Duration, primary: P, hedge: impl FnOnce() -> H) -> Result<T, E>
+where
+    P: Future<Output = Result<T, E>>,
+    H: Future<Output = Result<T, E>>,
+{
+    let mut primary = pin!(primary);
+    tokio::select! {
+        biased;
+        result = &mut primary => return result,
+        _ = tokio::time::sleep(delay) => {}
+    }
+
+    tracing::debug!(
+        target: "rig::bedrock",
+        "No Bedrock response after {delay:?}, sending a hedged request"
+    );
+    let mut hedge = pin!(hedge());
+    // Dropping the slower future cancels its request
+    tokio::select! {
+        biased;
+        result = &mut primary => match result {
+            Ok(response) => Ok(response),
+            Err(error) => hedge.await.or(Err(error)),
+        },
+        result = &mut hedge => match result {
+            Ok(response) => Ok(response),
+            Err(_) => primary.await,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    async fn respond(
+        after_ms: u64,
+        result: Result<&'static str, &'static str>,
+    ) -> Result<&'static str, &'static str> {
+        tokio::time::sleep(Duration::from_millis(after_ms)).await;
+        result
+    }
+
+    #[tokio::test]
+    async fn test_fast_primary_is_not_hedged() {
+        let hedged = AtomicBool::new(false);
+        let result = hedge(
+            Duration::from_millis(200),
+            respond(0, Ok("primary")),
+            || {
+                hedged.store(true, Ordering::SeqCst);
+                respond(0, Ok("hedge"))
+            },
+        )
+        .await;
+
+        assert_eq!(result, Ok("primary"));
+        assert!(!hedged.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_slow_primary_is_hedged() {
+        let result = hedge(
+            Duration::from_millis(10),
+            respond(1000, Ok("primary")),
+            || respond(0, Ok("hedge")),
+        )
+        .await;
+
+        assert_eq!(result, Ok("hedge"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_hedge_waits_for_primary() {
+        let result = hedge(
+            Duration::from_millis(10),
+            respond(50, Ok("primary")),
+            || respond(0, Err("throttled")),
+        )
+        .await;
+        assert_eq!(result, Ok("primary"));
+
+        let result = hedge(
+            Duration::from_millis(10),
+            respond(20, Err("primary")),
+            || respond(30, Err("hedge")),
+        )
+        .await;
+        assert_eq!(result, Err("primary"));
+    }
+}
@@ -1,9 +1,21 @@
 use crate::client::Client;
-use crate::types::errors::AwsSdkInvokeModelError;
-use crate::types::text_to_image::{TextToImageGeneration, TextToImageResponse};
+use crate::native::ModelFamily;
+use crate::telemetry;
+use crate::types::errors::BedrockError;
+use crate::types::stability::{self, StabilityParams};
+use crate::types::text_to_image::ImageGenerationConfig;
 use aws_smithy_types::Blob;
+use base64::{Engine, prelude::BASE64_STANDARD};
 use rig::image_generation::{
-    self, ImageGenerationError, ImageGenerationRequest, ImageGenerationResponse,
+    self, ImageGenerationError, ImageGenerationModel as _, ImageGenerationRequest,
+    ImageGenerationResponse,
+};
+use rig::message::{DocumentSourceKind, Image};
+use tracing::Instrument;
+
+pub use crate::types::text_to_image::{
+    ControlMode, ImageConditioning, ImageMask, ImageQuality, ImageTask, OutPaintingMode,
+    TextToImageResponse,
 };
 
 /// `amazon.titan-image-generator-v1`
@@ -12,11 +24,109 @@ pub const AMAZON_TITAN_IMAGE_GENERATOR_V1: &str = "amazon.titan-image-generator-
 pub const AMAZON_TITAN_IMAGE_GENERATOR_V2_0: &str = "amazon.titan-image-generator-v2:0";
 /// `amazon.nova-canvas-v1:0`
 pub const AMAZON_NOVA_CANVAS: &str = "amazon.nova-canvas-v1:0";
+/// `stability.sd3-large-v1:0`
+pub const STABILITY_SD3_LARGE: &str = "stability.sd3-large-v1:0";
+/// `stability.sd3-5-large-v1:0`
+pub const STABILITY_SD3_5_LARGE: &str = "stability.sd3-5-large-v1:0";
+/// `stability.stable-image-core-v1:1`
+pub const STABILITY_STABLE_IMAGE_CORE: &str = "stability.stable-image-core-v1:1";
+/// `stability.stable-image-ultra-v1:1`
+pub const STABILITY_STABLE_IMAGE_ULTRA: &str = "stability.stable-image-ultra-v1:1";
+/// `stability.stable-diffusion-xl-v1`
+pub const STABILITY_SDXL: &str = "stability.stable-diffusion-xl-v1";
+
+/// Reference images and prompt of an [`ImageGenerationModel::image_variation`] call.
+#[derive(Clone, Debug)]
+pub struct ImageVariationRequest {
+    /// Up to 5 base64 or raw PNG/JPEG images.
+    pub images: Vec<Image>,
+    pub prompt: String,
+    /// How similar the variations are to the references, between 0.2 and 1.0.
+    pub similarity_strength: Option<f32>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageVariationRequest {
+    /// A request for 1024x1024 variations of `images`.
+    pub fn new(images: Vec<Image>, prompt: impl Into<String>) -> Self {
+        Self {
+            images,
+            prompt: prompt.into(),
+            similarity_strength: None,
+            width: 1024,
+            height: 1024,
+        }
+    }
+
+    pub fn with_similarity_strength(mut self, similarity_strength: f32) -> Self {
+        self.similarity_strength = Some(similarity_strength);
+        self
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+}
+
+/// Source image, mask and prompt of an [`ImageGenerationModel::inpaint`] or
+/// [`ImageGenerationModel::outpaint`] call. The edited images have the size of the source image.
+#[derive(Clone, Debug)]
+pub struct ImageEditRequest {
+    /// Base64 or raw PNG/JPEG image.
+    pub image: Image,
+    pub mask: ImageMask,
+    /// What the edited area should contain.
+    pub prompt: String,
+}
 
+impl ImageEditRequest {
+    pub fn new(image: Image, mask: ImageMask, prompt: impl Into<String>) -> Self {
+        Self {
+            image,
+            mask,
+            prompt: prompt.into(),
+        }
+    }
+}
+
+impl ImageMask {
+    /// A mask image, black pixels being masked.
+    pub fn from_image(image: Image) -> Result<Self, ImageGenerationError> {
+        image_bytes(image).map(Self::Image)
+    }
+}
+
+/// Titan Image Generator, Nova Canvas and Stability AI image generation model.
+///
+/// Titan and Nova Canvas share the same request format, so they can be swapped freely. Stability
+/// models only support [`ImageTask::TextImage`], and SD3 and Stable Image models round the
+/// requested size to their closest supported aspect ratio. Tasks other
+/// than text-to-image, such as inpainting, are selected with [`ImageGenerationModel::with_task`]:
+///
+/// ```rust,ignore
+/// let model = client
+///     .image_generation_model(AMAZON_NOVA_CANVAS)
+///     .with_task(ImageTask::ImageVariation { images: vec![reference], similarity_strength: Some(0.8) });
+/// let response = model.image_generation_request().prompt("In watercolor").send().await?;
+/// ```
+///
+/// The generation request's `additional_params` are merged into the native request, e.g.
+/// `{"imageGenerationConfig": {"cfgScale": 6.5}}`, taking precedence over the options set on the
+/// model.
 #[derive(Clone)]
 pub struct ImageGenerationModel {
     pub(crate) client: Client,
     pub model: String,
+    number_of_images: Option<u32>,
+    quality: Option<ImageQuality>,
+    cfg_scale: Option<f32>,
+    seed: Option<u32>,
+    negative_text: Option<String>,
+    task: ImageTask,
+    conditioning: Option<ImageConditioning>,
 }
 
 impl ImageGenerationModel {
@@ -24,6 +134,262 @@ impl ImageGenerationModel {
         Self {
             client,
             model: model.into(),
+            number_of_images: None,
+            quality: None,
+            cfg_scale: None,
+            seed: None,
+            negative_text: None,
+            task: ImageTask::default(),
+            conditioning: None,
+        }
+    }
+
+    /// Set the task run by the model. Defaults to [`ImageTask::TextImage`].
+    pub fn with_task(mut self, task: ImageTask) -> Self {
+        self.task = task;
+        self
+    }
+
+    /// Generate variations of reference images with the `IMAGE_VARIATION` task of Titan Image
+    /// Generator v2 and Nova Canvas, returning the decoded images.
+    ///
+    /// Use [`ImageGenerationModel::with_number_of_images`] to get several variations.
+    pub async fn image_variation(
+        &self,
+        request: ImageVariationRequest,
+    ) -> Result<Vec<Vec<u8>>, ImageGenerationError> {
+        let task = ImageTask::ImageVariation {
+            images: request
+                .images
+                .into_iter()
+                .map(image_bytes)
+                .collect::<Result<_, _>>()?,
+            similarity_strength: request.similarity_strength,
+        };
+
+        self.run_task(task, &request.prompt, request.width, request.height)
+            .await
+    }
+
+    /// Replace the masked area of an image with the `INPAINTING` task of Titan Image Generator
+    /// and Nova Canvas, returning the decoded images.
+    pub async fn inpaint(
+        &self,
+        request: ImageEditRequest,
+    ) -> Result<Vec<Vec<u8>>, ImageGenerationError> {
+        let task = ImageTask::Inpainting {
+            image: image_bytes(request.image)?,
+            mask: request.mask,
+        };
+
+        self.run_task(task, &request.prompt, 0, 0).await
+    }
+
+    /// Replace everything but the masked area of an image with the `OUTPAINTING` task of Titan
+    /// Image Generator and Nova Canvas, returning the decoded images.
+    pub async fn outpaint(
+        &self,
+        request: ImageEditRequest,
+        mode: OutPaintingMode,
+    ) -> Result<Vec<Vec<u8>>, ImageGenerationError> {
+        let task = ImageTask::Outpainting {
+            image: image_bytes(request.image)?,
+            mask: request.mask,
+            mode,
+        };
+
+        self.run_task(task, &request.prompt, 0, 0).await
+    }
+
+    /// Remove the background of an image with the `BACKGROUND_REMOVAL` task of Titan Image
+    /// Generator v2 and Nova Canvas, returning a PNG with a transparent background.
+    pub async fn remove_background(&self, image: Image) -> Result<Vec<u8>, ImageGenerationError> {
+        let task = ImageTask::BackgroundRemoval {
+            image: image_bytes(image)?,
+        };
+
+        self.run_task(task, "", 0, 0)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ImageGenerationError::ResponseError("No image was returned".into()))
+    }
+
+    /// Run `task` instead of the model's configured task and decode all generated images.
+    async fn run_task(
+        &self,
+        task: ImageTask,
+        prompt: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<Vec<u8>>, ImageGenerationError> {
+        let response = self
+            .clone()
+            .with_task(task)
+            .image_generation_request()
+            .prompt(prompt)
+            .width(width)
+            .height(height)
+            .send()
+            .await?;
+
+        response.response.decoded_images()
+    }
+
+    /// Make text-to-image generation follow the structure of a reference image, e.g. a sketch or
+    /// the edges of a photo. Supported by Titan Image Generator v2 and Nova Canvas.
+    pub fn with_conditioning(mut self, conditioning: ImageConditioning) -> Self {
+        self.conditioning = Some(conditioning);
+        self
+    }
+
+    /// Number of images generated per request, between 1 and 5.
+    /// Use [`TextToImageResponse::decoded_images`] to get all of them.
+    pub fn with_number_of_images(mut self, number_of_images: u32) -> Self {
+        self.number_of_images = Some(number_of_images);
+        self
+    }
+
+    pub fn with_quality(mut self, quality: ImageQuality) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// How strongly the images adhere to the prompt, between 1.1 and 10.0.
+    pub fn with_cfg_scale(mut self, cfg_scale: f32) -> Self {
+        self.cfg_scale = Some(cfg_scale);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// What the images should not contain.
+    pub fn with_negative_text(mut self, negative_text: impl Into<String>) -> Self {
+        self.negative_text = Some(negative_text.into());
+        self
+    }
+
+    fn is_stability(&self) -> bool {
+        ModelFamily::from_model_id(&self.model) == Some(ModelFamily::Stability)
+    }
+
+    fn request_body(
+        &self,
+        generation_request: ImageGenerationRequest,
+    ) -> Result<serde_json::Value, ImageGenerationError> {
+        if self.is_stability() {
+            return self.stability_request_body(generation_request);
+        }
+
+        let mut config = ImageGenerationConfig {
+            width: Some(generation_request.width),
+            height: Some(generation_request.height),
+            ..Default::default()
+        };
+        if let Some(number_of_images) = self.number_of_images {
+            config.number_of_images = Some(number_of_images);
+        }
+        if let Some(quality) = self.quality {
+            config.quality = Some(quality);
+        }
+        config.cfg_scale = self.cfg_scale;
+        config.seed = self.seed;
+
+        let mut body = self.task.request_body(
+            generation_request.prompt,
+            self.negative_text.clone(),
+            &config,
+        )?;
+        if let Some(conditioning) = &self.conditioning {
+            if self.task != ImageTask::TextImage {
+                return Err(ImageGenerationError::ProviderError(format!(
+                    "Image conditioning is not supported by {}",
+                    self.task.task_type()
+                )));
+            }
+            conditioning.insert_into(&mut body["textToImageParams"]);
+        }
+        if let Some(additional_params) = &generation_request.additional_params {
+            merge_nested(&mut body, additional_params);
+        }
+        Ok(body)
+    }
+
+    fn stability_request_body(
+        &self,
+        generation_request: ImageGenerationRequest,
+    ) -> Result<serde_json::Value, ImageGenerationError> {
+        if self.task != ImageTask::TextImage {
+            return Err(ImageGenerationError::ProviderError(format!(
+                "{} is not supported by Stability models",
+                self.task.task_type()
+            )));
+        }
+        if self.conditioning.is_some() {
+            return Err(ImageGenerationError::ProviderError(
+                "Image conditioning is not supported by Stability models".into(),
+            ));
+        }
+
+        let mut body = stability::request_body(
+            &self.model,
+            StabilityParams {
+                prompt: generation_request.prompt,
+                negative_prompt: self.negative_text.clone(),
+                width: generation_request.width,
+                height: generation_request.height,
+                seed: self.seed,
+                cfg_scale: self.cfg_scale,
+                samples: self.number_of_images,
+            },
+        )?;
+        if let Some(additional_params) = &generation_request.additional_params {
+            merge_nested(&mut body, additional_params);
+        }
+        Ok(body)
+    }
+
+    fn parse_response(&self, response: &str) -> Result<TextToImageResponse, ImageGenerationError> {
+        if self.is_stability() {
+            return stability::parse_response(&self.model, response);
+        }
+
+        serde_json::from_str(response)
+            .map_err(|e| ImageGenerationError::ResponseError(e.to_string()))
+    }
+}
+
+/// The bytes of a base64 or raw rig image.
+fn image_bytes(image: Image) -> Result<Vec<u8>, ImageGenerationError> {
+    match image.data {
+        DocumentSourceKind::Base64(data) => BASE64_STANDARD
+            .decode(data)
+            .map_err(|e| ImageGenerationError::ProviderError(e.to_string())),
+        DocumentSourceKind::Raw(data) => Ok(data),
+        _ => Err(ImageGenerationError::ProviderError(
+            "Only base64 encoded or raw images are supported as reference images".into(),
+        )),
+    }
+}
+
+/// Merge `params` into `body`, merging nested objects one level deep.
+fn merge_nested(body: &mut serde_json::Value, params: &serde_json::Value) {
+    let (serde_json::Value::Object(body), serde_json::Value::Object(params)) = (body, params)
+    else {
+        return;
+    };
+
+    for (key, value) in params {
+        match (body.get_mut(key), value) {
+            (Some(serde_json::Value::Object(existing)), serde_json::Value::Object(value)) => {
+                existing.extend(value.clone());
+            }
+            _ => {
+                body.insert(key.clone(), value.clone());
+            }
         }
     }
 }
@@ -41,11 +407,8 @@ impl image_generation::ImageGenerationModel for ImageGenerationModel {
         &self,
         generation_request: ImageGenerationRequest,
     ) -> Result<ImageGenerationResponse<Self::Response>, ImageGenerationError> {
-        let mut request = TextToImageGeneration::new(generation_request.prompt);
-        request.width(generation_request.width);
-        request.height(generation_request.height);
-
-        let body = serde_json::to_string(&request)?;
+        let body = serde_json::to_string(&self.request_body(generation_request)?)?;
+        let span = telemetry::image_generation_span(&self.model);
         let model_response = self
             .client
             .get_inner()
@@ -56,17 +419,140 @@ impl image_generation::ImageGenerationModel for ImageGenerationModel {
             .accept("application/json")
             .body(Blob::new(body))
             .send()
+            .instrument(span.clone())
             .await
             .map_err(|sdk_error| {
-                Into::<ImageGenerationError>::into(AwsSdkInvokeModelError(sdk_error))
+                let error = BedrockError::from(sdk_error);
+                telemetry::record_error(&span, &error);
+                ImageGenerationError::from(error)
             })?;
 
         let response_str = String::from_utf8(model_response.body.into_inner())
             .map_err(|e| ImageGenerationError::ResponseError(e.to_string()))?;
 
-        let result: TextToImageResponse = serde_json::from_str(&response_str)
-            .map_err(|e| ImageGenerationError::ResponseError(e.to_string()))?;
+        self.parse_response(&response_str)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> ImageGenerationModel {
+        let client = Client::from(aws_sdk_bedrockruntime::Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version_latest()
+                .build(),
+        ));
+        ImageGenerationModel::new(client, AMAZON_TITAN_IMAGE_GENERATOR_V2_0)
+    }
+
+    fn request(additional_params: Option<serde_json::Value>) -> ImageGenerationRequest {
+        let builder = image_generation::ImageGenerationRequestBuilder::new(model())
+            .prompt("A lighthouse at dawn")
+            .width(1024)
+            .height(768);
+        match additional_params {
+            Some(params) => builder.additional_params(params).build(),
+            None => builder.build(),
+        }
+    }
+
+    #[test]
+    fn test_request_body_with_options() {
+        let body = model()
+            .with_number_of_images(3)
+            .with_quality(ImageQuality::Premium)
+            .with_seed(7)
+            .with_negative_text("people")
+            .request_body(request(None))
+            .unwrap();
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "taskType": "TEXT_IMAGE",
+                "textToImageParams": { "text": "A lighthouse at dawn", "negativeText": "people" },
+                "imageGenerationConfig": {
+                    "quality": "premium",
+                    "numberOfImages": 3,
+                    "width": 1024,
+                    "height": 768,
+                    "seed": 7
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_stability_models() {
+        let client = model().client;
+        let sd3 = ImageGenerationModel::new(client.clone(), STABILITY_SD3_LARGE).with_seed(1);
+
+        let body = sd3.request_body(request(None)).unwrap();
+        assert_eq!(body["aspect_ratio"], "5:4");
+        assert_eq!(body["seed"], 1);
+
+        let response = sd3
+            .parse_response(r#"{"seeds":[1],"finish_reasons":[null],"images":["aW1hZ2U="]}"#)
+            .unwrap();
+        let response: ImageGenerationResponse<TextToImageResponse> = response.try_into().unwrap();
+        assert_eq!(response.image, b"image");
+
+        let background_removal = sd3.with_task(ImageTask::BackgroundRemoval { image: vec![] });
+        assert!(background_removal.request_body(request(None)).is_err());
+    }
+
+    #[test]
+    fn test_image_bytes() {
+        let base64 = Image {
+            data: DocumentSourceKind::Base64(BASE64_STANDARD.encode(b"png")),
+            ..Default::default()
+        };
+        assert_eq!(image_bytes(base64).unwrap(), b"png");
+
+        let raw = Image {
+            data: DocumentSourceKind::Raw(b"png".to_vec()),
+            ..Default::default()
+        };
+        assert_eq!(image_bytes(raw).unwrap(), b"png");
+
+        let url = Image {
+            data: DocumentSourceKind::Url("https://example.com/cat.png".into()),
+            ..Default::default()
+        };
+        assert!(image_bytes(url).is_err());
+    }
+
+    #[test]
+    fn test_conditioning_is_text_to_image_only() {
+        let conditioned = model().with_conditioning(ImageConditioning::new(
+            b"png".to_vec(),
+            ControlMode::CannyEdge,
+        ));
+
+        let body = conditioned.request_body(request(None)).unwrap();
+        assert_eq!(body["textToImageParams"]["controlMode"], "CANNY_EDGE");
+
+        let variation = conditioned.with_task(ImageTask::ImageVariation {
+            images: vec![b"png".to_vec()],
+            similarity_strength: None,
+        });
+        assert!(variation.request_body(request(None)).is_err());
+    }
+
+    #[test]
+    fn test_additional_params_are_merged() {
+        let body = model()
+            .with_number_of_images(3)
+            .request_body(request(Some(serde_json::json!({
+                "imageGenerationConfig": { "numberOfImages": 2, "cfgScale": 6.5 }
+            }))))
+            .unwrap();
 
-        result.try_into()
+        let config = &body["imageGenerationConfig"];
+        assert_eq!(config["numberOfImages"], 2);
+        assert_eq!(config["cfgScale"], 6.5);
+        assert_eq!(config["width"], 1024);
     }
 }
@@ -0,0 +1,458 @@
+//! Fetching of URL-sourced images, since Converse only accepts image bytes (or S3 locations).
+//!
+//! Completion models can download images given by URL before sending the request. Fetching is
+//! off by default, as the URLs may come from untrusted input, and has to be turned on:
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_LITE)
+//!     .with_image_fetch(ImageFetch::new().with_max_bytes(1024 * 1024));
+//! ```
+//!
+//! Only `http` and `https` URLs are fetched, and URLs whose host is or resolves to a loopback,
+//! private or link-local address (such as the `169.254.169.254` instance metadata endpoint) are
+//! rejected, including after redirects, unless allowed with
+//! [`ImageFetch::with_private_addresses`].
+
+use std::net::IpAddr;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use futures::StreamExt;
+use reqwest::Url;
+use rig::completion::{CompletionError, CompletionRequest};
+use rig::message::{
+    DocumentSourceKind, Image, ImageMediaType, Message, MimeType, ToolResultContent, UserContent,
+};
+
+/// The largest image accepted by Converse.
+pub const MAX_IMAGE_BYTES: usize = 3_750_000;
+
+const MAX_REDIRECTS: usize = 10;
+
+/// The HTTP client shared by the image fetchers without their own. Redirects are followed by
+/// [`ImageFetch`] itself, so every URL is checked.
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("failed to build the image fetch HTTP client")
+});
+
+/// How URL-sourced images are fetched. The default doesn't fetch images.
+#[derive(Clone, Debug)]
+pub struct ImageFetch {
+    enabled: bool,
+    max_bytes: usize,
+    timeout: Duration,
+    private_addresses: bool,
+    http_client: reqwest::Client,
+}
+
+impl Default for ImageFetch {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: MAX_IMAGE_BYTES,
+            timeout: Duration::from_secs(30),
+            private_addresses: false,
+            http_client: HTTP_CLIENT.clone(),
+        }
+    }
+}
+
+impl ImageFetch {
+    /// Fetch images from public `http` and `https` URLs.
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    /// Don't fetch images: requests containing image URLs fail instead.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Reject images larger than `max_bytes`. Defaults to [`MAX_IMAGE_BYTES`].
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Timeout of each image download. Defaults to 30 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Also fetch images from loopback, private and link-local addresses, e.g. from a service on
+    /// the local network. Only allow this if the image URLs are trusted.
+    pub fn with_private_addresses(mut self, allowed: bool) -> Self {
+        self.private_addresses = allowed;
+        self
+    }
+
+    /// Use `http_client` to download images, e.g. to configure a proxy. The client should not
+    /// follow redirects itself, or redirected URLs aren't checked.
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Replace the URL of every image of `request` with the downloaded bytes.
+    pub(crate) async fn resolve(
+        &self,
+        request: &mut CompletionRequest,
+    ) -> Result<(), CompletionError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        for message in request.chat_history.iter_mut() {
+            let Message::User { content } = message else {
+                continue;
+            };
+            for content in content.iter_mut() {
+                match content {
+                    UserContent::Image(image) => self.resolve_image(image).await?,
+                    UserContent::ToolResult(result) => {
+                        for content in result.content.iter_mut() {
+                            if let ToolResultContent::Image(image) = content {
+                                self.resolve_image(image).await?;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn resolve_image(&self, image: &mut Image) -> Result<(), CompletionError> {
+        let Some(url) = image_url(&image.data) else {
+            return Ok(());
+        };
+        let (bytes, content_type) = self.fetch(url).await?;
+
+        if image.media_type.is_none() {
+            image.media_type = content_type
+                .as_deref()
+                .and_then(ImageMediaType::from_mime_type);
+        }
+        image.data = DocumentSourceKind::Raw(bytes);
+        Ok(())
+    }
+
+    async fn fetch(&self, url: &str) -> Result<(Vec<u8>, Option<String>), CompletionError> {
+        let request_error = |e: reqwest::Error| {
+            CompletionError::RequestError(format!("Failed to fetch image {url}: {e}").into())
+        };
+        let invalid_url =
+            |e| CompletionError::RequestError(format!("Invalid image URL {url}: {e}").into());
+
+        let mut location = Url::parse(url).map_err(invalid_url)?;
+        let mut redirects = 0;
+        let response = loop {
+            self.check_url(&location).await?;
+            let response = self
+                .http_client
+                .get(location.clone())
+                .timeout(self.timeout)
+                .send()
+                .await
+                .map_err(request_error)?;
+            if !response.status().is_redirection() {
+                break response.error_for_status().map_err(request_error)?;
+            }
+
+            redirects += 1;
+            let redirect = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok());
+            let (Some(redirect), true) = (redirect, redirects <= MAX_REDIRECTS) else {
+                return Err(CompletionError::RequestError(
+                    format!("Failed to fetch image {url}: invalid or too many redirects").into(),
+                ));
+            };
+            location = location.join(redirect).map_err(invalid_url)?;
+        };
+
+        let too_large = || {
+            CompletionError::RequestError(
+                format!("Image {url} is larger than {} bytes", self.max_bytes).into(),
+            )
+        };
+        if response
+            .content_length()
+            .is_some_and(|length| length > self.max_bytes as u64)
+        {
+            return Err(too_large());
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or(value).trim().to_string());
+
+        // The content length may be missing or wrong, so the limit is enforced while reading
+        let mut bytes = Vec::new();
+        let mut body = response.bytes_stream();
+        while let Some(chunk) = body.next().await {
+            bytes.extend_from_slice(&chunk.map_err(request_error)?);
+            if bytes.len() > self.max_bytes {
+                return Err(too_large());
+            }
+        }
+
+        Ok((bytes, content_type))
+    }
+
+    /// Check that `url` is an `http` or `https` URL whose host is a public address.
+    async fn check_url(&self, url: &Url) -> Result<(), CompletionError> {
+        let rejected = |reason: String| {
+            CompletionError::RequestError(
+                format!("Image URL {url} is not allowed: {reason}").into(),
+            )
+        };
+
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(rejected(format!("unsupported scheme `{}`", url.scheme())));
+        }
+        if self.private_addresses {
+            return Ok(());
+        }
+
+        let Some(host) = url.host_str() else {
+            return Err(rejected("missing host".into()));
+        };
+        let addresses = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+            Ok(address) => vec![address],
+            Err(_) => {
+                let port = url.port_or_known_default().unwrap_or(443);
+                tokio::net::lookup_host((host, port))
+                    .await
+                    .map_err(|e| rejected(format!("failed to resolve {host}: {e}")))?
+                    .map(|address| address.ip())
+                    .collect()
+            }
+        };
+        match addresses.into_iter().find(|address| !is_public(*address)) {
+            Some(address) => Err(rejected(format!("{address} is not a public address"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether `address` is reachable on the internet, rather than a loopback, private, link-local
+/// or otherwise reserved address.
+fn is_public(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let [first, second, ..] = address.octets();
+            !(address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_broadcast()
+                || address.is_documentation()
+                || first == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(address) => match address.to_ipv4_mapped() {
+            Some(address) => is_public(address.into()),
+            None => {
+                !(address.is_loopback()
+                    || address.is_unspecified()
+                    || address.is_unique_local()
+                    || address.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// The URL of an image given by URL, or as a string holding a URL rather than base64 data.
+fn image_url(data: &DocumentSourceKind) -> Option<&str> {
+    match data {
+        DocumentSourceKind::Url(url) => Some(url),
+        DocumentSourceKind::String(url)
+            if url.starts_with("https://") || url.starts_with("http://") =>
+        {
+            Some(url)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+    use rig::OneOrMany;
+    use rig::completion::CompletionRequest;
+
+    fn request_with_image(data: DocumentSourceKind) -> CompletionRequest {
+        completion_request(Message::User {
+            content: OneOrMany::one(UserContent::Image(Image {
+                data,
+                media_type: None,
+                detail: None,
+                additional_params: None,
+            })),
+        })
+        .build()
+    }
+
+    #[test]
+    fn test_image_url() {
+        assert_eq!(
+            image_url(&DocumentSourceKind::url("https://example.com/cat.png")),
+            Some("https://example.com/cat.png")
+        );
+        assert_eq!(
+            image_url(&DocumentSourceKind::String(
+                "http://example.com/cat.png".into()
+            )),
+            Some("http://example.com/cat.png")
+        );
+        assert_eq!(
+            image_url(&DocumentSourceKind::String("aW1hZ2U=".into())),
+            None
+        );
+        assert_eq!(image_url(&DocumentSourceKind::base64("aW1hZ2U=")), None);
+    }
+
+    fn image(request: &CompletionRequest) -> &Image {
+        let Message::User { content } = request.chat_history.first_ref() else {
+            panic!("expected a user message");
+        };
+        let UserContent::Image(image) = content.first_ref() else {
+            panic!("expected an image");
+        };
+        image
+    }
+
+    #[test]
+    fn test_is_public() {
+        for address in ["93.184.216.34", "2606:2800:220:1::"] {
+            assert!(is_public(address.parse().unwrap()), "{address}");
+        }
+        for address in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(address.parse().unwrap()), "{address}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_url() {
+        let fetch = ImageFetch::new();
+        for url in [
+            "file:///etc/passwd",
+            "ftp://example.com/cat.png",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://127.0.0.1:8080/cat.png",
+            "http://[::1]/cat.png",
+            "http://localhost/cat.png",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(fetch.check_url(&url).await.is_err(), "{url}");
+        }
+
+        let public = Url::parse("https://93.184.216.34/cat.png").unwrap();
+        assert!(fetch.check_url(&public).await.is_ok());
+        let local = Url::parse("http://127.0.0.1:8080/cat.png").unwrap();
+        assert!(
+            fetch
+                .with_private_addresses(true)
+                .check_url(&local)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_server() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::GET).path("/cat.png");
+                then.status(200)
+                    .header("content-type", "image/png; charset=binary")
+                    .body([1, 2, 3, 4]);
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::GET).path("/moved.png");
+                then.status(302).header("location", "/cat.png");
+            })
+            .await;
+        let fetch = ImageFetch::new().with_private_addresses(true);
+
+        for path in ["/cat.png", "/moved.png"] {
+            let mut request = request_with_image(DocumentSourceKind::url(&server.url(path)));
+            fetch.resolve(&mut request).await.unwrap();
+
+            let image = image(&request);
+            assert_eq!(image.data, DocumentSourceKind::Raw(vec![1, 2, 3, 4]));
+            assert_eq!(image.media_type, Some(ImageMediaType::PNG));
+        }
+
+        let mut request = request_with_image(DocumentSourceKind::url(&server.url("/cat.png")));
+        let error = fetch
+            .clone()
+            .with_max_bytes(3)
+            .resolve(&mut request)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("larger than 3 bytes"), "{error}");
+
+        // The server is on a loopback address
+        let mut request = request_with_image(DocumentSourceKind::url(&server.url("/cat.png")));
+        assert!(ImageFetch::new().resolve(&mut request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redirects_are_checked() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::GET).path("/cat.png");
+                then.status(302).header("location", "file:///etc/passwd");
+            })
+            .await;
+
+        let mut request = request_with_image(DocumentSourceKind::url(&server.url("/cat.png")));
+        let error = ImageFetch::new()
+            .with_private_addresses(true)
+            .resolve(&mut request)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("unsupported scheme"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_default_fetch_keeps_urls() {
+        let mut request =
+            request_with_image(DocumentSourceKind::url("https://example.com/cat.png"));
+        ImageFetch::default().resolve(&mut request).await.unwrap();
+
+        assert!(matches!(image(&request).data, DocumentSourceKind::Url(_)));
+    }
+}
@@ -0,0 +1,266 @@
+//! Downscaling and recompression of images that exceed the Converse limits, which Bedrock would
+//! reject, requires the `image-preprocessing` feature.
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_LITE)
+//!     .with_image_preprocessing(ImagePreprocessing::default().with_max_dimension(1568));
+//! ```
+//!
+//! Images within the limits are sent unchanged. Larger images are resized to fit the maximum
+//! dimension, keeping their aspect ratio, and re-encoded as PNG if they have transparency and
+//! still fit, or as JPEG otherwise, shrinking them further until they fit the maximum size.
+//! Animated GIFs are reduced to their first frame.
+//!
+//! Images given by URL are checked against the limit of their [`ImageFetch`] when downloaded,
+//! which should be raised to let them be preprocessed.
+//!
+//! [`ImageFetch`]: crate::image_fetch::ImageFetch
+
+use std::io::Cursor;
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use rig::completion::{CompletionError, CompletionRequest};
+use rig::message::{
+    DocumentSourceKind, Image, ImageMediaType, Message, ToolResultContent, UserContent,
+};
+
+use crate::image_fetch::MAX_IMAGE_BYTES;
+
+/// The largest width or height of an image accepted by Converse, in pixels.
+pub const MAX_IMAGE_DIMENSION: u32 = 8000;
+
+/// How many times an image is shrunk further when it doesn't fit the maximum size.
+const MAX_SHRINK_STEPS: usize = 8;
+
+/// How images exceeding the Converse limits are preprocessed, see [`crate::image_preprocessing`].
+#[derive(Clone, Debug)]
+pub struct ImagePreprocessing {
+    max_bytes: usize,
+    max_dimension: u32,
+    jpeg_quality: u8,
+}
+
+impl Default for ImagePreprocessing {
+    fn default() -> Self {
+        Self {
+            max_bytes: MAX_IMAGE_BYTES,
+            max_dimension: MAX_IMAGE_DIMENSION,
+            jpeg_quality: 85,
+        }
+    }
+}
+
+impl ImagePreprocessing {
+    /// Shrink images larger than `max_bytes`. Defaults to [`MAX_IMAGE_BYTES`].
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Downscale images wider or taller than `max_dimension` pixels. Defaults to
+    /// [`MAX_IMAGE_DIMENSION`], and can be lowered to the resolution a model actually uses to
+    /// save input tokens.
+    pub fn with_max_dimension(mut self, max_dimension: u32) -> Self {
+        self.max_dimension = max_dimension.max(1);
+        self
+    }
+
+    /// The quality of re-encoded JPEG images, from 1 to 100. Defaults to 85.
+    pub fn with_jpeg_quality(mut self, jpeg_quality: u8) -> Self {
+        self.jpeg_quality = jpeg_quality.clamp(1, 100);
+        self
+    }
+
+    /// The image encoded in `bytes`, downscaled and recompressed to fit the limits, with its new
+    /// media type, or `None` if it already fits.
+    pub fn preprocess(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Option<(Vec<u8>, ImageMediaType)>, CompletionError> {
+        let reader = ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(image_error)?;
+        let (width, height) = reader.into_dimensions().map_err(image_error)?;
+        if bytes.len() <= self.max_bytes && width.max(height) <= self.max_dimension {
+            return Ok(None);
+        }
+
+        let mut image = image::load_from_memory(bytes).map_err(image_error)?;
+        if image.width().max(image.height()) > self.max_dimension {
+            image = image.resize(self.max_dimension, self.max_dimension, FilterType::Lanczos3);
+        }
+
+        for _ in 0..MAX_SHRINK_STEPS {
+            if image.color().has_alpha() {
+                let png = encode_png(&image)?;
+                if png.len() <= self.max_bytes {
+                    return Ok(Some((png, ImageMediaType::PNG)));
+                }
+            }
+
+            let jpeg = self.encode_jpeg(&image)?;
+            if jpeg.len() <= self.max_bytes {
+                return Ok(Some((jpeg, ImageMediaType::JPEG)));
+            }
+
+            let (width, height) = (image.width() * 3 / 4, image.height() * 3 / 4);
+            if width == 0 || height == 0 {
+                break;
+            }
+            image = image.resize(width, height, FilterType::Triangle);
+        }
+
+        Err(CompletionError::RequestError(
+            format!(
+                "Image couldn't be compressed below {} bytes",
+                self.max_bytes
+            )
+            .into(),
+        ))
+    }
+
+    /// Preprocess the images of `request`, on a blocking thread since decoding and encoding
+    /// large images takes a while.
+    pub(crate) async fn apply(
+        &self,
+        mut request: CompletionRequest,
+    ) -> Result<CompletionRequest, CompletionError> {
+        let preprocessing = self.clone();
+        tokio::task::spawn_blocking(move || {
+            preprocessing.apply_blocking(&mut request)?;
+            Ok(request)
+        })
+        .await
+        .map_err(|e| CompletionError::RequestError(e.into()))?
+    }
+
+    fn apply_blocking(&self, request: &mut CompletionRequest) -> Result<(), CompletionError> {
+        for message in request.chat_history.iter_mut() {
+            let Message::User { content } = message else {
+                continue;
+            };
+            for content in content.iter_mut() {
+                match content {
+                    UserContent::Image(image) => self.preprocess_image(image)?,
+                    UserContent::ToolResult(result) => {
+                        for content in result.content.iter_mut() {
+                            if let ToolResultContent::Image(image) = content {
+                                self.preprocess_image(image)?;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn preprocess_image(&self, image: &mut Image) -> Result<(), CompletionError> {
+        let decoded;
+        let bytes = match &image.data {
+            DocumentSourceKind::Raw(bytes) => bytes,
+            DocumentSourceKind::Base64(data) => {
+                decoded = BASE64_STANDARD
+                    .decode(data)
+                    .map_err(|e| CompletionError::ProviderError(e.to_string()))?;
+                &decoded
+            }
+            _ => return Ok(()),
+        };
+
+        if let Some((bytes, media_type)) = self.preprocess(bytes)? {
+            image.data = DocumentSourceKind::Raw(bytes);
+            image.media_type = Some(media_type);
+        }
+        Ok(())
+    }
+
+    fn encode_jpeg(&self, image: &DynamicImage) -> Result<Vec<u8>, CompletionError> {
+        let mut bytes = Vec::new();
+        let encoder = JpegEncoder::new_with_quality(&mut bytes, self.jpeg_quality);
+        DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(encoder)
+            .map_err(image_error)?;
+        Ok(bytes)
+    }
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, CompletionError> {
+    let mut bytes = Cursor::new(Vec::new());
+    image
+        .write_to(&mut bytes, ImageFormat::Png)
+        .map_err(image_error)?;
+    Ok(bytes.into_inner())
+}
+
+fn image_error(error: impl std::fmt::Display) -> CompletionError {
+    CompletionError::RequestError(format!("Failed to preprocess image: {error}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn png(image: DynamicImage) -> Vec<u8> {
+        encode_png(&image).unwrap()
+    }
+
+    fn noise(width: u32, height: u32) -> DynamicImage {
+        let mut state = 1u32;
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |_, _| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let [r, g, b, _] = state.to_be_bytes();
+            Rgb([r, g, b])
+        }))
+    }
+
+    #[test]
+    fn test_images_within_limits_are_unchanged() {
+        let bytes = png(noise(64, 64));
+        assert_eq!(
+            ImagePreprocessing::default().preprocess(&bytes).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_downscales_large_images() {
+        let bytes = png(DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            400,
+            200,
+            Rgba([0, 0, 255, 128]),
+        )));
+
+        let (bytes, media_type) = ImagePreprocessing::default()
+            .with_max_dimension(100)
+            .preprocess(&bytes)
+            .unwrap()
+            .unwrap();
+
+        // Transparency is kept
+        assert_eq!(media_type, ImageMediaType::PNG);
+        let image = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (100, 50));
+    }
+
+    #[test]
+    fn test_recompresses_heavy_images() {
+        let bytes = png(noise(256, 256));
+
+        let (compressed, media_type) = ImagePreprocessing::default()
+            .with_max_bytes(20_000)
+            .preprocess(&bytes)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(media_type, ImageMediaType::JPEG);
+        assert!(compressed.len() <= 20_000);
+    }
+}
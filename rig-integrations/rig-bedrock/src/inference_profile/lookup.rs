@@ -0,0 +1,93 @@
+use super::{InferenceProfile, InferenceProfileStatus, InferenceProfileType};
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+
+/// An [`InferenceProfile`] from a summary or a `GetInferenceProfile` output, which have the same
+/// fields.
+macro_rules! inference_profile {
+    ($profile:expr) => {{
+        let profile = $profile;
+        InferenceProfile {
+            id: profile.inference_profile_id().to_string(),
+            arn: profile.inference_profile_arn().to_string(),
+            name: profile.inference_profile_name().to_string(),
+            description: profile.description().map(str::to_string),
+            profile_type: InferenceProfileType::from(profile.r#type().as_str()),
+            status: InferenceProfileStatus::from(profile.status().as_str()),
+            model_arns: profile
+                .models()
+                .iter()
+                .filter_map(|model| model.model_arn())
+                .map(str::to_string)
+                .collect(),
+        }
+    }};
+}
+
+impl Client {
+    /// The inference profiles available in the client's region, only the ones of
+    /// `profile_type` if set.
+    pub async fn list_inference_profiles(
+        &self,
+        profile_type: Option<InferenceProfileType>,
+    ) -> Result<Vec<InferenceProfile>, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let bedrock = aws_sdk_bedrock::Client::new(sdk_config);
+
+        let mut profiles = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = bedrock
+                .list_inference_profiles()
+                .set_type_equals(profile_type.as_ref().map(|kind| kind.as_str().into()))
+                .set_next_token(next_token)
+                .send()
+                .await?;
+
+            profiles.extend(
+                output
+                    .inference_profile_summaries()
+                    .iter()
+                    .map(|summary| inference_profile!(summary)),
+            );
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(profiles);
+            }
+        }
+    }
+
+    /// The inference profile `id`, its id or ARN.
+    pub async fn get_inference_profile(&self, id: &str) -> Result<InferenceProfile, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .get_inference_profile()
+            .inference_profile_identifier(id)
+            .send()
+            .await?;
+
+        Ok(inference_profile!(&output))
+    }
+
+    /// The inference profile named `name`, application profiles first, if any.
+    pub async fn find_inference_profile(
+        &self,
+        name: &str,
+    ) -> Result<Option<InferenceProfile>, BedrockError> {
+        for profile_type in [
+            InferenceProfileType::Application,
+            InferenceProfileType::SystemDefined,
+        ] {
+            let profile = self
+                .list_inference_profiles(Some(profile_type))
+                .await?
+                .into_iter()
+                .find(|profile| profile.name == name);
+            if profile.is_some() {
+                return Ok(profile);
+            }
+        }
+        Ok(None)
+    }
+}
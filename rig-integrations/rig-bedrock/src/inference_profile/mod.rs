@@ -0,0 +1,75 @@
+//! Inference profiles: cross-region profiles defined by AWS, and application profiles created
+//! to track the cost and usage of a workload.
+//!
+//! Looking profiles up at runtime lets applications bind their agents to a profile by name
+//! instead of hardcoding ARNs, which differ between accounts. The types in this module are
+//! always available; querying Bedrock requires the `control-plane` feature.
+//!
+//! ```rust,ignore
+//! let profile = client
+//!     .find_inference_profile("support-bot")
+//!     .await?
+//!     .expect("the support-bot inference profile should exist");
+//!
+//! let agent = AgentBuilder::new(client.inference_profile_completion_model(&profile))
+//!     .preamble("You are a helpful support agent")
+//!     .build();
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/inference-profiles.html>
+
+#[cfg(feature = "control-plane")]
+mod lookup;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::Client;
+use crate::completion::CompletionModel;
+use crate::types::converse_output::{UnknownVariantValue, api_values};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InferenceProfile {
+    /// The id, e.g. `us.anthropic.claude-3-5-haiku-20241022-v1:0` for cross-region profiles.
+    pub id: String,
+    pub arn: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub profile_type: InferenceProfileType,
+    pub status: InferenceProfileStatus,
+    /// The ARNs of the models the profile routes requests to, one per region.
+    pub model_arns: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InferenceProfileType {
+    /// A cross-region profile defined by AWS.
+    SystemDefined,
+    /// A profile created in the account, e.g. to tag the usage of a workload.
+    Application,
+    Unknown(UnknownVariantValue),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InferenceProfileStatus {
+    Active,
+    Unknown(UnknownVariantValue),
+}
+
+api_values!(InferenceProfileType {
+    SystemDefined => "SYSTEM_DEFINED",
+    Application => "APPLICATION",
+});
+
+api_values!(InferenceProfileStatus {
+    Active => "ACTIVE",
+});
+
+impl Client {
+    /// A completion model invoking the models of `profile` through it.
+    pub fn inference_profile_completion_model(
+        &self,
+        profile: &InferenceProfile,
+    ) -> CompletionModel {
+        CompletionModel::new(self.clone(), &profile.arn)
+    }
+}
@@ -0,0 +1,81 @@
+//! Conversions of the citations returned by the Bedrock agent runtime.
+
+use aws_sdk_bedrockagentruntime::types::{
+    self as aws, RetrievalResultLocation, RetrievalResultLocationType, RetrievedReference,
+};
+
+use super::{Citation, SourceChunk, SourceLocation};
+use crate::types::json::AwsDocument;
+
+pub(crate) fn citation(citation: &aws::Citation) -> Citation {
+    let part = citation
+        .generated_response_part()
+        .and_then(|part| part.text_response_part());
+    Citation {
+        text: part
+            .and_then(|part| part.text())
+            .unwrap_or_default()
+            .to_string(),
+        // Bedrock spans end at the last character of the cited part
+        span: part
+            .and_then(|part| part.span())
+            .and_then(|span| Some(span.start()? as usize..span.end()? as usize + 1)),
+        sources: citation
+            .retrieved_references()
+            .iter()
+            .map(source_chunk)
+            .collect(),
+    }
+}
+
+fn source_chunk(reference: &RetrievedReference) -> SourceChunk {
+    SourceChunk {
+        text: reference
+            .content()
+            .and_then(|content| content.text())
+            .map(str::to_string),
+        location: reference.location().map(source_location),
+        metadata: reference
+            .metadata()
+            .map(|metadata| {
+                metadata
+                    .iter()
+                    .map(|(key, value)| (key.clone(), AwsDocument(value.clone()).into()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+fn source_location(location: &RetrievalResultLocation) -> SourceLocation {
+    let url = |url: Option<&str>| url.map(str::to_string);
+    match location.r#type() {
+        RetrievalResultLocationType::S3 => SourceLocation::S3 {
+            uri: url(location.s3_location().and_then(|l| l.uri())),
+        },
+        RetrievalResultLocationType::Web => SourceLocation::Web {
+            url: url(location.web_location().and_then(|l| l.url())),
+        },
+        RetrievalResultLocationType::Confluence => SourceLocation::Confluence {
+            url: url(location.confluence_location().and_then(|l| l.url())),
+        },
+        RetrievalResultLocationType::Salesforce => SourceLocation::Salesforce {
+            url: url(location.salesforce_location().and_then(|l| l.url())),
+        },
+        RetrievalResultLocationType::Sharepoint => SourceLocation::SharePoint {
+            url: url(location.share_point_location().and_then(|l| l.url())),
+        },
+        RetrievalResultLocationType::Kendra => SourceLocation::Kendra {
+            uri: url(location.kendra_document_location().and_then(|l| l.uri())),
+        },
+        RetrievalResultLocationType::Custom => SourceLocation::Custom {
+            id: url(location.custom_document_location().and_then(|l| l.id())),
+        },
+        RetrievalResultLocationType::Sql => SourceLocation::Sql {
+            query: url(location.sql_location().and_then(|l| l.query())),
+        },
+        other => SourceLocation::Other {
+            location_type: other.as_str().to_string(),
+        },
+    }
+}
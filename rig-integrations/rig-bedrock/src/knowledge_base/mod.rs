@@ -0,0 +1,217 @@
+//! Retrieval augmented generation with Bedrock Knowledge Bases.
+//!
+//! `RetrieveAndGenerate` queries a knowledge base and generates an answer grounded in the
+//! retrieved chunks in a single call, returning the citations linking parts of the answer to
+//! their sources. The answer types in this module are always available; calling Bedrock requires
+//! the `knowledge-base` feature.
+//!
+//! ```rust,ignore
+//! let answer = client
+//!     .knowledge_base("KB12345678")
+//!     .retrieve_and_generate(MODEL_ARN, "What is our parental leave policy?")
+//!     .await?;
+//!
+//! println!("{}", answer.text_with_citation_markers());
+//! for (index, source) in answer.sources().iter().enumerate() {
+//!     println!("[{}] {:?}", index + 1, source.location);
+//! }
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/kb-test-retrieve-generate.html>
+
+#[cfg(any(feature = "knowledge-base", feature = "agents"))]
+pub(crate) mod convert;
+#[cfg(feature = "knowledge-base")]
+mod retrieve_and_generate;
+
+#[cfg(feature = "knowledge-base")]
+pub use retrieve_and_generate::KnowledgeBase;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// An answer generated from the chunks retrieved from a knowledge base.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroundedAnswer {
+    pub text: String,
+    /// The session to pass to follow-up queries, so they are answered in context.
+    pub session_id: String,
+    pub citations: Vec<Citation>,
+}
+
+impl GroundedAnswer {
+    /// The chunks cited by the answer, without duplicates, in order of first citation.
+    pub fn sources(&self) -> Vec<&SourceChunk> {
+        let mut sources: Vec<&SourceChunk> = Vec::new();
+        for source in self.citations.iter().flat_map(|citation| &citation.sources) {
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+        sources
+    }
+
+    /// The answer with `[n]` markers after each cited part, `n` being the 1-based index of the
+    /// source in [`GroundedAnswer::sources`].
+    pub fn text_with_citation_markers(&self) -> String {
+        let sources = self.sources();
+        let mut markers: Vec<(usize, String)> = self
+            .citations
+            .iter()
+            .filter_map(|citation| {
+                let end = citation.span.as_ref()?.end;
+                let numbers: Vec<String> = citation
+                    .sources
+                    .iter()
+                    .filter_map(|source| sources.iter().position(|s| *s == source))
+                    .map(|index| format!("[{}]", index + 1))
+                    .collect();
+                Some((end, numbers.concat()))
+            })
+            .collect();
+        markers.sort_by_key(|(end, _)| *end);
+
+        let mut text = String::with_capacity(self.text.len());
+        let mut position = 0;
+        for (end, marker) in markers {
+            // Spans are character offsets
+            let end = self
+                .text
+                .char_indices()
+                .nth(end)
+                .map_or(self.text.len(), |(index, _)| index);
+            if end < position {
+                continue;
+            }
+            text.push_str(&self.text[position..end]);
+            text.push_str(&marker);
+            position = end;
+        }
+        text.push_str(&self.text[position..]);
+        text
+    }
+}
+
+/// A part of an answer and the chunks supporting it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// The cited part of the answer.
+    pub text: String,
+    /// Character range of the cited part in the answer, when returned.
+    pub span: Option<Range<usize>>,
+    pub sources: Vec<SourceChunk>,
+}
+
+/// A chunk retrieved from a knowledge base.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SourceChunk {
+    /// The text of the chunk, if it is a text chunk.
+    pub text: Option<String>,
+    pub location: Option<SourceLocation>,
+    /// Metadata attributes of the source document, e.g. its title.
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// Where a retrieved chunk comes from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceLocation {
+    S3 {
+        uri: Option<String>,
+    },
+    Web {
+        url: Option<String>,
+    },
+    Confluence {
+        url: Option<String>,
+    },
+    Salesforce {
+        url: Option<String>,
+    },
+    SharePoint {
+        url: Option<String>,
+    },
+    Kendra {
+        uri: Option<String>,
+    },
+    Custom {
+        id: Option<String>,
+    },
+    Sql {
+        query: Option<String>,
+    },
+    /// A location type this crate doesn't know about.
+    Other {
+        location_type: String,
+    },
+}
+
+#[derive(Debug)]
+pub enum KnowledgeBaseError {
+    /// The Bedrock call failed, or its input couldn't be built.
+    Request(Box<dyn std::error::Error + Send + Sync>),
+    /// Bedrock returned no answer.
+    MissingOutput,
+}
+
+impl fmt::Display for KnowledgeBaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(error) => write!(f, "Knowledge base request failed: {error}"),
+            Self::MissingOutput => write!(f, "Knowledge base returned no answer"),
+        }
+    }
+}
+
+impl std::error::Error for KnowledgeBaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(error) => Some(error.as_ref()),
+            Self::MissingOutput => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(uri: &str) -> SourceChunk {
+        SourceChunk {
+            text: Some(format!("Contents of {uri}")),
+            location: Some(SourceLocation::S3 {
+                uri: Some(uri.to_string()),
+            }),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_citation_markers() {
+        let answer = GroundedAnswer {
+            text: "Leave is 16 weeks. It can be split.".into(),
+            session_id: "session".into(),
+            citations: vec![
+                Citation {
+                    text: "Leave is 16 weeks.".into(),
+                    span: Some(0..18),
+                    sources: vec![source("s3://docs/policy.pdf"), source("s3://docs/faq.pdf")],
+                },
+                Citation {
+                    text: "It can be split.".into(),
+                    span: Some(19..35),
+                    sources: vec![source("s3://docs/policy.pdf")],
+                },
+            ],
+        };
+
+        assert_eq!(answer.sources().len(), 2);
+        assert_eq!(
+            answer.text_with_citation_markers(),
+            "Leave is 16 weeks.[1][2] It can be split.[1]"
+        );
+    }
+}
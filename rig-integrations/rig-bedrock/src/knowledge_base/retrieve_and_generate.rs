@@ -0,0 +1,120 @@
+use aws_sdk_bedrockagentruntime::types::{
+    KnowledgeBaseRetrievalConfiguration, KnowledgeBaseRetrieveAndGenerateConfiguration,
+    KnowledgeBaseVectorSearchConfiguration, RetrieveAndGenerateConfiguration,
+    RetrieveAndGenerateInput, RetrieveAndGenerateType,
+};
+
+use super::convert::citation;
+use super::{GroundedAnswer, KnowledgeBaseError};
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+
+/// A Bedrock knowledge base. Created with [`Client::knowledge_base`].
+#[derive(Clone, Debug)]
+pub struct KnowledgeBase {
+    client: Client,
+    knowledge_base_id: String,
+    number_of_results: Option<i32>,
+}
+
+impl KnowledgeBase {
+    pub(crate) fn new(client: Client, knowledge_base_id: String) -> Self {
+        Self {
+            client,
+            knowledge_base_id,
+            number_of_results: None,
+        }
+    }
+
+    /// Retrieve at most `number_of_results` chunks for each query. Defaults to the knowledge
+    /// base's setting.
+    pub fn with_number_of_results(mut self, number_of_results: i32) -> Self {
+        self.number_of_results = Some(number_of_results);
+        self
+    }
+
+    /// Answer `query` with the model `model_arn`, grounded in the chunks retrieved for it.
+    pub async fn retrieve_and_generate(
+        &self,
+        model_arn: &str,
+        query: &str,
+    ) -> Result<GroundedAnswer, KnowledgeBaseError> {
+        self.send(model_arn, query, None).await
+    }
+
+    /// Answer a follow-up `query` in the session of a previous [`GroundedAnswer`].
+    pub async fn retrieve_and_generate_in_session(
+        &self,
+        session_id: &str,
+        model_arn: &str,
+        query: &str,
+    ) -> Result<GroundedAnswer, KnowledgeBaseError> {
+        self.send(model_arn, query, Some(session_id)).await
+    }
+
+    async fn send(
+        &self,
+        model_arn: &str,
+        query: &str,
+        session_id: Option<&str>,
+    ) -> Result<GroundedAnswer, KnowledgeBaseError> {
+        let retrieval_configuration = self
+            .number_of_results
+            .map(|number_of_results| {
+                KnowledgeBaseRetrievalConfiguration::builder()
+                    .vector_search_configuration(
+                        KnowledgeBaseVectorSearchConfiguration::builder()
+                            .number_of_results(number_of_results)
+                            .build(),
+                    )
+                    .build()
+            })
+            .transpose()
+            .map_err(|e| KnowledgeBaseError::Request(e.into()))?;
+        let knowledge_base_configuration = KnowledgeBaseRetrieveAndGenerateConfiguration::builder()
+            .knowledge_base_id(&self.knowledge_base_id)
+            .model_arn(model_arn)
+            .set_retrieval_configuration(retrieval_configuration)
+            .build()
+            .map_err(|e| KnowledgeBaseError::Request(e.into()))?;
+        let configuration = RetrieveAndGenerateConfiguration::builder()
+            .r#type(RetrieveAndGenerateType::KnowledgeBase)
+            .knowledge_base_configuration(knowledge_base_configuration)
+            .build()
+            .map_err(|e| KnowledgeBaseError::Request(e.into()))?;
+        let input = RetrieveAndGenerateInput::builder()
+            .text(query)
+            .build()
+            .map_err(|e| KnowledgeBaseError::Request(e.into()))?;
+
+        let sdk_config = self.client.sdk_config().await;
+        let output = aws_sdk_bedrockagentruntime::Client::new(sdk_config)
+            .retrieve_and_generate()
+            .input(input)
+            .retrieve_and_generate_configuration(configuration)
+            .set_session_id(session_id.map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| KnowledgeBaseError::Request(BedrockError::from(e).into()))?;
+
+        let text = output
+            .output()
+            .ok_or(KnowledgeBaseError::MissingOutput)?
+            .text()
+            .to_string();
+        let citations = output.citations().iter().map(citation).collect();
+
+        Ok(GroundedAnswer {
+            text,
+            session_id: output.session_id().to_string(),
+            citations,
+        })
+    }
+}
+
+impl Client {
+    /// The knowledge base `knowledge_base_id`, to answer queries grounded in its documents.
+    pub fn knowledge_base(&self, knowledge_base_id: impl Into<String>) -> KnowledgeBase {
+        KnowledgeBase::new(self.clone(), knowledge_base_id.into())
+    }
+}
@@ -1,6 +1,59 @@
+pub mod async_invoke;
+pub mod batch;
+pub mod bedrock_agent;
+pub mod budget;
+pub mod cache;
+pub mod circuit_breaker;
 pub mod client;
+#[cfg(feature = "cloudwatch-metrics")]
+pub mod cloudwatch;
 pub mod completion;
+pub mod concurrency;
+pub mod conversation;
+pub mod converse_json;
+pub mod debug_logging;
+pub mod document_chunking;
 pub mod embedding;
+pub mod evaluation;
+pub mod extraction;
+pub mod failover;
+pub mod flow;
+pub mod guardrails;
+pub mod health;
+pub mod hedging;
 pub mod image;
+pub mod image_fetch;
+#[cfg(feature = "image-preprocessing")]
+pub mod image_preprocessing;
+pub mod inference_profile;
+pub mod knowledge_base;
+pub mod load_balancer;
+mod metrics;
+pub mod model_catalog;
+pub mod model_import;
+pub mod model_params;
+pub mod native;
+pub mod param_ranges;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod permissions;
+mod prefill;
+pub mod presets;
+pub mod pricing;
+pub mod provisioned_throughput;
+pub mod rate_limit;
+pub mod request_limits;
+pub mod rerank;
+pub mod retry;
+pub mod sse;
 pub mod streaming;
+mod telemetry;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub mod think_tags;
+pub mod tool_hooks;
+pub mod tool_names;
+pub mod tool_schema;
+pub mod tool_validation;
 pub mod types;
+pub mod usage;
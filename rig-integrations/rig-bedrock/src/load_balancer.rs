@@ -0,0 +1,170 @@
+//! Load balancing of completions across model ids or inference profiles, to spread the load of
+//! high-throughput workloads over the quotas of several cross-region profiles.
+//!
+//! ```rust,ignore
+//! let model = LoadBalancedCompletionModel::new(client.completion_model("us.amazon.nova-lite-v1:0"))
+//!     .with_model_id("eu.amazon.nova-lite-v1:0", 1)
+//!     .with_model_id("apac.amazon.nova-lite-v1:0", 1);
+//! ```
+//!
+//! Requests are spread with a smooth weighted round-robin: a model with weight 2 gets twice as
+//! many requests as a model with weight 1, interleaved rather than in bursts. The models can be
+//! wrapped in a [`RetryingCompletionModel`](crate::retry::RetryingCompletionModel) or a
+//! [`FailoverCompletionModel`](crate::failover::FailoverCompletionModel) to handle throttling.
+
+use std::sync::{Arc, Mutex};
+
+use rig::completion::{self, CompletionError, CompletionRequest, CompletionResponse};
+use rig::streaming::StreamingCompletionResponse;
+
+use crate::completion::CompletionModel;
+
+/// A completion model sending each request to one of its models, in proportion to their
+/// weights, see [`crate::load_balancer`].
+///
+/// Clones share the round-robin state.
+#[derive(Clone, Debug)]
+pub struct LoadBalancedCompletionModel<M = CompletionModel> {
+    models: Vec<(M, u32)>,
+    /// The current weight of each model in the smooth weighted round-robin.
+    current_weights: Arc<Mutex<Vec<i64>>>,
+}
+
+impl<M> LoadBalancedCompletionModel<M> {
+    /// A load balancer sending every request to `model`, with a weight of 1, until other models
+    /// are added.
+    pub fn new(model: M) -> Self {
+        Self {
+            models: vec![(model, 1)],
+            current_weights: Arc::new(Mutex::new(vec![0])),
+        }
+    }
+
+    /// Send a share of the requests proportional to `weight` to `model`. Models with a weight of
+    /// 0 don't get any request.
+    pub fn with_model(mut self, model: M, weight: u32) -> Self {
+        self.models.push((model, weight));
+        self.lock().push(0);
+        self
+    }
+
+    /// The models and their weights.
+    pub fn models(&self) -> impl Iterator<Item = (&M, u32)> {
+        self.models.iter().map(|(model, weight)| (model, *weight))
+    }
+
+    /// The index of the model to send the next request to.
+    fn next(&self) -> usize {
+        let total = self
+            .models
+            .iter()
+            .map(|(_, weight)| i64::from(*weight))
+            .sum::<i64>();
+        let mut current_weights = self.lock();
+
+        let mut selected = 0;
+        for (index, (_, weight)) in self.models.iter().enumerate() {
+            current_weights[index] += i64::from(*weight);
+            if current_weights[index] > current_weights[selected] {
+                selected = index;
+            }
+        }
+        current_weights[selected] -= total;
+        selected
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<i64>> {
+        self.current_weights
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl LoadBalancedCompletionModel<CompletionModel> {
+    /// Send a share of the requests proportional to `weight` to the model id or inference
+    /// profile `model`, called with the client and settings of the first model.
+    pub fn with_model_id(self, model: impl Into<String>, weight: u32) -> Self {
+        let mut completion_model = self.models[0].0.clone();
+        completion_model.model = model.into();
+        self.with_model(completion_model, weight)
+    }
+}
+
+impl<M> completion::CompletionModel for LoadBalancedCompletionModel<M>
+where
+    M: completion::CompletionModel,
+{
+    type Response = M::Response;
+    type StreamingResponse = M::StreamingResponse;
+
+    type Client = M::Client;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self::new(M::make(client, model))
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
+        self.models[self.next()].0.completion(request).await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
+        self.models[self.next()].0.stream(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::client::CompletionClient;
+    use rig::completion::CompletionModel as _;
+
+    #[test]
+    fn test_smooth_weighted_round_robin() {
+        let balancer = LoadBalancedCompletionModel::new("a")
+            .with_model("b", 2)
+            .with_model("c", 0);
+
+        let picks = (0..6)
+            .map(|_| balancer.models[balancer.next()].0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(picks, vec!["b", "a", "b", "b", "a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_spreads_requests_across_profiles() {
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::text("one"))
+            .with_response(MockResponse::text("two"));
+
+        let model = LoadBalancedCompletionModel::new(
+            mock.client().completion_model("us.amazon.nova-lite-v1:0"),
+        )
+        .with_model_id("eu.amazon.nova-lite-v1:0", 1);
+
+        for _ in 0..2 {
+            let request = model.completion_request("Hi").build();
+            model.completion(request).await.unwrap();
+        }
+
+        let model_ids = mock
+            .requests()
+            .iter()
+            .map(|request| request.model_id())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            model_ids,
+            vec![
+                Some("us.amazon.nova-lite-v1:0".to_string()),
+                Some("eu.amazon.nova-lite-v1:0".to_string())
+            ]
+        );
+    }
+}
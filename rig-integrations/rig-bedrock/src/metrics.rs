@@ -0,0 +1,167 @@
+//! OpenTelemetry metrics of completion and embedding calls, recorded with the global meter
+//! provider when the `otel-metrics` feature is enabled.
+//!
+//! Following the OpenTelemetry GenAI semantic conventions, the instruments are:
+//! - `gen_ai.client.operation.duration`: histogram of call durations, in seconds
+//! - `gen_ai.client.token.usage`: histogram of input and output tokens per call, distinguished
+//!   by the `gen_ai.token.type` attribute
+//! - `aws.bedrock.requests`: counter of calls
+//! - `aws.bedrock.errors`: counter of failed calls, by `error.type`
+//!
+//! All of them have the `gen_ai.operation.name`, `gen_ai.provider.name` and
+//! `gen_ai.request.model` attributes.
+//!
+//! With the `cloudwatch-metrics` feature, calls are also recorded with the installed
+//! [`CloudWatchMetrics`](crate::cloudwatch::CloudWatchMetrics) publisher, if any.
+
+use crate::types::errors::BedrockError;
+
+/// Records the metrics of a single call, started when created. Either [`Self::success`] or
+/// [`Self::failure`] is called once it completes.
+pub(crate) struct InvocationMetrics {
+    #[cfg(feature = "otel-metrics")]
+    inner: otel::Invocation,
+    #[cfg(feature = "cloudwatch-metrics")]
+    cloudwatch: Option<crate::cloudwatch::Invocation>,
+}
+
+impl InvocationMetrics {
+    #[cfg_attr(
+        not(any(feature = "otel-metrics", feature = "cloudwatch-metrics")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn start(operation: &'static str, model: &str) -> Self {
+        Self {
+            #[cfg(feature = "otel-metrics")]
+            inner: otel::Invocation::start(operation, model),
+            #[cfg(feature = "cloudwatch-metrics")]
+            cloudwatch: crate::cloudwatch::Invocation::start(operation, model),
+        }
+    }
+
+    /// Record a successful call and its token usage, if known.
+    #[cfg_attr(
+        not(any(feature = "otel-metrics", feature = "cloudwatch-metrics")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn success(&self, input_tokens: Option<u64>, output_tokens: Option<u64>) {
+        #[cfg(feature = "otel-metrics")]
+        self.inner.finish(input_tokens, output_tokens, None);
+        #[cfg(feature = "cloudwatch-metrics")]
+        if let Some(cloudwatch) = &self.cloudwatch {
+            cloudwatch.finish(input_tokens, output_tokens, None);
+        }
+    }
+
+    /// Record a failed call, by Bedrock error kind when it failed calling Bedrock.
+    #[cfg_attr(
+        not(any(feature = "otel-metrics", feature = "cloudwatch-metrics")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn failure(&self, error: Option<&BedrockError>) {
+        #[cfg(any(feature = "otel-metrics", feature = "cloudwatch-metrics"))]
+        let error_type = error
+            .map(|error| format!("{:?}", error.kind()))
+            .unwrap_or_else(|| "_OTHER".to_string());
+        #[cfg(feature = "cloudwatch-metrics")]
+        if let Some(cloudwatch) = &self.cloudwatch {
+            cloudwatch.finish(None, None, Some(&error_type));
+        }
+        #[cfg(feature = "otel-metrics")]
+        self.inner.finish(None, None, Some(error_type));
+    }
+}
+
+#[cfg(feature = "otel-metrics")]
+mod otel {
+    use std::sync::OnceLock;
+
+    use opentelemetry::KeyValue;
+    use opentelemetry::metrics::{Counter, Histogram};
+    use tokio::time::Instant;
+
+    use crate::telemetry::PROVIDER_NAME;
+
+    struct Instruments {
+        duration: Histogram<f64>,
+        token_usage: Histogram<u64>,
+        requests: Counter<u64>,
+        errors: Counter<u64>,
+    }
+
+    fn instruments() -> &'static Instruments {
+        static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+        INSTRUMENTS.get_or_init(|| {
+            let meter = opentelemetry::global::meter("rig-bedrock");
+            Instruments {
+                duration: meter
+                    .f64_histogram("gen_ai.client.operation.duration")
+                    .with_unit("s")
+                    .with_description("GenAI operation duration")
+                    .build(),
+                token_usage: meter
+                    .u64_histogram("gen_ai.client.token.usage")
+                    .with_unit("{token}")
+                    .with_description("Measures number of input and output tokens used")
+                    .build(),
+                requests: meter
+                    .u64_counter("aws.bedrock.requests")
+                    .with_unit("{request}")
+                    .with_description("Number of Bedrock model invocations")
+                    .build(),
+                errors: meter
+                    .u64_counter("aws.bedrock.errors")
+                    .with_unit("{error}")
+                    .with_description("Number of failed Bedrock model invocations")
+                    .build(),
+            }
+        })
+    }
+
+    pub(super) struct Invocation {
+        attributes: Vec<KeyValue>,
+        started: Instant,
+    }
+
+    impl Invocation {
+        pub(super) fn start(operation: &'static str, model: &str) -> Self {
+            Self {
+                attributes: vec![
+                    KeyValue::new("gen_ai.operation.name", operation),
+                    KeyValue::new("gen_ai.provider.name", PROVIDER_NAME),
+                    KeyValue::new("gen_ai.request.model", model.to_string()),
+                ],
+                started: Instant::now(),
+            }
+        }
+
+        pub(super) fn finish(
+            &self,
+            input_tokens: Option<u64>,
+            output_tokens: Option<u64>,
+            error_type: Option<String>,
+        ) {
+            let instruments = instruments();
+            let elapsed = self.started.elapsed().as_secs_f64();
+
+            instruments.requests.add(1, &self.attributes);
+            for (token_type, tokens) in [("input", input_tokens), ("output", output_tokens)] {
+                if let Some(tokens) = tokens {
+                    let mut attributes = self.attributes.clone();
+                    attributes.push(KeyValue::new("gen_ai.token.type", token_type));
+                    instruments.token_usage.record(tokens, &attributes);
+                }
+            }
+
+            match error_type {
+                Some(error_type) => {
+                    let mut attributes = self.attributes.clone();
+                    attributes.push(KeyValue::new("error.type", error_type));
+                    instruments.errors.add(1, &attributes);
+                    instruments.duration.record(elapsed, &attributes);
+                }
+                None => instruments.duration.record(elapsed, &self.attributes),
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use aws_sdk_bedrock::types as aws;
+
+use super::{
+    Customization, FoundationModel, InferenceType, LifecycleStatus, Modality, ModelFilter,
+};
+use crate::client::Client;
+use crate::native::base_model_id;
+use crate::types::errors::BedrockError;
+
+/// A [`FoundationModel`] from a model summary or model details, which have the same fields.
+macro_rules! foundation_model {
+    ($model:expr) => {{
+        let model = $model;
+        FoundationModel {
+            model_id: model.model_id().to_string(),
+            model_arn: model.model_arn().to_string(),
+            model_name: model.model_name().map(str::to_string),
+            provider_name: model.provider_name().map(str::to_string),
+            input_modalities: modalities(model.input_modalities()),
+            output_modalities: modalities(model.output_modalities()),
+            response_streaming_supported: model.response_streaming_supported().unwrap_or_default(),
+            customizations_supported: model
+                .customizations_supported()
+                .iter()
+                .map(|customization| Customization::from(customization.as_str()))
+                .collect(),
+            inference_types_supported: model
+                .inference_types_supported()
+                .iter()
+                .map(|inference_type| InferenceType::from(inference_type.as_str()))
+                .collect(),
+            lifecycle_status: model
+                .model_lifecycle()
+                .map(|lifecycle| LifecycleStatus::from(lifecycle.status().as_str())),
+        }
+    }};
+}
+
+impl Client {
+    /// The foundation models offered in the client's region and matching `filter`.
+    pub async fn list_foundation_models(
+        &self,
+        filter: ModelFilter,
+    ) -> Result<Vec<FoundationModel>, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .list_foundation_models()
+            .set_by_provider(filter.provider)
+            .set_by_output_modality(
+                filter
+                    .output_modality
+                    .map(|modality| modality.as_str().into()),
+            )
+            .set_by_customization_type(
+                filter
+                    .customization
+                    .map(|customization| customization.as_str().into()),
+            )
+            .set_by_inference_type(
+                filter
+                    .inference_type
+                    .map(|inference_type| inference_type.as_str().into()),
+            )
+            .send()
+            .await?;
+
+        Ok(output
+            .model_summaries()
+            .iter()
+            .map(|summary| foundation_model!(summary))
+            .collect())
+    }
+
+    /// The foundation model `model`, a model id, cross-region inference profile id or ARN.
+    /// Models not offered in the client's region fail with
+    /// [`BedrockErrorKind::ResourceNotFound`](crate::types::errors::BedrockErrorKind). `None` if
+    /// Bedrock returned no details for the model.
+    pub async fn describe_model(
+        &self,
+        model: &str,
+    ) -> Result<Option<FoundationModel>, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .get_foundation_model()
+            .model_identifier(base_model_id(model))
+            .send()
+            .await?;
+
+        Ok(output
+            .model_details()
+            .map(|details| foundation_model!(details)))
+    }
+}
+
+fn modalities(modalities: &[aws::ModelModality]) -> Vec<Modality> {
+    modalities
+        .iter()
+        .map(|modality| Modality::from(modality.as_str()))
+        .collect()
+}
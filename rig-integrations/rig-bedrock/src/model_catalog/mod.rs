@@ -0,0 +1,173 @@
+//! The catalog of foundation models offered by Bedrock in a region.
+//!
+//! Listing the models lets applications populate model pickers and validate their configuration
+//! at startup, rather than failing on the first call. The catalog types in this module are always
+//! available; querying Bedrock requires the `control-plane` feature.
+//!
+//! ```rust,ignore
+//! let models = client
+//!     .list_foundation_models(
+//!         ModelFilter::new()
+//!             .with_output_modality(Modality::Text)
+//!             .with_inference_type(InferenceType::OnDemand),
+//!     )
+//!     .await?;
+//!
+//! for model in models.iter().filter(|model| model.response_streaming_supported) {
+//!     println!("{} ({})", model.model_id, model.provider_name.as_deref().unwrap_or_default());
+//! }
+//! ```
+//!
+//! A single model, including cross-region inference profiles of it, is described with
+//! `Client::describe_model`, e.g. to refuse to start with a legacy model:
+//!
+//! ```rust,ignore
+//! let model = client.describe_model("us.anthropic.claude-3-haiku-20240307-v1:0").await?;
+//! if let Some(model) = model.filter(FoundationModel::is_legacy) {
+//!     tracing::warn!("{} is scheduled for removal", model.model_id);
+//! }
+//! ```
+
+#[cfg(feature = "control-plane")]
+mod catalog;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::converse_output::{UnknownVariantValue, api_values};
+
+/// A foundation model available in the client's region.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FoundationModel {
+    pub model_id: String,
+    pub model_arn: String,
+    pub model_name: Option<String>,
+    pub provider_name: Option<String>,
+    pub input_modalities: Vec<Modality>,
+    pub output_modalities: Vec<Modality>,
+    pub response_streaming_supported: bool,
+    pub customizations_supported: Vec<Customization>,
+    pub inference_types_supported: Vec<InferenceType>,
+    pub lifecycle_status: Option<LifecycleStatus>,
+}
+
+impl FoundationModel {
+    /// Whether the model is scheduled for removal.
+    pub fn is_legacy(&self) -> bool {
+        self.lifecycle_status == Some(LifecycleStatus::Legacy)
+    }
+
+    /// Whether the model can be invoked without provisioned throughput.
+    pub fn supports_on_demand(&self) -> bool {
+        self.inference_types_supported
+            .contains(&InferenceType::OnDemand)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Modality {
+    Embedding,
+    Image,
+    Text,
+    Unknown(UnknownVariantValue),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Customization {
+    ContinuedPreTraining,
+    Distillation,
+    FineTuning,
+    Unknown(UnknownVariantValue),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InferenceType {
+    OnDemand,
+    Provisioned,
+    Unknown(UnknownVariantValue),
+}
+
+/// Legacy models are scheduled for removal and should be migrated away from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LifecycleStatus {
+    Active,
+    Legacy,
+    Unknown(UnknownVariantValue),
+}
+
+api_values!(Modality {
+    Embedding => "EMBEDDING",
+    Image => "IMAGE",
+    Text => "TEXT",
+});
+
+api_values!(Customization {
+    ContinuedPreTraining => "CONTINUED_PRE_TRAINING",
+    Distillation => "DISTILLATION",
+    FineTuning => "FINE_TUNING",
+});
+
+api_values!(InferenceType {
+    OnDemand => "ON_DEMAND",
+    Provisioned => "PROVISIONED",
+});
+
+api_values!(LifecycleStatus {
+    Active => "ACTIVE",
+    Legacy => "LEGACY",
+});
+
+/// Criteria the listed models must all match.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelFilter {
+    pub provider: Option<String>,
+    pub output_modality: Option<Modality>,
+    pub customization: Option<Customization>,
+    pub inference_type: Option<InferenceType>,
+}
+
+impl ModelFilter {
+    /// Match every model.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match the models of `provider`, e.g. `Anthropic` or `Amazon`.
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    pub fn with_output_modality(mut self, output_modality: Modality) -> Self {
+        self.output_modality = Some(output_modality);
+        self
+    }
+
+    pub fn with_customization(mut self, customization: Customization) -> Self {
+        self.customization = Some(customization);
+        self
+    }
+
+    pub fn with_inference_type(mut self, inference_type: InferenceType) -> Self {
+        self.inference_type = Some(inference_type);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_values() {
+        assert_eq!(Modality::from("TEXT"), Modality::Text);
+        assert_eq!(InferenceType::OnDemand.as_str(), "ON_DEMAND");
+        assert_eq!(
+            Customization::from("CONTINUED_PRE_TRAINING"),
+            Customization::ContinuedPreTraining
+        );
+
+        let unknown = Modality::from("VIDEO");
+        assert!(matches!(unknown, Modality::Unknown(_)));
+        assert_eq!(unknown.as_str(), "VIDEO");
+    }
+}
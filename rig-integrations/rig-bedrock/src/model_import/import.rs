@@ -0,0 +1,200 @@
+use std::time::{Duration, SystemTime};
+
+use aws_sdk_bedrock::types::{ModelDataSource, S3DataSource};
+use aws_smithy_types::DateTime;
+use tokio::time::Instant;
+
+use super::{
+    ImportedModel, ModelImportError, ModelImportJob, ModelImportJobConfig, ModelImportJobStatus,
+};
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+
+impl Client {
+    /// Start importing the model weights of `config` and return the ARN of the import job.
+    pub async fn create_model_import_job(
+        &self,
+        config: &ModelImportJobConfig,
+    ) -> Result<String, ModelImportError> {
+        let source = S3DataSource::builder()
+            .s3_uri(&config.model_uri)
+            .build()
+            .map_err(|e| ModelImportError::InvalidConfig(e.into()))?;
+
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .create_model_import_job()
+            .job_name(&config.job_name)
+            .imported_model_name(&config.imported_model_name)
+            .role_arn(&config.role_arn)
+            .model_data_source(ModelDataSource::S3DataSource(source))
+            .set_imported_model_kms_key_id(config.kms_key_id.clone())
+            .send()
+            .await
+            .map_err(BedrockError::from)?;
+
+        let job_arn = output.job_arn().to_string();
+        tracing::info!(target: "rig::bedrock", "Started model import job {job_arn}");
+        Ok(job_arn)
+    }
+
+    /// The model import job `job`, its name or ARN.
+    pub async fn get_model_import_job(&self, job: &str) -> Result<ModelImportJob, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .get_model_import_job()
+            .job_identifier(job)
+            .send()
+            .await?;
+
+        Ok(ModelImportJob {
+            job_arn: output.job_arn().unwrap_or(job).to_string(),
+            job_name: output.job_name().unwrap_or_default().to_string(),
+            status: output
+                .status()
+                .map(|status| ModelImportJobStatus::from(status.as_str()))
+                .unwrap_or(ModelImportJobStatus::InProgress),
+            imported_model_name: output.imported_model_name().map(str::to_string),
+            imported_model_arn: output.imported_model_arn().map(str::to_string),
+            failure_message: output.failure_message().map(str::to_string),
+            creation_time: time(output.creation_time()),
+            end_time: time(output.end_time()),
+        })
+    }
+
+    /// The model import jobs of the account in the client's region.
+    pub async fn list_model_import_jobs(&self) -> Result<Vec<ModelImportJob>, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let bedrock = aws_sdk_bedrock::Client::new(sdk_config);
+
+        let mut jobs = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = bedrock
+                .list_model_import_jobs()
+                .set_next_token(next_token)
+                .send()
+                .await?;
+
+            jobs.extend(
+                output
+                    .model_import_job_summaries()
+                    .iter()
+                    .map(|summary| ModelImportJob {
+                        job_arn: summary.job_arn().to_string(),
+                        job_name: summary.job_name().to_string(),
+                        status: ModelImportJobStatus::from(summary.status().as_str()),
+                        imported_model_name: summary.imported_model_name().map(str::to_string),
+                        imported_model_arn: summary.imported_model_arn().map(str::to_string),
+                        failure_message: None,
+                        creation_time: time(Some(summary.creation_time())),
+                        end_time: time(summary.end_time()),
+                    }),
+            );
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(jobs);
+            }
+        }
+    }
+
+    /// Check the model import job `job` every `poll_interval` until it completes, failing if it
+    /// fails or doesn't finish within `timeout`. Imports usually take tens of minutes.
+    pub async fn wait_for_model_import_job(
+        &self,
+        job: &str,
+        poll_interval: Duration,
+        timeout: Option<Duration>,
+    ) -> Result<ModelImportJob, ModelImportError> {
+        let started = Instant::now();
+        loop {
+            let import = self.get_model_import_job(job).await?;
+            match import.status {
+                ModelImportJobStatus::Completed => return Ok(import),
+                ModelImportJobStatus::Failed => {
+                    return Err(ModelImportError::JobFailed {
+                        job_arn: import.job_arn,
+                        message: import.failure_message.unwrap_or_default(),
+                    });
+                }
+                _ => {}
+            }
+
+            if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                return Err(ModelImportError::Timeout {
+                    job_arn: import.job_arn,
+                });
+            }
+
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// The models imported to the client's region.
+    pub async fn list_imported_models(&self) -> Result<Vec<ImportedModel>, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let bedrock = aws_sdk_bedrock::Client::new(sdk_config);
+
+        let mut models = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = bedrock
+                .list_imported_models()
+                .set_next_token(next_token)
+                .send()
+                .await?;
+
+            models.extend(
+                output
+                    .model_summaries()
+                    .iter()
+                    .map(|summary| ImportedModel {
+                        arn: summary.model_arn().to_string(),
+                        name: summary.model_name().to_string(),
+                        architecture: summary.model_architecture().map(str::to_string),
+                        instruct_supported: summary.instruct_supported(),
+                        creation_time: time(Some(summary.creation_time())),
+                    }),
+            );
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(models);
+            }
+        }
+    }
+
+    /// The imported model `model`, its name or ARN.
+    pub async fn get_imported_model(&self, model: &str) -> Result<ImportedModel, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .get_imported_model()
+            .model_identifier(model)
+            .send()
+            .await?;
+
+        Ok(ImportedModel {
+            arn: output.model_arn().unwrap_or(model).to_string(),
+            name: output.model_name().unwrap_or_default().to_string(),
+            architecture: output.model_architecture().map(str::to_string),
+            instruct_supported: output.instruct_supported(),
+            creation_time: time(output.creation_time()),
+        })
+    }
+
+    /// Delete the imported model `model`, its name or ARN.
+    pub async fn delete_imported_model(&self, model: &str) -> Result<(), BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        aws_sdk_bedrock::Client::new(sdk_config)
+            .delete_imported_model()
+            .model_identifier(model)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+fn time(time: Option<&DateTime>) -> Option<SystemTime> {
+    time.and_then(|time| SystemTime::try_from(*time).ok())
+}
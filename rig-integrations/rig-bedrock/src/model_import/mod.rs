@@ -0,0 +1,198 @@
+//! Custom model import: bring model weights trained elsewhere, e.g. a fine-tuned Llama or
+//! Mistral in Hugging Face format, to Bedrock and invoke them like any other model.
+//!
+//! The types in this module are always available; importing models requires the
+//! `control-plane` feature.
+//!
+//! ```rust,ignore
+//! let job_arn = client
+//!     .create_model_import_job(&ModelImportJobConfig::new(
+//!         "support-llama-import",
+//!         "support-llama",
+//!         ROLE_ARN,
+//!         "s3://my-bucket/weights/support-llama/",
+//!     ))
+//!     .await?;
+//!
+//! let job = client
+//!     .wait_for_model_import_job(&job_arn, Duration::from_secs(60), None)
+//!     .await?;
+//! let model = client.get_imported_model(&job.imported_model_arn.unwrap()).await?;
+//! let agent = AgentBuilder::new(client.imported_completion_model(&model)).build();
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/model-customization-import-model.html>
+
+#[cfg(feature = "control-plane")]
+mod import;
+
+use std::fmt;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::Client;
+use crate::completion::CompletionModel;
+use crate::types::converse_output::{UnknownVariantValue, api_values};
+use crate::types::errors::BedrockError;
+
+/// The definition of a model import job.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelImportJobConfig {
+    pub job_name: String,
+    /// The name of the imported model.
+    pub imported_model_name: String,
+    /// IAM role Bedrock assumes to read the model weights.
+    pub role_arn: String,
+    /// S3 prefix of the model weights and configuration files.
+    pub model_uri: String,
+    /// KMS key encrypting the imported model, instead of an AWS owned key.
+    pub kms_key_id: Option<String>,
+}
+
+impl ModelImportJobConfig {
+    pub fn new(
+        job_name: impl Into<String>,
+        imported_model_name: impl Into<String>,
+        role_arn: impl Into<String>,
+        model_uri: impl Into<String>,
+    ) -> Self {
+        Self {
+            job_name: job_name.into(),
+            imported_model_name: imported_model_name.into(),
+            role_arn: role_arn.into(),
+            model_uri: model_uri.into(),
+            kms_key_id: None,
+        }
+    }
+
+    pub fn with_kms_key_id(mut self, kms_key_id: impl Into<String>) -> Self {
+        self.kms_key_id = Some(kms_key_id.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModelImportJobStatus {
+    InProgress,
+    Completed,
+    Failed,
+    Unknown(UnknownVariantValue),
+}
+
+api_values!(ModelImportJobStatus {
+    InProgress => "InProgress",
+    Completed => "Completed",
+    Failed => "Failed",
+});
+
+/// The state of a model import job.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelImportJob {
+    pub job_arn: String,
+    pub job_name: String,
+    pub status: ModelImportJobStatus,
+    pub imported_model_name: Option<String>,
+    /// The ARN of the imported model, once the job completed.
+    pub imported_model_arn: Option<String>,
+    /// Why the job failed, only available from `Client::get_model_import_job`.
+    pub failure_message: Option<String>,
+    pub creation_time: Option<SystemTime>,
+    pub end_time: Option<SystemTime>,
+}
+
+/// A model imported to Bedrock.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportedModel {
+    /// The ARN to invoke the model through, in place of a model id.
+    pub arn: String,
+    pub name: String,
+    /// The architecture of the model, e.g. `llama3` or `mistral`.
+    pub architecture: Option<String>,
+    /// Whether the model supports chat templates, which the Converse API requires.
+    pub instruct_supported: Option<bool>,
+    pub creation_time: Option<SystemTime>,
+}
+
+impl Client {
+    /// A completion model invoking the imported `model`. Converse calls require
+    /// [`ImportedModel::instruct_supported`]. The first calls after a period of inactivity may
+    /// fail with `ModelNotReadyException` while the model is being loaded.
+    pub fn imported_completion_model(&self, model: &ImportedModel) -> CompletionModel {
+        CompletionModel::new(self.clone(), &model.arn)
+    }
+}
+
+#[derive(Debug)]
+pub enum ModelImportError {
+    /// The configuration couldn't be converted to a request.
+    InvalidConfig(Box<dyn std::error::Error + Send + Sync>),
+    Request(BedrockError),
+    /// The job failed.
+    JobFailed {
+        job_arn: String,
+        message: String,
+    },
+    /// The job didn't finish within the timeout.
+    Timeout {
+        job_arn: String,
+    },
+}
+
+impl fmt::Display for ModelImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidConfig(error) => write!(f, "Invalid model import job: {error}"),
+            Self::Request(error) => write!(f, "Model import request failed: {error}"),
+            Self::JobFailed { job_arn, message } => {
+                write!(f, "Model import job {job_arn} failed: {message}")
+            }
+            Self::Timeout { job_arn } => {
+                write!(f, "Timed out waiting for model import job {job_arn}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidConfig(error) => Some(error.as_ref()),
+            Self::Request(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<BedrockError> for ModelImportError {
+    fn from(error: BedrockError) -> Self {
+        Self::Request(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imported_completion_model() {
+        let client = Client::from(aws_sdk_bedrockruntime::Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version_latest()
+                .build(),
+        ));
+        let model = ImportedModel {
+            arn: "arn:aws:bedrock:us-east-1:123456789012:imported-model/abc123".into(),
+            name: "support-llama".into(),
+            architecture: Some("llama3".into()),
+            instruct_supported: Some(true),
+            creation_time: None,
+        };
+
+        assert_eq!(client.imported_completion_model(&model).model, model.arn);
+        assert_eq!(
+            ModelImportJobStatus::from("Completed"),
+            ModelImportJobStatus::Completed
+        );
+    }
+}
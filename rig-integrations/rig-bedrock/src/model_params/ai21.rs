@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request fields of AI21 Jamba 1.5 models. Temperature, top p, max tokens and stop sequences are
+/// set through the completion request instead.
+/// <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-jamba.html>
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct JambaParams {
+    /// Penalize tokens by how often they already appeared, from 0 to 2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    /// Penalize tokens that already appeared, however often, from 0 to 5.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+}
+
+impl JambaParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_frequency_penalty(mut self, frequency_penalty: f64) -> Self {
+        self.frequency_penalty = Some(frequency_penalty);
+        self
+    }
+
+    pub fn with_presence_penalty(mut self, presence_penalty: f64) -> Self {
+        self.presence_penalty = Some(presence_penalty);
+        self
+    }
+}
+
+impl From<JambaParams> for Value {
+    fn from(params: JambaParams) -> Self {
+        serde_json::to_value(params).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{AI21_JAMBA_1_5_MINI, CompletionModel};
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::CompletionModel as _;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_jamba_params_sent_as_additional_fields() {
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello!"));
+        let model = CompletionModel::new(mock.client(), AI21_JAMBA_1_5_MINI);
+
+        model
+            .completion_request("Hi")
+            .temperature(0.25)
+            .additional_params(JambaParams::new().with_frequency_penalty(0.5).into())
+            .send()
+            .await
+            .unwrap();
+
+        let body = mock.requests()[0].json().unwrap();
+        assert_eq!(
+            body["additionalModelRequestFields"],
+            json!({ "frequency_penalty": 0.5 })
+        );
+        assert_eq!(body["inferenceConfig"]["temperature"], 0.25);
+    }
+}
@@ -0,0 +1,45 @@
+use serde_json::{Value, json};
+
+/// Request fields of Amazon Nova models. Temperature, top p, max tokens and stop sequences are set
+/// through the completion request instead.
+/// <https://docs.aws.amazon.com/nova/latest/userguide/complete-request-schema.html>
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NovaParams {
+    /// Sample only from the `top_k` most likely tokens.
+    pub top_k: Option<u32>,
+}
+
+impl NovaParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+}
+
+/// Nova takes its fields in an `inferenceConfig` object, alongside Converse's own.
+impl From<NovaParams> for Value {
+    fn from(params: NovaParams) -> Self {
+        let mut inference_config = json!({});
+        if let Some(top_k) = params.top_k {
+            inference_config["topK"] = json!(top_k);
+        }
+        json!({ "inferenceConfig": inference_config })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nova_params() {
+        assert_eq!(
+            Value::from(NovaParams::new().with_top_k(20)),
+            json!({ "inferenceConfig": { "topK": 20 } })
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use rig::completion::CompletionRequest;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request fields of Anthropic Claude models. Temperature, top p, max tokens and stop sequences
+/// are set through the completion request instead.
+/// <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-anthropic-claude-messages.html>
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnthropicParams {
+    /// Sample only from the `top_k` most likely tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<AnthropicThinking>,
+    /// Beta features to enable, e.g. `interleaved-thinking-2025-05-14`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anthropic_beta: Vec<String>,
+}
+
+impl AnthropicParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Let the model think before answering, using up to `budget_tokens` of the request's max
+    /// tokens, at least 1024. Thinking requires a temperature of 1 and no top k, so completions
+    /// drop them, see [`AnthropicThinking`].
+    pub fn with_thinking(mut self, budget_tokens: u32) -> Self {
+        self.thinking = Some(AnthropicThinking::Enabled { budget_tokens });
+        self
+    }
+
+    pub fn with_thinking_disabled(mut self) -> Self {
+        self.thinking = Some(AnthropicThinking::Disabled);
+        self
+    }
+
+    pub fn with_beta(mut self, feature: impl Into<String>) -> Self {
+        self.anthropic_beta.push(feature.into());
+        self
+    }
+}
+
+impl From<AnthropicParams> for Value {
+    fn from(params: AnthropicParams) -> Self {
+        serde_json::to_value(params).unwrap_or_default()
+    }
+}
+
+/// Extended thinking configuration.
+///
+/// Completions of requests enabling thinking relax the parameters it conflicts with, instead of
+/// failing with a `ValidationException`: a temperature other than 1 and top k are dropped, and max
+/// tokens not above the budget are raised by the budget, so the answer keeps them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicThinking {
+    Enabled { budget_tokens: u32 },
+    Disabled,
+}
+
+/// Relax the parameters of `request` conflicting with the thinking enabled by its additional
+/// params, if any, see [`AnthropicThinking`].
+pub(crate) fn relax_for_thinking(request: &mut CompletionRequest) {
+    let Some(params) = request.additional_params.as_mut() else {
+        return;
+    };
+    let Some(Ok(AnthropicThinking::Enabled { budget_tokens })) = params
+        .get("thinking")
+        .map(|thinking| serde_json::from_value(thinking.clone()))
+    else {
+        return;
+    };
+
+    if let Some(temperature) = request.temperature
+        && temperature != 1.0
+    {
+        tracing::debug!(
+            target: "rig::bedrock",
+            "Dropping temperature {temperature}, extended thinking requires a temperature of 1"
+        );
+        request.temperature = None;
+    }
+    if let Some(top_k) = params
+        .as_object_mut()
+        .and_then(|params| params.remove("top_k"))
+    {
+        tracing::debug!(
+            target: "rig::bedrock",
+            "Dropping top k {top_k}, extended thinking doesn't support it"
+        );
+    }
+    if let Some(max_tokens) = request.max_tokens
+        && max_tokens <= budget_tokens as u64
+    {
+        tracing::debug!(
+            target: "rig::bedrock",
+            "Raising max tokens {max_tokens} above the thinking budget of {budget_tokens} tokens"
+        );
+        request.max_tokens = Some(max_tokens + budget_tokens as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{ANTHROPIC_CLAUDE_3_7_SONNET, CompletionModel};
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::CompletionModel as _;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_anthropic_params_sent_as_additional_fields() {
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello!"));
+        let model = CompletionModel::new(mock.client(), ANTHROPIC_CLAUDE_3_7_SONNET);
+
+        model
+            .completion_request("Hi")
+            .max_tokens(4096)
+            .additional_params(
+                AnthropicParams::new()
+                    .with_thinking(2048)
+                    .with_beta("interleaved-thinking-2025-05-14")
+                    .into(),
+            )
+            .send()
+            .await
+            .unwrap();
+
+        let body = mock.requests()[0].json().unwrap();
+        assert_eq!(
+            body["additionalModelRequestFields"],
+            json!({
+                "thinking": { "type": "enabled", "budget_tokens": 2048 },
+                "anthropic_beta": ["interleaved-thinking-2025-05-14"],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_thinking_relaxes_conflicting_params() {
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello!"));
+        let model = CompletionModel::new(mock.client(), ANTHROPIC_CLAUDE_3_7_SONNET);
+
+        model
+            .completion_request("Hi")
+            .temperature(0.2)
+            .max_tokens(1024)
+            .additional_params(
+                AnthropicParams::new()
+                    .with_top_k(40)
+                    .with_thinking(2048)
+                    .into(),
+            )
+            .send()
+            .await
+            .unwrap();
+
+        let body = mock.requests()[0].json().unwrap();
+        assert_eq!(body["inferenceConfig"], json!({ "maxTokens": 3072 }));
+        assert_eq!(
+            body["additionalModelRequestFields"],
+            json!({ "thinking": { "type": "enabled", "budget_tokens": 2048 } })
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request fields of Cohere Command R and R+. Temperature, top p, max tokens and stop sequences
+/// are set through the completion request instead. Command R models use tools, but can't be
+/// forced to call one, so requests shouldn't set a required or specific tool choice.
+/// <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-cohere-command-r-plus.html>
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandRParams {
+    /// Sample only from the `top_k` most likely tokens, from 0 (disabled) to 500.
+    #[serde(rename = "k", skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Penalize tokens by how often they already appeared, from 0 to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    /// Penalize tokens that already appeared, however often, from 0 to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    /// Sample deterministically, for the same seed and parameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// How the chat history is shortened when it exceeds the context window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_truncation: Option<PromptTruncation>,
+    /// Only generate search queries for the message, instead of answering it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_queries_only: Option<bool>,
+    /// Send the message to the model as is, without Cohere's prompt template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_prompting: Option<bool>,
+}
+
+impl CommandRParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    pub fn with_frequency_penalty(mut self, frequency_penalty: f64) -> Self {
+        self.frequency_penalty = Some(frequency_penalty);
+        self
+    }
+
+    pub fn with_presence_penalty(mut self, presence_penalty: f64) -> Self {
+        self.presence_penalty = Some(presence_penalty);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_prompt_truncation(mut self, prompt_truncation: PromptTruncation) -> Self {
+        self.prompt_truncation = Some(prompt_truncation);
+        self
+    }
+
+    pub fn with_search_queries_only(mut self, search_queries_only: bool) -> Self {
+        self.search_queries_only = Some(search_queries_only);
+        self
+    }
+
+    pub fn with_raw_prompting(mut self, raw_prompting: bool) -> Self {
+        self.raw_prompting = Some(raw_prompting);
+        self
+    }
+}
+
+impl From<CommandRParams> for Value {
+    fn from(params: CommandRParams) -> Self {
+        serde_json::to_value(params).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PromptTruncation {
+    /// Fail when the chat history doesn't fit.
+    Off,
+    /// Drop the oldest messages of the chat history, keeping the order of the rest.
+    AutoPreserveOrder,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{COHERE_COMMAND_R_PLUS, CompletionModel};
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::{CompletionModel as _, ToolDefinition};
+    use rig::message::AssistantContent;
+    use serde_json::json;
+
+    #[test]
+    fn test_command_r_params() {
+        let params: Value = CommandRParams::new()
+            .with_top_k(40)
+            .with_seed(7)
+            .with_prompt_truncation(PromptTruncation::AutoPreserveOrder)
+            .into();
+
+        assert_eq!(
+            params,
+            json!({ "k": 40, "seed": 7, "prompt_truncation": "AUTO_PRESERVE_ORDER" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_r_tool_use() {
+        let mock = MockBedrock::new().with_response(MockResponse::tool_use(
+            "tooluse_1",
+            "get_weather",
+            json!({ "city": "Paris" }),
+        ));
+        let model = CompletionModel::new(mock.client(), COHERE_COMMAND_R_PLUS);
+
+        let response = model
+            .completion_request("What's the weather in Paris?")
+            .tool(ToolDefinition {
+                name: "get_weather".into(),
+                description: "Get the weather of a city".into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                }),
+            })
+            .additional_params(CommandRParams::new().with_seed(7).into())
+            .send()
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            response.choice.first(),
+            AssistantContent::ToolCall(tool_call)
+                if tool_call.function.name == "get_weather"
+                    && tool_call.function.arguments == json!({ "city": "Paris" })
+        ));
+
+        let body = mock.requests()[0].json().unwrap();
+        assert_eq!(body["additionalModelRequestFields"], json!({ "seed": 7 }));
+        assert_eq!(
+            body["toolConfig"]["tools"][0]["toolSpec"]["name"],
+            "get_weather"
+        );
+        assert!(body["toolConfig"].get("toolChoice").is_none());
+    }
+}
@@ -0,0 +1,27 @@
+//! Typed model specific request fields, for the parameters of a model family that Converse has no
+//! field for. They convert to the request's `additional_params`, which are sent as Converse's
+//! `additionalModelRequestFields`.
+//!
+//! ```rust,ignore
+//! let agent = client
+//!     .agent(ANTHROPIC_CLAUDE_3_7_SONNET)
+//!     .max_tokens(8192)
+//!     .additional_params(AnthropicParams::new().with_thinking(4096).into())
+//!     .build();
+//!
+//! let agent = client
+//!     .agent(AMAZON_NOVA_PRO)
+//!     .additional_params(NovaParams::new().with_top_k(20).into())
+//!     .build();
+//! ```
+
+mod ai21;
+mod amazon;
+mod anthropic;
+mod cohere;
+
+pub use ai21::JambaParams;
+pub use amazon::NovaParams;
+pub(crate) use anthropic::relax_for_thinking;
+pub use anthropic::{AnthropicParams, AnthropicThinking};
+pub use cohere::{CommandRParams, PromptTruncation};
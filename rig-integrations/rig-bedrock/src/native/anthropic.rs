@@ -0,0 +1,588 @@
+//! Anthropic Messages API payloads for Bedrock.
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-anthropic-claude-messages.html>
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use rig::completion::{CompletionError, CompletionRequest, Message};
+use rig::message::{
+    AssistantContent, DocumentMediaType, DocumentSourceKind, Image, MimeType, Reasoning,
+    ToolChoice, ToolResultContent, UserContent,
+};
+use rig::streaming::{RawStreamingChoice, RawStreamingToolCall};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{ChunkParser, InvocationMetrics, NativeResponse, merge_additional_params, stop_reason};
+use crate::streaming::{BedrockStreamingResponse, BedrockUsage};
+use crate::types::converse_output::StopReason;
+
+const ANTHROPIC_VERSION: &str = "bedrock-2023-05-31";
+/// Anthropic requires `max_tokens`, use this when the request doesn't set one.
+const DEFAULT_MAX_TOKENS: u64 = 4096;
+
+pub(super) fn request_body(
+    request: &CompletionRequest,
+) -> Result<serde_json::Value, CompletionError> {
+    let messages = request
+        .normalized_documents()
+        .into_iter()
+        .chain(request.chat_history.iter().cloned())
+        .map(message)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut body = json!({
+        "anthropic_version": ANTHROPIC_VERSION,
+        "max_tokens": request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        "messages": messages,
+    });
+
+    if let Some(preamble) = &request.preamble {
+        body["system"] = json!(preamble);
+    }
+
+    if let Some(temperature) = request.temperature {
+        body["temperature"] = json!(temperature);
+    }
+
+    if !request.tools.is_empty() {
+        body["tools"] = request
+            .tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": tool.parameters,
+                })
+            })
+            .collect();
+
+        if let Some(tool_choice) = &request.tool_choice {
+            body["tool_choice"] = match tool_choice {
+                ToolChoice::Auto => json!({ "type": "auto" }),
+                ToolChoice::None => json!({ "type": "none" }),
+                ToolChoice::Required => json!({ "type": "any" }),
+                ToolChoice::Specific { function_names } => match function_names.first() {
+                    Some(name) => json!({ "type": "tool", "name": name }),
+                    None => json!({ "type": "any" }),
+                },
+            };
+        }
+    }
+
+    merge_additional_params(&mut body, request.additional_params.as_ref());
+
+    Ok(body)
+}
+
+fn message(message: Message) -> Result<serde_json::Value, CompletionError> {
+    match message {
+        Message::User { content } => Ok(json!({
+            "role": "user",
+            "content": content
+                .into_iter()
+                .map(user_content)
+                .collect::<Result<Vec<_>, _>>()?,
+        })),
+        Message::Assistant { content, .. } => Ok(json!({
+            "role": "assistant",
+            "content": content
+                .into_iter()
+                .filter_map(|content| assistant_content(content).transpose())
+                .collect::<Result<Vec<_>, _>>()?,
+        })),
+    }
+}
+
+fn user_content(content: UserContent) -> Result<serde_json::Value, CompletionError> {
+    match content {
+        UserContent::Text(text) => Ok(json!({ "type": "text", "text": text.text })),
+        UserContent::Image(image) => image_block(image),
+        UserContent::Document(document) => match (document.data, document.media_type) {
+            (DocumentSourceKind::String(text), _) => Ok(json!({ "type": "text", "text": text })),
+            (DocumentSourceKind::Base64(data), Some(DocumentMediaType::PDF)) => Ok(json!({
+                "type": "document",
+                "source": { "type": "base64", "media_type": "application/pdf", "data": data },
+            })),
+            (DocumentSourceKind::Raw(bytes), Some(DocumentMediaType::PDF)) => Ok(json!({
+                "type": "document",
+                "source": {
+                    "type": "base64",
+                    "media_type": "application/pdf",
+                    "data": BASE64_STANDARD.encode(bytes),
+                },
+            })),
+            _ => Err(CompletionError::RequestError(
+                "Only text and PDF documents are supported by the native Anthropic format".into(),
+            )),
+        },
+        UserContent::ToolResult(result) => Ok(json!({
+            "type": "tool_result",
+            "tool_use_id": result.id,
+            "content": result
+                .content
+                .into_iter()
+                .map(|content| match content {
+                    ToolResultContent::Text(text) => Ok(json!({ "type": "text", "text": text.text })),
+                    ToolResultContent::Image(image) => image_block(image),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        })),
+        _ => Err(CompletionError::RequestError(
+            "Audio and video content are not supported by the native Anthropic format".into(),
+        )),
+    }
+}
+
+fn assistant_content(
+    content: AssistantContent,
+) -> Result<Option<serde_json::Value>, CompletionError> {
+    match content {
+        AssistantContent::Text(text) => Ok(Some(json!({ "type": "text", "text": text.text }))),
+        AssistantContent::ToolCall(tool_call) => Ok(Some(json!({
+            "type": "tool_use",
+            "id": tool_call.id,
+            "name": tool_call.function.name,
+            "input": tool_call.function.arguments,
+        }))),
+        // Thinking blocks can only be sent back along with their signature
+        AssistantContent::Reasoning(reasoning) => Ok(reasoning.signature.map(|signature| {
+            json!({
+                "type": "thinking",
+                "thinking": reasoning.reasoning.join(""),
+                "signature": signature,
+            })
+        })),
+        AssistantContent::Image(_) => Err(CompletionError::RequestError(
+            "Assistant images are not supported by the native Anthropic format".into(),
+        )),
+    }
+}
+
+fn image_block(image: Image) -> Result<serde_json::Value, CompletionError> {
+    let media_type = image
+        .media_type
+        .ok_or_else(|| CompletionError::RequestError("Image media type is required".into()))?
+        .to_mime_type();
+
+    let data = match image.data {
+        DocumentSourceKind::Base64(data) => data,
+        DocumentSourceKind::Raw(bytes) => BASE64_STANDARD.encode(bytes),
+        _ => {
+            return Err(CompletionError::RequestError(
+                "Only base64 and raw images are supported by the native Anthropic format".into(),
+            ));
+        }
+    };
+
+    Ok(json!({
+        "type": "image",
+        "source": { "type": "base64", "media_type": media_type, "data": data },
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: MessageStart,
+    },
+    ContentBlockStart {
+        content_block: ContentBlock,
+    },
+    ContentBlockDelta {
+        delta: ContentDelta,
+    },
+    ContentBlockStop,
+    MessageDelta {
+        delta: MessageDelta,
+        usage: Option<OutputUsage>,
+    },
+    MessageStop {
+        #[serde(rename = "amazon-bedrock-invocationMetrics")]
+        invocation_metrics: Option<InvocationMetrics>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct MessageStart {
+    usage: Option<InputUsage>,
+}
+
+#[derive(Deserialize)]
+struct InputUsage {
+    input_tokens: i32,
+    cache_read_input_tokens: Option<i32>,
+    cache_creation_input_tokens: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct OutputUsage {
+    output_tokens: i32,
+}
+
+#[derive(Deserialize)]
+struct MessageDelta {
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+    },
+    Thinking {
+        thinking: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    SignatureDelta {
+        signature: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+struct ToolUse {
+    id: String,
+    name: String,
+    input_json: String,
+}
+
+#[derive(Default)]
+struct Thinking {
+    content: String,
+    signature: Option<String>,
+}
+
+#[derive(Default)]
+pub(super) struct StreamParser {
+    input_tokens: i32,
+    output_tokens: i32,
+    cache_read_input_tokens: Option<i32>,
+    cache_write_input_tokens: Option<i32>,
+    stop_reason: Option<StopReason>,
+    tool_use: Option<ToolUse>,
+    thinking: Option<Thinking>,
+}
+
+impl ChunkParser for StreamParser {
+    fn parse(
+        &mut self,
+        chunk: &[u8],
+    ) -> Result<Vec<RawStreamingChoice<BedrockStreamingResponse>>, CompletionError> {
+        let mut choices = Vec::new();
+
+        match serde_json::from_slice::<StreamEvent>(chunk)? {
+            StreamEvent::MessageStart { message } => {
+                if let Some(usage) = message.usage {
+                    self.input_tokens = usage.input_tokens;
+                    self.cache_read_input_tokens = usage.cache_read_input_tokens;
+                    self.cache_write_input_tokens = usage.cache_creation_input_tokens;
+                }
+            }
+            StreamEvent::ContentBlockStart { content_block } => match content_block {
+                ContentBlock::Text { text } if !text.is_empty() => {
+                    choices.push(RawStreamingChoice::Message(text));
+                }
+                ContentBlock::ToolUse { id, name } => {
+                    self.tool_use = Some(ToolUse {
+                        id,
+                        name,
+                        input_json: String::new(),
+                    });
+                }
+                ContentBlock::Thinking { thinking } => {
+                    self.thinking = Some(Thinking {
+                        content: thinking,
+                        signature: None,
+                    });
+                }
+                _ => {}
+            },
+            StreamEvent::ContentBlockDelta { delta } => match delta {
+                ContentDelta::TextDelta { text } => choices.push(RawStreamingChoice::Message(text)),
+                ContentDelta::InputJsonDelta { partial_json } => {
+                    if let Some(tool_use) = self.tool_use.as_mut() {
+                        tool_use.input_json.push_str(&partial_json);
+                        choices.push(RawStreamingChoice::ToolCallDelta {
+                            id: tool_use.id.clone(),
+                            delta: partial_json,
+                        });
+                    }
+                }
+                ContentDelta::ThinkingDelta { thinking } => {
+                    self.thinking
+                        .get_or_insert_with(Thinking::default)
+                        .content
+                        .push_str(&thinking);
+                    choices.push(RawStreamingChoice::ReasoningDelta {
+                        id: None,
+                        reasoning: thinking,
+                    });
+                }
+                ContentDelta::SignatureDelta { signature } => {
+                    self.thinking
+                        .get_or_insert_with(Thinking::default)
+                        .signature = Some(signature);
+                }
+                ContentDelta::Other => {}
+            },
+            StreamEvent::ContentBlockStop => {
+                if let Some(tool_use) = self.tool_use.take() {
+                    // Tools without parameters stream no input at all
+                    let arguments = if tool_use.input_json.is_empty() {
+                        json!({})
+                    } else {
+                        serde_json::from_str(&tool_use.input_json)?
+                    };
+                    choices.push(RawStreamingChoice::ToolCall(RawStreamingToolCall::new(
+                        tool_use.id,
+                        tool_use.name,
+                        arguments,
+                    )));
+                } else if let Some(thinking) = self.thinking.take()
+                    && !thinking.content.is_empty()
+                {
+                    choices.push(RawStreamingChoice::Reasoning {
+                        id: None,
+                        reasoning: thinking.content,
+                        signature: thinking.signature,
+                    });
+                }
+            }
+            StreamEvent::MessageDelta { delta, usage } => {
+                self.stop_reason = delta.stop_reason.as_deref().map(stop_reason);
+                if let Some(usage) = usage {
+                    self.output_tokens = usage.output_tokens;
+                }
+            }
+            StreamEvent::MessageStop { invocation_metrics } => {
+                let stop_reason = self.stop_reason.take();
+                let mut response = match invocation_metrics {
+                    Some(metrics) => metrics.into_response(stop_reason),
+                    None => BedrockStreamingResponse {
+                        usage: Some(BedrockUsage {
+                            input_tokens: self.input_tokens,
+                            output_tokens: self.output_tokens,
+                            total_tokens: self.input_tokens + self.output_tokens,
+                            ..Default::default()
+                        }),
+                        stop_reason,
+                        ..Default::default()
+                    },
+                };
+                // The invocation metrics don't count the cached tokens
+                if let Some(usage) = &mut response.usage {
+                    usage.cache_read_input_tokens = self.cache_read_input_tokens;
+                    usage.cache_write_input_tokens = self.cache_write_input_tokens;
+                }
+                choices.push(RawStreamingChoice::FinalResponse(response));
+            }
+            StreamEvent::Other => {}
+        }
+
+        Ok(choices)
+    }
+}
+
+/// A complete Messages API response.
+#[derive(Deserialize)]
+struct Response {
+    content: Vec<ResponseBlock>,
+    stop_reason: Option<String>,
+    usage: Option<ResponseUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ResponseBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    Thinking {
+        thinking: String,
+        signature: Option<String>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct ResponseUsage {
+    input_tokens: i32,
+    output_tokens: i32,
+    cache_read_input_tokens: Option<i32>,
+    cache_creation_input_tokens: Option<i32>,
+}
+
+pub(super) fn parse_response(body: serde_json::Value) -> Result<NativeResponse, serde_json::Error> {
+    let response: Response = serde_json::from_value(body)?;
+
+    Ok(NativeResponse {
+        content: response
+            .content
+            .into_iter()
+            .filter_map(|block| match block {
+                ResponseBlock::Text { text } => Some(AssistantContent::text(text)),
+                ResponseBlock::ToolUse { id, name, input } => {
+                    Some(AssistantContent::tool_call(id, name, input))
+                }
+                ResponseBlock::Thinking {
+                    thinking,
+                    signature,
+                } => Some(AssistantContent::Reasoning(
+                    Reasoning::new(&thinking).with_signature(signature),
+                )),
+                ResponseBlock::Other => None,
+            })
+            .collect(),
+        usage: response.usage.map(|usage| BedrockUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.input_tokens + usage.output_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+            cache_write_input_tokens: usage.cache_creation_input_tokens,
+        }),
+        stop_reason: response.stop_reason.as_deref().map(stop_reason),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+    use rig::completion::ToolDefinition;
+
+    fn parse_all(
+        chunks: &[serde_json::Value],
+    ) -> Vec<RawStreamingChoice<BedrockStreamingResponse>> {
+        let mut parser = StreamParser::default();
+        chunks
+            .iter()
+            .flat_map(|chunk| parser.parse(&serde_json::to_vec(chunk).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_request_body() {
+        let request = completion_request("What's the weather?")
+            .preamble("Be brief".into())
+            .tool(ToolDefinition {
+                name: "get_weather".into(),
+                description: "Get the weather".into(),
+                parameters: json!({ "type": "object", "properties": {} }),
+            })
+            .temperature(0.5)
+            .tool_choice(ToolChoice::Required)
+            .additional_params(json!({ "top_k": 10 }))
+            .build();
+
+        let body = request_body(&request).unwrap();
+
+        assert_eq!(body["anthropic_version"], ANTHROPIC_VERSION);
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(body["system"], "Be brief");
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["top_k"], 10);
+        assert_eq!(body["tool_choice"], json!({ "type": "any" }));
+        assert_eq!(body["tools"][0]["name"], "get_weather");
+        assert_eq!(
+            body["messages"],
+            json!([{ "role": "user", "content": [{ "type": "text", "text": "What's the weather?" }] }])
+        );
+    }
+
+    #[test]
+    fn test_parse_text_stream() {
+        let choices = parse_all(&[
+            json!({ "type": "message_start", "message": { "usage": { "input_tokens": 12, "output_tokens": 1 } } }),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Hello" } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": " world" } }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({ "type": "message_delta", "delta": { "stop_reason": "end_turn" }, "usage": { "output_tokens": 3 } }),
+            json!({
+                "type": "message_stop",
+                "amazon-bedrock-invocationMetrics": {
+                    "inputTokenCount": 12,
+                    "outputTokenCount": 3,
+                    "invocationLatency": 321,
+                    "firstByteLatency": 120
+                }
+            }),
+        ]);
+
+        assert_eq!(choices.len(), 3);
+        assert!(matches!(&choices[0], RawStreamingChoice::Message(text) if text == "Hello"));
+        assert!(matches!(&choices[1], RawStreamingChoice::Message(text) if text == " world"));
+
+        let RawStreamingChoice::FinalResponse(response) = &choices[2] else {
+            panic!("expected a final response");
+        };
+        let usage = response.usage.as_ref().unwrap();
+        assert_eq!(usage.input_tokens, 12);
+        assert_eq!(usage.output_tokens, 3);
+        assert_eq!(usage.total_tokens, 15);
+        assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+        assert_eq!(response.metrics.as_ref().unwrap().latency_ms, 321);
+    }
+
+    #[test]
+    fn test_parse_tool_use_and_thinking_stream() {
+        let choices = parse_all(&[
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "thinking", "thinking": "" } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "thinking_delta", "thinking": "Need weather" } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "signature_delta", "signature": "sig" } }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({ "type": "content_block_start", "index": 1, "content_block": { "type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {} } }),
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": "{\"city\":" } }),
+            json!({ "type": "content_block_delta", "index": 1, "delta": { "type": "input_json_delta", "partial_json": "\"Paris\"}" } }),
+            json!({ "type": "content_block_stop", "index": 1 }),
+            json!({ "type": "message_delta", "delta": { "stop_reason": "tool_use" }, "usage": { "output_tokens": 20 } }),
+            json!({ "type": "message_stop" }),
+        ]);
+
+        assert!(matches!(
+            &choices[1],
+            RawStreamingChoice::Reasoning { reasoning, signature: Some(signature), .. }
+                if reasoning == "Need weather" && signature == "sig"
+        ));
+        assert!(matches!(
+            &choices[4],
+            RawStreamingChoice::ToolCall(tool_call)
+                if tool_call.id == "toolu_1" && tool_call.arguments == json!({ "city": "Paris" })
+        ));
+        assert!(matches!(
+            &choices[5],
+            RawStreamingChoice::FinalResponse(response)
+                if response.stop_reason == Some(StopReason::ToolUse)
+                    && response.usage.as_ref().unwrap().output_tokens == 20
+        ));
+    }
+}
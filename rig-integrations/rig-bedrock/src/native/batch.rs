@@ -0,0 +1,181 @@
+//! Completions with Bedrock batch inference jobs, whose records use the native request format.
+
+use rig::OneOrMany;
+use rig::completion::{
+    CompletionError, CompletionRequest, CompletionResponse, GetTokenUsage, Usage,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{parse_response, request_body};
+use crate::batch::{
+    BatchError, BatchInputRecord, BatchOutputRecord, from_jsonl, outputs_in_order, record_id,
+};
+use crate::completion::CompletionModel;
+use crate::streaming::BedrockUsage;
+use crate::types::converse_output::StopReason;
+
+/// The output of a batch completion record.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchCompletionOutput {
+    /// `None` for families whose native response doesn't report it, e.g. Mistral.
+    pub usage: Option<BedrockUsage>,
+    pub stop_reason: Option<StopReason>,
+    /// The `modelOutput` of the record, in the native response format of the model family.
+    pub model_output: serde_json::Value,
+}
+
+impl GetTokenUsage for BatchCompletionOutput {
+    fn token_usage(&self) -> Option<Usage> {
+        self.usage.as_ref().map(|usage| Usage {
+            input_tokens: usage.input_tokens as u64,
+            output_tokens: usage.output_tokens as u64,
+            total_tokens: usage.total_tokens as u64,
+        })
+    }
+}
+
+impl CompletionModel {
+    /// The batch inference input records of `requests`, one per request, in the native request
+    /// format of the model family. Amazon Titan Text, Anthropic, Meta Llama and Mistral models
+    /// are supported.
+    pub fn batch_records(
+        &self,
+        requests: &[CompletionRequest],
+    ) -> Result<Vec<BatchInputRecord<serde_json::Value>>, CompletionError> {
+        let family = self.native_family()?;
+        requests
+            .iter()
+            .enumerate()
+            .map(|(index, request)| {
+                Ok(BatchInputRecord {
+                    record_id: record_id(index),
+                    model_input: request_body(&self.model, family, request)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Parse the output file of a batch job created from `expected` requests with
+    /// [`CompletionModel::batch_records`], in the order of the requests.
+    pub fn parse_batch_output(
+        &self,
+        output: &str,
+        expected: usize,
+    ) -> Result<Vec<CompletionResponse<BatchCompletionOutput>>, BatchError> {
+        let family = self
+            .native_family()
+            .map_err(|e| BatchError::Bedrock(e.to_string()))?;
+        let records: Vec<BatchOutputRecord<serde_json::Value>> = from_jsonl(output)?;
+
+        outputs_in_order(records, expected)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, model_output)| {
+                let response = parse_response(family, model_output.clone())?;
+                let choice =
+                    OneOrMany::many(response.content).map_err(|_| BatchError::RecordFailed {
+                        record_id: record_id(index),
+                        message: "Empty model output".into(),
+                    })?;
+                let raw_response = BatchCompletionOutput {
+                    usage: response.usage,
+                    stop_reason: response.stop_reason,
+                    model_output,
+                };
+
+                Ok(CompletionResponse {
+                    choice,
+                    usage: raw_response.token_usage().unwrap_or_default(),
+                    raw_response,
+                })
+            })
+            .collect()
+    }
+
+    /// Complete `requests` with a Bedrock batch inference job instead of on-demand calls, e.g.
+    /// for offline evaluation or bulk generation.
+    ///
+    /// The requests are written as JSONL to [`BatchConfig::input_uri`](crate::batch::BatchConfig),
+    /// and the call returns once the job completed and its output was parsed. Batch jobs require
+    /// at least 100 records and may take hours.
+    #[cfg(feature = "batch")]
+    pub async fn completion_batch(
+        &self,
+        requests: Vec<CompletionRequest>,
+        config: &crate::batch::BatchConfig,
+    ) -> Result<Vec<CompletionResponse<BatchCompletionOutput>>, CompletionError> {
+        let jsonl = crate::batch::to_jsonl(&self.batch_records(&requests)?)?;
+        let output = crate::batch::run_job(&self.client, &self.model, jsonl, config)
+            .await
+            .map_err(|e| CompletionError::ProviderError(e.to_string()))?;
+
+        self.parse_batch_output(&output, requests.len())
+            .map_err(|e| CompletionError::ResponseError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Client;
+    use crate::testing::completion_request;
+    use rig::message::AssistantContent;
+
+    fn request(prompt: &str) -> CompletionRequest {
+        completion_request(prompt).max_tokens(256).build()
+    }
+
+    fn model(model: &str) -> CompletionModel {
+        let client = Client::from(aws_sdk_bedrockruntime::Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version_latest()
+                .build(),
+        ));
+        CompletionModel::new(client, model)
+    }
+
+    #[test]
+    fn test_batch_records() {
+        let records = model("anthropic.claude-3-haiku-20240307-v1:0")
+            .batch_records(&[request("Hello"), request("World")])
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].record_id, "00000000001");
+        assert_eq!(records[1].model_input["max_tokens"], 256);
+        assert_eq!(
+            records[1].model_input["messages"][0]["content"][0]["text"],
+            "World"
+        );
+
+        assert!(
+            model("amazon.nova-lite-v1:0")
+                .batch_records(&[request("Hello")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_batch_output() {
+        let output = r#"{"recordId":"00000000001","modelInput":{},"modelOutput":{"generation":"Second","prompt_token_count":5,"generation_token_count":1,"stop_reason":"stop"}}
+{"recordId":"00000000000","modelInput":{},"modelOutput":{"generation":"First","prompt_token_count":4,"generation_token_count":2,"stop_reason":"length"}}"#;
+
+        let responses = model("meta.llama3-8b-instruct-v1:0")
+            .parse_batch_output(output, 2)
+            .unwrap();
+
+        assert!(matches!(
+            responses[0].choice.first(),
+            AssistantContent::Text(text) if text.text == "First"
+        ));
+        assert_eq!(responses[0].usage.total_tokens, 6);
+        assert!(matches!(
+            responses[0].raw_response.stop_reason,
+            Some(StopReason::MaxTokens)
+        ));
+        assert!(matches!(
+            responses[1].choice.first(),
+            AssistantContent::Text(text) if text.text == "Second"
+        ));
+    }
+}
@@ -0,0 +1,267 @@
+//! Meta Llama payloads for Bedrock, whose chat history is rendered into a single prompt with the
+//! chat template of the model generation.
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-meta.html>
+
+use rig::completion::{CompletionError, CompletionRequest};
+use rig::message::AssistantContent;
+use rig::streaming::RawStreamingChoice;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    ChunkParser, InvocationMetrics, NativeResponse, Role, Turn, base_model_id,
+    merge_additional_params, stop_reason, text_turns,
+};
+use crate::streaming::{BedrockStreamingResponse, BedrockUsage};
+use crate::types::converse_output::StopReason;
+
+/// The chat template a Llama generation was trained with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum PromptTemplate {
+    /// `[INST]` blocks with a `<<SYS>>` system prompt, used by Llama 2.
+    Llama2,
+    /// Header tokens per turn, used by Llama 3 and later.
+    Llama3,
+}
+
+impl PromptTemplate {
+    pub(super) fn for_model(model: &str) -> Self {
+        if base_model_id(model).starts_with("meta.llama2") {
+            Self::Llama2
+        } else {
+            Self::Llama3
+        }
+    }
+
+    fn render(self, preamble: Option<&str>, turns: &[Turn]) -> String {
+        match self {
+            Self::Llama2 => llama2_prompt(preamble, turns),
+            Self::Llama3 => llama3_prompt(preamble, turns),
+        }
+    }
+}
+
+pub(super) fn request_body(
+    template: PromptTemplate,
+    request: &CompletionRequest,
+) -> Result<serde_json::Value, CompletionError> {
+    let turns = text_turns(request, "Llama")?;
+    let mut body = json!({ "prompt": template.render(request.preamble.as_deref(), &turns) });
+
+    if let Some(max_tokens) = request.max_tokens {
+        body["max_gen_len"] = json!(max_tokens);
+    }
+
+    if let Some(temperature) = request.temperature {
+        body["temperature"] = json!(temperature);
+    }
+
+    merge_additional_params(&mut body, request.additional_params.as_ref());
+
+    Ok(body)
+}
+
+fn llama3_prompt(preamble: Option<&str>, turns: &[Turn]) -> String {
+    let mut prompt = String::from("<|begin_of_text|>");
+
+    if let Some(preamble) = preamble {
+        push_turn(&mut prompt, "system", preamble);
+    }
+
+    for turn in turns {
+        let role = match turn.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        push_turn(&mut prompt, role, &turn.text);
+    }
+
+    prompt.push_str("<|start_header_id|>assistant<|end_header_id|>\n\n");
+    prompt
+}
+
+fn push_turn(prompt: &mut String, role: &str, text: &str) {
+    prompt.push_str(&format!(
+        "<|start_header_id|>{role}<|end_header_id|>\n\n{text}<|eot_id|>"
+    ));
+}
+
+/// The system prompt goes into the first user turn, each exchange is wrapped in `<s>` and
+/// `</s>`, and the prompt ends with the open `[INST]` block of the last user turn.
+fn llama2_prompt(preamble: Option<&str>, turns: &[Turn]) -> String {
+    let mut prompt = String::new();
+    let mut preamble = preamble;
+
+    for turn in turns {
+        match turn.role {
+            Role::User => {
+                prompt.push_str("<s>[INST] ");
+                if let Some(preamble) = preamble.take() {
+                    prompt.push_str(&format!("<<SYS>>\n{preamble}\n<</SYS>>\n\n"));
+                }
+                prompt.push_str(&turn.text);
+                prompt.push_str(" [/INST]");
+            }
+            Role::Assistant => prompt.push_str(&format!(" {} </s>", turn.text)),
+        }
+    }
+
+    prompt
+}
+
+#[derive(Deserialize)]
+struct Chunk {
+    generation: Option<String>,
+    stop_reason: Option<String>,
+    #[serde(rename = "amazon-bedrock-invocationMetrics")]
+    invocation_metrics: Option<InvocationMetrics>,
+}
+
+#[derive(Default)]
+pub(super) struct StreamParser {
+    stop_reason: Option<StopReason>,
+}
+
+impl ChunkParser for StreamParser {
+    fn parse(
+        &mut self,
+        chunk: &[u8],
+    ) -> Result<Vec<RawStreamingChoice<BedrockStreamingResponse>>, CompletionError> {
+        let chunk: Chunk = serde_json::from_slice(chunk)?;
+        let mut choices = Vec::new();
+
+        if let Some(generation) = chunk.generation
+            && !generation.is_empty()
+        {
+            choices.push(RawStreamingChoice::Message(generation));
+        }
+
+        if let Some(reason) = chunk.stop_reason {
+            self.stop_reason = Some(stop_reason(&reason));
+        }
+
+        if let Some(metrics) = chunk.invocation_metrics {
+            choices.push(RawStreamingChoice::FinalResponse(
+                metrics.into_response(self.stop_reason.take()),
+            ));
+        }
+
+        Ok(choices)
+    }
+}
+
+/// A complete Llama response.
+#[derive(Deserialize)]
+struct Response {
+    generation: String,
+    prompt_token_count: Option<i32>,
+    generation_token_count: Option<i32>,
+    stop_reason: Option<String>,
+}
+
+pub(super) fn parse_response(body: serde_json::Value) -> Result<NativeResponse, serde_json::Error> {
+    let response: Response = serde_json::from_value(body)?;
+
+    Ok(NativeResponse {
+        content: vec![AssistantContent::text(response.generation)],
+        usage: response
+            .prompt_token_count
+            .zip(response.generation_token_count)
+            .map(|(input_tokens, output_tokens)| BedrockUsage {
+                input_tokens,
+                output_tokens,
+                total_tokens: input_tokens + output_tokens,
+                ..Default::default()
+            }),
+        stop_reason: response.stop_reason.as_deref().map(stop_reason),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+    use rig::completion::Message;
+
+    #[test]
+    fn test_request_body_prompt_format() {
+        let request = completion_request("Tell me a joke")
+            .messages(vec![Message::user("Hi"), Message::assistant("Hello!")])
+            .preamble("You are helpful".into())
+            .temperature(0.2)
+            .max_tokens(256)
+            .additional_params(json!({ "top_p": 0.9 }))
+            .build();
+
+        let body = request_body(PromptTemplate::Llama3, &request).unwrap();
+
+        assert_eq!(
+            body["prompt"],
+            "<|begin_of_text|>\
+             <|start_header_id|>system<|end_header_id|>\n\nYou are helpful<|eot_id|>\
+             <|start_header_id|>user<|end_header_id|>\n\nHi<|eot_id|>\
+             <|start_header_id|>assistant<|end_header_id|>\n\nHello!<|eot_id|>\
+             <|start_header_id|>user<|end_header_id|>\n\nTell me a joke<|eot_id|>\
+             <|start_header_id|>assistant<|end_header_id|>\n\n"
+        );
+        assert_eq!(body["max_gen_len"], 256);
+        assert_eq!(body["top_p"], 0.9);
+    }
+
+    #[test]
+    fn test_llama2_prompt_format() {
+        let turns = [
+            Turn {
+                role: Role::User,
+                text: "Hi".into(),
+            },
+            Turn {
+                role: Role::Assistant,
+                text: "Hello!".into(),
+            },
+            Turn {
+                role: Role::User,
+                text: "Tell me a joke".into(),
+            },
+        ];
+
+        assert_eq!(
+            PromptTemplate::Llama2.render(Some("You are helpful"), &turns),
+            "<s>[INST] <<SYS>>\nYou are helpful\n<</SYS>>\n\nHi [/INST] Hello! </s>\
+             <s>[INST] Tell me a joke [/INST]"
+        );
+        assert_eq!(
+            PromptTemplate::for_model("meta.llama2-13b-chat-v1"),
+            PromptTemplate::Llama2
+        );
+        assert_eq!(
+            PromptTemplate::for_model("us.meta.llama3-2-11b-instruct-v1:0"),
+            PromptTemplate::Llama3
+        );
+    }
+
+    #[test]
+    fn test_parse_stream() {
+        let mut parser = StreamParser::default();
+        let mut choices = Vec::new();
+        for chunk in [
+            json!({ "generation": "Why", "prompt_token_count": 20, "generation_token_count": 1, "stop_reason": null }),
+            json!({ "generation": " not?", "prompt_token_count": null, "generation_token_count": 3, "stop_reason": "stop",
+                "amazon-bedrock-invocationMetrics": {
+                    "inputTokenCount": 20, "outputTokenCount": 3, "invocationLatency": 150, "firstByteLatency": 80
+                }
+            }),
+        ] {
+            choices.extend(parser.parse(&serde_json::to_vec(&chunk).unwrap()).unwrap());
+        }
+
+        assert_eq!(choices.len(), 3);
+        assert!(matches!(&choices[1], RawStreamingChoice::Message(text) if text == " not?"));
+        assert!(matches!(
+            &choices[2],
+            RawStreamingChoice::FinalResponse(response)
+                if response.stop_reason == Some(StopReason::EndTurn)
+                    && response.usage.as_ref().unwrap().total_tokens == 23
+        ));
+    }
+}
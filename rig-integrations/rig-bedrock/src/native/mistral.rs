@@ -0,0 +1,477 @@
+//! Mistral payloads for Bedrock, in the chat completion format for Mistral Large and the text
+//! completion format for the other models.
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-mistral-text-completion.html>
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-mistral-chat-completion.html>
+
+use rig::completion::{CompletionError, CompletionRequest, Message};
+use rig::message::{AssistantContent, ToolChoice, UserContent};
+use rig::streaming::{RawStreamingChoice, RawStreamingToolCall};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    ChunkParser, InvocationMetrics, NativeResponse, Role, base_model_id, merge_additional_params,
+    stop_reason, text_turns, unsupported_content, user_text,
+};
+use crate::streaming::BedrockStreamingResponse;
+use crate::types::converse_output::StopReason;
+
+/// The request format a Mistral model accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum RequestFormat {
+    /// A single `[INST]` prompt, without tools.
+    Text,
+    /// A list of messages, with tools. Only Mistral Large supports it.
+    Chat,
+}
+
+impl RequestFormat {
+    pub(super) fn for_model(model: &str) -> Self {
+        if base_model_id(model).starts_with("mistral.mistral-large") {
+            Self::Chat
+        } else {
+            Self::Text
+        }
+    }
+}
+
+pub(super) fn request_body(
+    format: RequestFormat,
+    request: &CompletionRequest,
+) -> Result<serde_json::Value, CompletionError> {
+    let mut body = match format {
+        RequestFormat::Text => json!({ "prompt": text_prompt(request)? }),
+        RequestFormat::Chat => chat_body(request)?,
+    };
+
+    if let Some(max_tokens) = request.max_tokens {
+        body["max_tokens"] = json!(max_tokens);
+    }
+
+    if let Some(temperature) = request.temperature {
+        body["temperature"] = json!(temperature);
+    }
+
+    merge_additional_params(&mut body, request.additional_params.as_ref());
+
+    Ok(body)
+}
+
+fn text_prompt(request: &CompletionRequest) -> Result<String, CompletionError> {
+    let mut prompt = String::from("<s>");
+    // Mistral has no system role, so the preamble is prepended to the first instruction
+    let mut preamble = request.preamble.clone();
+
+    for turn in text_turns(request, "Mistral")? {
+        match turn.role {
+            Role::User => {
+                prompt.push_str("[INST] ");
+                if let Some(preamble) = preamble.take() {
+                    prompt.push_str(&preamble);
+                    prompt.push_str("\n\n");
+                }
+                prompt.push_str(&turn.text);
+                prompt.push_str(" [/INST]");
+            }
+            Role::Assistant => {
+                prompt.push_str(&turn.text);
+                prompt.push_str("</s>");
+            }
+        }
+    }
+
+    Ok(prompt)
+}
+
+fn chat_body(request: &CompletionRequest) -> Result<serde_json::Value, CompletionError> {
+    let mut messages = Vec::new();
+    if let Some(preamble) = &request.preamble {
+        messages.push(json!({ "role": "system", "content": preamble }));
+    }
+
+    for message in request
+        .normalized_documents()
+        .into_iter()
+        .chain(request.chat_history.iter().cloned())
+    {
+        match message {
+            Message::User { content } => {
+                let mut texts = Vec::new();
+                for content in content {
+                    match content {
+                        UserContent::ToolResult(result) => messages.push(json!({
+                            "role": "tool",
+                            "tool_call_id": result.id.clone(),
+                            "content": user_text(UserContent::ToolResult(result), "Mistral")?,
+                        })),
+                        content => texts.push(user_text(content, "Mistral")?),
+                    }
+                }
+                if !texts.is_empty() {
+                    messages.push(json!({ "role": "user", "content": texts.join("\n\n") }));
+                }
+            }
+            Message::Assistant { content, .. } => {
+                let mut texts = Vec::new();
+                let mut tool_calls = Vec::new();
+                for content in content {
+                    match content {
+                        AssistantContent::Text(text) => texts.push(text.text),
+                        AssistantContent::ToolCall(tool_call) => tool_calls.push(json!({
+                            "id": tool_call.id,
+                            "function": {
+                                "name": tool_call.function.name,
+                                "arguments": tool_call.function.arguments.to_string(),
+                            },
+                        })),
+                        AssistantContent::Reasoning(_) => {}
+                        _ => return Err(unsupported_content("Mistral")),
+                    }
+                }
+
+                let mut message = json!({ "role": "assistant", "content": texts.join("\n\n") });
+                if !tool_calls.is_empty() {
+                    message["tool_calls"] = json!(tool_calls);
+                }
+                messages.push(message);
+            }
+        }
+    }
+
+    let mut body = json!({ "messages": messages });
+
+    if !request.tools.is_empty() {
+        body["tools"] = request
+            .tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters,
+                    },
+                })
+            })
+            .collect();
+
+        // Mistral can't be made to call one specific tool, only any of them
+        if let Some(tool_choice) = &request.tool_choice {
+            body["tool_choice"] = match tool_choice {
+                ToolChoice::Auto => json!("auto"),
+                ToolChoice::None => json!("none"),
+                ToolChoice::Required | ToolChoice::Specific { .. } => json!("any"),
+            };
+        }
+    }
+
+    Ok(body)
+}
+
+#[derive(Deserialize)]
+struct Chunk {
+    #[serde(default)]
+    outputs: Vec<Output>,
+    #[serde(default)]
+    choices: Vec<Choice>,
+    #[serde(rename = "amazon-bedrock-invocationMetrics")]
+    invocation_metrics: Option<InvocationMetrics>,
+}
+
+#[derive(Deserialize)]
+struct Output {
+    text: String,
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    #[serde(alias = "delta")]
+    message: ChatMessage,
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+    tool_calls: Option<Vec<ChatToolCall>>,
+}
+
+#[derive(Deserialize)]
+struct ChatToolCall {
+    id: String,
+    function: ChatFunction,
+}
+
+#[derive(Deserialize)]
+struct ChatFunction {
+    name: String,
+    /// The arguments as a JSON string.
+    arguments: String,
+}
+
+impl ChatToolCall {
+    fn arguments(&self) -> Result<serde_json::Value, serde_json::Error> {
+        if self.function.arguments.is_empty() {
+            Ok(json!({}))
+        } else {
+            serde_json::from_str(&self.function.arguments)
+        }
+    }
+}
+
+#[derive(Default)]
+pub(super) struct StreamParser {
+    stop_reason: Option<StopReason>,
+}
+
+impl ChunkParser for StreamParser {
+    fn parse(
+        &mut self,
+        chunk: &[u8],
+    ) -> Result<Vec<RawStreamingChoice<BedrockStreamingResponse>>, CompletionError> {
+        let chunk: Chunk = serde_json::from_slice(chunk)?;
+        let mut choices = Vec::new();
+
+        for output in chunk.outputs {
+            if !output.text.is_empty() {
+                choices.push(RawStreamingChoice::Message(output.text));
+            }
+            if let Some(reason) = output.stop_reason {
+                self.stop_reason = Some(stop_reason(&reason));
+            }
+        }
+
+        for choice in chunk.choices {
+            if let Some(content) = choice.message.content
+                && !content.is_empty()
+            {
+                choices.push(RawStreamingChoice::Message(content));
+            }
+            for tool_call in choice.message.tool_calls.unwrap_or_default() {
+                let arguments = tool_call.arguments()?;
+                choices.push(RawStreamingChoice::ToolCall(RawStreamingToolCall::new(
+                    tool_call.id,
+                    tool_call.function.name,
+                    arguments,
+                )));
+            }
+            if let Some(reason) = choice.stop_reason {
+                self.stop_reason = Some(stop_reason(&reason));
+            }
+        }
+
+        if let Some(metrics) = chunk.invocation_metrics {
+            choices.push(RawStreamingChoice::FinalResponse(
+                metrics.into_response(self.stop_reason.take()),
+            ));
+        }
+
+        Ok(choices)
+    }
+}
+
+/// A complete response, which doesn't report token usage.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Chat { choices: Vec<Choice> },
+    Text { outputs: Vec<Output> },
+}
+
+pub(super) fn parse_response(body: serde_json::Value) -> Result<NativeResponse, serde_json::Error> {
+    match serde_json::from_value(body)? {
+        Response::Chat { choices } => {
+            let mut content = Vec::new();
+            for choice in &choices {
+                if let Some(text) = &choice.message.content
+                    && !text.is_empty()
+                {
+                    content.push(AssistantContent::text(text));
+                }
+                for tool_call in choice.message.tool_calls.iter().flatten() {
+                    content.push(AssistantContent::tool_call(
+                        &tool_call.id,
+                        &tool_call.function.name,
+                        tool_call.arguments()?,
+                    ));
+                }
+            }
+
+            Ok(NativeResponse {
+                stop_reason: choices
+                    .iter()
+                    .find_map(|choice| choice.stop_reason.as_deref())
+                    .map(stop_reason),
+                content,
+                usage: None,
+            })
+        }
+        Response::Text { outputs } => Ok(NativeResponse {
+            stop_reason: outputs
+                .iter()
+                .find_map(|output| output.stop_reason.as_deref())
+                .map(stop_reason),
+            content: outputs
+                .into_iter()
+                .map(|output| AssistantContent::text(output.text))
+                .collect(),
+            usage: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+    use rig::OneOrMany;
+    use rig::completion::ToolDefinition;
+
+    fn request(mut chat_history: Vec<Message>) -> CompletionRequest {
+        let prompt = chat_history.pop().unwrap();
+        completion_request(prompt)
+            .messages(chat_history)
+            .preamble("Answer in French".into())
+            .max_tokens(100)
+            .build()
+    }
+
+    #[test]
+    fn test_request_body_prompt_format() {
+        let body = request_body(
+            RequestFormat::Text,
+            &request(vec![
+                Message::user("Hi"),
+                Message::assistant("Bonjour"),
+                Message::user("How are you?"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            body["prompt"],
+            "<s>[INST] Answer in French\n\nHi [/INST]Bonjour</s>[INST] How are you? [/INST]"
+        );
+        assert_eq!(body["max_tokens"], 100);
+    }
+
+    #[test]
+    fn test_request_body_rejects_tools() {
+        let request = CompletionRequest {
+            tools: vec![ToolDefinition {
+                name: "add".into(),
+                description: "Add numbers".into(),
+                parameters: json!({}),
+            }],
+            ..request(vec![Message::user("Hi")])
+        };
+
+        assert!(matches!(
+            request_body(RequestFormat::Text, &request),
+            Err(CompletionError::RequestError(_))
+        ));
+    }
+
+    #[test]
+    fn test_chat_request_body() {
+        let request = CompletionRequest {
+            tools: vec![ToolDefinition {
+                name: "add".into(),
+                description: "Add numbers".into(),
+                parameters: json!({ "type": "object" }),
+            }],
+            tool_choice: Some(ToolChoice::Required),
+            additional_params: Some(json!({ "top_k": 50 })),
+            ..request(vec![
+                Message::user("What is 1 + 2?"),
+                Message::Assistant {
+                    id: None,
+                    content: OneOrMany::one(AssistantContent::tool_call(
+                        "abc123DEF",
+                        "add",
+                        json!({ "x": 1, "y": 2 }),
+                    )),
+                },
+                Message::tool_result("abc123DEF", "3"),
+            ])
+        };
+
+        let body = request_body(RequestFormat::Chat, &request).unwrap();
+
+        assert_eq!(
+            body["messages"],
+            json!([
+                { "role": "system", "content": "Answer in French" },
+                { "role": "user", "content": "What is 1 + 2?" },
+                {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "id": "abc123DEF", "function": { "name": "add", "arguments": "{\"x\":1,\"y\":2}" } }],
+                },
+                { "role": "tool", "tool_call_id": "abc123DEF", "content": "3" },
+            ])
+        );
+        assert_eq!(body["tools"][0]["function"]["name"], "add");
+        assert_eq!(body["tool_choice"], "any");
+        assert_eq!(body["top_k"], 50);
+        assert_eq!(body["max_tokens"], 100);
+        assert_eq!(
+            RequestFormat::for_model("mistral.mistral-large-2407-v1:0"),
+            RequestFormat::Chat
+        );
+        assert_eq!(
+            RequestFormat::for_model("mistral.mixtral-8x7b-instruct-v0:1"),
+            RequestFormat::Text
+        );
+    }
+
+    #[test]
+    fn test_parse_chat_response() {
+        let response = parse_response(json!({
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "id": "abc123DEF", "function": { "name": "add", "arguments": "{\"x\":1,\"y\":2}" } }],
+                },
+                "stop_reason": "tool_calls",
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(response.stop_reason, Some(StopReason::ToolUse));
+        assert!(matches!(
+            response.content.as_slice(),
+            [AssistantContent::ToolCall(tool_call)]
+                if tool_call.function.arguments == json!({ "x": 1, "y": 2 })
+        ));
+    }
+
+    #[test]
+    fn test_parse_stream() {
+        let mut parser = StreamParser::default();
+        let mut choices = Vec::new();
+        for chunk in [
+            json!({ "outputs": [{ "text": "Ça va", "stop_reason": null }] }),
+            json!({ "outputs": [{ "text": " bien", "stop_reason": "length" }],
+                "amazon-bedrock-invocationMetrics": {
+                    "inputTokenCount": 18, "outputTokenCount": 100, "invocationLatency": 900, "firstByteLatency": 90
+                }
+            }),
+        ] {
+            choices.extend(parser.parse(&serde_json::to_vec(&chunk).unwrap()).unwrap());
+        }
+
+        assert_eq!(choices.len(), 3);
+        assert!(matches!(&choices[0], RawStreamingChoice::Message(text) if text == "Ça va"));
+        assert!(matches!(
+            &choices[2],
+            RawStreamingChoice::FinalResponse(response)
+                if response.stop_reason == Some(StopReason::MaxTokens)
+                    && response.metrics.as_ref().unwrap().latency_ms == 900
+        ));
+    }
+}
@@ -0,0 +1,788 @@
+//! Completions through `InvokeModel` and `InvokeModelWithResponseStream` with each model family's
+//! native payload.
+//!
+//! Converse covers most use cases, but some models and parameters are only reachable through the
+//! provider specific request format. [`CompletionModel::completion_native`] and
+//! [`CompletionModel::stream_native`] build that payload and parse the family specific response
+//! back into rig's abstractions. To use them for all completions of a model, including those of
+//! agents, select [`CompletionApi::Native`](crate::completion::CompletionApi::Native):
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(ANTHROPIC_CLAUDE_3_5_SONNET)
+//!     .with_api(CompletionApi::Native);
+//!
+//! let response = model
+//!     .completion_request("Hello")
+//!     .additional_params(json!({ "anthropic_beta": ["token-efficient-tools-2025-02-19"] }))
+//!     .send()
+//!     .await?;
+//! ```
+
+mod anthropic;
+mod batch;
+mod llama;
+mod mistral;
+mod titan;
+
+pub use batch::BatchCompletionOutput;
+
+use llama::PromptTemplate;
+use mistral::RequestFormat;
+
+use async_stream::stream;
+use aws_sdk_bedrockruntime::types::{self as aws_bedrock, ResponseStream};
+use aws_smithy_types::Blob;
+use rig::completion::{CompletionError, CompletionRequest, CompletionResponse, Message};
+use rig::message::{AssistantContent, DocumentSourceKind, ToolResultContent, UserContent};
+use rig::streaming::{RawStreamingChoice, StreamingCompletionResponse};
+use serde::Deserialize;
+
+use crate::completion::{CompletionModel, PreparedCall, record_usage};
+use crate::metrics;
+use crate::streaming::{
+    BedrockStreamingResponse, BedrockUsage, StreamCancellation, next_or_cancelled,
+};
+use crate::telemetry;
+use crate::think_tags::ThinkTagSplitter;
+use crate::types::assistant_content::{AwsConverseOutput, RigAssistantContent};
+use crate::types::converse_output::{
+    self, ContentBlock, ConversationRole, ConverseMetrics, ConverseOutput, Document,
+    InternalConverseOutput, StopReason, TokenUsage, UnknownVariantValue,
+};
+use crate::types::errors::{
+    AwsSdkInvokeModelError, AwsSdkInvokeModelWithResponseStreamError, BedrockError,
+    TypeConversionError,
+};
+use crate::types::json::AwsDocument;
+
+/// Model provider family, derived from a Bedrock model id, inference profile id or ARN.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelFamily {
+    Ai21,
+    Amazon,
+    Anthropic,
+    Cohere,
+    DeepSeek,
+    Meta,
+    Mistral,
+    Stability,
+}
+
+impl ModelFamily {
+    /// Detect the family of `model`, e.g. `us.anthropic.claude-3-5-haiku-20241022-v1:0` or
+    /// `arn:aws:bedrock:us-east-1::foundation-model/meta.llama3-8b-instruct-v1:0`.
+    pub fn from_model_id(model: &str) -> Option<Self> {
+        let (provider, _) = base_model_id(model).split_once('.')?;
+
+        match provider {
+            "ai21" => Some(Self::Ai21),
+            "amazon" => Some(Self::Amazon),
+            "anthropic" => Some(Self::Anthropic),
+            "cohere" => Some(Self::Cohere),
+            "deepseek" => Some(Self::DeepSeek),
+            "meta" => Some(Self::Meta),
+            "mistral" => Some(Self::Mistral),
+            "stability" => Some(Self::Stability),
+            _ => None,
+        }
+    }
+}
+
+/// Geography prefixes used by cross-region inference profiles.
+const INFERENCE_PROFILE_PREFIXES: &[&str] =
+    &["us", "us-gov", "eu", "apac", "jp", "au", "ca", "global"];
+
+/// Strip the ARN and cross-region inference profile prefix from `model`, leaving the foundation
+/// model id (e.g. `anthropic.claude-3-5-haiku-20241022-v1:0`).
+pub fn base_model_id(model: &str) -> &str {
+    let model = if model.starts_with("arn:") {
+        model.rsplit('/').next().unwrap_or(model)
+    } else {
+        model
+    };
+
+    match model.split_once('.') {
+        Some((prefix, rest)) if INFERENCE_PROFILE_PREFIXES.contains(&prefix) => rest,
+        _ => model,
+    }
+}
+
+/// Turns the chunks of one family's response stream into streaming choices.
+pub(crate) trait ChunkParser: Send {
+    fn parse(
+        &mut self,
+        chunk: &[u8],
+    ) -> Result<Vec<RawStreamingChoice<BedrockStreamingResponse>>, CompletionError>;
+}
+
+impl CompletionModel {
+    /// Stream a completion through `InvokeModelWithResponseStream`, using the native request format
+    /// of the model family instead of Converse.
+    ///
+    /// Amazon Titan Text, Anthropic, Meta Llama and Mistral models are supported. Use this for
+    /// parameters that Converse doesn't accept; they can be passed through the request's
+    /// `additional_params`, which are merged into the top level of the native payload, or into
+    /// `textGenerationConfig` for Titan.
+    pub async fn stream_native(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<BedrockStreamingResponse>, CompletionError> {
+        let family = self.native_family()?;
+        self.check_native_options()?;
+        let PreparedCall {
+            request,
+            tool_names,
+            mut prefill,
+        } = self.prepare_call(completion_request).await?;
+        let body = serde_json::to_vec(&request_body(&self.model, family, &request)?)?;
+        let mut parser: Box<dyn ChunkParser> = match family {
+            ModelFamily::Anthropic => Box::<anthropic::StreamParser>::default(),
+            ModelFamily::Amazon => Box::<titan::StreamParser>::default(),
+            ModelFamily::Meta => Box::<llama::StreamParser>::default(),
+            _ => Box::<mistral::StreamParser>::default(),
+        };
+
+        let mut cancelled = self
+            .stream_cancellation
+            .as_ref()
+            .map(StreamCancellation::subscribe);
+
+        // Held until the stream ends
+        let permit = self.client.concurrency.acquire_completion().await;
+        let span = telemetry::chat_streaming_span(&self.model);
+        let metrics = metrics::InvocationMetrics::start("chat_streaming", &self.model);
+        let operation = self
+            .client
+            .get_inner()
+            .await
+            .invoke_model_with_response_stream()
+            .model_id(self.model.as_str())
+            .content_type("application/json")
+            .accept("application/json")
+            .body(Blob::new(body));
+        let response = self
+            .send_call(&span, &metrics, async {
+                operation
+                    .send()
+                    .await
+                    .map_err(|sdk_error| AwsSdkInvokeModelWithResponseStreamError(sdk_error).into())
+            })
+            .await?;
+
+        let cost_tracker = self.cost_tracker.clone();
+        let mut think_tags = self.think_tags.then(ThinkTagSplitter::default);
+        let model = self.model.clone();
+        let stream = Box::pin(stream! {
+            let _permit = permit;
+            let mut body = response.body;
+            loop {
+                let Some(next) = next_or_cancelled(cancelled.as_mut(), body.recv()).await else {
+                    break;
+                };
+
+                match next {
+                    Ok(Some(ResponseStream::Chunk(part))) => {
+                        let Some(bytes) = part.bytes else {
+                            continue;
+                        };
+                        for choice in parser.parse(bytes.as_ref())? {
+                            match choice {
+                                RawStreamingChoice::Message(text) => {
+                                    let text = prefill.restore_chunk(text);
+                                    match think_tags.as_mut() {
+                                        Some(splitter) => {
+                                            for choice in splitter.push(&text) {
+                                                yield Ok(choice);
+                                            }
+                                        }
+                                        None => yield Ok(RawStreamingChoice::Message(text)),
+                                    }
+                                }
+                                RawStreamingChoice::ToolCall(mut tool_call) => {
+                                    tool_call.name = tool_names.restore(tool_call.name);
+                                    yield Ok(RawStreamingChoice::ToolCall(tool_call));
+                                }
+                                RawStreamingChoice::FinalResponse(mut response) => {
+                                    if let Some(splitter) = think_tags.as_mut() {
+                                        for choice in splitter.finish() {
+                                            yield Ok(choice);
+                                        }
+                                    }
+                                    if let Some(stop_reason) = &response.stop_reason {
+                                        telemetry::record_finish_reason(&span, stop_reason);
+                                    }
+                                    if let Some(metrics) = &response.metrics {
+                                        telemetry::record_latency(&span, metrics.latency_ms);
+                                    }
+                                    response.estimated_cost = record_usage(&span, &metrics, &cost_tracker, response.usage.as_ref());
+                                    response.model = Some(model.clone());
+                                    yield Ok(RawStreamingChoice::FinalResponse(response));
+                                }
+                                choice => yield Ok(choice),
+                            }
+                        }
+                    },
+                    Ok(Some(_)) => {},
+                    Ok(None) => break,
+                    Err(error) => {
+                        let error = BedrockError::from(error);
+                        telemetry::record_error(&span, &error);
+                        metrics.failure(Some(&error));
+                        yield Err(error.into());
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(StreamingCompletionResponse::stream(stream))
+    }
+
+    /// Complete a request through `InvokeModel`, using the native request format of the model
+    /// family instead of Converse.
+    ///
+    /// The response is converted to the Converse output, with the native response body in its
+    /// `additional_model_response_fields`, so fields Converse doesn't know stay reachable.
+    pub async fn completion_native(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<CompletionResponse<AwsConverseOutput>, CompletionError> {
+        let family = self.native_family()?;
+        self.check_native_options()?;
+        let PreparedCall {
+            request,
+            tool_names,
+            prefill,
+        } = self.prepare_call(completion_request).await?;
+        let body = serde_json::to_vec(&request_body(&self.model, family, &request)?)?;
+
+        let _permit = self.client.concurrency.acquire_completion().await;
+        let span = telemetry::chat_span(&self.model);
+        let metrics = metrics::InvocationMetrics::start("chat", &self.model);
+        let operation = self
+            .client
+            .get_inner()
+            .await
+            .invoke_model()
+            .model_id(self.model.as_str())
+            .content_type("application/json")
+            .accept("application/json")
+            .body(Blob::new(body));
+        let response = self
+            .send_call(&span, &metrics, async {
+                operation
+                    .send()
+                    .await
+                    .map_err(|sdk_error| AwsSdkInvokeModelError(sdk_error).into())
+            })
+            .await?;
+
+        let body: serde_json::Value = serde_json::from_slice(response.body.as_ref())
+            .inspect_err(|_| metrics.failure(None))?;
+        let response =
+            parse_response(family, body.clone()).inspect_err(|_| metrics.failure(None))?;
+        if let Some(stop_reason) = &response.stop_reason {
+            telemetry::record_finish_reason(&span, stop_reason);
+        }
+        let estimated_cost =
+            record_usage(&span, &metrics, &self.cost_tracker, response.usage.as_ref());
+        let mut output = response.into_converse_output(body)?;
+        output.estimated_cost = estimated_cost;
+        output.model = Some(self.model.clone());
+
+        self.restore_response(AwsConverseOutput(output).try_into()?, &tool_names, &prefill)
+    }
+
+    /// Fail if the model is set up with options that the native request formats can't honor.
+    fn check_native_options(&self) -> Result<(), CompletionError> {
+        let option = if self.guardrail.is_some() {
+            "Guardrails"
+        } else if self.tool_cache_point {
+            "Tool cache points"
+        } else if self.prompt_cache_points {
+            "Prompt cache points"
+        } else {
+            return Ok(());
+        };
+        Err(CompletionError::RequestError(
+            format!(
+                "{option} are not supported when calling models natively, use Converse instead"
+            )
+            .into(),
+        ))
+    }
+
+    /// The family of the model, if its native request format is supported.
+    fn native_family(&self) -> Result<ModelFamily, CompletionError> {
+        match ModelFamily::from_model_id(&self.model) {
+            Some(family @ (ModelFamily::Anthropic | ModelFamily::Meta | ModelFamily::Mistral)) => {
+                Ok(family)
+            }
+            // Nova models only have the Converse format
+            Some(ModelFamily::Amazon) if is_titan_text(&self.model) => Ok(ModelFamily::Amazon),
+            Some(family) => Err(CompletionError::ProviderError(format!(
+                "The native request format of the {family:?} model family is not supported"
+            ))),
+            None => Err(CompletionError::ProviderError(format!(
+                "Cannot determine the model family of `{}`",
+                self.model
+            ))),
+        }
+    }
+}
+
+fn is_titan_text(model: &str) -> bool {
+    let model = base_model_id(model);
+    model.starts_with("amazon.titan-text") || model.starts_with("amazon.titan-tg1")
+}
+
+/// The native payload of `request` for `model`, of a family returned by
+/// `CompletionModel::native_family`.
+fn request_body(
+    model: &str,
+    family: ModelFamily,
+    request: &CompletionRequest,
+) -> Result<serde_json::Value, CompletionError> {
+    match family {
+        ModelFamily::Amazon => titan::request_body(request),
+        ModelFamily::Anthropic => anthropic::request_body(request),
+        ModelFamily::Meta => llama::request_body(PromptTemplate::for_model(model), request),
+        _ => mistral::request_body(RequestFormat::for_model(model), request),
+    }
+}
+
+/// A complete, non-streamed response in a family's native format.
+pub(crate) struct NativeResponse {
+    pub content: Vec<AssistantContent>,
+    pub usage: Option<BedrockUsage>,
+    pub stop_reason: Option<StopReason>,
+}
+
+impl NativeResponse {
+    /// The Converse output equivalent to this response, keeping the native `body` in its
+    /// `additional_model_response_fields`.
+    fn into_converse_output(
+        self,
+        body: serde_json::Value,
+    ) -> Result<InternalConverseOutput, CompletionError> {
+        let content = self
+            .content
+            .into_iter()
+            .map(|content| {
+                let block = aws_bedrock::ContentBlock::try_from(RigAssistantContent(content))?;
+                ContentBlock::try_from(block).map_err(type_conversion_error)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let body = Document::try_from(AwsDocument::from(body).0).map_err(type_conversion_error)?;
+
+        Ok(InternalConverseOutput {
+            output: Some(ConverseOutput::Message(converse_output::Message {
+                role: ConversationRole::Assistant,
+                content,
+            })),
+            stop_reason: self.stop_reason.unwrap_or(StopReason::EndTurn),
+            usage: self.usage.map(|usage| TokenUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                total_tokens: usage.total_tokens,
+                cache_read_input_tokens: usage.cache_read_input_tokens,
+                cache_write_input_tokens: usage.cache_write_input_tokens,
+            }),
+            metrics: None,
+            additional_model_response_fields: Some(body),
+            trace: None,
+            performance_config: None,
+            estimated_cost: None,
+            model: None,
+        })
+    }
+}
+
+fn type_conversion_error(error: TypeConversionError) -> CompletionError {
+    CompletionError::ProviderError(format!("Type conversion error: {error}"))
+}
+
+/// Parse a native response of a family returned by `CompletionModel::native_family`.
+fn parse_response(
+    family: ModelFamily,
+    body: serde_json::Value,
+) -> Result<NativeResponse, serde_json::Error> {
+    match family {
+        ModelFamily::Amazon => titan::parse_response(body),
+        ModelFamily::Anthropic => anthropic::parse_response(body),
+        ModelFamily::Meta => llama::parse_response(body),
+        _ => mistral::parse_response(body),
+    }
+}
+
+/// The `amazon-bedrock-invocationMetrics` object Bedrock appends to the last chunk of a stream.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InvocationMetrics {
+    input_token_count: i32,
+    output_token_count: i32,
+    invocation_latency: i64,
+}
+
+impl InvocationMetrics {
+    pub(crate) fn into_response(self, stop_reason: Option<StopReason>) -> BedrockStreamingResponse {
+        BedrockStreamingResponse {
+            usage: Some(BedrockUsage {
+                input_tokens: self.input_token_count,
+                output_tokens: self.output_token_count,
+                total_tokens: self.input_token_count + self.output_token_count,
+                ..Default::default()
+            }),
+            stop_reason,
+            metrics: Some(ConverseMetrics {
+                latency_ms: self.invocation_latency,
+            }),
+            trace: None,
+            estimated_cost: None,
+            model: None,
+        }
+    }
+}
+
+/// Map a native stop reason onto the Converse one.
+pub(crate) fn stop_reason(reason: &str) -> StopReason {
+    match reason {
+        "end_turn" | "stop" | "FINISH" => StopReason::EndTurn,
+        "max_tokens" | "length" | "LENGTH" => StopReason::MaxTokens,
+        "stop_sequence" | "STOP_CRITERIA_MET" => StopReason::StopSequence,
+        "CONTENT_FILTERED" => StopReason::ContentFiltered,
+        "tool_use" | "tool_calls" => StopReason::ToolUse,
+        other => StopReason::Unknown(UnknownVariantValue(other.to_string())),
+    }
+}
+
+/// Merge the request's `additional_params` into the top level of a native payload.
+pub(crate) fn merge_additional_params(
+    body: &mut serde_json::Value,
+    additional_params: Option<&serde_json::Value>,
+) {
+    if let (serde_json::Value::Object(body), Some(serde_json::Value::Object(params))) =
+        (body, additional_params)
+    {
+        body.extend(params.clone());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Role {
+    User,
+    Assistant,
+}
+
+/// A text-only conversation turn, for families whose native format is a single prompt string.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Turn {
+    pub role: Role,
+    pub text: String,
+}
+
+/// Flatten documents and chat history into text turns, merging consecutive turns of the same role.
+pub(crate) fn text_turns(
+    request: &CompletionRequest,
+    family: &str,
+) -> Result<Vec<Turn>, CompletionError> {
+    if !request.tools.is_empty() {
+        return Err(CompletionError::RequestError(
+            format!("Tools are not supported when calling {family} models natively, use Converse instead").into(),
+        ));
+    }
+
+    let mut turns: Vec<Turn> = Vec::new();
+    for message in request
+        .normalized_documents()
+        .into_iter()
+        .chain(request.chat_history.iter().cloned())
+    {
+        let (role, texts) = match message {
+            Message::User { content } => (
+                Role::User,
+                content
+                    .into_iter()
+                    .map(|content| user_text(content, family))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Message::Assistant { content, .. } => (
+                Role::Assistant,
+                content
+                    .into_iter()
+                    .filter_map(|content| match content {
+                        AssistantContent::Text(text) => Some(Ok(text.text)),
+                        AssistantContent::Reasoning(_) => None,
+                        _ => Some(Err(unsupported_content(family))),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        };
+
+        let text = texts.join("\n\n");
+        match turns.last_mut() {
+            Some(last) if last.role == role => {
+                last.text.push_str("\n\n");
+                last.text.push_str(&text);
+            }
+            _ => turns.push(Turn { role, text }),
+        }
+    }
+
+    Ok(turns)
+}
+
+fn user_text(content: UserContent, family: &str) -> Result<String, CompletionError> {
+    match content {
+        UserContent::Text(text) => Ok(text.text),
+        UserContent::Document(document) => match document.data {
+            DocumentSourceKind::String(text) => Ok(text),
+            _ => Err(unsupported_content(family)),
+        },
+        UserContent::ToolResult(result) => result
+            .content
+            .into_iter()
+            .map(|content| match content {
+                ToolResultContent::Text(text) => Ok(text.text),
+                _ => Err(unsupported_content(family)),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|texts| texts.join("\n")),
+        _ => Err(unsupported_content(family)),
+    }
+}
+
+fn unsupported_content(family: &str) -> CompletionError {
+    CompletionError::RequestError(
+        format!("Only text content is supported when calling {family} models natively").into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+
+    #[test]
+    fn test_model_family_detection() {
+        assert_eq!(
+            ModelFamily::from_model_id("anthropic.claude-3-5-haiku-20241022-v1:0"),
+            Some(ModelFamily::Anthropic)
+        );
+        assert_eq!(
+            ModelFamily::from_model_id("us.meta.llama3-2-90b-instruct-v1:0"),
+            Some(ModelFamily::Meta)
+        );
+        assert_eq!(
+            ModelFamily::from_model_id(
+                "arn:aws:bedrock:us-east-1::foundation-model/mistral.mistral-large-2402-v1:0"
+            ),
+            Some(ModelFamily::Mistral)
+        );
+        assert_eq!(
+            ModelFamily::from_model_id(
+                "arn:aws:bedrock:eu-west-1:123456789012:inference-profile/eu.amazon.nova-lite-v1:0"
+            ),
+            Some(ModelFamily::Amazon)
+        );
+        assert_eq!(ModelFamily::from_model_id("unknown-model"), None);
+    }
+
+    #[test]
+    fn test_base_model_id() {
+        assert_eq!(
+            base_model_id("global.anthropic.claude-sonnet-4-20250514-v1:0"),
+            "anthropic.claude-sonnet-4-20250514-v1:0"
+        );
+        assert_eq!(
+            base_model_id("amazon.nova-lite-v1:0"),
+            "amazon.nova-lite-v1:0"
+        );
+    }
+
+    #[test]
+    fn test_text_turns_merges_consecutive_roles() {
+        let request = completion_request(Message::assistant("Yes"))
+            .messages(vec![
+                Message::user("Hello"),
+                Message::user("Are you there?"),
+            ])
+            .build();
+
+        let turns = text_turns(&request, "Llama").unwrap();
+        assert_eq!(
+            turns,
+            vec![
+                Turn {
+                    role: Role::User,
+                    text: "Hello\n\nAre you there?".into()
+                },
+                Turn {
+                    role: Role::Assistant,
+                    text: "Yes".into()
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_native_completion_api() {
+        use crate::completion::CompletionApi;
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new().with_response(MockResponse::json(
+            200,
+            serde_json::json!({
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "content": [{ "type": "text", "text": "Hello!" }],
+                "stop_reason": "end_turn",
+                "usage": { "input_tokens": 10, "output_tokens": 2 }
+            }),
+        ));
+        let model = CompletionModel::new(mock.client(), "anthropic.claude-3-5-haiku-20241022-v1:0")
+            .with_api(CompletionApi::Native);
+
+        let response = model
+            .completion_request("Hi")
+            .additional_params(serde_json::json!({ "anthropic_beta": ["beta-feature"] }))
+            .send()
+            .await
+            .unwrap();
+        assert!(matches!(
+            response.choice.first(),
+            AssistantContent::Text(text) if text.text == "Hello!"
+        ));
+        assert_eq!(response.usage.total_tokens, 12);
+        assert!(
+            response
+                .raw_response
+                .0
+                .additional_model_response_fields
+                .is_some()
+        );
+
+        let request = &mock.requests()[0];
+        assert_eq!(request.operation(), Some("invoke"));
+        let body = request.json().unwrap();
+        assert_eq!(body["anthropic_beta"], serde_json::json!(["beta-feature"]));
+        assert_eq!(body["messages"][0]["role"], "user");
+    }
+
+    #[tokio::test]
+    async fn test_native_stream_records_cost() {
+        use crate::testing::{MockBedrock, MockResponse};
+        use base64::{Engine, prelude::BASE64_STANDARD};
+        use futures::StreamExt;
+        use rig::completion::CompletionModel as _;
+        use serde_json::json;
+
+        let chunks = [
+            json!({ "type": "message_start", "message": { "usage": { "input_tokens": 1000, "output_tokens": 1 } } }),
+            json!({ "type": "content_block_start", "index": 0, "content_block": { "type": "text", "text": "" } }),
+            json!({ "type": "content_block_delta", "index": 0, "delta": { "type": "text_delta", "text": "Hello!" } }),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({ "type": "message_delta", "delta": { "stop_reason": "end_turn" }, "usage": { "output_tokens": 3 } }),
+            json!({ "type": "message_stop" }),
+        ];
+        let mock = MockBedrock::new().with_response(MockResponse::event_stream(chunks.iter().map(
+            |chunk| {
+                (
+                    "chunk",
+                    json!({ "bytes": BASE64_STANDARD.encode(chunk.to_string()) }),
+                )
+            },
+        )));
+        let model = CompletionModel::new(mock.client(), "anthropic.claude-3-5-haiku-20241022-v1:0");
+
+        let mut stream = model
+            .stream_native(model.completion_request("Hi").build())
+            .await
+            .unwrap();
+        while let Some(choice) = stream.next().await {
+            choice.unwrap();
+        }
+
+        let cost = stream.response.unwrap().estimated_cost.unwrap();
+        assert!(cost.total() > 0.0);
+        assert_eq!(model.estimated_cost().total(), cost.total());
+    }
+
+    #[tokio::test]
+    async fn test_native_completion_records_cache_tokens() {
+        use crate::completion::CompletionApi;
+        use crate::testing::{MockBedrock, MockResponse};
+        use rig::completion::CompletionModel as _;
+
+        let response = |usage| {
+            MockResponse::json(
+                200,
+                serde_json::json!({
+                    "content": [{ "type": "text", "text": "Hello!" }],
+                    "stop_reason": "end_turn",
+                    "usage": usage
+                }),
+            )
+        };
+        let mock = MockBedrock::new()
+            .with_response(response(
+                serde_json::json!({ "input_tokens": 10, "output_tokens": 2 }),
+            ))
+            .with_response(response(serde_json::json!({
+                "input_tokens": 10,
+                "output_tokens": 2,
+                "cache_read_input_tokens": 10_000
+            })));
+        let model = CompletionModel::new(mock.client(), "anthropic.claude-3-5-haiku-20241022-v1:0")
+            .with_api(CompletionApi::Native);
+
+        let uncached = model.completion_request("Hi").send().await.unwrap();
+        let cached = model.completion_request("Hi").send().await.unwrap();
+
+        let cost = |response: &CompletionResponse<AwsConverseOutput>| {
+            response.raw_response.0.estimated_cost.unwrap().total()
+        };
+        assert!(cost(&cached) > cost(&uncached));
+    }
+
+    #[tokio::test]
+    async fn test_native_api_rejects_unsupported_options() {
+        use crate::completion::CompletionApi;
+        use crate::guardrails::GuardrailConfig;
+        use crate::testing::MockBedrock;
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new();
+        let model = CompletionModel::new(mock.client(), "anthropic.claude-3-5-haiku-20241022-v1:0")
+            .with_api(CompletionApi::Native);
+
+        for model in [
+            model
+                .clone()
+                .with_guardrail(GuardrailConfig::new("guardrail-1", "1")),
+            model.clone().with_tool_cache_point(),
+            model.with_prompt_cache_points(),
+        ] {
+            assert!(matches!(
+                model.completion_request("Hi").send().await,
+                Err(CompletionError::RequestError(_))
+            ));
+            assert!(matches!(
+                model.stream(model.completion_request("Hi").build()).await,
+                Err(CompletionError::RequestError(_))
+            ));
+        }
+        assert!(mock.requests().is_empty());
+    }
+
+    #[test]
+    fn test_stop_reason_mapping() {
+        assert_eq!(stop_reason("end_turn"), StopReason::EndTurn);
+        assert_eq!(stop_reason("length"), StopReason::MaxTokens);
+        assert_eq!(stop_reason("tool_use"), StopReason::ToolUse);
+        assert!(matches!(stop_reason("refusal"), StopReason::Unknown(_)));
+    }
+}
@@ -0,0 +1,211 @@
+//! Amazon Titan Text payloads for Bedrock.
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters-titan-text.html>
+
+use rig::completion::{CompletionError, CompletionRequest};
+use rig::message::AssistantContent;
+use rig::streaming::RawStreamingChoice;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    ChunkParser, InvocationMetrics, NativeResponse, Role, merge_additional_params, stop_reason,
+    text_turns,
+};
+use crate::streaming::{BedrockStreamingResponse, BedrockUsage};
+use crate::types::converse_output::StopReason;
+
+/// The request's `additional_params` are merged into `textGenerationConfig`, e.g. `topP` or
+/// `stopSequences`, since Titan has no other parameters.
+pub(super) fn request_body(
+    request: &CompletionRequest,
+) -> Result<serde_json::Value, CompletionError> {
+    let mut prompt = String::new();
+    if let Some(preamble) = &request.preamble {
+        prompt.push_str(preamble);
+        prompt.push_str("\n\n");
+    }
+
+    for turn in text_turns(request, "Titan")? {
+        let role = match turn.role {
+            Role::User => "User",
+            Role::Assistant => "Bot",
+        };
+        prompt.push_str(&format!("{role}: {}\n", turn.text));
+    }
+    prompt.push_str("Bot:");
+
+    let mut config = json!({});
+
+    if let Some(max_tokens) = request.max_tokens {
+        config["maxTokenCount"] = json!(max_tokens);
+    }
+
+    if let Some(temperature) = request.temperature {
+        config["temperature"] = json!(temperature);
+    }
+
+    merge_additional_params(&mut config, request.additional_params.as_ref());
+
+    Ok(json!({
+        "inputText": prompt,
+        "textGenerationConfig": config,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Chunk {
+    output_text: Option<String>,
+    completion_reason: Option<String>,
+    #[serde(rename = "amazon-bedrock-invocationMetrics")]
+    invocation_metrics: Option<InvocationMetrics>,
+}
+
+#[derive(Default)]
+pub(super) struct StreamParser {
+    stop_reason: Option<StopReason>,
+}
+
+impl ChunkParser for StreamParser {
+    fn parse(
+        &mut self,
+        chunk: &[u8],
+    ) -> Result<Vec<RawStreamingChoice<BedrockStreamingResponse>>, CompletionError> {
+        let chunk: Chunk = serde_json::from_slice(chunk)?;
+        let mut choices = Vec::new();
+
+        if let Some(text) = chunk.output_text
+            && !text.is_empty()
+        {
+            choices.push(RawStreamingChoice::Message(text));
+        }
+
+        if let Some(reason) = chunk.completion_reason {
+            self.stop_reason = Some(stop_reason(&reason));
+        }
+
+        if let Some(metrics) = chunk.invocation_metrics {
+            choices.push(RawStreamingChoice::FinalResponse(
+                metrics.into_response(self.stop_reason.take()),
+            ));
+        }
+
+        Ok(choices)
+    }
+}
+
+/// A complete Titan Text response.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    input_text_token_count: Option<i32>,
+    results: Vec<Output>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Output {
+    token_count: Option<i32>,
+    output_text: String,
+    completion_reason: Option<String>,
+}
+
+pub(super) fn parse_response(body: serde_json::Value) -> Result<NativeResponse, serde_json::Error> {
+    let response: Response = serde_json::from_value(body)?;
+    let output_tokens = response
+        .results
+        .iter()
+        .map(|output| output.token_count)
+        .sum::<Option<i32>>();
+
+    Ok(NativeResponse {
+        stop_reason: response
+            .results
+            .iter()
+            .find_map(|output| output.completion_reason.as_deref())
+            .map(stop_reason),
+        usage: response.input_text_token_count.zip(output_tokens).map(
+            |(input_tokens, output_tokens)| BedrockUsage {
+                input_tokens,
+                output_tokens,
+                total_tokens: input_tokens + output_tokens,
+                ..Default::default()
+            },
+        ),
+        content: response
+            .results
+            .into_iter()
+            .map(|output| AssistantContent::text(output.output_text.trim_start()))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+    use rig::completion::Message;
+
+    #[test]
+    fn test_request_body() {
+        let request = completion_request("Name a color")
+            .messages(vec![Message::user("Hi"), Message::assistant("Hello!")])
+            .preamble("Be brief".into())
+            .temperature(0.5)
+            .max_tokens(64)
+            .additional_params(json!({ "topP": 0.9, "stopSequences": ["User:"] }))
+            .build();
+
+        assert_eq!(
+            request_body(&request).unwrap(),
+            json!({
+                "inputText": "Be brief\n\nUser: Hi\nBot: Hello!\nUser: Name a color\nBot:",
+                "textGenerationConfig": {
+                    "maxTokenCount": 64,
+                    "temperature": 0.5,
+                    "topP": 0.9,
+                    "stopSequences": ["User:"],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = parse_response(json!({
+            "inputTextTokenCount": 12,
+            "results": [{ "tokenCount": 2, "outputText": " Blue.", "completionReason": "FINISH" }]
+        }))
+        .unwrap();
+
+        assert!(matches!(
+            response.content.as_slice(),
+            [AssistantContent::Text(text)] if text.text == "Blue."
+        ));
+        assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+        assert_eq!(response.usage.unwrap().total_tokens, 14);
+    }
+
+    #[test]
+    fn test_parse_stream() {
+        let mut parser = StreamParser::default();
+        let mut choices = Vec::new();
+        for chunk in [
+            json!({ "outputText": " Blue", "index": 0, "totalOutputTextTokenCount": 1, "completionReason": null, "inputTextTokenCount": 12 }),
+            json!({ "outputText": ".", "index": 0, "totalOutputTextTokenCount": 2, "completionReason": "LENGTH", "inputTextTokenCount": null,
+                "amazon-bedrock-invocationMetrics": {
+                    "inputTokenCount": 12, "outputTokenCount": 2, "invocationLatency": 200, "firstByteLatency": 100
+                }
+            }),
+        ] {
+            choices.extend(parser.parse(&serde_json::to_vec(&chunk).unwrap()).unwrap());
+        }
+
+        assert_eq!(choices.len(), 3);
+        assert!(matches!(
+            &choices[2],
+            RawStreamingChoice::FinalResponse(response)
+                if response.stop_reason == Some(StopReason::MaxTokens)
+        ));
+    }
+}
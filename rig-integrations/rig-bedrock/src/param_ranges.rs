@@ -0,0 +1,239 @@
+//! Checks of the temperature, top p and max tokens of completion requests against the ranges the
+//! model accepts, so out of range values don't fail with a `ValidationException`.
+//!
+//! By default, completion models use the ranges of their model family and clamp the values
+//! outside of them, logging a warning. Requests can be rejected with a [`ParamRangeError`]
+//! instead, the ranges adjusted, or the checks turned off:
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(META_LLAMA_3_3_70B_INSTRUCT)
+//!     .with_param_ranges(ParamRanges::for_model(META_LLAMA_3_3_70B_INSTRUCT).rejecting());
+//!
+//! let unchecked = client
+//!     .completion_model(AMAZON_NOVA_LITE)
+//!     .with_param_ranges(ParamRanges::disabled());
+//! ```
+//!
+//! Top p is checked when set in the request's `additional_params`, as `top_p` or `topP`. Models
+//! of unknown families are only checked against the ranges set explicitly.
+
+use std::ops::RangeInclusive;
+
+use rig::completion::CompletionRequest;
+use serde_json::Value;
+
+use crate::native::ModelFamily;
+use crate::types::errors::ParamRangeError;
+
+/// The keys of top p in `additional_params`.
+const TOP_P_KEYS: &[&str] = &["top_p", "topP"];
+
+/// What happens to values outside of their range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfRange {
+    /// Replace the value by the closest one in range.
+    #[default]
+    Clamp,
+    /// Fail the request with a [`ParamRangeError`].
+    Reject,
+}
+
+/// The ranges completion requests are checked against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamRanges {
+    enabled: bool,
+    out_of_range: OutOfRange,
+    temperature: Option<RangeInclusive<f64>>,
+    top_p: Option<RangeInclusive<f64>>,
+    max_tokens: Option<u64>,
+}
+
+impl Default for ParamRanges {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            out_of_range: OutOfRange::default(),
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
+        }
+    }
+}
+
+impl ParamRanges {
+    /// The ranges of the family of `model`. Max tokens are only limited for the families whose
+    /// models share the same limit.
+    /// <https://docs.aws.amazon.com/bedrock/latest/userguide/model-parameters.html>
+    pub fn for_model(model: &str) -> Self {
+        let ranges = Self::default();
+        let Some(family) = ModelFamily::from_model_id(model) else {
+            return ranges;
+        };
+
+        match family {
+            ModelFamily::Ai21 => ranges
+                .with_temperature(0.0..=2.0)
+                .with_top_p(0.0..=1.0)
+                .with_max_tokens(4096),
+            ModelFamily::Amazon | ModelFamily::Anthropic => {
+                ranges.with_temperature(0.0..=1.0).with_top_p(0.0..=1.0)
+            }
+            ModelFamily::Cohere => ranges
+                .with_temperature(0.0..=1.0)
+                .with_top_p(0.01..=0.99)
+                .with_max_tokens(4000),
+            ModelFamily::DeepSeek => ranges
+                .with_temperature(0.0..=1.0)
+                .with_top_p(0.0..=1.0)
+                .with_max_tokens(32_768),
+            ModelFamily::Meta => ranges
+                .with_temperature(0.0..=1.0)
+                .with_top_p(0.0..=1.0)
+                .with_max_tokens(2048),
+            ModelFamily::Mistral => ranges
+                .with_temperature(0.0..=1.0)
+                .with_top_p(0.0..=1.0)
+                .with_max_tokens(8192),
+            ModelFamily::Stability => ranges,
+        }
+    }
+
+    /// Send requests without checking them.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Fail requests with values out of range instead of clamping them.
+    pub fn rejecting(mut self) -> Self {
+        self.out_of_range = OutOfRange::Reject;
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: RangeInclusive<f64>) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: RangeInclusive<f64>) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Clamp the values of `request` sent to `model` into their ranges, or fail on the first one
+    /// out of range when rejecting.
+    pub(crate) fn apply(
+        &self,
+        model: &str,
+        request: &mut CompletionRequest,
+    ) -> Result<(), ParamRangeError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if let (Some(range), Some(temperature)) = (&self.temperature, request.temperature.as_mut())
+        {
+            *temperature = self.check(model, "temperature", *temperature, range)?;
+        }
+        if let (Some(range), Some(Value::Object(params))) =
+            (&self.top_p, request.additional_params.as_mut())
+        {
+            for key in TOP_P_KEYS {
+                if let Some(top_p) = params.get(*key).and_then(Value::as_f64) {
+                    let top_p = self.check(model, "top_p", top_p, range)?;
+                    params.insert(key.to_string(), top_p.into());
+                }
+            }
+        }
+        if let (Some(max), Some(max_tokens)) = (self.max_tokens, request.max_tokens.as_mut()) {
+            let range = 1.0..=max as f64;
+            *max_tokens = self.check(model, "max_tokens", *max_tokens as f64, &range)? as u64;
+        }
+        Ok(())
+    }
+
+    /// `value` if it is in `range`, or clamped into it.
+    fn check(
+        &self,
+        model: &str,
+        parameter: &'static str,
+        value: f64,
+        range: &RangeInclusive<f64>,
+    ) -> Result<f64, ParamRangeError> {
+        if range.contains(&value) {
+            return Ok(value);
+        }
+        let error = ParamRangeError {
+            model: model.to_string(),
+            parameter,
+            value,
+            min: *range.start(),
+            max: *range.end(),
+        };
+        match self.out_of_range {
+            OutOfRange::Reject => Err(error),
+            OutOfRange::Clamp => {
+                let clamped = value.clamp(*range.start(), *range.end());
+                tracing::warn!(target: "rig::bedrock", "{error}, sending {clamped} instead");
+                Ok(clamped)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{
+        AMAZON_NOVA_LITE, COHERE_COMMAND_R, CompletionModel, META_LLAMA_3_3_70B_INSTRUCT,
+    };
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::CompletionModel as _;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_out_of_range_params_are_clamped() {
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello!"));
+        let model = CompletionModel::new(mock.client(), META_LLAMA_3_3_70B_INSTRUCT);
+
+        model
+            .completion_request("Hi")
+            .temperature(1.5)
+            .max_tokens(10_000)
+            .send()
+            .await
+            .unwrap();
+
+        let body = mock.requests()[0].json().unwrap();
+        assert_eq!(body["inferenceConfig"]["temperature"], 1.0);
+        assert_eq!(body["inferenceConfig"]["maxTokens"], 2048);
+    }
+
+    #[test]
+    fn test_out_of_range_params_are_rejected() {
+        let ranges = ParamRanges::for_model(COHERE_COMMAND_R).rejecting();
+        let mut request = CompletionModel::new(MockBedrock::new().client(), COHERE_COMMAND_R)
+            .completion_request("Hi")
+            .additional_params(json!({ "p": 0.5, "top_p": 1.0 }))
+            .build();
+
+        let error = ranges.apply(COHERE_COMMAND_R, &mut request).unwrap_err();
+
+        assert_eq!(error.parameter, "top_p");
+        assert_eq!(error.max, 0.99);
+        assert!(
+            ParamRanges::for_model(AMAZON_NOVA_LITE)
+                .rejecting()
+                .apply(AMAZON_NOVA_LITE, &mut request)
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Extraction of page ranges from PDF documents, to attach only the pages a prompt needs instead
+//! of a whole book, requires the `pdf` feature.
+//!
+//! ```rust,ignore
+//! let report = std::fs::read("annual-report.pdf")?;
+//! // Chapter 3 and the appendix
+//! let document = pdf::document_with_pages(&report, &[41..=58, 290..=296])?;
+//!
+//! let answer = agent
+//!     .prompt(Message::User {
+//!         content: OneOrMany::many([
+//!             UserContent::Document(document),
+//!             UserContent::text("Summarize the revenue figures."),
+//!         ])?,
+//!     })
+//!     .await?;
+//! ```
+//!
+//! Page numbers start at 1, as displayed by PDF viewers. The extracted document keeps the pages
+//! in their original order, without the objects only used by the removed pages.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use rig::completion::CompletionError;
+use rig::message::{Document, DocumentMediaType, DocumentSourceKind};
+
+/// The number of pages of the PDF document `pdf`.
+pub fn page_count(pdf: &[u8]) -> Result<u32, PdfError> {
+    let document = lopdf::Document::load_mem(pdf).map_err(PdfError::Invalid)?;
+    Ok(document.get_pages().len() as u32)
+}
+
+/// A PDF document with the `pages` of the PDF document `pdf`.
+pub fn extract_pages(pdf: &[u8], pages: &[RangeInclusive<u32>]) -> Result<Vec<u8>, PdfError> {
+    let mut document = lopdf::Document::load_mem(pdf).map_err(PdfError::Invalid)?;
+    let page_count = document.get_pages().len() as u32;
+
+    for range in pages {
+        if range.is_empty() || *range.start() == 0 || *range.end() > page_count {
+            return Err(PdfError::InvalidPageRange {
+                pages: range.clone(),
+                page_count,
+            });
+        }
+    }
+    if pages.is_empty() {
+        return Err(PdfError::NoPages);
+    }
+
+    let removed = (1..=page_count)
+        .filter(|page| !pages.iter().any(|range| range.contains(page)))
+        .collect::<Vec<_>>();
+    document.delete_pages(&removed);
+    document.prune_objects();
+    document.compress();
+
+    let mut bytes = Vec::new();
+    document
+        .save_to(&mut bytes)
+        .map_err(|e| PdfError::Invalid(e.into()))?;
+    Ok(bytes)
+}
+
+/// A PDF document attachment with the `pages` of the PDF document `pdf`.
+pub fn document_with_pages(
+    pdf: &[u8],
+    pages: &[RangeInclusive<u32>],
+) -> Result<Document, PdfError> {
+    Ok(Document {
+        data: DocumentSourceKind::Raw(extract_pages(pdf, pages)?),
+        media_type: Some(DocumentMediaType::PDF),
+        additional_params: None,
+    })
+}
+
+/// Failure to extract pages from a PDF document.
+#[derive(Debug)]
+pub enum PdfError {
+    /// The document couldn't be parsed or written back.
+    Invalid(lopdf::Error),
+    InvalidPageRange {
+        pages: RangeInclusive<u32>,
+        page_count: u32,
+    },
+    NoPages,
+}
+
+impl fmt::Display for PdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(error) => write!(f, "Invalid PDF document: {error}"),
+            Self::InvalidPageRange { pages, page_count } => write!(
+                f,
+                "Invalid page range {}-{}, the document has {page_count} pages numbered from 1",
+                pages.start(),
+                pages.end()
+            ),
+            Self::NoPages => write!(f, "No pages to extract"),
+        }
+    }
+}
+
+impl std::error::Error for PdfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<PdfError> for CompletionError {
+    fn from(error: PdfError) -> Self {
+        CompletionError::RequestError(Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{Object, Stream, dictionary};
+
+    fn pdf(page_count: u32) -> Vec<u8> {
+        let mut document = lopdf::Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        let mut kids = Vec::new();
+        for page in 1..=page_count {
+            let content = document.add_object(Stream::new(
+                dictionary! {},
+                format!("BT /F1 12 Tf (Page {page}) Tj ET").into_bytes(),
+            ));
+            let page = document.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content,
+            });
+            kids.push(Object::Reference(page));
+        }
+        document.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => page_count as i64,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        document.trailer.set("Root", catalog);
+
+        let mut bytes = Vec::new();
+        document.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_extract_pages() {
+        let pdf = pdf(10);
+        assert_eq!(page_count(&pdf).unwrap(), 10);
+
+        let extracted = extract_pages(&pdf, &[3..=4, 9..=9]).unwrap();
+        assert_eq!(page_count(&extracted).unwrap(), 3);
+        assert!(extracted.len() < pdf.len());
+    }
+
+    #[test]
+    fn test_invalid_page_ranges() {
+        let pdf = pdf(2);
+
+        assert!(matches!(
+            extract_pages(&pdf, &[2..=3]),
+            Err(PdfError::InvalidPageRange { page_count: 2, .. })
+        ));
+        assert!(matches!(
+            extract_pages(&pdf, &[0..=1]),
+            Err(PdfError::InvalidPageRange { .. })
+        ));
+        assert!(matches!(extract_pages(&pdf, &[]), Err(PdfError::NoPages)));
+        assert!(matches!(
+            extract_pages(b"not a pdf", &[1..=1]),
+            Err(PdfError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+//! Pre-flight check of the IAM permissions needed to invoke a model, so misconfigured roles are
+//! reported at startup with the missing actions, rather than on the first completion.
+//!
+//! ```rust,ignore
+//! let report = client.check_permissions(AMAZON_NOVA_PRO).await?;
+//! if !report.is_allowed() {
+//!     anyhow::bail!("{report}");
+//! }
+//! ```
+//!
+//! Each action is checked with an empty request body, which Bedrock authorizes before rejecting
+//! it as invalid, so the check doesn't run the model and isn't billed.
+
+use std::fmt;
+
+use aws_sdk_bedrockruntime::error::{ProvideErrorMetadata, SdkError};
+use aws_smithy_types::Blob;
+use aws_smithy_types::error::display::DisplayErrorContext;
+
+use crate::client::Client;
+use crate::types::errors::ModelAccessError;
+
+/// An IAM action used to invoke models.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InvokeAction {
+    /// Used by `InvokeModel` and `Converse`.
+    InvokeModel,
+    /// Used by `InvokeModelWithResponseStream` and `ConverseStream`.
+    InvokeModelWithResponseStream,
+}
+
+impl InvokeAction {
+    /// The action as written in IAM policies.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvokeModel => "bedrock:InvokeModel",
+            Self::InvokeModelWithResponseStream => "bedrock:InvokeModelWithResponseStream",
+        }
+    }
+}
+
+impl fmt::Display for InvokeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An action the caller isn't allowed to perform on the model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissingPermission {
+    pub action: InvokeAction,
+    /// The access denied message of Bedrock, naming the IAM identity and resource.
+    pub message: String,
+}
+
+/// The outcome of [`Client::check_permissions`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermissionReport {
+    pub model: String,
+    pub missing: Vec<MissingPermission>,
+}
+
+impl PermissionReport {
+    /// Whether the model can be invoked both with and without streaming.
+    pub fn is_allowed(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    pub fn is_missing(&self, action: InvokeAction) -> bool {
+        self.missing
+            .iter()
+            .any(|permission| permission.action == action)
+    }
+}
+
+impl fmt::Display for PermissionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.missing.is_empty() {
+            return write!(
+                f,
+                "All permissions to invoke model `{}` are granted",
+                self.model
+            );
+        }
+
+        write!(f, "Missing permissions to invoke model `{}`:", self.model)?;
+        for permission in &self.missing {
+            write!(f, "\n- {}: {}", permission.action, permission.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Check that the current credentials are allowed to invoke `model` with and without
+    /// streaming. Missing IAM permissions are listed in the report; models that can't be invoked
+    /// for other reasons, e.g. because access to them wasn't granted, are an error.
+    pub async fn check_permissions(
+        &self,
+        model: &str,
+    ) -> Result<PermissionReport, ModelAccessError> {
+        let client = self.get_inner().await;
+        let mut missing = Vec::new();
+
+        let result = client
+            .invoke_model()
+            .model_id(model)
+            .content_type("application/json")
+            .body(Blob::new("{}"))
+            .send()
+            .await;
+        if let Some(permission) = check(model, InvokeAction::InvokeModel, result)? {
+            missing.push(permission);
+        }
+
+        let result = client
+            .invoke_model_with_response_stream()
+            .model_id(model)
+            .content_type("application/json")
+            .body(Blob::new("{}"))
+            .send()
+            .await;
+        if let Some(permission) = check(model, InvokeAction::InvokeModelWithResponseStream, result)?
+        {
+            missing.push(permission);
+        }
+
+        Ok(PermissionReport {
+            model: model.to_string(),
+            missing,
+        })
+    }
+}
+
+/// The permission missing for `action`, according to the outcome of its dry call.
+fn check<T, E, R>(
+    model: &str,
+    action: InvokeAction,
+    result: Result<T, SdkError<E, R>>,
+) -> Result<Option<MissingPermission>, ModelAccessError>
+where
+    E: ProvideErrorMetadata + std::error::Error + 'static,
+    R: fmt::Debug,
+{
+    let error = match result {
+        Ok(_) => return Ok(None),
+        Err(SdkError::ServiceError(error)) => error.into_err(),
+        Err(error) => {
+            return Err(ModelAccessError::Other(
+                DisplayErrorContext(&error).to_string(),
+            ));
+        }
+    };
+
+    let model = model.to_string();
+    let message = error.message().unwrap_or_default().to_string();
+    let lowercase = message.to_lowercase();
+    match error.code() {
+        // The request was authorized before being found invalid
+        Some("ValidationException") if lowercase.contains("inference profile") => {
+            Err(ModelAccessError::InferenceProfileRequired { model, message })
+        }
+        Some("ValidationException") if lowercase.contains("model identifier is invalid") => {
+            Err(ModelAccessError::ModelNotFound { model, message })
+        }
+        Some("ValidationException") => Ok(None),
+        Some("AccessDeniedException") if lowercase.contains("access to the model") => {
+            Err(ModelAccessError::ModelNotEnabled { model, message })
+        }
+        Some("AccessDeniedException") => Ok(Some(MissingPermission { action, message })),
+        Some("ResourceNotFoundException") => {
+            Err(ModelAccessError::ModelNotFound { model, message })
+        }
+        _ => Err(ModelAccessError::Other(
+            DisplayErrorContext(&error).to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockBedrock, MockResponse};
+
+    #[tokio::test]
+    async fn test_check_permissions() {
+        let mock = MockBedrock::new()
+            .with_response(MockResponse::error(
+                400,
+                "ValidationException",
+                "Malformed input request, please reformat your input and try again.",
+            ))
+            .with_response(MockResponse::error(
+                403,
+                "AccessDeniedException",
+                "User: arn:aws:iam::123456789012:user/app is not authorized to perform: \
+                 bedrock:InvokeModelWithResponseStream",
+            ));
+
+        let report = mock
+            .client()
+            .check_permissions("amazon.nova-lite-v1:0")
+            .await
+            .unwrap();
+
+        assert!(!report.is_allowed());
+        assert!(!report.is_missing(InvokeAction::InvokeModel));
+        assert!(report.is_missing(InvokeAction::InvokeModelWithResponseStream));
+        assert!(
+            report
+                .to_string()
+                .contains("- bedrock:InvokeModelWithResponseStream: User:")
+        );
+
+        let operations = mock
+            .requests()
+            .iter()
+            .map(|request| request.operation().map(str::to_string))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            operations,
+            vec![
+                Some("invoke".to_string()),
+                Some("invoke-with-response-stream".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_permissions_model_not_enabled() {
+        let mock = MockBedrock::new().with_response(MockResponse::error(
+            403,
+            "AccessDeniedException",
+            "You don't have access to the model with the specified model ID.",
+        ));
+
+        let error = mock
+            .client()
+            .check_permissions("anthropic.claude-3-haiku-20240307-v1:0")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, ModelAccessError::ModelNotEnabled { .. }));
+    }
+}
@@ -0,0 +1,114 @@
+//! Assistant message prefills.
+//!
+//! A chat history ending with an assistant message asks the model to continue that message, e.g.
+//! to start its reply with `{` so it answers with JSON:
+//!
+//! ```rust,ignore
+//! let mut request = model
+//!     .completion_request("List three colors as a JSON array")
+//!     .build();
+//! request.chat_history.push(Message::assistant("["));
+//! let response = model.completion(request).await?;
+//! // The text of the response starts with `[`
+//! ```
+//!
+//! Bedrock rejects prefills ending with whitespace, so their trailing whitespace is removed
+//! before they are sent. The model only generates the continuation; completion models add the
+//! prefill back at the start of the text of the response, and of the first text chunk of
+//! streams, so the response reads as a whole.
+
+use rig::OneOrMany;
+use rig::completion::CompletionRequest;
+use rig::message::{AssistantContent, Message};
+
+/// The text a request was prefilled with, to restore it in the response.
+#[derive(Debug, Default)]
+pub(crate) struct Prefill(Option<String>);
+
+impl Prefill {
+    /// Trim the trailing whitespace of the final assistant message of `request`, if it is a text
+    /// prefill, dropping it when nothing is left.
+    pub(crate) fn prepare_request(request: &mut CompletionRequest) -> Self {
+        let Message::Assistant { content, .. } = request.chat_history.last_ref() else {
+            return Self::default();
+        };
+        let Some(text) = content
+            .iter()
+            .map(|content| match content {
+                AssistantContent::Text(text) => Some(text.text.as_str()),
+                _ => None,
+            })
+            .collect::<Option<String>>()
+        else {
+            // Tool calls or reasoning aren't prefills
+            return Self::default();
+        };
+
+        let prefill = text.trim_end().to_string();
+        let mut messages = request.chat_history.iter().cloned().collect::<Vec<_>>();
+        messages.pop();
+        if !prefill.is_empty() {
+            messages.push(Message::Assistant {
+                id: None,
+                content: OneOrMany::one(AssistantContent::text(prefill.clone())),
+            });
+        }
+        match OneOrMany::many(messages) {
+            Ok(chat_history) => request.chat_history = chat_history,
+            // A history of only a prefill is left for Bedrock to reject
+            Err(_) => return Self::default(),
+        }
+        Self((!prefill.is_empty()).then_some(prefill))
+    }
+
+    /// Add the prefill back at the start of the first text of `choice`.
+    pub(crate) fn restore_choice(&self, choice: &mut OneOrMany<AssistantContent>) {
+        let Some(prefill) = &self.0 else {
+            return;
+        };
+        if let Some(AssistantContent::Text(text)) = choice
+            .iter_mut()
+            .find(|content| matches!(content, AssistantContent::Text(_)))
+        {
+            text.text.insert_str(0, prefill);
+        }
+    }
+
+    /// `text` with the prefill at its start, the first time a text chunk is streamed.
+    pub(crate) fn restore_chunk(&mut self, text: String) -> String {
+        match self.0.take() {
+            Some(prefill) => prefill + &text,
+            None => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::completion::{AMAZON_NOVA_PRO, CompletionModel};
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::CompletionModel as _;
+    use rig::message::{AssistantContent, Message};
+
+    #[tokio::test]
+    async fn test_prefill_is_trimmed_and_restored() {
+        let mock = MockBedrock::new().with_response(MockResponse::text("\"red\", \"blue\"]"));
+        let model = CompletionModel::new(mock.client(), AMAZON_NOVA_PRO);
+        let mut request = model
+            .completion_request("List two colors as a JSON array")
+            .build();
+        request.chat_history.push(Message::assistant("[ \n"));
+
+        let response = model.completion(request).await.unwrap();
+
+        let AssistantContent::Text(text) = response.choice.first() else {
+            panic!("expected text");
+        };
+        assert_eq!(text.text, "[\"red\", \"blue\"]");
+        let body = mock.requests()[0].json().unwrap();
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"][0]["text"], "[");
+    }
+}
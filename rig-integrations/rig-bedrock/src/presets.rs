@@ -0,0 +1,163 @@
+//! Per-model defaults for agents: a `max_tokens` suited to the model's output limit and the
+//! provider's default temperature, made explicit so agents don't silently depend on defaults
+//! that differ between model families.
+//!
+//! ```rust,ignore
+//! let agent = client
+//!     .agent_nova_pro()
+//!     .preamble("You are a helpful assistant")
+//!     .build();
+//!
+//! // Any model in the table, falling back to rig's defaults for unknown models
+//! let agent = client.agent_with_preset(MISTRAL_LARGE_24_07).build();
+//!
+//! // Or applied to an existing builder, then overridden
+//! let agent = ModelPreset::for_model(AMAZON_NOVA_LITE)
+//!     .unwrap_or_default()
+//!     .apply(client.agent(AMAZON_NOVA_LITE))
+//!     .temperature(0.0)
+//!     .build();
+//! ```
+
+use rig::agent::AgentBuilder;
+use rig::client::CompletionClient;
+use serde_json::Value;
+
+use crate::client::Client;
+use crate::completion::{
+    AMAZON_NOVA_LITE, AMAZON_NOVA_MICRO, AMAZON_NOVA_PRO, ANTHROPIC_CLAUDE_SONNET_4,
+    CompletionModel,
+};
+use crate::native::base_model_id;
+
+/// Defaults applied to an agent builder.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelPreset {
+    pub max_tokens: Option<u64>,
+    pub temperature: Option<f64>,
+    /// Model-specific request fields, sent as `additionalModelRequestFields`.
+    pub additional_params: Option<Value>,
+}
+
+impl ModelPreset {
+    pub const fn new(max_tokens: u64, temperature: f64) -> Self {
+        Self {
+            max_tokens: Some(max_tokens),
+            temperature: Some(temperature),
+            additional_params: None,
+        }
+    }
+
+    pub fn with_additional_params(mut self, additional_params: Value) -> Self {
+        self.additional_params = Some(additional_params);
+        self
+    }
+
+    /// The preset of `model`, a model id, inference profile id or ARN, if known.
+    pub fn for_model(model: &str) -> Option<Self> {
+        let model = base_model_id(model);
+        PRESETS
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, preset)| preset.clone())
+    }
+
+    /// Set the defaults of this preset on `builder`. Settings made on the builder afterwards
+    /// take precedence.
+    pub fn apply<M>(&self, mut builder: AgentBuilder<M>) -> AgentBuilder<M>
+    where
+        M: rig::completion::CompletionModel,
+    {
+        if let Some(max_tokens) = self.max_tokens {
+            builder = builder.max_tokens(max_tokens);
+        }
+        if let Some(temperature) = self.temperature {
+            builder = builder.temperature(temperature);
+        }
+        if let Some(additional_params) = &self.additional_params {
+            builder = builder.additional_params(additional_params.clone());
+        }
+        builder
+    }
+}
+
+/// Presets by model id prefix, the longest matching prefix wins. Temperatures are the
+/// providers' documented defaults.
+const PRESETS: &[(&str, ModelPreset)] = &[
+    ("ai21.jamba", ModelPreset::new(4_096, 1.0)),
+    ("amazon.nova-", ModelPreset::new(5_000, 0.7)),
+    ("amazon.nova-premier", ModelPreset::new(32_000, 0.7)),
+    ("anthropic.claude-3-", ModelPreset::new(4_096, 1.0)),
+    ("anthropic.claude-3-5", ModelPreset::new(8_192, 1.0)),
+    ("anthropic.claude-3-7", ModelPreset::new(8_192, 1.0)),
+    ("anthropic.claude-opus-4", ModelPreset::new(8_192, 1.0)),
+    ("anthropic.claude-sonnet-4", ModelPreset::new(8_192, 1.0)),
+    ("cohere.command-r", ModelPreset::new(4_000, 0.3)),
+    ("deepseek.r1", ModelPreset::new(32_768, 0.6)),
+    ("meta.llama3", ModelPreset::new(2_048, 0.5)),
+    ("meta.llama4", ModelPreset::new(2_048, 0.5)),
+    ("mistral.", ModelPreset::new(8_192, 0.7)),
+];
+
+impl Client {
+    /// An agent builder for `model` with its [`ModelPreset`], or rig's defaults if the model
+    /// has none.
+    pub fn agent_with_preset(&self, model: impl Into<String>) -> AgentBuilder<CompletionModel> {
+        let model = model.into();
+        let preset = ModelPreset::for_model(&model).unwrap_or_default();
+        preset.apply(self.agent(model))
+    }
+
+    /// An agent builder for Amazon Nova Micro with its preset.
+    pub fn agent_nova_micro(&self) -> AgentBuilder<CompletionModel> {
+        self.agent_with_preset(AMAZON_NOVA_MICRO)
+    }
+
+    /// An agent builder for Amazon Nova Lite with its preset.
+    pub fn agent_nova_lite(&self) -> AgentBuilder<CompletionModel> {
+        self.agent_with_preset(AMAZON_NOVA_LITE)
+    }
+
+    /// An agent builder for Amazon Nova Pro with its preset.
+    pub fn agent_nova_pro(&self) -> AgentBuilder<CompletionModel> {
+        self.agent_with_preset(AMAZON_NOVA_PRO)
+    }
+
+    /// An agent builder for Claude Sonnet 4 with its preset. Claude 4 models are only
+    /// available through cross-region inference profiles, so the `us.` profile is used.
+    pub fn agent_claude_sonnet_4(&self) -> AgentBuilder<CompletionModel> {
+        self.agent_with_preset(format!("us.{ANTHROPIC_CLAUDE_SONNET_4}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{DEEPSEEK_R1, META_LLAMA_3_3_70B_INSTRUCT};
+
+    #[test]
+    fn test_for_model() {
+        assert_eq!(
+            ModelPreset::for_model(AMAZON_NOVA_PRO),
+            Some(ModelPreset::new(5_000, 0.7))
+        );
+        assert_eq!(
+            ModelPreset::for_model("us.amazon.nova-premier-v1:0"),
+            Some(ModelPreset::new(32_000, 0.7))
+        );
+        assert_eq!(
+            ModelPreset::for_model("anthropic.claude-3-5-haiku-20241022-v1:0"),
+            Some(ModelPreset::new(8_192, 1.0))
+        );
+        assert_eq!(
+            ModelPreset::for_model(META_LLAMA_3_3_70B_INSTRUCT).and_then(|p| p.max_tokens),
+            Some(2_048)
+        );
+        assert_eq!(
+            ModelPreset::for_model(DEEPSEEK_R1).and_then(|p| p.temperature),
+            Some(0.6)
+        );
+        assert_eq!(ModelPreset::for_model("amazon.titan-embed-text-v2:0"), None);
+    }
+}
@@ -0,0 +1,312 @@
+//! Cost estimation of Bedrock calls from their token usage.
+//!
+//! Prices are the public on-demand prices in `us-east-1`, in USD per million tokens, and may be
+//! outdated or differ in other regions. Cross-region inference profiles are priced as their base
+//! model. Models missing from the table, or with negotiated prices, can be priced explicitly:
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_PRO)
+//!     .with_pricing(ModelPricing::new(0.8, 3.2));
+//!
+//! let response = model.completion(request).await?;
+//! println!("{:?}", response.raw_response.0.estimated_cost);
+//! println!("{}", model.estimated_cost().total());
+//! ```
+
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::budget::BudgetGuard;
+use crate::native::base_model_id;
+use crate::types::errors::BudgetExceededError;
+use crate::usage::UsageTracker;
+
+/// Prices of a model, in USD per million tokens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    /// Price of input tokens read from the prompt cache, if different from `input`.
+    pub cache_read: Option<f64>,
+    /// Price of input tokens written to the prompt cache, if different from `input`.
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPricing {
+    pub const fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read: None,
+            cache_write: None,
+        }
+    }
+
+    pub const fn with_cache_prices(mut self, cache_read: f64, cache_write: f64) -> Self {
+        self.cache_read = Some(cache_read);
+        self.cache_write = Some(cache_write);
+        self
+    }
+
+    /// The on-demand pricing of `model`, a model id, inference profile id or ARN, if known.
+    pub fn for_model(model: &str) -> Option<Self> {
+        let model = base_model_id(model);
+        PRICES
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, pricing)| *pricing)
+    }
+
+    /// The cost of a call with the given usage. `input_tokens` excludes cached tokens, as
+    /// reported by Converse.
+    pub fn estimate(
+        &self,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+    ) -> CostEstimate {
+        let per_token = |price: f64, tokens: u64| price * tokens as f64 / 1_000_000.0;
+        CostEstimate {
+            input: per_token(self.input, input_tokens)
+                + per_token(self.cache_read.unwrap_or(self.input), cache_read_tokens)
+                + per_token(self.cache_write.unwrap_or(self.input), cache_write_tokens),
+            output: per_token(self.output, output_tokens),
+        }
+    }
+}
+
+/// The estimated cost of one or more calls, in USD.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// Cost of the input tokens, including cached ones.
+    pub input: f64,
+    pub output: f64,
+}
+
+impl CostEstimate {
+    pub fn total(&self) -> f64 {
+        self.input + self.output
+    }
+}
+
+impl Add for CostEstimate {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            input: self.input + other.input,
+            output: self.output + other.output,
+        }
+    }
+}
+
+impl AddAssign for CostEstimate {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for CostEstimate {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// Estimates the cost of the calls made by a model, and totals it across the model's clones.
+/// The usage of the calls is also recorded by the model's [`UsageTracker`], if any.
+#[derive(Clone, Debug)]
+pub(crate) struct CostTracker {
+    pricing: Option<ModelPricing>,
+    total: Arc<Mutex<CostEstimate>>,
+    usage_tracker: Option<UsageTracker>,
+    budget_guard: Option<BudgetGuard>,
+}
+
+impl CostTracker {
+    pub(crate) fn new(model: &str, usage_tracker: Option<UsageTracker>) -> Self {
+        Self {
+            pricing: ModelPricing::for_model(model),
+            total: Default::default(),
+            usage_tracker,
+            budget_guard: None,
+        }
+    }
+
+    /// A tracker for `model` adding to the same total and usage tracker.
+    pub(crate) fn for_model(&self, model: &str) -> Self {
+        Self {
+            pricing: ModelPricing::for_model(model),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn with_pricing(mut self, pricing: ModelPricing) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    pub(crate) fn with_usage_tracker(mut self, usage_tracker: UsageTracker) -> Self {
+        self.usage_tracker = Some(usage_tracker);
+        self
+    }
+
+    pub(crate) fn with_budget_guard(mut self, budget_guard: BudgetGuard) -> Self {
+        self.budget_guard = Some(budget_guard);
+        self
+    }
+
+    /// Check the budget before sending a call, see [`BudgetGuard`].
+    pub(crate) async fn acquire_budget(&self) -> Result<(), BudgetExceededError> {
+        match &self.budget_guard {
+            Some(budget_guard) => budget_guard.acquire().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Estimate the cost of a call and add it to the total.
+    pub(crate) fn record(
+        &self,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+    ) -> Option<CostEstimate> {
+        let cost = self.pricing.map(|pricing| {
+            pricing.estimate(
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
+            )
+        });
+        if let Some(cost) = cost {
+            *self.total.lock().expect("cost total lock poisoned") += cost;
+            if let Some(budget_guard) = &self.budget_guard {
+                budget_guard.record(cost);
+            }
+        }
+        if let Some(usage_tracker) = &self.usage_tracker {
+            usage_tracker.record(
+                input_tokens,
+                output_tokens,
+                cache_read_tokens,
+                cache_write_tokens,
+                cost,
+            );
+        }
+        cost
+    }
+
+    pub(crate) fn total(&self) -> CostEstimate {
+        *self.total.lock().expect("cost total lock poisoned")
+    }
+
+    pub(crate) fn reset(&self) {
+        *self.total.lock().expect("cost total lock poisoned") = CostEstimate::default();
+    }
+}
+
+/// Anthropic models bill cache reads at a tenth of the input price, and cache writes at 125%.
+const fn anthropic(input: f64, output: f64) -> ModelPricing {
+    ModelPricing::new(input, output).with_cache_prices(input * 0.1, input * 1.25)
+}
+
+/// Nova models bill cache reads at a quarter of the input price, and cache writes as input.
+const fn nova(input: f64, output: f64) -> ModelPricing {
+    ModelPricing::new(input, output).with_cache_prices(input * 0.25, input)
+}
+
+/// On-demand prices by base model id prefix, the longest matching prefix applies.
+const PRICES: &[(&str, ModelPricing)] = &[
+    ("ai21.jamba-1-5-large", ModelPricing::new(2.0, 8.0)),
+    ("ai21.jamba-1-5-mini", ModelPricing::new(0.2, 0.4)),
+    ("amazon.nova-lite", nova(0.06, 0.24)),
+    ("amazon.nova-micro", nova(0.035, 0.14)),
+    ("amazon.nova-premier", nova(2.5, 12.5)),
+    ("amazon.nova-pro", nova(0.8, 3.2)),
+    ("amazon.titan-embed-image-v1", ModelPricing::new(0.8, 0.0)),
+    ("amazon.titan-embed-text-v1", ModelPricing::new(0.1, 0.0)),
+    ("amazon.titan-embed-text-v2", ModelPricing::new(0.02, 0.0)),
+    ("amazon.titan-text-express", ModelPricing::new(0.2, 0.6)),
+    ("amazon.titan-text-lite", ModelPricing::new(0.15, 0.2)),
+    ("amazon.titan-text-premier", ModelPricing::new(0.5, 1.5)),
+    ("anthropic.claude-3-5-haiku", anthropic(0.8, 4.0)),
+    ("anthropic.claude-3-5-sonnet", anthropic(3.0, 15.0)),
+    ("anthropic.claude-3-7-sonnet", anthropic(3.0, 15.0)),
+    ("anthropic.claude-3-haiku", anthropic(0.25, 1.25)),
+    ("anthropic.claude-3-opus", anthropic(15.0, 75.0)),
+    ("anthropic.claude-3-sonnet", anthropic(3.0, 15.0)),
+    ("anthropic.claude-instant", ModelPricing::new(0.8, 2.4)),
+    ("anthropic.claude-opus-4", anthropic(15.0, 75.0)),
+    ("anthropic.claude-sonnet-4", anthropic(3.0, 15.0)),
+    ("anthropic.claude-v2", ModelPricing::new(8.0, 24.0)),
+    ("cohere.command-light-text", ModelPricing::new(0.3, 0.6)),
+    ("cohere.command-r-plus", ModelPricing::new(3.0, 15.0)),
+    ("cohere.command-r-v1", ModelPricing::new(0.5, 1.5)),
+    ("cohere.command-text", ModelPricing::new(1.5, 2.0)),
+    ("cohere.embed-english-v3", ModelPricing::new(0.1, 0.0)),
+    ("cohere.embed-multilingual-v3", ModelPricing::new(0.1, 0.0)),
+    ("deepseek.r1", ModelPricing::new(1.35, 5.4)),
+    ("meta.llama3-1-405b", ModelPricing::new(2.4, 2.4)),
+    ("meta.llama3-1-70b", ModelPricing::new(0.72, 0.72)),
+    ("meta.llama3-1-8b", ModelPricing::new(0.22, 0.22)),
+    ("meta.llama3-2-11b", ModelPricing::new(0.16, 0.16)),
+    ("meta.llama3-2-1b", ModelPricing::new(0.1, 0.1)),
+    ("meta.llama3-2-3b", ModelPricing::new(0.15, 0.15)),
+    ("meta.llama3-2-90b", ModelPricing::new(0.72, 0.72)),
+    ("meta.llama3-3-70b", ModelPricing::new(0.72, 0.72)),
+    ("meta.llama3-70b", ModelPricing::new(2.65, 3.5)),
+    ("meta.llama3-8b", ModelPricing::new(0.3, 0.6)),
+    ("meta.llama4-maverick-17b", ModelPricing::new(0.24, 0.97)),
+    ("meta.llama4-scout-17b", ModelPricing::new(0.17, 0.66)),
+    ("mistral.mistral-7b", ModelPricing::new(0.15, 0.2)),
+    ("mistral.mistral-large-2402", ModelPricing::new(4.0, 12.0)),
+    ("mistral.mistral-large-2407", ModelPricing::new(2.0, 6.0)),
+    ("mistral.mistral-small-2402", ModelPricing::new(1.0, 3.0)),
+    ("mistral.mixtral-8x7b", ModelPricing::new(0.45, 0.7)),
+    ("mistral.pixtral-large-2502", ModelPricing::new(2.0, 6.0)),
+    ("writer.palmyra-x4", ModelPricing::new(2.5, 10.0)),
+    ("writer.palmyra-x5", ModelPricing::new(0.6, 6.0)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_lookup() {
+        assert_eq!(
+            ModelPricing::for_model("anthropic.claude-3-5-haiku-20241022-v1:0"),
+            Some(anthropic(0.8, 4.0))
+        );
+        // Inference profiles are priced as their base model
+        assert_eq!(
+            ModelPricing::for_model("us.anthropic.claude-3-haiku-20240307-v1:0"),
+            Some(anthropic(0.25, 1.25))
+        );
+        assert_eq!(
+            ModelPricing::for_model("meta.llama3-1-8b-instruct-v1:0"),
+            Some(ModelPricing::new(0.22, 0.22))
+        );
+        assert_eq!(ModelPricing::for_model("acme.unknown-v1"), None);
+    }
+
+    #[test]
+    fn test_estimate() {
+        let pricing = ModelPricing::new(3.0, 15.0).with_cache_prices(0.3, 3.75);
+        let cost = pricing.estimate(1_000, 500, 10_000, 0);
+
+        assert!((cost.input - 0.006).abs() < 1e-12);
+        assert!((cost.output - 0.0075).abs() < 1e-12);
+        assert!((cost.total() - 0.0135).abs() < 1e-12);
+
+        let total: CostEstimate = [cost, cost].into_iter().sum();
+        assert!((total.total() - 0.027).abs() < 1e-12);
+    }
+}
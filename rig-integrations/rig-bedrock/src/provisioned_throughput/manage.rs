@@ -0,0 +1,136 @@
+use std::time::SystemTime;
+
+use aws_smithy_types::DateTime;
+
+use super::{CommitmentDuration, ProvisionedThroughput, ProvisionedThroughputStatus};
+use crate::client::Client;
+use crate::types::errors::BedrockError;
+
+/// A [`ProvisionedThroughput`] from a summary or a `GetProvisionedModelThroughput` output,
+/// which have the same fields.
+macro_rules! provisioned_throughput {
+    ($throughput:expr) => {{
+        let throughput = $throughput;
+        ProvisionedThroughput {
+            name: throughput.provisioned_model_name().to_string(),
+            arn: throughput.provisioned_model_arn().to_string(),
+            model_arn: throughput.model_arn().to_string(),
+            desired_model_arn: throughput.desired_model_arn().to_string(),
+            foundation_model_arn: throughput.foundation_model_arn().to_string(),
+            model_units: throughput.model_units(),
+            desired_model_units: throughput.desired_model_units(),
+            status: ProvisionedThroughputStatus::from(throughput.status().as_str()),
+            commitment_duration: throughput
+                .commitment_duration()
+                .map(|duration| CommitmentDuration::from(duration.as_str())),
+            commitment_expiration_time: time(throughput.commitment_expiration_time()),
+            creation_time: time(Some(throughput.creation_time())),
+            last_modified_time: time(Some(throughput.last_modified_time())),
+        }
+    }};
+}
+
+impl Client {
+    /// Provision `model_units` units of `model`, a foundation or custom model id or ARN, and
+    /// return the ARN of the provisioned throughput. Without a commitment, the throughput can be
+    /// deleted at any time.
+    pub async fn create_provisioned_throughput(
+        &self,
+        name: &str,
+        model: &str,
+        model_units: i32,
+        commitment_duration: Option<CommitmentDuration>,
+    ) -> Result<String, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .create_provisioned_model_throughput()
+            .provisioned_model_name(name)
+            .model_id(model)
+            .model_units(model_units)
+            .set_commitment_duration(commitment_duration.map(|duration| duration.as_str().into()))
+            .send()
+            .await?;
+
+        Ok(output.provisioned_model_arn().to_string())
+    }
+
+    /// The provisioned throughputs of the account in the client's region.
+    pub async fn list_provisioned_throughputs(
+        &self,
+    ) -> Result<Vec<ProvisionedThroughput>, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let bedrock = aws_sdk_bedrock::Client::new(sdk_config);
+
+        let mut throughputs = Vec::new();
+        let mut next_token = None;
+        loop {
+            let output = bedrock
+                .list_provisioned_model_throughputs()
+                .set_next_token(next_token)
+                .send()
+                .await?;
+
+            throughputs.extend(
+                output
+                    .provisioned_model_summaries()
+                    .iter()
+                    .map(|summary| provisioned_throughput!(summary)),
+            );
+
+            next_token = output.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(throughputs);
+            }
+        }
+    }
+
+    /// The provisioned throughput `id`, its name or ARN.
+    pub async fn get_provisioned_throughput(
+        &self,
+        id: &str,
+    ) -> Result<ProvisionedThroughput, BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        let output = aws_sdk_bedrock::Client::new(sdk_config)
+            .get_provisioned_model_throughput()
+            .provisioned_model_id(id)
+            .send()
+            .await?;
+
+        Ok(provisioned_throughput!(&output))
+    }
+
+    /// Rename the provisioned throughput `id`, or switch it to another custom model of the same
+    /// base model.
+    pub async fn update_provisioned_throughput(
+        &self,
+        id: &str,
+        name: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<(), BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        aws_sdk_bedrock::Client::new(sdk_config)
+            .update_provisioned_model_throughput()
+            .provisioned_model_id(id)
+            .set_desired_provisioned_model_name(name.map(str::to_string))
+            .set_desired_model_id(model.map(str::to_string))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Delete the provisioned throughput `id`. Throughput with a commitment can only be deleted
+    /// once the commitment expired.
+    pub async fn delete_provisioned_throughput(&self, id: &str) -> Result<(), BedrockError> {
+        let sdk_config = self.sdk_config().await;
+        aws_sdk_bedrock::Client::new(sdk_config)
+            .delete_provisioned_model_throughput()
+            .provisioned_model_id(id)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+fn time(time: Option<&DateTime>) -> Option<SystemTime> {
+    time.and_then(|time| SystemTime::try_from(*time).ok())
+}
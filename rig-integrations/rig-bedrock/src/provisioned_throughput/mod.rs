@@ -0,0 +1,90 @@
+//! Provisioned throughput: dedicated model capacity, billed hourly, for workloads that need
+//! higher or more predictable throughput than on-demand quotas allow, or custom models.
+//!
+//! The types in this module are always available; managing provisioned throughput requires the
+//! `control-plane` feature.
+//!
+//! ```rust,ignore
+//! let arn = client
+//!     .create_provisioned_throughput("support-bot", AMAZON_NOVA_PRO, 1, None)
+//!     .await?;
+//!
+//! // Creation takes a few minutes
+//! let throughput = client.get_provisioned_throughput(&arn).await?;
+//! if throughput.status == ProvisionedThroughputStatus::InService {
+//!     let model = client.provisioned_completion_model(&throughput);
+//! }
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/prov-throughput.html>
+
+#[cfg(feature = "control-plane")]
+mod manage;
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::Client;
+use crate::completion::CompletionModel;
+use crate::types::converse_output::{UnknownVariantValue, api_values};
+
+/// A provisioned throughput resource.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProvisionedThroughput {
+    pub name: String,
+    /// The ARN to invoke the model through, in place of its model id.
+    pub arn: String,
+    pub model_arn: String,
+    /// The model the resource is being updated to, if an update is in progress.
+    pub desired_model_arn: String,
+    pub foundation_model_arn: String,
+    pub model_units: i32,
+    pub desired_model_units: i32,
+    pub status: ProvisionedThroughputStatus,
+    /// `None` for no-commitment throughput, which can be deleted at any time.
+    pub commitment_duration: Option<CommitmentDuration>,
+    pub commitment_expiration_time: Option<SystemTime>,
+    pub creation_time: Option<SystemTime>,
+    pub last_modified_time: Option<SystemTime>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProvisionedThroughputStatus {
+    Creating,
+    InService,
+    Updating,
+    Failed,
+    Unknown(UnknownVariantValue),
+}
+
+/// How long provisioned throughput is committed to, at a discount over no commitment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommitmentDuration {
+    OneMonth,
+    SixMonths,
+    Unknown(UnknownVariantValue),
+}
+
+api_values!(ProvisionedThroughputStatus {
+    Creating => "Creating",
+    InService => "InService",
+    Updating => "Updating",
+    Failed => "Failed",
+});
+
+api_values!(CommitmentDuration {
+    OneMonth => "OneMonth",
+    SixMonths => "SixMonths",
+});
+
+impl Client {
+    /// A completion model invoking `throughput`. Calls fail until it is
+    /// [`ProvisionedThroughputStatus::InService`].
+    pub fn provisioned_completion_model(
+        &self,
+        throughput: &ProvisionedThroughput,
+    ) -> CompletionModel {
+        CompletionModel::new(self.clone(), &throughput.arn)
+    }
+}
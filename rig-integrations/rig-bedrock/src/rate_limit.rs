@@ -0,0 +1,277 @@
+//! Client-side rate limiting, to stay under Bedrock account quotas instead of being throttled.
+//!
+//! Bedrock enforces requests-per-minute and tokens-per-minute quotas for each model. Retrying
+//! throttled requests lets bursts through and then backs off, while a limiter spreads requests
+//! to stay under the quotas:
+//!
+//! ```rust,ignore
+//! let client = Client::from_env().with_completion_rate_limit(
+//!     AMAZON_NOVA_PRO,
+//!     RateLimiter::new()
+//!         .with_requests_per_minute(200)
+//!         .with_tokens_per_minute(200_000),
+//! );
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rig::completion::CompletionRequest;
+use rig::message::{AssistantContent, DocumentSourceKind, Message, ToolResultContent, UserContent};
+use tokio::time::Instant;
+
+/// Limits the requests and tokens per minute sent to Bedrock.
+///
+/// A limiter can be cloned and shared between models, in which case they share the same budget,
+/// e.g. to keep several workers embedding with the same model under the account quota.
+///
+/// Tokens are estimated before sending each request (roughly four characters per token), so the
+/// tokens-per-minute limit should leave some headroom below the actual quota. Completion requests
+/// also count their `max_tokens`, which Bedrock reserves against the quota until they finish.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    /// A limiter without any limit; configure it with the `with_*` methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow at most `requests_per_second` requests per second, with bursts of up to one
+    /// second worth of requests.
+    pub fn with_requests_per_second(self, requests_per_second: f64) -> Self {
+        self.buckets
+            .lock()
+            .expect("rate limiter lock poisoned")
+            .requests = Some(TokenBucket::new(requests_per_second, 1.0));
+        self
+    }
+
+    /// Allow at most `requests_per_minute` requests per minute, with bursts of up to one minute
+    /// worth of requests, like the Bedrock quotas.
+    pub fn with_requests_per_minute(self, requests_per_minute: u32) -> Self {
+        self.buckets
+            .lock()
+            .expect("rate limiter lock poisoned")
+            .requests = Some(TokenBucket::new(requests_per_minute as f64 / 60.0, 60.0));
+        self
+    }
+
+    /// Allow at most `tokens_per_minute` tokens per minute.
+    pub fn with_tokens_per_minute(self, tokens_per_minute: u32) -> Self {
+        self.buckets
+            .lock()
+            .expect("rate limiter lock poisoned")
+            .tokens = Some(TokenBucket::new(tokens_per_minute as f64 / 60.0, 60.0));
+        self
+    }
+
+    /// Wait until a request with `tokens` input tokens can be sent.
+    pub(crate) async fn acquire(&self, tokens: u32) {
+        let delay = {
+            let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+            let now = Instant::now();
+            let requests = buckets
+                .requests
+                .as_mut()
+                .map(|bucket| bucket.reserve(now, 1.0))
+                .unwrap_or_default();
+            let tokens = buckets
+                .tokens
+                .as_mut()
+                .map(|bucket| bucket.reserve(now, tokens as f64))
+                .unwrap_or_default();
+            requests.max(tokens)
+        };
+
+        if !delay.is_zero() {
+            tracing::debug!(target: "rig::bedrock", "Rate limited, waiting {delay:?}");
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// A token bucket that can go into debt: callers reserve their cost immediately and wait until
+/// the bucket would have refilled, so concurrent callers are served in order.
+#[derive(Debug)]
+struct TokenBucket {
+    /// Refill rate, per second.
+    rate: f64,
+    capacity: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket refilling at `rate` per second and holding up to `burst_seconds` worth.
+    fn new(rate: f64, burst_seconds: f64) -> Self {
+        let capacity = (rate * burst_seconds).max(1.0);
+        Self {
+            rate,
+            capacity,
+            available: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Take `cost` from the bucket and return how long to wait before using it.
+    fn reserve(&mut self, now: Instant, cost: f64) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+        self.available -= cost;
+
+        if self.available >= 0.0 || self.rate <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.rate)
+        }
+    }
+}
+
+/// A rough estimate of the number of tokens of `text`, for rate limiting.
+pub(crate) fn estimate_tokens(text: &str) -> u32 {
+    text.chars().count().div_ceil(4) as u32
+}
+
+/// A rough estimate of the tokens `request` counts against the tokens-per-minute quota: its text
+/// and tool definitions, plus the `max_tokens` reserved for the output. Images and other media
+/// aren't counted.
+pub(crate) fn estimate_request_tokens(request: &CompletionRequest) -> u32 {
+    let mut tokens = request.preamble.as_deref().map_or(0, estimate_tokens);
+    tokens += request
+        .documents
+        .iter()
+        .map(|document| estimate_tokens(&document.text))
+        .sum::<u32>();
+    tokens += request
+        .tools
+        .iter()
+        .map(|tool| {
+            estimate_tokens(&tool.name)
+                + estimate_tokens(&tool.description)
+                + estimate_tokens(&tool.parameters.to_string())
+        })
+        .sum::<u32>();
+
+    for message in request.chat_history.iter() {
+        match message {
+            Message::User { content } => {
+                for content in content.iter() {
+                    tokens += match content {
+                        UserContent::Text(text) => estimate_tokens(&text.text),
+                        UserContent::ToolResult(result) => result
+                            .content
+                            .iter()
+                            .map(|content| match content {
+                                ToolResultContent::Text(text) => estimate_tokens(&text.text),
+                                _ => 0,
+                            })
+                            .sum(),
+                        UserContent::Document(document) => match &document.data {
+                            DocumentSourceKind::String(text) => estimate_tokens(text),
+                            _ => 0,
+                        },
+                        _ => 0,
+                    };
+                }
+            }
+            Message::Assistant { content, .. } => {
+                for content in content.iter() {
+                    tokens += match content {
+                        AssistantContent::Text(text) => estimate_tokens(&text.text),
+                        AssistantContent::ToolCall(call) => {
+                            estimate_tokens(&call.function.arguments.to_string())
+                        }
+                        AssistantContent::Reasoning(reasoning) => reasoning
+                            .reasoning
+                            .iter()
+                            .map(|text| estimate_tokens(text))
+                            .sum(),
+                        _ => 0,
+                    };
+                }
+            }
+        }
+    }
+
+    tokens.saturating_add(request.max_tokens.unwrap_or_default() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+
+    #[test]
+    fn test_bucket_allows_burst_then_waits() {
+        let mut bucket = TokenBucket::new(2.0, 1.0);
+        let now = bucket.updated;
+
+        assert_eq!(bucket.reserve(now, 1.0), Duration::ZERO);
+        assert_eq!(bucket.reserve(now, 1.0), Duration::ZERO);
+        assert_eq!(bucket.reserve(now, 1.0), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(now, 1.0), Duration::from_secs(1));
+
+        // Refilled after the debt was paid off and another request's worth of time passed
+        let later = now + Duration::from_millis(1500);
+        assert_eq!(bucket.reserve(later, 1.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_bucket_refill_is_capped() {
+        let mut bucket = TokenBucket::new(1000.0 / 60.0, 60.0);
+        let later = bucket.updated + Duration::from_secs(3600);
+
+        assert_eq!(bucket.reserve(later, 1000.0), Duration::ZERO);
+        assert_eq!(bucket.reserve(later, 500.0), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello"), 2);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+    }
+
+    #[test]
+    fn test_estimate_request_tokens() {
+        let request = completion_request(Message::assistant("Paris."))
+            .message(Message::user("What is the capital of France?"))
+            .preamble("Be concise.".into())
+            .max_tokens(100)
+            .build();
+
+        assert_eq!(estimate_request_tokens(&request), 3 + 8 + 2 + 100);
+    }
+
+    #[test]
+    fn test_requests_per_minute_bucket() {
+        let limiter = RateLimiter::new().with_requests_per_minute(2);
+        let mut buckets = limiter.buckets.lock().unwrap();
+        let bucket = buckets.requests.as_mut().unwrap();
+        let now = bucket.updated;
+
+        assert_eq!(bucket.reserve(now, 1.0), Duration::ZERO);
+        assert_eq!(bucket.reserve(now, 1.0), Duration::ZERO);
+        assert_eq!(bucket.reserve(now, 1.0), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_limiter_does_not_wait() {
+        let limiter = RateLimiter::new();
+        let started = Instant::now();
+        for _ in 0..100 {
+            limiter.acquire(10_000).await;
+        }
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,350 @@
+//! Checks of completion requests against the Converse limits before sending them, so oversized
+//! requests fail with a [`RequestLimitError`] naming the limit instead of a `ValidationException`.
+//!
+//! The checks run after URL images are fetched and are on by default. Limits can be adjusted,
+//! e.g. for models with lower limits, or the checks turned off:
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_LITE)
+//!     .with_request_limits(RequestLimits::default().with_max_images(10).with_max_tools(64));
+//!
+//! let unchecked = client
+//!     .completion_model(AMAZON_NOVA_LITE)
+//!     .with_request_limits(RequestLimits::disabled());
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/conversation-inference-call.html>
+
+use rig::completion::CompletionRequest;
+use rig::message::{DocumentSourceKind, Message, ToolResultContent, UserContent};
+
+use crate::image_fetch::MAX_IMAGE_BYTES;
+use crate::types::errors::RequestLimitError;
+
+/// The most documents accepted in a Converse request.
+pub const MAX_DOCUMENTS: usize = 5;
+/// The largest document accepted by Converse.
+pub const MAX_DOCUMENT_BYTES: usize = 4_500_000;
+/// The most images accepted in a Converse request.
+pub const MAX_IMAGES: usize = 20;
+/// The largest Converse request body accepted by Bedrock.
+pub const MAX_PAYLOAD_BYTES: usize = 20_000_000;
+
+/// The limits completion requests are checked against.
+#[derive(Clone, Debug)]
+pub struct RequestLimits {
+    enabled: bool,
+    max_documents: usize,
+    max_document_bytes: usize,
+    max_images: usize,
+    max_image_bytes: usize,
+    max_tools: Option<usize>,
+    max_payload_bytes: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_documents: MAX_DOCUMENTS,
+            max_document_bytes: MAX_DOCUMENT_BYTES,
+            max_images: MAX_IMAGES,
+            max_image_bytes: MAX_IMAGE_BYTES,
+            max_tools: None,
+            max_payload_bytes: MAX_PAYLOAD_BYTES,
+        }
+    }
+}
+
+impl RequestLimits {
+    /// Send requests without checking them.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_documents(mut self, max_documents: usize) -> Self {
+        self.max_documents = max_documents;
+        self
+    }
+
+    pub fn with_max_document_bytes(mut self, max_document_bytes: usize) -> Self {
+        self.max_document_bytes = max_document_bytes;
+        self
+    }
+
+    pub fn with_max_images(mut self, max_images: usize) -> Self {
+        self.max_images = max_images;
+        self
+    }
+
+    pub fn with_max_image_bytes(mut self, max_image_bytes: usize) -> Self {
+        self.max_image_bytes = max_image_bytes;
+        self
+    }
+
+    /// Reject requests with more than `max_tools` tools. Not limited by default, as the limit
+    /// depends on the model.
+    pub fn with_max_tools(mut self, max_tools: usize) -> Self {
+        self.max_tools = Some(max_tools);
+        self
+    }
+
+    pub fn with_max_payload_bytes(mut self, max_payload_bytes: usize) -> Self {
+        self.max_payload_bytes = max_payload_bytes;
+        self
+    }
+
+    /// Check `request`, failing on the first limit exceeded.
+    pub(crate) fn check(&self, request: &CompletionRequest) -> Result<(), RequestLimitError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if let Some(max) = self.max_tools
+            && request.tools.len() > max
+        {
+            return Err(RequestLimitError::TooManyTools {
+                count: request.tools.len(),
+                max,
+            });
+        }
+
+        let mut documents = 0;
+        let mut images = 0;
+        let mut payload = request.preamble.as_ref().map_or(0, String::len)
+            + request
+                .documents
+                .iter()
+                .map(|document| document.text.len())
+                .sum::<usize>()
+            + request
+                .tools
+                .iter()
+                .map(|tool| serde_json::to_vec(tool).map_or(0, |tool| tool.len()))
+                .sum::<usize>();
+
+        for message in request.chat_history.iter() {
+            let Message::User { content } = message else {
+                payload += serde_json::to_vec(message).map_or(0, |message| message.len());
+                continue;
+            };
+
+            for content in content.iter() {
+                match content {
+                    UserContent::Document(document) => {
+                        documents += 1;
+                        self.check_document(&document.data)?;
+                        payload += encoded_len(&document.data);
+                    }
+                    UserContent::Image(image) => {
+                        images += 1;
+                        self.check_image(&image.data)?;
+                        payload += encoded_len(&image.data);
+                    }
+                    UserContent::Video(video) => payload += encoded_len(&video.data),
+                    UserContent::Audio(audio) => payload += encoded_len(&audio.data),
+                    UserContent::Text(text) => payload += text.text.len(),
+                    UserContent::ToolResult(result) => {
+                        for content in result.content.iter() {
+                            match content {
+                                ToolResultContent::Image(image) => {
+                                    images += 1;
+                                    self.check_image(&image.data)?;
+                                    payload += encoded_len(&image.data);
+                                }
+                                ToolResultContent::Text(text) => payload += text.text.len(),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if documents > self.max_documents {
+            return Err(RequestLimitError::TooManyDocuments {
+                count: documents,
+                max: self.max_documents,
+            });
+        }
+        if images > self.max_images {
+            return Err(RequestLimitError::TooManyImages {
+                count: images,
+                max: self.max_images,
+            });
+        }
+        if payload > self.max_payload_bytes {
+            return Err(RequestLimitError::PayloadTooLarge {
+                bytes: payload,
+                max: self.max_payload_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_document(&self, data: &DocumentSourceKind) -> Result<(), RequestLimitError> {
+        let bytes = decoded_len(data);
+        if bytes > self.max_document_bytes {
+            return Err(RequestLimitError::DocumentTooLarge {
+                bytes,
+                max: self.max_document_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_image(&self, data: &DocumentSourceKind) -> Result<(), RequestLimitError> {
+        let bytes = decoded_len(data);
+        if bytes > self.max_image_bytes {
+            return Err(RequestLimitError::ImageTooLarge {
+                bytes,
+                max: self.max_image_bytes,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The size of the content of a source, as sent to Bedrock.
+fn decoded_len(data: &DocumentSourceKind) -> usize {
+    match data {
+        DocumentSourceKind::Base64(base64) => {
+            let padding = base64
+                .bytes()
+                .rev()
+                .take_while(|byte| *byte == b'=')
+                .count();
+            (base64.len() / 4 * 3).saturating_sub(padding)
+        }
+        DocumentSourceKind::Raw(bytes) => bytes.len(),
+        DocumentSourceKind::String(text) => text.len(),
+        _ => 0,
+    }
+}
+
+/// The size of a source in the request body, where binary content is base64 encoded.
+fn encoded_len(data: &DocumentSourceKind) -> usize {
+    match data {
+        DocumentSourceKind::Raw(bytes) => bytes.len().div_ceil(3) * 4,
+        DocumentSourceKind::Base64(text)
+        | DocumentSourceKind::String(text)
+        | DocumentSourceKind::Url(text) => text.len(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::completion_request;
+    use rig::OneOrMany;
+    use rig::completion::ToolDefinition;
+    use rig::message::{Document, Image};
+
+    fn request(content: Vec<UserContent>) -> CompletionRequest {
+        completion_request(Message::User {
+            content: OneOrMany::many(content).unwrap(),
+        })
+        .build()
+    }
+
+    fn image(bytes: usize) -> UserContent {
+        UserContent::Image(Image {
+            data: DocumentSourceKind::Raw(vec![0; bytes]),
+            media_type: None,
+            detail: None,
+            additional_params: None,
+        })
+    }
+
+    fn document(bytes: usize) -> UserContent {
+        UserContent::Document(Document {
+            data: DocumentSourceKind::Raw(vec![0; bytes]),
+            media_type: None,
+            additional_params: None,
+        })
+    }
+
+    #[test]
+    fn test_within_limits() {
+        let request = request(vec![
+            UserContent::text("Describe"),
+            image(1000),
+            document(1000),
+        ]);
+        assert_eq!(RequestLimits::default().check(&request), Ok(()));
+    }
+
+    #[test]
+    fn test_counts() {
+        assert_eq!(
+            RequestLimits::default().check(&request(vec![document(10); 6])),
+            Err(RequestLimitError::TooManyDocuments { count: 6, max: 5 })
+        );
+        assert_eq!(
+            RequestLimits::default()
+                .with_max_images(2)
+                .check(&request(vec![image(10); 3])),
+            Err(RequestLimitError::TooManyImages { count: 3, max: 2 })
+        );
+
+        let mut with_tools = request(vec![UserContent::text("Hi")]);
+        with_tools.tools = vec![
+            ToolDefinition {
+                name: "add".into(),
+                description: "Add numbers".into(),
+                parameters: serde_json::json!({}),
+            };
+            3
+        ];
+        assert_eq!(RequestLimits::default().check(&with_tools), Ok(()));
+        assert_eq!(
+            RequestLimits::default()
+                .with_max_tools(2)
+                .check(&with_tools),
+            Err(RequestLimitError::TooManyTools { count: 3, max: 2 })
+        );
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(
+            RequestLimits::default().check(&request(vec![image(MAX_IMAGE_BYTES + 1)])),
+            Err(RequestLimitError::ImageTooLarge {
+                bytes: MAX_IMAGE_BYTES + 1,
+                max: MAX_IMAGE_BYTES
+            })
+        );
+        assert_eq!(
+            RequestLimits::default()
+                .with_max_document_bytes(100)
+                .check(&request(vec![document(101)])),
+            Err(RequestLimitError::DocumentTooLarge {
+                bytes: 101,
+                max: 100
+            })
+        );
+        assert_eq!(
+            RequestLimits::default()
+                .with_max_payload_bytes(1000)
+                .check(&request(vec![image(600), image(600)])),
+            Err(RequestLimitError::PayloadTooLarge {
+                bytes: 1600,
+                max: 1000
+            })
+        );
+        assert_eq!(
+            RequestLimits::disabled().check(&request(vec![image(MAX_IMAGE_BYTES + 1)])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_decoded_len() {
+        assert_eq!(decoded_len(&DocumentSourceKind::base64("aGVsbG8=")), 5);
+        assert_eq!(decoded_len(&DocumentSourceKind::base64("aGVsbG8h")), 6);
+    }
+}
@@ -0,0 +1,97 @@
+//! Reranking with the Bedrock `Rerank` operation and the Amazon Rerank or Cohere Rerank models.
+//!
+//! Vector search ranks documents by embedding distance, which is cheap but coarse. Reranking the
+//! retrieved candidates against the query with a cross-encoder model usually surfaces the most
+//! relevant ones, so a common pattern is to retrieve generously and keep the best few after
+//! reranking. The result types in this module are always available; calling Bedrock requires
+//! the `rerank` feature.
+//!
+//! ```rust,ignore
+//! let candidates = index.top_n::<Article>(VectorSearchRequest::builder()
+//!     .query(query)
+//!     .samples(50)
+//!     .build()?).await?;
+//!
+//! let best = client
+//!     .rerank_model(AMAZON_RERANK_1_0)
+//!     .with_number_of_results(5)
+//!     .rerank_top_n(query, candidates)
+//!     .await?;
+//! ```
+//!
+//! <https://docs.aws.amazon.com/bedrock/latest/userguide/rerank.html>
+
+#[cfg(feature = "rerank")]
+mod model;
+
+#[cfg(feature = "rerank")]
+pub use model::RerankModel;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A document with its relevance to the query, from 0 to 1.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RankedDocument<T> {
+    /// The position of the document in the documents that were reranked.
+    pub index: usize,
+    pub relevance_score: f64,
+    pub document: T,
+}
+
+/// The ARN of the foundation model `model` in `region`. Rerank only accepts model ARNs.
+pub fn foundation_model_arn(region: &str, model: &str) -> String {
+    if model.starts_with("arn:") {
+        model.to_string()
+    } else {
+        format!("arn:aws:bedrock:{region}::foundation-model/{model}")
+    }
+}
+
+#[derive(Debug)]
+pub enum RerankError {
+    /// The Bedrock call failed, or its input couldn't be built.
+    Request(Box<dyn std::error::Error + Send + Sync>),
+    /// A document couldn't be serialized.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for RerankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(error) => write!(f, "Rerank request failed: {error}"),
+            Self::Json(error) => write!(f, "Invalid rerank document: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RerankError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(error) => Some(error.as_ref()),
+            Self::Json(error) => Some(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for RerankError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foundation_model_arn() {
+        assert_eq!(
+            foundation_model_arn("us-west-2", "amazon.rerank-v1:0"),
+            "arn:aws:bedrock:us-west-2::foundation-model/amazon.rerank-v1:0"
+        );
+        let arn = "arn:aws:bedrock:eu-central-1::foundation-model/cohere.rerank-v3-5:0";
+        assert_eq!(foundation_model_arn("us-west-2", arn), arn);
+    }
+}
@@ -0,0 +1,163 @@
+use aws_sdk_bedrockagentruntime::error::BuildError;
+use aws_sdk_bedrockagentruntime::types::{
+    BedrockRerankingConfiguration, BedrockRerankingModelConfiguration, RerankDocument,
+    RerankDocumentType, RerankQuery, RerankQueryContentType, RerankSource, RerankSourceType,
+    RerankTextDocument, RerankingConfiguration, RerankingConfigurationType,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{RankedDocument, RerankError, foundation_model_arn};
+use crate::client::{Client, DEFAULT_AWS_REGION};
+use crate::types::errors::BedrockError;
+use crate::types::json::AwsDocument;
+
+/// A Bedrock reranking model. Created with [`Client::rerank_model`].
+#[derive(Clone, Debug)]
+pub struct RerankModel {
+    client: Client,
+    pub model: String,
+    number_of_results: Option<usize>,
+}
+
+impl RerankModel {
+    pub fn new(client: Client, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            number_of_results: None,
+        }
+    }
+
+    /// Keep only the `number_of_results` most relevant documents. Defaults to all of them.
+    pub fn with_number_of_results(mut self, number_of_results: usize) -> Self {
+        self.number_of_results = Some(number_of_results);
+        self
+    }
+
+    /// Rank `documents` by relevance to `query`, most relevant first. Documents serialized to
+    /// JSON strings are ranked as text, others as JSON.
+    pub async fn rerank<T: Serialize>(
+        &self,
+        query: &str,
+        documents: Vec<T>,
+    ) -> Result<Vec<RankedDocument<T>>, RerankError> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sources = documents
+            .iter()
+            .map(|document| source(serde_json::to_value(document)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        let query = RerankQuery::builder()
+            .r#type(RerankQueryContentType::Text)
+            .text_query(RerankTextDocument::builder().text(query).build())
+            .build()
+            .map_err(build_error)?;
+
+        let sdk_config = self.client.sdk_config().await;
+        let region = sdk_config
+            .region()
+            .map(|region| region.as_ref())
+            .unwrap_or(DEFAULT_AWS_REGION);
+        let number_of_results = self
+            .number_of_results
+            .unwrap_or(documents.len())
+            .min(documents.len());
+        let configuration = RerankingConfiguration::builder()
+            .r#type(RerankingConfigurationType::BedrockRerankingModel)
+            .bedrock_reranking_configuration(
+                BedrockRerankingConfiguration::builder()
+                    .number_of_results(number_of_results as i32)
+                    .model_configuration(
+                        BedrockRerankingModelConfiguration::builder()
+                            .model_arn(foundation_model_arn(region, &self.model))
+                            .build()
+                            .map_err(build_error)?,
+                    )
+                    .build()
+                    .map_err(build_error)?,
+            )
+            .build()
+            .map_err(build_error)?;
+
+        let output = aws_sdk_bedrockagentruntime::Client::new(sdk_config)
+            .rerank()
+            .queries(query)
+            .set_sources(Some(sources))
+            .reranking_configuration(configuration)
+            .send()
+            .await
+            .map_err(|e| RerankError::Request(BedrockError::from(e).into()))?;
+
+        let mut documents: Vec<Option<T>> = documents.into_iter().map(Some).collect();
+        Ok(output
+            .results()
+            .iter()
+            .filter_map(|result| {
+                let index = usize::try_from(result.index()).ok()?;
+                Some(RankedDocument {
+                    index,
+                    relevance_score: result.relevance_score() as f64,
+                    document: documents.get_mut(index)?.take()?,
+                })
+            })
+            .collect())
+    }
+
+    /// Rerank the `(score, id, document)` results of a vector search, such as
+    /// [`rig::vector_store::VectorStoreIndex::top_n`], replacing their scores by relevance
+    /// scores.
+    pub async fn rerank_top_n<T: Serialize>(
+        &self,
+        query: &str,
+        results: Vec<(f64, String, T)>,
+    ) -> Result<Vec<(f64, String, T)>, RerankError> {
+        let (ids, documents): (Vec<String>, Vec<T>) = results
+            .into_iter()
+            .map(|(_, id, document)| (id, document))
+            .unzip();
+        let mut ids: Vec<Option<String>> = ids.into_iter().map(Some).collect();
+
+        Ok(self
+            .rerank(query, documents)
+            .await?
+            .into_iter()
+            .filter_map(|ranked| {
+                let id = ids.get_mut(ranked.index)?.take()?;
+                Some((ranked.relevance_score, id, ranked.document))
+            })
+            .collect())
+    }
+}
+
+impl Client {
+    /// The reranking model `model`, e.g. [`crate::completion::AMAZON_RERANK_1_0`].
+    pub fn rerank_model(&self, model: impl Into<String>) -> RerankModel {
+        RerankModel::new(self.clone(), model)
+    }
+}
+
+fn source(document: Value) -> Result<RerankSource, RerankError> {
+    let document = match document {
+        Value::String(text) => RerankDocument::builder()
+            .r#type(RerankDocumentType::Text)
+            .text_document(RerankTextDocument::builder().text(text).build()),
+        json => RerankDocument::builder()
+            .r#type(RerankDocumentType::Json)
+            .json_document(AwsDocument::from(json).0),
+    }
+    .build()
+    .map_err(build_error)?;
+
+    RerankSource::builder()
+        .r#type(RerankSourceType::Inline)
+        .inline_document_source(document)
+        .build()
+        .map_err(build_error)
+}
+
+fn build_error(error: BuildError) -> RerankError {
+    RerankError::Request(error.into())
+}
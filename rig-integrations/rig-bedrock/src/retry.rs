@@ -0,0 +1,402 @@
+//! Exponential backoff with jitter for transient Bedrock errors such as throttling.
+//!
+//! When a throttled call tells how long to wait with a `Retry-After` header, the retry waits that
+//! long instead, up to the policy's `max_delay`.
+//!
+//! Embedding models retry with their [`RetryPolicy`]. Any completion model can be wrapped in a
+//! [`RetryingCompletionModel`], which retries on top of the SDK's own retries:
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_LITE)
+//!     .with_retries(RetryPolicy::new(8).with_budget(Duration::from_secs(60)));
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use rig::completion::{self, CompletionError, CompletionRequest, CompletionResponse};
+use rig::streaming::StreamingCompletionResponse;
+use tokio::time::Instant;
+
+use crate::types::errors::{BedrockError, BedrockErrorKind};
+
+/// How often and how long to wait before retrying a request that failed with a transient error.
+///
+/// Delays grow exponentially from `initial_delay` up to `max_delay`, and a random "full jitter"
+/// is applied so concurrent callers don't retry in lockstep.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt. `0` disables retries.
+    pub max_retries: u32,
+    /// Upper bound of the delay before the first retry.
+    pub initial_delay: Duration,
+    /// Upper bound of the delay between any two attempts.
+    pub max_delay: Duration,
+    /// Total time after which no more retries are attempted, counted from the first attempt.
+    pub budget: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(20),
+            budget: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy with `max_retries` retries.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Don't retry when the retry would start more than `budget` after the first attempt.
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// The delay before retry number `attempt` (starting at 0): a random duration between zero
+    /// and `min(max_delay, initial_delay * 2^attempt)`.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        ceiling.mul_f64(random_fraction())
+    }
+
+    /// Run `operation`, retrying it while it fails with an error for which `is_retryable` is true,
+    /// after the delay `retry_after` returns for the error if any.
+    pub(crate) async fn retry<T, E, F, Fut>(
+        &self,
+        is_retryable: impl Fn(&E) -> bool,
+        retry_after: impl Fn(&E) -> Option<Duration>,
+        mut operation: F,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Err(error) if attempt < self.max_retries && is_retryable(&error) => {
+                    let delay = match retry_after(&error) {
+                        Some(retry_after) => retry_after.min(self.max_delay),
+                        None => self.delay(attempt),
+                    };
+                    if self
+                        .budget
+                        .is_some_and(|budget| started.elapsed() + delay > budget)
+                    {
+                        tracing::warn!(
+                            target: "rig::bedrock",
+                            "Transient Bedrock error, retry budget exhausted after {} retries",
+                            attempt
+                        );
+                        return Err(error);
+                    }
+                    tracing::warn!(
+                        target: "rig::bedrock",
+                        "Transient Bedrock error, retrying in {delay:?} (retry {} of {})",
+                        attempt + 1,
+                        self.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A completion model retrying requests throttled by Bedrock, or sent while the model or the
+/// service is unavailable, following a [`RetryPolicy`].
+///
+/// Streaming requests are only retried until the stream starts.
+#[derive(Clone, Debug)]
+pub struct RetryingCompletionModel<M> {
+    model: M,
+    policy: RetryPolicy,
+}
+
+impl<M> RetryingCompletionModel<M> {
+    pub fn new(model: M, policy: RetryPolicy) -> Self {
+        Self { model, policy }
+    }
+
+    /// The wrapped model.
+    pub fn inner(&self) -> &M {
+        &self.model
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+impl<M> completion::CompletionModel for RetryingCompletionModel<M>
+where
+    M: completion::CompletionModel,
+{
+    type Response = M::Response;
+    type StreamingResponse = M::StreamingResponse;
+
+    type Client = M::Client;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self::new(M::make(client, model), RetryPolicy::default())
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
+        self.policy
+            .retry(
+                is_retryable_completion_error,
+                completion_retry_after,
+                || self.model.completion(request.clone()),
+            )
+            .await
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
+        self.policy
+            .retry(
+                is_retryable_completion_error,
+                completion_retry_after,
+                || self.model.stream(request.clone()),
+            )
+            .await
+    }
+}
+
+/// Whether a completion failed because Bedrock throttled it, or the model or the service
+/// wasn't available yet.
+pub fn is_retryable_completion_error(error: &CompletionError) -> bool {
+    BedrockError::from_completion_error(error).is_some_and(|error| {
+        matches!(
+            error.kind(),
+            BedrockErrorKind::Throttled
+                | BedrockErrorKind::ServiceUnavailable
+                | BedrockErrorKind::ModelNotReady
+        )
+    })
+}
+
+/// How long Bedrock asked to wait before retrying a throttled completion, if it did.
+pub fn completion_retry_after(error: &CompletionError) -> Option<Duration> {
+    BedrockError::from_completion_error(error).and_then(BedrockError::retry_after)
+}
+
+/// A random number in `[0, 1)`, without pulling in an RNG dependency.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy::new(max_retries)
+            .with_initial_delay(Duration::from_millis(1))
+            .with_max_delay(Duration::from_millis(2))
+    }
+
+    #[test]
+    fn test_delay_is_bounded() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..40 {
+            let ceiling =
+                (policy.initial_delay * 2u32.saturating_pow(attempt)).min(policy.max_delay);
+            assert!(policy.delay(attempt) <= ceiling);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let calls = AtomicU32::new(0);
+        let result: Result<u32, &str> = fast_policy(3)
+            .retry(
+                |_| true,
+                |_| None,
+                || async {
+                    match calls.fetch_add(1, Ordering::SeqCst) {
+                        0 | 1 => Err("throttled"),
+                        n => Ok(n),
+                    }
+                },
+            )
+            .await;
+
+        assert_eq!(result, Ok(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), &str> = fast_policy(2)
+            .retry(
+                |_| true,
+                |_| None,
+                || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err("throttled")
+                },
+            )
+            .await;
+
+        assert_eq!(result, Err("throttled"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stops_retrying_when_budget_is_exhausted() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(5)
+            .with_initial_delay(Duration::from_millis(40))
+            .with_max_delay(Duration::from_millis(40))
+            .with_budget(Duration::ZERO);
+        let result: Result<(), &str> = policy
+            .retry(
+                |_| true,
+                |_| None,
+                || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err("throttled")
+                },
+            )
+            .await;
+
+        assert_eq!(result, Err("throttled"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retryable_completion_errors() {
+        use aws_sdk_bedrockruntime::config::http::HttpResponse;
+        use aws_sdk_bedrockruntime::error::{ErrorMetadata, SdkError};
+        use aws_sdk_bedrockruntime::operation::converse::ConverseError;
+        use aws_smithy_types::body::SdkBody;
+
+        let error = |code: &str| {
+            let service_error = ConverseError::generic(ErrorMetadata::builder().code(code).build());
+            let response = HttpResponse::new(400.try_into().unwrap(), SdkBody::empty());
+            CompletionError::from(BedrockError::from(SdkError::service_error(
+                service_error,
+                response,
+            )))
+        };
+
+        assert!(is_retryable_completion_error(&error("ThrottlingException")));
+        assert!(is_retryable_completion_error(&error(
+            "ServiceUnavailableException"
+        )));
+        assert!(is_retryable_completion_error(&error(
+            "ModelNotReadyException"
+        )));
+        assert!(!is_retryable_completion_error(&error(
+            "ValidationException"
+        )));
+        assert!(!is_retryable_completion_error(
+            &CompletionError::ProviderError("throttled".into())
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_retry_after_hints() {
+        use aws_sdk_bedrockruntime::config::http::HttpResponse;
+        use aws_sdk_bedrockruntime::error::{ErrorMetadata, SdkError};
+        use aws_sdk_bedrockruntime::operation::converse::ConverseError;
+        use aws_smithy_types::body::SdkBody;
+
+        let service_error =
+            ConverseError::generic(ErrorMetadata::builder().code("ThrottlingException").build());
+        let mut response = HttpResponse::new(429.try_into().unwrap(), SdkBody::empty());
+        response.headers_mut().insert("Retry-After", "0.005");
+        let error = CompletionError::from(BedrockError::from(SdkError::service_error(
+            service_error,
+            response,
+        )));
+        assert_eq!(
+            completion_retry_after(&error),
+            Some(Duration::from_millis(5))
+        );
+
+        // Without the hint, the first retry could wait up to a minute
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(1)
+            .with_initial_delay(Duration::from_secs(60))
+            .with_max_delay(Duration::from_secs(60));
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            policy.retry(
+                |_| true,
+                |_| completion_retry_after(&error),
+                || async {
+                    match calls.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err("throttled"),
+                        n => Ok(n),
+                    }
+                },
+            ),
+        )
+        .await;
+
+        assert_eq!(result, Ok(Ok(1)));
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_permanent_errors() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), &str> = fast_policy(5)
+            .retry(
+                |error| *error == "throttled",
+                |_| None,
+                || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err("validation")
+                },
+            )
+            .await;
+
+        assert_eq!(result, Err("validation"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Server-Sent Events adapter for Bedrock streaming completions.
+//!
+//! [`sse_events`] turns a streaming completion into a stream of [`SseEvent`]s that web frameworks
+//! can forward to browsers, e.g. with axum:
+//!
+//! ```rust,ignore
+//! let response = agent.stream_completion(prompt, vec![]).await?.stream().await?;
+//! let events = rig_bedrock::sse::sse_events(response).map(|event| {
+//!     Ok::<_, Infallible>(Event::default().event(event.event_name()).data(event.data()))
+//! });
+//! Sse::new(events)
+//! ```
+
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use rig::streaming::{StreamedAssistantContent, StreamingCompletionResponse};
+use serde::Serialize;
+
+use crate::streaming::{BedrockStreamingResponse, BedrockUsage};
+use crate::types::converse_output::StopReason;
+
+/// A streaming completion event, ready to be sent as a Server-Sent Event.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SseEvent {
+    /// A chunk of generated text.
+    TextDelta { text: String },
+    /// A chunk of the model's reasoning.
+    ReasoningDelta { reasoning: String },
+    /// A chunk of a tool call's JSON arguments.
+    ToolCallDelta { id: String, delta: String },
+    /// A complete tool call.
+    ToolCall {
+        id: String,
+        name: String,
+        arguments: serde_json::Value,
+    },
+    /// The stream finished successfully. Always the last event unless an error occurred.
+    Done {
+        usage: Option<BedrockUsage>,
+        stop_reason: Option<StopReason>,
+    },
+    /// The stream failed; no further events are sent.
+    Error { message: String },
+}
+
+impl SseEvent {
+    /// The SSE `event:` name.
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            Self::TextDelta { .. } => "text_delta",
+            Self::ReasoningDelta { .. } => "reasoning_delta",
+            Self::ToolCallDelta { .. } => "tool_call_delta",
+            Self::ToolCall { .. } => "tool_call",
+            Self::Done { .. } => "done",
+            Self::Error { .. } => "error",
+        }
+    }
+
+    /// The SSE `data:` payload, as JSON.
+    pub fn data(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            serde_json::json!({ "type": "error", "message": e.to_string() }).to_string()
+        })
+    }
+
+    /// The full wire representation of the event, for frameworks without SSE helpers.
+    pub fn to_sse_string(&self) -> String {
+        format!("event: {}\ndata: {}\n\n", self.event_name(), self.data())
+    }
+}
+
+/// Convert a Bedrock streaming completion into a stream of [`SseEvent`]s.
+///
+/// The stream ends with [`SseEvent::Done`], or with [`SseEvent::Error`] if the completion failed.
+pub fn sse_events(
+    mut response: StreamingCompletionResponse<BedrockStreamingResponse>,
+) -> impl Stream<Item = SseEvent> + Send {
+    stream! {
+        let mut final_response: Option<BedrockStreamingResponse> = None;
+
+        while let Some(item) = response.next().await {
+            match item {
+                Ok(StreamedAssistantContent::Text(text)) => {
+                    yield SseEvent::TextDelta { text: text.text }
+                },
+                Ok(StreamedAssistantContent::ReasoningDelta { reasoning, .. }) => {
+                    yield SseEvent::ReasoningDelta { reasoning }
+                },
+                Ok(StreamedAssistantContent::ToolCallDelta { id, delta }) => {
+                    yield SseEvent::ToolCallDelta { id, delta }
+                },
+                Ok(StreamedAssistantContent::ToolCall(tool_call)) => {
+                    yield SseEvent::ToolCall {
+                        id: tool_call.id,
+                        name: tool_call.function.name,
+                        arguments: tool_call.function.arguments,
+                    }
+                },
+                Ok(StreamedAssistantContent::Final(response)) => final_response = Some(response),
+                // Reasoning was already sent as deltas
+                Ok(StreamedAssistantContent::Reasoning(_)) => {},
+                Err(error) => {
+                    yield SseEvent::Error { message: error.to_string() };
+                    return;
+                }
+            }
+        }
+
+        let (usage, stop_reason) = final_response
+            .map(|response| (response.usage, response.stop_reason))
+            .unwrap_or_default();
+        yield SseEvent::Done { usage, stop_reason };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rig::completion::CompletionError;
+    use rig::streaming::{RawStreamingChoice, RawStreamingToolCall};
+
+    fn response(
+        choices: Vec<Result<RawStreamingChoice<BedrockStreamingResponse>, CompletionError>>,
+    ) -> StreamingCompletionResponse<BedrockStreamingResponse> {
+        StreamingCompletionResponse::stream(Box::pin(futures::stream::iter(choices)))
+    }
+
+    #[tokio::test]
+    async fn test_sse_events() {
+        let events: Vec<SseEvent> = sse_events(response(vec![
+            Ok(RawStreamingChoice::Message("Hello".into())),
+            Ok(RawStreamingChoice::ToolCall(RawStreamingToolCall::new(
+                "tool_1".into(),
+                "get_weather".into(),
+                serde_json::json!({ "city": "Paris" }),
+            ))),
+            Ok(RawStreamingChoice::FinalResponse(
+                BedrockStreamingResponse {
+                    usage: Some(BedrockUsage {
+                        input_tokens: 10,
+                        output_tokens: 5,
+                        total_tokens: 15,
+                        ..Default::default()
+                    }),
+                    stop_reason: Some(StopReason::ToolUse),
+                    ..Default::default()
+                },
+            )),
+        ]))
+        .collect()
+        .await;
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0].to_sse_string(),
+            "event: text_delta\ndata: {\"type\":\"text_delta\",\"text\":\"Hello\"}\n\n"
+        );
+        assert!(matches!(&events[1], SseEvent::ToolCall { name, .. } if name == "get_weather"));
+        assert!(matches!(
+            &events[2],
+            SseEvent::Done { usage: Some(usage), stop_reason: Some(StopReason::ToolUse) }
+                if usage.total_tokens == 15
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sse_events_stop_after_error() {
+        let events: Vec<SseEvent> = sse_events(response(vec![
+            Ok(RawStreamingChoice::Message("Partial".into())),
+            Err(CompletionError::ProviderError("Throttled".into())),
+            Ok(RawStreamingChoice::Message("Never sent".into())),
+        ]))
+        .collect()
+        .await;
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].event_name(), "error");
+        assert!(events[1].data().contains("Throttled"));
+    }
+}
@@ -1,7 +1,20 @@
+use crate::guardrails::{GuardrailConfig, GuardrailInterventionError};
+use crate::metrics::InvocationMetrics;
+use crate::prefill::Prefill;
+use crate::pricing::{CostEstimate, CostTracker};
+use crate::telemetry;
+use crate::think_tags::ThinkTagSplitter;
+use crate::tool_names::ToolNames;
 use crate::types::completion_request::AwsCompletionRequest;
-use crate::{completion::CompletionModel, types::errors::AwsSdkConverseStreamError};
+use crate::types::converse_output::{ConverseMetrics, ConverseTrace, StopReason, TokenUsage};
+use crate::{
+    completion::{CompletionApi, CompletionModel, PreparedCall, record_usage},
+    types::errors::{AwsSdkConverseStreamError, BedrockError},
+};
 use async_stream::stream;
+use aws_sdk_bedrockruntime::operation::converse_stream::ConverseStreamOutput as ConverseStreamResponse;
 use aws_sdk_bedrockruntime::types as aws_bedrock;
+use futures::Stream;
 use rig::completion::GetTokenUsage;
 use rig::streaming::StreamingCompletionResponse;
 use rig::{
@@ -9,17 +22,41 @@ use rig::{
     streaming::{RawStreamingChoice, RawStreamingToolCall},
 };
 use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, watch};
 
-#[derive(Clone, Deserialize, Serialize)]
+/// Final item of a Bedrock stream, built from the trailing `metadata` event of ConverseStream.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct BedrockStreamingResponse {
     pub usage: Option<BedrockUsage>,
+    /// The reason why the model stopped generating output.
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
+    /// Latency reported by Bedrock for the streamed call.
+    #[serde(default)]
+    pub metrics: Option<ConverseMetrics>,
+    /// Guardrail and prompt router trace, when tracing is enabled for the request.
+    #[serde(default)]
+    pub trace: Option<ConverseTrace>,
+    /// Cost of the call estimated from its usage, if the model's pricing is known.
+    #[serde(default)]
+    pub estimated_cost: Option<CostEstimate>,
+    /// The model id or inference profile that served the call, see
+    /// [`CompletionModel::with_fallback_models`].
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct BedrockUsage {
     pub input_tokens: i32,
     pub output_tokens: i32,
     pub total_tokens: i32,
+    #[serde(default)]
+    pub cache_read_input_tokens: Option<i32>,
+    #[serde(default)]
+    pub cache_write_input_tokens: Option<i32>,
 }
 
 impl GetTokenUsage for BedrockStreamingResponse {
@@ -32,6 +69,96 @@ impl GetTokenUsage for BedrockStreamingResponse {
     }
 }
 
+impl From<TokenUsage> for BedrockUsage {
+    fn from(usage: TokenUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+            cache_write_input_tokens: usage.cache_write_input_tokens,
+        }
+    }
+}
+
+impl From<aws_bedrock::TokenUsage> for BedrockUsage {
+    fn from(usage: aws_bedrock::TokenUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+            cache_write_input_tokens: usage.cache_write_input_tokens,
+        }
+    }
+}
+
+/// Cloneable handle used to stop in-flight streaming completions, e.g. from a "stop generating"
+/// button running on another task.
+///
+/// Calling [`StreamCancellation::cancel`] ends every stream that was started with this handle and
+/// is still running: the Bedrock event stream is dropped and its connection released. Streams
+/// started afterwards are not affected, so the same handle can be reused across turns.
+#[derive(Clone, Debug)]
+pub struct StreamCancellation {
+    generation: Arc<watch::Sender<u64>>,
+}
+
+impl StreamCancellation {
+    pub fn new() -> Self {
+        let (generation, _) = watch::channel(0);
+        Self {
+            generation: Arc::new(generation),
+        }
+    }
+
+    /// Stop all in-flight streams attached to this handle.
+    pub fn cancel(&self) {
+        self.generation.send_modify(|generation| *generation += 1);
+    }
+
+    /// Returns a receiver that is notified by the next call to [`StreamCancellation::cancel`].
+    pub(crate) fn subscribe(&self) -> watch::Receiver<u64> {
+        self.generation.subscribe()
+    }
+}
+
+impl Default for StreamCancellation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Await `next`, or return `None` if [`StreamCancellation::cancel`] is called first.
+pub(crate) async fn next_or_cancelled<T>(
+    cancelled: Option<&mut watch::Receiver<u64>>,
+    next: impl Future<Output = T>,
+) -> Option<T> {
+    match cancelled {
+        Some(cancelled) => tokio::select! {
+            biased;
+            Ok(()) = cancelled.changed() => None,
+            next = next => Some(next),
+        },
+        None => Some(next.await),
+    }
+}
+
+/// The events of a ConverseStream call as sent by Bedrock, see [`CompletionModel::raw_stream`].
+pub type ConverseStreamEvents =
+    Pin<Box<dyn Stream<Item = Result<aws_bedrock::ConverseStreamOutput, CompletionError>> + Send>>;
+
+/// A sent ConverseStream call, with what is held until its stream ends.
+struct ConverseStreamCall {
+    response: ConverseStreamResponse,
+    permit: Option<OwnedSemaphorePermit>,
+    span: tracing::Span,
+    metrics: InvocationMetrics,
+    cancelled: Option<watch::Receiver<u64>>,
+    tool_names: ToolNames,
+    prefill: Prefill,
+}
+
 #[derive(Default)]
 struct ToolCallState {
     name: String,
@@ -50,40 +177,64 @@ impl CompletionModel {
         &self,
         completion_request: rig::completion::CompletionRequest,
     ) -> Result<StreamingCompletionResponse<BedrockStreamingResponse>, CompletionError> {
-        let request = AwsCompletionRequest(completion_request);
-
-        let mut converse_builder = self
-            .client
-            .get_inner()
-            .await
-            .converse_stream()
-            .model_id(self.model.as_str());
-
-        let tool_config = request.tools_config()?;
-        let prompt_with_history = request.messages()?;
-        converse_builder = converse_builder
-            .set_additional_model_request_fields(request.additional_params())
-            .set_inference_config(request.inference_config())
-            .set_tool_config(tool_config)
-            .set_system(request.system_prompt())
-            .set_messages(Some(prompt_with_history));
-
-        let response = converse_builder.send().await.map_err(|sdk_error| {
-            Into::<CompletionError>::into(AwsSdkConverseStreamError(sdk_error))
-        })?;
+        if self.api == CompletionApi::Native {
+            return self.stream_native(completion_request).await;
+        }
 
+        let tool_validation = self
+            .tool_call_validation
+            .map(|validation| (validation, completion_request.tools.clone()));
+        let ConverseStreamCall {
+            response,
+            permit,
+            span,
+            metrics,
+            mut cancelled,
+            tool_names,
+            mut prefill,
+        } = self.send_converse_stream(completion_request).await?;
+        let cost_tracker = self.cost_tracker.clone();
+        let mut think_tags = self.think_tags.then(ThinkTagSplitter::default);
+
+        let model = self.model.clone();
         let stream = Box::pin(stream! {
+            let _permit = permit;
             let mut current_tool_call: Option<ToolCallState> = None;
             let mut current_reasoning: Option<ReasoningState> = None;
+            let mut stop_reason: Option<StopReason> = None;
+            // The streamed text, for the error of a guardrail intervention
+            let mut text_output = String::new();
             let mut stream = response.stream;
-            while let Ok(Some(output)) = stream.recv().await {
+            loop {
+                let output = match next_or_cancelled(cancelled.as_mut(), stream.recv()).await {
+                    Some(Ok(Some(output))) => output,
+                    Some(Ok(None)) | None => break,
+                    Some(Err(error)) => {
+                        let error = BedrockError::from(error);
+                        telemetry::record_error(&span, &error);
+                        metrics.failure(Some(&error));
+                        yield Err(error.into());
+                        break;
+                    }
+                };
+
                 match output {
                     aws_bedrock::ConverseStreamOutput::ContentBlockDelta(event) => {
                         let delta = event.delta.ok_or(CompletionError::ProviderError("The delta for a content block is missing".into()))?;
                         match delta {
                             aws_bedrock::ContentBlockDelta::Text(text) => {
-                                if current_tool_call.is_none() {
-                                    yield Ok(RawStreamingChoice::Message(text))
+                                if current_tool_call.is_some() {
+                                    continue;
+                                }
+                                let text = prefill.restore_chunk(text);
+                                text_output.push_str(&text);
+                                match think_tags.as_mut() {
+                                    Some(splitter) => {
+                                        for choice in splitter.push(&text) {
+                                            yield Ok(choice);
+                                        }
+                                    }
+                                    None => yield Ok(RawStreamingChoice::Message(text)),
                                 }
                             },
                             aws_bedrock::ContentBlockDelta::ToolUse(tool) => {
@@ -135,7 +286,7 @@ impl CompletionModel {
                         match event.start.ok_or(CompletionError::ProviderError("ContentBlockStart has no data".into()))? {
                             aws_bedrock::ContentBlockStart::ToolUse(tool_use) => {
                                 current_tool_call = Some(ToolCallState {
-                                    name: tool_use.name,
+                                    name: tool_names.restore(tool_use.name),
                                     id: tool_use.tool_use_id,
                                     input_json: String::new(),
                                 });
@@ -154,6 +305,15 @@ impl CompletionModel {
                             }
                     },
                     aws_bedrock::ConverseStreamOutput::MessageStop(message_stop_event) => {
+                        if let Some(splitter) = think_tags.as_mut() {
+                            for choice in splitter.finish() {
+                                yield Ok(choice);
+                            }
+                        }
+                        stop_reason = message_stop_event.stop_reason.clone().try_into().ok();
+                        if let Some(stop_reason) = &stop_reason {
+                            telemetry::record_finish_reason(&span, stop_reason);
+                        }
                         match message_stop_event.stop_reason {
                             aws_bedrock::StopReason::ToolUse => {
                                 if let Some(tool_call) = current_tool_call.take() {
@@ -163,6 +323,13 @@ impl CompletionModel {
                                     } else {
                                         serde_json::from_str(tool_call.input_json.as_str())?
                                     };
+                                    if let Some((validation, tools)) = &tool_validation {
+                                        let call = rig::message::ToolCall::new(
+                                            tool_call.id.clone(),
+                                            rig::message::ToolFunction::new(tool_call.name.clone(), tool_input.clone()),
+                                        );
+                                        validation.check_tool_call(tools, &call)?;
+                                    }
                                     yield Ok(RawStreamingChoice::ToolCall(RawStreamingToolCall::new(tool_call.id, tool_call.name, tool_input)));
                                 } else {
                                     yield Err(CompletionError::ProviderError("Failed to call tool".into()))
@@ -175,15 +342,31 @@ impl CompletionModel {
                         }
                     },
                     aws_bedrock::ConverseStreamOutput::Metadata(metadata_event) => {
-                        // Extract usage information from metadata
-                        if let Some(usage) = metadata_event.usage {
-                            yield Ok(RawStreamingChoice::FinalResponse(BedrockStreamingResponse {
-                                usage: Some(BedrockUsage {
-                                    input_tokens: usage.input_tokens,
-                                    output_tokens: usage.output_tokens,
-                                    total_tokens: usage.total_tokens,
-                                }),
-                            }));
+                        // The metadata event is always the last one, so surface usage, metrics and trace as the final response
+                        let estimated_cost = record_metadata(&span, &metrics, &cost_tracker, &metadata_event);
+                        let response = BedrockStreamingResponse {
+                            usage: metadata_event.usage.map(BedrockUsage::from),
+                            stop_reason: stop_reason.take(),
+                            metrics: metadata_event
+                                .metrics
+                                .map(TryInto::try_into)
+                                .transpose()
+                                .map_err(|x| CompletionError::ProviderError(format!("Type conversion error: {x}")))?,
+                            trace: metadata_event
+                                .trace
+                                .map(TryInto::try_into)
+                                .transpose()
+                                .map_err(|x| CompletionError::ProviderError(format!("Type conversion error: {x}")))?,
+                            estimated_cost,
+                            model: Some(model.clone()),
+                        };
+                        let intervention = (response.stop_reason == Some(StopReason::GuardrailIntervened)).then(|| {
+                            let assessment = response.trace.as_ref().and_then(|trace| trace.guardrail.clone());
+                            GuardrailInterventionError::new(std::mem::take(&mut text_output), assessment)
+                        });
+                        yield Ok(RawStreamingChoice::FinalResponse(response));
+                        if let Some(intervention) = intervention {
+                            yield Err(intervention.into());
                         }
                     },
                     _ => {}
@@ -193,6 +376,155 @@ impl CompletionModel {
 
         Ok(StreamingCompletionResponse::stream(stream))
     }
+
+    /// Stream a completion through ConverseStream, yielding its events as sent by Bedrock instead
+    /// of rig's streaming choices, for custom handling of content block boundaries, citations or
+    /// guardrail traces.
+    ///
+    /// Exceptions sent in the middle of the stream, such as a `ModelStreamErrorException`, end it
+    /// with a [`BedrockError`]. Usage, latency and cost are recorded from the `metadata` event like
+    /// for other streams, and [`StreamCancellation`] ends the stream as well.
+    ///
+    /// ```rust,ignore
+    /// let mut events = model.raw_stream(request).await?;
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         ConverseStreamOutput::ContentBlockStart(start) => { /* ... */ }
+    ///         ConverseStreamOutput::ContentBlockDelta(delta) => { /* ... */ }
+    ///         _ => {}
+    ///     }
+    /// }
+    /// ```
+    pub async fn raw_stream(
+        &self,
+        completion_request: rig::completion::CompletionRequest,
+    ) -> Result<ConverseStreamEvents, CompletionError> {
+        let ConverseStreamCall {
+            response,
+            permit,
+            span,
+            metrics,
+            mut cancelled,
+            ..
+        } = self.send_converse_stream(completion_request).await?;
+        let cost_tracker = self.cost_tracker.clone();
+
+        Ok(Box::pin(stream! {
+            let _permit = permit;
+            let mut stream = response.stream;
+            loop {
+                let Some(next) = next_or_cancelled(cancelled.as_mut(), stream.recv()).await else {
+                    break;
+                };
+
+                match next {
+                    Ok(Some(event)) => {
+                        match &event {
+                            aws_bedrock::ConverseStreamOutput::MessageStop(message_stop_event) => {
+                                if let Ok(stop_reason) = message_stop_event.stop_reason.clone().try_into() {
+                                    telemetry::record_finish_reason(&span, &stop_reason);
+                                }
+                            }
+                            aws_bedrock::ConverseStreamOutput::Metadata(metadata_event) => {
+                                record_metadata(&span, &metrics, &cost_tracker, metadata_event);
+                            }
+                            _ => {}
+                        }
+                        yield Ok(event);
+                    }
+                    Ok(None) => break,
+                    Err(error) => {
+                        let error = BedrockError::from(error);
+                        telemetry::record_error(&span, &error);
+                        metrics.failure(Some(&error));
+                        yield Err(error.into());
+                        break;
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Send a ConverseStream request, failing if Bedrock rejects it.
+    async fn send_converse_stream(
+        &self,
+        completion_request: rig::completion::CompletionRequest,
+    ) -> Result<ConverseStreamCall, CompletionError> {
+        let PreparedCall {
+            request,
+            tool_names,
+            prefill,
+        } = self.prepare_call(completion_request).await?;
+        let request = AwsCompletionRequest(request);
+        let cancelled = self
+            .stream_cancellation
+            .as_ref()
+            .map(StreamCancellation::subscribe);
+
+        let mut converse_builder = self
+            .client
+            .get_inner()
+            .await
+            .converse_stream()
+            .model_id(self.model.as_str());
+
+        let tool_config = self.tools_config(&request)?;
+        converse_builder = converse_builder
+            .set_additional_model_request_fields(request.additional_params())
+            .set_inference_config(request.inference_config())
+            .set_tool_config(tool_config)
+            .set_system(self.system_prompt(&request)?)
+            .set_messages(Some(self.messages(request)?))
+            .set_request_metadata(self.request_metadata())
+            .set_guardrail_config(
+                self.guardrail
+                    .as_ref()
+                    .map(GuardrailConfig::converse_stream)
+                    .transpose()?,
+            );
+
+        // Held until the stream ends
+        let permit = self.client.concurrency.acquire_completion().await;
+        let span = telemetry::chat_streaming_span(&self.model);
+        let metrics = InvocationMetrics::start("chat_streaming", &self.model);
+        let mut operation = converse_builder.customize();
+        if let Some(debug_logging) = &self.debug_logging {
+            operation = operation.interceptor(debug_logging.clone());
+        }
+        let response = self
+            .send_call(&span, &metrics, async {
+                operation
+                    .send()
+                    .await
+                    .map_err(|sdk_error| AwsSdkConverseStreamError(sdk_error).into())
+            })
+            .await?;
+
+        Ok(ConverseStreamCall {
+            response,
+            permit,
+            span,
+            metrics,
+            cancelled,
+            tool_names,
+            prefill,
+        })
+    }
+}
+
+/// Record the usage and latency of a stream from its trailing `metadata` event, returning its
+/// estimated cost.
+fn record_metadata(
+    span: &tracing::Span,
+    metrics: &InvocationMetrics,
+    cost_tracker: &CostTracker,
+    metadata_event: &aws_bedrock::ConverseStreamMetadataEvent,
+) -> Option<CostEstimate> {
+    if let Some(metrics) = &metadata_event.metrics {
+        telemetry::record_latency(span, metrics.latency_ms);
+    }
+    let usage = metadata_event.usage.clone().map(BedrockUsage::from);
+    record_usage(span, metrics, cost_tracker, usage.as_ref())
 }
 
 #[cfg(test)]
@@ -205,6 +537,7 @@ mod tests {
             input_tokens: 100,
             output_tokens: 50,
             total_tokens: 150,
+            ..Default::default()
         };
 
         assert_eq!(usage.input_tokens, 100);
@@ -219,7 +552,9 @@ mod tests {
                 input_tokens: 200,
                 output_tokens: 75,
                 total_tokens: 275,
+                ..Default::default()
             }),
+            ..Default::default()
         };
 
         let rig_usage = response.token_usage();
@@ -233,7 +568,7 @@ mod tests {
 
     #[test]
     fn test_bedrock_streaming_response_without_usage() {
-        let response = BedrockStreamingResponse { usage: None };
+        let response = BedrockStreamingResponse::default();
 
         let rig_usage = response.token_usage();
         assert!(rig_usage.is_none());
@@ -246,7 +581,9 @@ mod tests {
                 input_tokens: 448,
                 output_tokens: 68,
                 total_tokens: 516,
+                ..Default::default()
             }),
+            ..Default::default()
         };
 
         // Test that GetTokenUsage trait is properly implemented
@@ -262,6 +599,7 @@ mod tests {
             input_tokens: 100,
             output_tokens: 50,
             total_tokens: 150,
+            ..Default::default()
         };
 
         // Test serialization
@@ -284,7 +622,9 @@ mod tests {
                 input_tokens: 200,
                 output_tokens: 75,
                 total_tokens: 275,
+                ..Default::default()
             }),
+            ..Default::default()
         };
 
         // Test serialization
@@ -301,6 +641,82 @@ mod tests {
         assert_eq!(usage.total_tokens, 275);
     }
 
+    #[test]
+    fn test_bedrock_usage_from_sdk_usage_keeps_cache_tokens() {
+        let sdk_usage = aws_bedrock::TokenUsage::builder()
+            .input_tokens(120)
+            .output_tokens(30)
+            .total_tokens(150)
+            .cache_read_input_tokens(100)
+            .cache_write_input_tokens(20)
+            .build()
+            .expect("Token usage should build");
+
+        let usage = BedrockUsage::from(sdk_usage);
+        assert_eq!(usage.total_tokens, 150);
+        assert_eq!(usage.cache_read_input_tokens, Some(100));
+        assert_eq!(usage.cache_write_input_tokens, Some(20));
+    }
+
+    #[test]
+    fn test_bedrock_streaming_response_metadata_serde() {
+        let response = BedrockStreamingResponse {
+            stop_reason: Some(StopReason::EndTurn),
+            metrics: Some(ConverseMetrics { latency_ms: 412 }),
+            trace: Some(ConverseTrace {
+                guardrail: None,
+                prompt_router: Some(crate::types::converse_output::PromptRouterTrace {
+                    invoked_model_id: Some("amazon.nova-lite-v1:0".into()),
+                }),
+            }),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&response).expect("Should serialize");
+        let deserialized: BedrockStreamingResponse =
+            serde_json::from_str(&json).expect("Should deserialize");
+        assert_eq!(deserialized.stop_reason, Some(StopReason::EndTurn));
+        assert_eq!(
+            deserialized.metrics,
+            Some(ConverseMetrics { latency_ms: 412 })
+        );
+        assert_eq!(deserialized.trace, response.trace);
+
+        // Responses serialized before metadata was captured still deserialize
+        let legacy: BedrockStreamingResponse = serde_json::from_str(
+            r#"{"usage":{"input_tokens":1,"output_tokens":2,"total_tokens":3}}"#,
+        )
+        .expect("Should deserialize");
+        assert!(legacy.stop_reason.is_none());
+        assert_eq!(legacy.usage.unwrap().cache_read_input_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn test_stream_cancellation_notifies_subscribers() {
+        let cancellation = StreamCancellation::new();
+        let mut in_flight = cancellation.subscribe();
+        let handle = cancellation.clone();
+
+        tokio::spawn(async move { handle.cancel() });
+
+        tokio::time::timeout(std::time::Duration::from_secs(1), in_flight.changed())
+            .await
+            .expect("cancellation should be observed")
+            .expect("sender should still be alive");
+    }
+
+    #[test]
+    fn test_stream_cancellation_does_not_affect_later_streams() {
+        let cancellation = StreamCancellation::new();
+        let in_flight = cancellation.subscribe();
+
+        cancellation.cancel();
+        let started_after_cancel = cancellation.subscribe();
+
+        assert!(in_flight.has_changed().unwrap());
+        assert!(!started_after_cancel.has_changed().unwrap());
+    }
+
     #[test]
     fn test_reasoning_state_default() {
         // Test that ReasoningState defaults are correct
@@ -469,4 +885,95 @@ mod tests {
         assert_eq!(state.content, "Reasoning content here");
         assert_eq!(state.signature, Some("sig_part1_part2".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_raw_stream_yields_converse_events() {
+        use crate::testing::{MockBedrock, MockResponse};
+        use futures::StreamExt;
+        use rig::client::CompletionClient;
+        use rig::completion::CompletionModel as _;
+
+        let mock = MockBedrock::new().with_response(MockResponse::text_stream(["Hello", " there"]));
+        let model = mock.client().completion_model("amazon.nova-lite-v1:0");
+        let request = model.completion_request("Hi").build();
+
+        let events = model
+            .raw_stream(request)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert!(matches!(
+            events.first(),
+            Some(aws_bedrock::ConverseStreamOutput::MessageStart(_))
+        ));
+        let text = events
+            .iter()
+            .filter_map(|event| match event {
+                aws_bedrock::ConverseStreamOutput::ContentBlockDelta(delta) => {
+                    delta.delta.as_ref()?.as_text().ok().cloned()
+                }
+                _ => None,
+            })
+            .collect::<String>();
+        assert_eq!(text, "Hello there");
+        assert!(matches!(
+            events.last(),
+            Some(aws_bedrock::ConverseStreamOutput::Metadata(_))
+        ));
+        // Usage is recorded like for other streams
+        assert!(model.estimated_cost().total() > 0.0);
+        assert_eq!(mock.requests()[0].operation(), Some("converse-stream"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_surfaces_mid_stream_exceptions() {
+        use crate::testing::{MockBedrock, MockResponse};
+        use aws_smithy_eventstream::frame::write_message_to;
+        use aws_smithy_types::event_stream::{Header, HeaderValue, Message};
+        use futures::StreamExt;
+        use rig::client::CompletionClient;
+        use rig::completion::CompletionModel as _;
+
+        let mut response = MockResponse::event_stream([
+            ("messageStart", serde_json::json!({ "role": "assistant" })),
+            (
+                "contentBlockDelta",
+                serde_json::json!({ "contentBlockIndex": 0, "delta": { "text": "Hello" } }),
+            ),
+        ]);
+        let exception = Message::new(bytes::Bytes::from(
+            serde_json::json!({ "message": "The model failed" }).to_string(),
+        ))
+        .add_header(Header::new(
+            ":message-type",
+            HeaderValue::String("exception".into()),
+        ))
+        .add_header(Header::new(
+            ":exception-type",
+            HeaderValue::String("modelStreamErrorException".into()),
+        ))
+        .add_header(Header::new(
+            ":content-type",
+            HeaderValue::String("application/json".into()),
+        ));
+        write_message_to(&exception, &mut response.body).unwrap();
+
+        let mock = MockBedrock::new().with_response(response);
+        let model = mock.client().completion_model("amazon.nova-lite-v1:0");
+        let request = model.completion_request("Hi").build();
+
+        let mut stream = model.stream(request).await.unwrap();
+        let mut error = None;
+        while let Some(item) = stream.next().await {
+            if let Err(e) = item {
+                error = Some(e);
+            }
+        }
+
+        let error = error.expect("the exception should end the stream with an error");
+        assert!(error.to_string().contains("The model failed"), "{error}");
+    }
 }
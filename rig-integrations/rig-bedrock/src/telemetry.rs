@@ -0,0 +1,104 @@
+//! Tracing spans around model invocations, following the OpenTelemetry GenAI semantic
+//! conventions: <https://opentelemetry.io/docs/specs/semconv/gen-ai/gen-ai-spans/>.
+//!
+//! Besides the span duration, the server side latency reported by Converse is recorded as
+//! `aws.bedrock.latency_ms`.
+
+use tracing::Span;
+
+use crate::types::converse_output::StopReason;
+use crate::types::errors::BedrockError;
+
+/// `gen_ai.provider.name` of all spans.
+pub(crate) const PROVIDER_NAME: &str = "aws.bedrock";
+
+/// A span named after its operation, with all the fields recorded by this module.
+macro_rules! invocation_span {
+    ($target:literal, $operation:literal, $model:expr) => {
+        tracing::info_span!(
+            target: $target,
+            $operation,
+            gen_ai.operation.name = $operation,
+            gen_ai.provider.name = $crate::telemetry::PROVIDER_NAME,
+            gen_ai.request.model = $model,
+            gen_ai.response.finish_reasons = tracing::field::Empty,
+            gen_ai.usage.input_tokens = tracing::field::Empty,
+            gen_ai.usage.output_tokens = tracing::field::Empty,
+            aws.bedrock.latency_ms = tracing::field::Empty,
+            error.type = tracing::field::Empty,
+        )
+    };
+}
+
+/// The span of a Converse call. Like rig's own providers, it records into the current span
+/// instead when there is one, e.g. the span of an agent prompt.
+pub(crate) fn chat_span(model: &str) -> Span {
+    if Span::current().is_disabled() {
+        invocation_span!("rig::completions", "chat", model)
+    } else {
+        Span::current()
+    }
+}
+
+/// The span of a ConverseStream call, see [`chat_span`].
+pub(crate) fn chat_streaming_span(model: &str) -> Span {
+    if Span::current().is_disabled() {
+        invocation_span!("rig::completions", "chat_streaming", model)
+    } else {
+        Span::current()
+    }
+}
+
+/// The span of an embedding InvokeModel call.
+pub(crate) fn embeddings_span(model: &str) -> Span {
+    invocation_span!("rig::embeddings", "embeddings", model)
+}
+
+/// The span of an image generation InvokeModel call.
+pub(crate) fn image_generation_span(model: &str) -> Span {
+    invocation_span!("rig::image_generation", "generate_content", model)
+}
+
+pub(crate) fn record_usage(span: &Span, input_tokens: u64, output_tokens: Option<u64>) {
+    span.record("gen_ai.usage.input_tokens", input_tokens);
+    if let Some(output_tokens) = output_tokens {
+        span.record("gen_ai.usage.output_tokens", output_tokens);
+    }
+}
+
+pub(crate) fn record_finish_reason(span: &Span, stop_reason: &StopReason) {
+    span.record("gen_ai.response.finish_reasons", finish_reason(stop_reason));
+}
+
+pub(crate) fn record_latency(span: &Span, latency_ms: i64) {
+    span.record("aws.bedrock.latency_ms", latency_ms);
+}
+
+/// Record the `error.type` of a call that failed calling Bedrock.
+pub(crate) fn record_error(span: &Span, error: &BedrockError) {
+    span.record("error.type", format!("{:?}", error.kind()));
+}
+
+/// The Bedrock name of a stop reason, e.g. `end_turn`.
+fn finish_reason(stop_reason: &StopReason) -> &str {
+    match stop_reason {
+        StopReason::ContentFiltered => "content_filtered",
+        StopReason::EndTurn => "end_turn",
+        StopReason::GuardrailIntervened => "guardrail_intervened",
+        StopReason::MaxTokens => "max_tokens",
+        StopReason::StopSequence => "stop_sequence",
+        StopReason::ToolUse => "tool_use",
+        StopReason::Unknown(value) => &value.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finish_reason() {
+        assert_eq!(finish_reason(&StopReason::EndTurn), "end_turn");
+        assert_eq!(finish_reason(&StopReason::ToolUse), "tool_use");
+    }
+}
@@ -0,0 +1,532 @@
+//! A mock Bedrock runtime endpoint, to unit test agents and pipelines built on this crate
+//! without AWS credentials or network access.
+//!
+//! [`MockBedrock`] replaces the HTTP client of the Bedrock runtime client: it answers the
+//! requests with canned [`MockResponse`]s, in order, and records them so tests can assert on
+//! what was sent. Requires the `test-util` feature.
+//!
+//! ```rust,ignore
+//! let mock = MockBedrock::new()
+//!     .with_response(MockResponse::tool_use("tool-1", "add", json!({ "x": 1, "y": 2 })))
+//!     .with_response(MockResponse::text("1 + 2 = 3"));
+//!
+//! let agent = mock
+//!     .client()
+//!     .agent(AMAZON_NOVA_LITE)
+//!     .tool(Adder)
+//!     .build();
+//! assert_eq!(agent.prompt("What is 1 + 2?").multi_turn(2).await?, "1 + 2 = 3");
+//!
+//! let requests = mock.requests();
+//! assert_eq!(requests[0].model_id(), Some(AMAZON_NOVA_LITE.to_string()));
+//! ```
+//!
+//! Responses can also be recorded from the real endpoint with a [`Recorder`] and replayed from
+//! the fixture file, to build deterministic integration tests or reproduce provider-specific
+//! bugs offline:
+//!
+//! ```rust,ignore
+//! // Once, with AWS credentials
+//! let recorder = Recorder::new("tests/fixtures/weather_agent.json");
+//! let client = recorder.record(&Client::from_env()).await;
+//!
+//! // In CI
+//! let mock = MockBedrock::replay("tests/fixtures/weather_agent.json")?;
+//! let client = mock.client();
+//! ```
+
+mod record;
+
+pub use record::Recorder;
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use aws_sdk_bedrockruntime::config::{Credentials, Region};
+use aws_smithy_eventstream::frame::write_message_to;
+use aws_smithy_runtime_api::client::http::{
+    HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpConnector,
+};
+use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, HttpResponse};
+use aws_smithy_runtime_api::client::result::ConnectorError;
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_runtime_api::http::StatusCode;
+use aws_smithy_types::body::SdkBody;
+use aws_smithy_types::event_stream::{Header, HeaderValue, Message};
+use bytes::Bytes;
+use rig::completion::CompletionRequestBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::client::Client;
+use crate::completion::CompletionModel;
+
+/// A fake Bedrock runtime endpoint. Clones share the same responses and recorded requests.
+#[derive(Clone, Debug, Default)]
+pub struct MockBedrock {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// The responses to serve, with the request they were recorded for if replayed.
+    responses: VecDeque<(MockResponse, Option<RecordedRequest>)>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockBedrock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer the next request with `response`, after the responses added before.
+    pub fn with_response(self, response: MockResponse) -> Self {
+        self.push_response(response);
+        self
+    }
+
+    /// Answer the next request with `response`, after the responses added before.
+    pub fn push_response(&self, response: MockResponse) {
+        self.state().responses.push_back((response, None));
+    }
+
+    /// Replay the interactions recorded by a [`Recorder`] to `path`. Requests fail if their
+    /// method, operation or model differ from the recorded ones.
+    pub fn replay(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let interactions: Vec<Interaction> =
+            serde_json::from_slice(&std::fs::read(path)?).map_err(std::io::Error::other)?;
+
+        let mock = Self::new();
+        mock.state().responses.extend(
+            interactions
+                .into_iter()
+                .map(|interaction| (interaction.response, Some(interaction.request))),
+        );
+        Ok(mock)
+    }
+
+    /// A client sending its Bedrock runtime requests to this mock, with static credentials and
+    /// without retries. Requests fail once all the responses were served.
+    pub fn client(&self) -> Client {
+        let config = aws_sdk_bedrockruntime::Config::builder()
+            .behavior_version_latest()
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new(
+                "AKIDMOCK",
+                "mock-secret",
+                None,
+                None,
+                "rig-bedrock-mock",
+            ))
+            .retry_config(aws_sdk_bedrockruntime::config::retry::RetryConfig::disabled())
+            .http_client(self.clone())
+            .build();
+        Client::from(aws_sdk_bedrockruntime::Client::from_conf(config))
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// The number of responses not served yet.
+    pub fn remaining_responses(&self) -> usize {
+        self.state().responses.len()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A builder of a completion request sending `prompt`, for tests of the request conversions that
+/// don't send it. Requests built with it get the defaults of the fields they don't set.
+pub fn completion_request(
+    prompt: impl Into<rig::message::Message>,
+) -> CompletionRequestBuilder<CompletionModel> {
+    CompletionRequestBuilder::new(
+        CompletionModel::new(MockBedrock::new().client(), "mock"),
+        prompt,
+    )
+}
+
+impl HttpClient for MockBedrock {
+    fn http_connector(
+        &self,
+        _settings: &HttpConnectorSettings,
+        _components: &RuntimeComponents,
+    ) -> SharedHttpConnector {
+        SharedHttpConnector::new(self.clone())
+    }
+}
+
+impl HttpConnector for MockBedrock {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let received = RecordedRequest::from_http(&request);
+        let mut state = self.state();
+        state.requests.push(received.clone());
+
+        let result = match state.responses.pop_front() {
+            Some((_, Some(recorded))) if !recorded.matches(&received) => {
+                Err(ConnectorError::other(
+                    format!(
+                        "{} {} doesn't match the recorded request {} {}",
+                        received.method, received.uri, recorded.method, recorded.uri
+                    )
+                    .into(),
+                    None,
+                ))
+            }
+            Some((response, _)) => Ok(response.into_http()),
+            None => Err(ConnectorError::other(
+                format!("No mock response left for {}", received.uri).into(),
+                None,
+            )),
+        };
+        HttpConnectorFuture::ready(result)
+    }
+}
+
+/// A request and the response it got, as stored in fixture files.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: MockResponse,
+}
+
+/// A request received by [`MockBedrock`] or a [`Recorder`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub uri: String,
+    #[serde(with = "body")]
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    fn from_http(request: &HttpRequest) -> Self {
+        Self {
+            method: request.method().to_string(),
+            uri: request.uri().to_string(),
+            body: request
+                .body()
+                .bytes()
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether `other` is the same call: same method, operation and model. Bodies aren't
+    /// compared, as they may contain ids or timestamps changing between runs.
+    fn matches(&self, other: &Self) -> bool {
+        self.method == other.method && self.path() == other.path()
+    }
+
+    /// The operation path segment, e.g. `converse`, `converse-stream` or `invoke`.
+    pub fn operation(&self) -> Option<&str> {
+        self.path().rsplit('/').next()
+    }
+
+    /// The model id the request was sent to, e.g. `amazon.nova-lite-v1:0`.
+    pub fn model_id(&self) -> Option<String> {
+        let mut segments = self.path().trim_start_matches('/').split('/');
+        match (segments.next(), segments.next()) {
+            (Some("model"), Some(model_id)) => Some(percent_decode(model_id)),
+            _ => None,
+        }
+    }
+
+    /// The body as JSON, e.g. the Converse request.
+    pub fn json(&self) -> Result<Value, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+
+    fn path(&self) -> &str {
+        let path = self
+            .uri
+            .split_once("://")
+            .map(|(_, rest)| rest.find('/').map_or("", |index| &rest[index..]))
+            .unwrap_or(&self.uri);
+        path.split('?').next().unwrap_or(path)
+    }
+}
+
+/// A canned response of [`MockBedrock`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    #[serde(with = "body")]
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// A response with a JSON body, e.g. an InvokeModel response in the model's native format.
+    pub fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    /// A Converse response of assistant message `content`, e.g.
+    /// `[{ "text": "Hello" }]`, ended by `stop_reason`.
+    pub fn converse(content: Value, stop_reason: &str) -> Self {
+        Self::json(
+            200,
+            json!({
+                "output": { "message": { "role": "assistant", "content": content } },
+                "stopReason": stop_reason,
+                "usage": usage(),
+                "metrics": { "latencyMs": 1 },
+            }),
+        )
+    }
+
+    /// A Converse response answering `text`.
+    pub fn text(text: &str) -> Self {
+        Self::converse(json!([{ "text": text }]), "end_turn")
+    }
+
+    /// A Converse response calling the tool `name` with `input`.
+    pub fn tool_use(tool_use_id: &str, name: &str, input: Value) -> Self {
+        Self::converse(
+            json!([{ "toolUse": { "toolUseId": tool_use_id, "name": name, "input": input } }]),
+            "tool_use",
+        )
+    }
+
+    /// A service error, e.g. `MockResponse::error(429, "ThrottlingException", "Too many requests")`.
+    pub fn error(status: u16, error_type: &str, message: &str) -> Self {
+        let mut response = Self::json(status, json!({ "message": message }));
+        response
+            .headers
+            .push(("x-amzn-errortype".into(), error_type.into()));
+        response
+    }
+
+    /// A ConverseStream or InvokeModelWithResponseStream response of `(event type, payload)`
+    /// events, e.g. `("contentBlockDelta", json!({ "contentBlockIndex": 0, "delta": { "text": "Hi" } }))`.
+    pub fn event_stream<'a>(events: impl IntoIterator<Item = (&'a str, Value)>) -> Self {
+        let mut body = Vec::new();
+        for (event_type, payload) in events {
+            let message = Message::new(Bytes::from(payload.to_string()))
+                .add_header(Header::new(
+                    ":message-type",
+                    HeaderValue::String("event".into()),
+                ))
+                .add_header(Header::new(
+                    ":event-type",
+                    HeaderValue::String(event_type.to_string().into()),
+                ))
+                .add_header(Header::new(
+                    ":content-type",
+                    HeaderValue::String("application/json".into()),
+                ));
+            write_message_to(&message, &mut body).expect("mock events are valid");
+        }
+
+        Self {
+            status: 200,
+            headers: vec![(
+                "content-type".into(),
+                "application/vnd.amazon.eventstream".into(),
+            )],
+            body,
+        }
+    }
+
+    /// A ConverseStream response streaming `chunks` of text.
+    pub fn text_stream<'a>(chunks: impl IntoIterator<Item = &'a str>) -> Self {
+        let deltas = chunks.into_iter().map(|chunk| {
+            (
+                "contentBlockDelta",
+                json!({ "contentBlockIndex": 0, "delta": { "text": chunk } }),
+            )
+        });
+
+        Self::event_stream(
+            std::iter::once(("messageStart", json!({ "role": "assistant" })))
+                .chain(deltas)
+                .chain([
+                    ("contentBlockStop", json!({ "contentBlockIndex": 0 })),
+                    ("messageStop", json!({ "stopReason": "end_turn" })),
+                    (
+                        "metadata",
+                        json!({ "usage": usage(), "metrics": { "latencyMs": 1 } }),
+                    ),
+                ]),
+        )
+    }
+
+    fn into_http(self) -> HttpResponse {
+        let status = StatusCode::try_from(self.status).expect("valid HTTP status code");
+        let mut response = HttpResponse::new(status, SdkBody::from(self.body));
+        for (name, value) in self.headers {
+            response.headers_mut().insert(name, value);
+        }
+        response
+    }
+}
+
+fn usage() -> Value {
+    json!({ "inputTokens": 10, "outputTokens": 5, "totalTokens": 15 })
+}
+
+/// Bodies are stored as JSON when they are JSON, to keep fixtures readable, and as base64
+/// otherwise, e.g. event streams.
+mod body {
+    use base64::{Engine, prelude::BASE64_STANDARD};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde_json::{Value, json};
+
+    pub(super) fn serialize<S: Serializer>(body: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(json) => json.serialize(serializer),
+            Err(_) if body.is_empty() => serializer.serialize_none(),
+            Err(_) => json!({ "base64": BASE64_STANDARD.encode(body) }).serialize(serializer),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Null => Ok(Vec::new()),
+            Value::Object(object) if object.len() == 1 && object.contains_key("base64") => {
+                let encoded = object["base64"].as_str().unwrap_or_default();
+                BASE64_STANDARD
+                    .decode(encoded)
+                    .map_err(serde::de::Error::custom)
+            }
+            json => Ok(json.to_string().into_bytes()),
+        }
+    }
+}
+
+/// Decode the percent-encoded characters of a URI path segment, e.g. `%3A` in model ids.
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::errors::{BedrockError, BedrockErrorKind};
+    use futures::StreamExt;
+    use rig::completion::CompletionModel as _;
+    use rig::message::AssistantContent;
+    use rig::streaming::StreamedAssistantContent;
+
+    const MODEL: &str = "amazon.nova-lite-v1:0";
+
+    #[tokio::test]
+    async fn test_completion() {
+        let mock = MockBedrock::new().with_response(MockResponse::text("Hello!"));
+        let model = CompletionModel::new(mock.client(), MODEL);
+
+        let response = model.completion_request("Hi").send().await.unwrap();
+        assert!(matches!(
+            response.choice.first(),
+            AssistantContent::Text(text) if text.text == "Hello!"
+        ));
+        assert_eq!(response.usage.total_tokens, 15);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].operation(), Some("converse"));
+        assert_eq!(requests[0].model_id().as_deref(), Some(MODEL));
+        assert_eq!(
+            requests[0].json().unwrap()["messages"][0]["content"][0]["text"],
+            "Hi"
+        );
+        assert_eq!(mock.remaining_responses(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tool_use() {
+        let mock = MockBedrock::new().with_response(MockResponse::tool_use(
+            "tool-1",
+            "add",
+            json!({ "x": 1, "y": 2 }),
+        ));
+        let model = CompletionModel::new(mock.client(), MODEL);
+
+        let response = model.completion_request("1 + 2?").send().await.unwrap();
+        assert!(matches!(
+            response.choice.first(),
+            AssistantContent::ToolCall(call)
+                if call.function.name == "add" && call.function.arguments == json!({ "x": 1, "y": 2 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_error() {
+        let mock = MockBedrock::new().with_response(MockResponse::error(
+            400,
+            "ValidationException",
+            "Malformed input",
+        ));
+        let model = CompletionModel::new(mock.client(), MODEL);
+
+        let Err(error) = model.completion_request("Hi").send().await else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(
+            BedrockError::from_completion_error(&error).map(BedrockError::kind),
+            Some(BedrockErrorKind::Validation)
+        );
+        assert!(error.to_string().contains("Malformed input"));
+    }
+
+    #[tokio::test]
+    async fn test_stream() {
+        let mock = MockBedrock::new().with_response(MockResponse::text_stream(["Hel", "lo!"]));
+        let model = CompletionModel::new(mock.client(), MODEL);
+
+        let mut stream = model.completion_request("Hi").stream().await.unwrap();
+        let mut text = String::new();
+        while let Some(chunk) = stream.next().await {
+            if let StreamedAssistantContent::Text(chunk) = chunk.unwrap() {
+                text.push_str(&chunk.text);
+            }
+        }
+        assert_eq!(text, "Hello!");
+        assert_eq!(mock.requests()[0].operation(), Some("converse-stream"));
+    }
+
+    #[test]
+    fn test_recorded_request() {
+        let request = RecordedRequest {
+            method: "POST".into(),
+            uri: "https://bedrock-runtime.us-east-1.amazonaws.com/model/amazon.nova-lite-v1%3A0/converse?x=1"
+                .into(),
+            body: Vec::new(),
+        };
+        assert_eq!(request.operation(), Some("converse"));
+        assert_eq!(request.model_id().as_deref(), Some(MODEL));
+    }
+}
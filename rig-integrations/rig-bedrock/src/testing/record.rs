@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use aws_config::SdkConfig;
+use aws_sdk_bedrockruntime::config::retry::RetryConfig;
+use aws_smithy_runtime_api::client::http::{
+    HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpClient,
+    SharedHttpConnector,
+};
+use aws_smithy_runtime_api::client::orchestrator::HttpRequest;
+use aws_smithy_runtime_api::client::result::ConnectorError;
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_types::body::SdkBody;
+use aws_smithy_types::byte_stream::ByteStream;
+
+use super::{Interaction, MockResponse, RecordedRequest};
+use crate::client::Client;
+
+/// Records the Bedrock runtime requests of a client and the responses of the real endpoint to
+/// a fixture file, for [`MockBedrock::replay`](super::MockBedrock::replay).
+///
+/// The file is rewritten after each response, so it is complete even if the test fails
+/// midway. Request headers aren't recorded, so fixtures don't contain credentials or
+/// signatures, but request and response bodies are recorded as is.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    path: Arc<PathBuf>,
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl Recorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Arc::new(path.into()),
+            interactions: Arc::default(),
+        }
+    }
+
+    /// A client sending its Bedrock runtime requests through the HTTP client of `client`'s
+    /// AWS configuration and recording them. Retries are disabled, so that the recorded
+    /// interactions replay the same way. Client options such as request metadata must be set
+    /// on the returned client.
+    pub async fn record(&self, client: &Client) -> Client {
+        self.client(client.sdk_config().await)
+    }
+
+    fn client(&self, sdk_config: &SdkConfig) -> Client {
+        let inner = sdk_config
+            .http_client()
+            .expect("the AWS configuration has an HTTP client");
+
+        let config = aws_sdk_bedrockruntime::config::Builder::from(sdk_config)
+            .retry_config(RetryConfig::disabled())
+            .http_client(RecordingHttpClient {
+                inner,
+                recorder: self.clone(),
+            })
+            .build();
+        Client::from(aws_sdk_bedrockruntime::Client::from_conf(config))
+    }
+
+    /// The interactions recorded so far, in order.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().clone()
+    }
+
+    fn push(&self, interaction: Interaction) {
+        let mut interactions = self.lock();
+        interactions.push(interaction);
+
+        let written = serde_json::to_vec_pretty(&*interactions)
+            .map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(self.path.as_ref(), json));
+        if let Err(e) = written {
+            tracing::warn!(
+                target: "rig::bedrock",
+                "Failed to write the recorded interactions to {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Interaction>> {
+        self.interactions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug)]
+struct RecordingHttpClient {
+    inner: SharedHttpClient,
+    recorder: Recorder,
+}
+
+impl HttpClient for RecordingHttpClient {
+    fn http_connector(
+        &self,
+        settings: &HttpConnectorSettings,
+        components: &RuntimeComponents,
+    ) -> SharedHttpConnector {
+        SharedHttpConnector::new(RecordingConnector {
+            inner: self.inner.http_connector(settings, components),
+            recorder: self.recorder.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct RecordingConnector {
+    inner: SharedHttpConnector,
+    recorder: Recorder,
+}
+
+impl HttpConnector for RecordingConnector {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let recorded = RecordedRequest::from_http(&request);
+        let response = self.inner.call(request);
+        let recorder = self.recorder.clone();
+
+        HttpConnectorFuture::new(async move {
+            let mut response = response.await?;
+
+            // Event streams are buffered entirely, so recorded streams arrive all at once
+            let body = ByteStream::new(response.take_body())
+                .collect()
+                .await
+                .map_err(|e| ConnectorError::io(e.into()))?
+                .into_bytes();
+
+            recorder.push(Interaction {
+                request: recorded,
+                response: MockResponse {
+                    status: response.status().as_u16(),
+                    headers: response
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                    body: body.to_vec(),
+                },
+            });
+
+            *response.body_mut() = SdkBody::from(body);
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::CompletionModel;
+    use crate::testing::MockBedrock;
+    use aws_config::{BehaviorVersion, Region};
+    use aws_sdk_bedrockruntime::config::{Credentials, SharedCredentialsProvider};
+    use rig::completion::CompletionModel as _;
+    use rig::message::AssistantContent;
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("rig-bedrock-{}.json", uuid::Uuid::new_v4()));
+
+        // A mock stands in for the real endpoint
+        let endpoint = MockBedrock::new()
+            .with_response(MockResponse::text("Hello!"))
+            .with_response(MockResponse::text_stream(["Bye", "!"]));
+        let sdk_config = SdkConfig::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+                "AKIDMOCK",
+                "mock-secret",
+                None,
+                None,
+                "test",
+            )))
+            .http_client(endpoint)
+            .build();
+
+        let recorder = Recorder::new(&path);
+        let model = CompletionModel::new(recorder.client(&sdk_config), "amazon.nova-lite-v1:0");
+        model.completion_request("Hi").send().await.unwrap();
+        let mut stream = model.completion_request("Bye").stream().await.unwrap();
+        while futures::StreamExt::next(&mut stream).await.is_some() {}
+        assert_eq!(recorder.interactions().len(), 2);
+
+        let replay = MockBedrock::replay(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replay.remaining_responses(), 2);
+
+        let model = CompletionModel::new(replay.client(), "amazon.nova-lite-v1:0");
+        let response = model.completion_request("Hi").send().await.unwrap();
+        assert!(matches!(
+            response.choice.first(),
+            AssistantContent::Text(text) if text.text == "Hello!"
+        ));
+
+        // The recorded request was a ConverseStream call
+        assert!(model.completion_request("Hi").send().await.is_err());
+    }
+}
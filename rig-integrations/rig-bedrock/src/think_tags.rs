@@ -0,0 +1,252 @@
+//! Extraction of the `<think>` sections reasoning models such as DeepSeek-R1 write at the start of
+//! their text output, into rig's reasoning content, so the chain of thought can be told apart from
+//! the final answer.
+//!
+//! Models whose chat template opens the section in the prompt only write the closing tag, so text
+//! before a lone `</think>` is reasoning too. Streams can't know that until the tag arrives, so
+//! they only extract sections with an opening tag.
+
+use rig::OneOrMany;
+use rig::completion::{CompletionError, CompletionResponse};
+use rig::message::{AssistantContent, Reasoning};
+use rig::streaming::RawStreamingChoice;
+
+use crate::native::ModelFamily;
+use crate::streaming::BedrockStreamingResponse;
+
+const OPEN: &str = "<think>";
+const CLOSE: &str = "</think>";
+
+/// Whether think tags are extracted from the output of `model` by default.
+pub(crate) fn extracted_by_default(model: &str) -> bool {
+    ModelFamily::from_model_id(model) == Some(ModelFamily::DeepSeek)
+}
+
+/// Split the reasoning, if any, from the answer of `text`.
+fn split(text: &str) -> (Option<&str>, &str) {
+    let trimmed = text.trim_start();
+    match trimmed.split_once(CLOSE) {
+        Some((reasoning, answer)) => (
+            Some(reasoning.strip_prefix(OPEN).unwrap_or(reasoning).trim()),
+            answer.trim_start(),
+        ),
+        // The output was cut off while reasoning
+        None => match trimmed.strip_prefix(OPEN) {
+            Some(reasoning) => (Some(reasoning.trim()), ""),
+            None => (None, text),
+        },
+    }
+}
+
+/// Move the think sections of the text content of `response` into reasoning content.
+pub(crate) fn extract<T>(
+    response: CompletionResponse<T>,
+) -> Result<CompletionResponse<T>, CompletionError> {
+    let mut choice = Vec::new();
+    for content in response.choice {
+        let AssistantContent::Text(text) = &content else {
+            choice.push(content);
+            continue;
+        };
+
+        match split(&text.text) {
+            (Some(reasoning), answer) => {
+                choice.push(AssistantContent::Reasoning(Reasoning::new(reasoning)));
+                if !answer.is_empty() {
+                    choice.push(AssistantContent::text(answer));
+                }
+            }
+            (None, _) => choice.push(content),
+        }
+    }
+
+    Ok(CompletionResponse {
+        choice: OneOrMany::many(choice).map_err(|_| {
+            CompletionError::ResponseError("Response contained no content".to_owned())
+        })?,
+        ..response
+    })
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum State {
+    /// Waiting for enough text to tell whether the output opens with a think tag.
+    #[default]
+    Start,
+    Thinking,
+    Answer,
+}
+
+/// Turns the text deltas of a stream into reasoning and message deltas.
+#[derive(Debug, Default)]
+pub(crate) struct ThinkTagSplitter {
+    state: State,
+    /// Text that may be the start of a tag.
+    pending: String,
+    reasoning: String,
+}
+
+impl ThinkTagSplitter {
+    pub(crate) fn push(
+        &mut self,
+        delta: &str,
+    ) -> Vec<RawStreamingChoice<BedrockStreamingResponse>> {
+        self.pending.push_str(delta);
+        let mut choices = Vec::new();
+
+        loop {
+            match self.state {
+                State::Start => {
+                    let trimmed = self.pending.trim_start();
+                    if let Some(rest) = trimmed.strip_prefix(OPEN) {
+                        self.pending = rest.to_string();
+                        self.state = State::Thinking;
+                    } else if OPEN.starts_with(trimmed) {
+                        return choices;
+                    } else {
+                        self.state = State::Answer;
+                    }
+                }
+                State::Thinking => match self.pending.split_once(CLOSE) {
+                    Some((reasoning, answer)) => {
+                        let answer = answer.trim_start().to_string();
+                        self.push_reasoning(reasoning.to_string(), &mut choices);
+                        choices.push(RawStreamingChoice::Reasoning {
+                            id: None,
+                            reasoning: std::mem::take(&mut self.reasoning).trim().to_string(),
+                            signature: None,
+                        });
+                        self.pending = answer;
+                        self.state = State::Answer;
+                    }
+                    None => {
+                        // Keep what could be the start of the closing tag
+                        let keep = (1..CLOSE.len())
+                            .rev()
+                            .find(|len| self.pending.ends_with(&CLOSE[..*len]))
+                            .unwrap_or(0);
+                        let reasoning = self.pending[..self.pending.len() - keep].to_string();
+                        self.pending.drain(..reasoning.len());
+                        self.push_reasoning(reasoning, &mut choices);
+                        return choices;
+                    }
+                },
+                State::Answer => {
+                    if !self.pending.is_empty() {
+                        choices.push(RawStreamingChoice::Message(std::mem::take(
+                            &mut self.pending,
+                        )));
+                    }
+                    return choices;
+                }
+            }
+        }
+    }
+
+    /// Flush the text held back, once the stream ended.
+    pub(crate) fn finish(&mut self) -> Vec<RawStreamingChoice<BedrockStreamingResponse>> {
+        let mut choices = Vec::new();
+        let pending = std::mem::take(&mut self.pending);
+        match self.state {
+            State::Thinking => {
+                self.push_reasoning(pending, &mut choices);
+                if !self.reasoning.trim().is_empty() {
+                    choices.push(RawStreamingChoice::Reasoning {
+                        id: None,
+                        reasoning: std::mem::take(&mut self.reasoning).trim().to_string(),
+                        signature: None,
+                    });
+                }
+            }
+            State::Start | State::Answer if !pending.is_empty() => {
+                choices.push(RawStreamingChoice::Message(pending));
+            }
+            _ => {}
+        }
+        self.state = State::Answer;
+        choices
+    }
+
+    fn push_reasoning(
+        &mut self,
+        reasoning: String,
+        choices: &mut Vec<RawStreamingChoice<BedrockStreamingResponse>>,
+    ) {
+        if !reasoning.is_empty() {
+            self.reasoning.push_str(&reasoning);
+            choices.push(RawStreamingChoice::ReasoningDelta {
+                id: None,
+                reasoning,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::DEEPSEEK_R1;
+
+    fn texts(choices: &[RawStreamingChoice<BedrockStreamingResponse>]) -> (String, String) {
+        let mut reasoning = String::new();
+        let mut message = String::new();
+        for choice in choices {
+            match choice {
+                RawStreamingChoice::ReasoningDelta {
+                    reasoning: delta, ..
+                } => reasoning.push_str(delta),
+                RawStreamingChoice::Message(delta) => message.push_str(delta),
+                _ => {}
+            }
+        }
+        (reasoning, message)
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(
+            split("<think>\nAdd them.\n</think>\n\n3"),
+            (Some("Add them."), "3")
+        );
+        assert_eq!(split("Add them.</think>3"), (Some("Add them."), "3"));
+        assert_eq!(split("<think>Add them"), (Some("Add them"), ""));
+        assert_eq!(split("3"), (None, "3"));
+        assert!(extracted_by_default(DEEPSEEK_R1));
+        assert!(!extracted_by_default("amazon.nova-lite-v1:0"));
+    }
+
+    #[test]
+    fn test_splitter_across_chunks() {
+        let mut splitter = ThinkTagSplitter::default();
+        let mut choices = Vec::new();
+        for delta in [
+            "\n<thi",
+            "nk>1 + 2",
+            " is 3</th",
+            "ink>\n\nThe answer",
+            " is 3.",
+        ] {
+            choices.extend(splitter.push(delta));
+        }
+        choices.extend(splitter.finish());
+
+        assert_eq!(
+            texts(&choices),
+            ("1 + 2 is 3".to_string(), "The answer is 3.".to_string())
+        );
+        assert!(choices.iter().any(|choice| matches!(
+            choice,
+            RawStreamingChoice::Reasoning { reasoning, .. } if reasoning == "1 + 2 is 3"
+        )));
+    }
+
+    #[test]
+    fn test_splitter_without_tags() {
+        let mut splitter = ThinkTagSplitter::default();
+        let mut choices = splitter.push("<b>");
+        choices.extend(splitter.push("bold</b>"));
+        choices.extend(splitter.finish());
+
+        assert_eq!(texts(&choices), (String::new(), "<b>bold</b>".to_string()));
+    }
+}
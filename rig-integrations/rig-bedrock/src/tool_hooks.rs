@@ -0,0 +1,416 @@
+//! Hooks around the tool calls of an agent, e.g. for auditing, rewriting arguments or asking a
+//! human to approve dangerous tools.
+//!
+//! A [`ToolHookCompletionModel`] runs its hooks on each tool call made by the model before it is
+//! returned to the agent, and on each tool result before it is sent back to the model. An agent
+//! built on it gets its hooks:
+//!
+//! ```rust,ignore
+//! #[derive(Clone)]
+//! struct ApproveDeletes;
+//!
+//! impl ToolHook for ApproveDeletes {
+//!     async fn on_tool_call(&self, tool_call: ToolCall) -> ToolCallDecision {
+//!         if tool_call.function.name == "delete_file" && !ask_operator(&tool_call).await {
+//!             return ToolCallDecision::Deny("the operator didn't approve it".into());
+//!         }
+//!         ToolCallDecision::Run(tool_call)
+//!     }
+//! }
+//!
+//! let model = client.completion_model(AMAZON_NOVA_PRO).with_tool_hook(ApproveDeletes);
+//! let agent = AgentBuilder::new(model).tool(DeleteFile).build();
+//! ```
+//!
+//! A denied tool call fails the completion with a [`ToolCallDeniedError`], so the tool isn't run.
+
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_stream::stream;
+use futures::StreamExt;
+use rig::OneOrMany;
+use rig::completion::{self, CompletionError, CompletionRequest, CompletionResponse};
+use rig::message::{AssistantContent, Message, ToolCall, ToolResult, UserContent};
+use rig::streaming::{
+    RawStreamingChoice, RawStreamingToolCall, StreamedAssistantContent, StreamingCompletionResponse,
+};
+
+use crate::completion::CompletionModel;
+
+/// What to do with a tool call made by the model.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ToolCallDecision {
+    /// Return the tool call, possibly with rewritten arguments, to be run by the agent.
+    Run(ToolCall),
+    /// Fail the completion with a [`ToolCallDeniedError`] with this reason.
+    Deny(String),
+}
+
+/// Hooks run around the tool calls of a [`ToolHookCompletionModel`], see [`crate::tool_hooks`].
+pub trait ToolHook: Send + Sync + 'static {
+    /// Called on each tool call made by the model, before it is returned to the agent.
+    fn on_tool_call(&self, tool_call: ToolCall) -> impl Future<Output = ToolCallDecision> + Send {
+        async move { ToolCallDecision::Run(tool_call) }
+    }
+
+    /// Called on each tool result before it is sent to the model, returning the result to send.
+    ///
+    /// Requests carry the whole chat history, so this is called on the results of earlier turns
+    /// again and rewrites apply to every request. Use the tool call id to audit each result once.
+    fn on_tool_result(&self, tool_result: ToolResult) -> impl Future<Output = ToolResult> + Send {
+        async move { tool_result }
+    }
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object safe version of [`ToolHook`], to chain hooks of different types.
+trait DynToolHook: Send + Sync {
+    fn on_tool_call(&self, tool_call: ToolCall) -> BoxFuture<'_, ToolCallDecision>;
+
+    fn on_tool_result(&self, tool_result: ToolResult) -> BoxFuture<'_, ToolResult>;
+}
+
+impl<H: ToolHook> DynToolHook for H {
+    fn on_tool_call(&self, tool_call: ToolCall) -> BoxFuture<'_, ToolCallDecision> {
+        Box::pin(ToolHook::on_tool_call(self, tool_call))
+    }
+
+    fn on_tool_result(&self, tool_result: ToolResult) -> BoxFuture<'_, ToolResult> {
+        Box::pin(ToolHook::on_tool_result(self, tool_result))
+    }
+}
+
+/// A tool call denied by a [`ToolHook`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToolCallDeniedError {
+    pub tool: String,
+    pub id: String,
+    pub reason: String,
+}
+
+impl ToolCallDeniedError {
+    /// The denied tool call a completion failed with, if any.
+    pub fn from_completion_error(error: &CompletionError) -> Option<&Self> {
+        match error {
+            CompletionError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ToolCallDeniedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Call to tool `{}` denied: {}", self.tool, self.reason)
+    }
+}
+
+impl std::error::Error for ToolCallDeniedError {}
+
+impl From<ToolCallDeniedError> for CompletionError {
+    fn from(error: ToolCallDeniedError) -> Self {
+        CompletionError::RequestError(Box::new(error))
+    }
+}
+
+/// A completion model running [`ToolHook`]s around the tool calls of its model, in the order they
+/// were added, see [`crate::tool_hooks`].
+#[derive(Clone)]
+pub struct ToolHookCompletionModel<M = CompletionModel> {
+    model: M,
+    hooks: Vec<Arc<dyn DynToolHook>>,
+}
+
+impl<M> ToolHookCompletionModel<M> {
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Run `hook` after the hooks already added.
+    pub fn with_tool_hook(mut self, hook: impl ToolHook) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Run the hooks on the tool results of the chat history of `request`.
+    async fn hook_tool_results(&self, mut request: CompletionRequest) -> CompletionRequest {
+        if self.hooks.is_empty() {
+            return request;
+        }
+        let mut messages = Vec::with_capacity(request.chat_history.len());
+        for message in request.chat_history {
+            let message = match message {
+                Message::User { content } => {
+                    let mut hooked = Vec::with_capacity(content.len());
+                    for content in content {
+                        hooked.push(match content {
+                            UserContent::ToolResult(mut tool_result) => {
+                                for hook in &self.hooks {
+                                    tool_result = hook.on_tool_result(tool_result).await;
+                                }
+                                UserContent::ToolResult(tool_result)
+                            }
+                            content => content,
+                        });
+                    }
+                    Message::User {
+                        content: OneOrMany::many(hooked).expect("content isn't empty"),
+                    }
+                }
+                message => message,
+            };
+            messages.push(message);
+        }
+        request.chat_history = OneOrMany::many(messages).expect("chat history isn't empty");
+        request
+    }
+}
+
+/// Run `hooks` on `tool_call`, failing if one of them denies it.
+async fn hook_tool_call(
+    hooks: &[Arc<dyn DynToolHook>],
+    mut tool_call: ToolCall,
+) -> Result<ToolCall, ToolCallDeniedError> {
+    for hook in hooks {
+        match hook.on_tool_call(tool_call.clone()).await {
+            ToolCallDecision::Run(hooked) => tool_call = hooked,
+            ToolCallDecision::Deny(reason) => {
+                tracing::info!(
+                    target: "rig::bedrock",
+                    "Call to tool `{}` denied: {reason}",
+                    tool_call.function.name
+                );
+                return Err(ToolCallDeniedError {
+                    tool: tool_call.function.name,
+                    id: tool_call.id,
+                    reason,
+                });
+            }
+        }
+    }
+    Ok(tool_call)
+}
+
+impl<M> completion::CompletionModel for ToolHookCompletionModel<M>
+where
+    M: completion::CompletionModel + 'static,
+{
+    type Response = M::Response;
+    type StreamingResponse = M::StreamingResponse;
+
+    type Client = M::Client;
+
+    /// A model without hooks, add them with [`ToolHookCompletionModel::with_tool_hook`].
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        Self::new(M::make(client, model))
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
+        let request = self.hook_tool_results(request).await;
+        let mut response = self.model.completion(request).await?;
+        if self.hooks.is_empty() {
+            return Ok(response);
+        }
+
+        let mut choice = Vec::with_capacity(response.choice.len());
+        for content in response.choice {
+            choice.push(match content {
+                AssistantContent::ToolCall(tool_call) => {
+                    AssistantContent::ToolCall(hook_tool_call(&self.hooks, tool_call).await?)
+                }
+                content => content,
+            });
+        }
+        response.choice = OneOrMany::many(choice).expect("choice isn't empty");
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
+        let request = self.hook_tool_results(request).await;
+        let mut inner = self.model.stream(request).await?;
+        if self.hooks.is_empty() {
+            return Ok(inner);
+        }
+
+        let hooks = self.hooks.clone();
+        let stream = stream! {
+            while let Some(content) = inner.next().await {
+                yield Ok(match content? {
+                    StreamedAssistantContent::Text(text) => RawStreamingChoice::Message(text.text),
+                    StreamedAssistantContent::ToolCall(tool_call) => {
+                        let tool_call = hook_tool_call(&hooks, tool_call).await?;
+                        let mut raw = RawStreamingToolCall::new(
+                            tool_call.id,
+                            tool_call.function.name,
+                            tool_call.function.arguments,
+                        )
+                        .with_signature(tool_call.signature);
+                        if let Some(call_id) = tool_call.call_id {
+                            raw = raw.with_call_id(call_id);
+                        }
+                        raw.additional_params = tool_call.additional_params;
+                        RawStreamingChoice::ToolCall(raw)
+                    }
+                    StreamedAssistantContent::ToolCallDelta { id, delta } => {
+                        RawStreamingChoice::ToolCallDelta { id, delta }
+                    }
+                    StreamedAssistantContent::Reasoning(reasoning) => RawStreamingChoice::Reasoning {
+                        id: reasoning.id,
+                        reasoning: reasoning.reasoning.concat(),
+                        signature: reasoning.signature,
+                    },
+                    StreamedAssistantContent::ReasoningDelta { id, reasoning } => {
+                        RawStreamingChoice::ReasoningDelta { id, reasoning }
+                    }
+                    StreamedAssistantContent::Final(response) => {
+                        RawStreamingChoice::FinalResponse(response)
+                    }
+                });
+            }
+        };
+        Ok(StreamingCompletionResponse::stream(Box::pin(stream)))
+    }
+}
+
+impl<M: fmt::Debug> fmt::Debug for ToolHookCompletionModel<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolHookCompletionModel")
+            .field("model", &self.model)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::AMAZON_NOVA_PRO;
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::CompletionModel as _;
+    use rig::message::ToolResultContent;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct Audit {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ToolHook for Audit {
+        async fn on_tool_call(&self, tool_call: ToolCall) -> ToolCallDecision {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(tool_call.function.name.clone());
+            if tool_call.function.name == "delete_file" {
+                return ToolCallDecision::Deny("not approved".into());
+            }
+            let mut tool_call = tool_call;
+            tool_call.function.arguments["units"] = "metric".into();
+            ToolCallDecision::Run(tool_call)
+        }
+
+        async fn on_tool_result(&self, mut tool_result: ToolResult) -> ToolResult {
+            tool_result.content = OneOrMany::one(ToolResultContent::text("[redacted]"));
+            tool_result
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_rewrite_tool_calls_and_results() {
+        let mock = MockBedrock::new().with_response(MockResponse::tool_use(
+            "tool_2",
+            "get_weather",
+            json!({ "city": "Lisbon" }),
+        ));
+        let audit = Audit::default();
+        let model =
+            CompletionModel::new(mock.client(), AMAZON_NOVA_PRO).with_tool_hook(audit.clone());
+        let request = model
+            .completion_request(Message::User {
+                content: OneOrMany::one(UserContent::tool_result(
+                    "tool_1",
+                    OneOrMany::one(ToolResultContent::text("password: hunter2")),
+                )),
+            })
+            .message(Message::assistant("Let me check."))
+            .build();
+
+        let response = model.completion(request).await.unwrap();
+
+        let AssistantContent::ToolCall(tool_call) = response.choice.first() else {
+            panic!("expected a tool call");
+        };
+        assert_eq!(
+            tool_call.function.arguments,
+            json!({ "city": "Lisbon", "units": "metric" })
+        );
+        assert_eq!(*audit.calls.lock().unwrap(), vec!["get_weather"]);
+        let sent = mock.requests()[0].json().unwrap();
+        assert_eq!(
+            sent["messages"][1]["content"][0]["toolResult"]["content"][0]["text"],
+            "[redacted]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_denied_tool_call_fails_the_stream() {
+        let mock = MockBedrock::new().with_response(MockResponse::event_stream([
+            ("messageStart", json!({ "role": "assistant" })),
+            (
+                "contentBlockStart",
+                json!({
+                    "contentBlockIndex": 0,
+                    "start": { "toolUse": { "toolUseId": "tool_1", "name": "delete_file" } }
+                }),
+            ),
+            (
+                "contentBlockDelta",
+                json!({
+                    "contentBlockIndex": 0,
+                    "delta": { "toolUse": { "input": "{\"path\": \"/etc/hosts\"}" } }
+                }),
+            ),
+            ("contentBlockStop", json!({ "contentBlockIndex": 0 })),
+            ("messageStop", json!({ "stopReason": "tool_use" })),
+        ]));
+        let model =
+            CompletionModel::new(mock.client(), AMAZON_NOVA_PRO).with_tool_hook(Audit::default());
+
+        let mut stream = model
+            .stream(model.completion_request("Clean up").build())
+            .await
+            .unwrap();
+        let mut denied = None;
+        while let Some(item) = stream.next().await {
+            if let Err(error) = item {
+                denied = ToolCallDeniedError::from_completion_error(&error).cloned();
+                break;
+            }
+        }
+
+        assert_eq!(
+            denied,
+            Some(ToolCallDeniedError {
+                tool: "delete_file".into(),
+                id: "tool_1".into(),
+                reason: "not approved".into(),
+            })
+        );
+    }
+}
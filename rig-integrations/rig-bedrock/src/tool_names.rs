@@ -0,0 +1,187 @@
+//! Tool names accepted by Bedrock.
+//!
+//! Bedrock rejects tool names that aren't 1 to 64 letters, digits, `_` or `-` with a
+//! `ValidationException`, while rig tools may have any name, e.g. `search.web` or the names of MCP
+//! tools. Completion models send the [`sanitize`]d names instead, and restore the original names
+//! in the tool calls of the responses, so agents run the tools they defined.
+//!
+//! Names Bedrock accepts are sent unchanged. The events of
+//! [`CompletionModel::raw_stream`](crate::completion::CompletionModel::raw_stream) keep the names
+//! sent to Bedrock.
+
+use std::collections::HashMap;
+
+use rig::OneOrMany;
+use rig::completion::CompletionRequest;
+use rig::message::{AssistantContent, Message, ToolChoice};
+
+/// The longest tool name Bedrock accepts.
+pub const MAX_TOOL_NAME_LENGTH: usize = 64;
+
+/// Characters kept from long names, before the hash of the name.
+const TRUNCATED_LENGTH: usize = MAX_TOOL_NAME_LENGTH - 9;
+
+/// `name` with the characters Bedrock doesn't accept replaced by `_`, and shortened with a hash
+/// of the name when it is too long.
+pub fn sanitize(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if sanitized.is_empty() {
+        hashed(name, "tool")
+    } else if sanitized.len() > MAX_TOOL_NAME_LENGTH {
+        hashed(name, &sanitized)
+    } else {
+        sanitized
+    }
+}
+
+/// `sanitized` truncated and suffixed with a hash of `name`, to tell apart names sanitized the
+/// same way.
+fn hashed(name: &str, sanitized: &str) -> String {
+    // FNV-1a, stable across processes unlike the std hasher
+    let hash = name.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    });
+    let prefix = &sanitized[..sanitized.len().min(TRUNCATED_LENGTH)];
+    format!("{prefix}_{hash:08x}")
+}
+
+/// The names a request was sent with, to restore the original names in the response.
+#[derive(Debug, Default)]
+pub(crate) struct ToolNames {
+    /// Original name to sent name.
+    sent: HashMap<String, String>,
+    /// Sent name to original name.
+    original: HashMap<String, String>,
+}
+
+impl ToolNames {
+    /// Replace the tool names of `request` by names Bedrock accepts: in the tool definitions, the
+    /// tool choice and the tool calls of the chat history.
+    pub(crate) fn sanitize_request(request: &mut CompletionRequest) -> Self {
+        let mut names = Self::default();
+        for tool in request.tools.iter_mut() {
+            tool.name = names.send(&tool.name);
+        }
+        if let Some(ToolChoice::Specific { function_names }) = request.tool_choice.as_mut() {
+            for name in function_names.iter_mut() {
+                *name = names.send(name);
+            }
+        }
+        for message in request.chat_history.iter_mut() {
+            if let Message::Assistant { content, .. } = message {
+                for content in content.iter_mut() {
+                    if let AssistantContent::ToolCall(tool_call) = content {
+                        tool_call.function.name = names.send(&tool_call.function.name);
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// The name to send for the tool named `name`.
+    fn send(&mut self, name: &str) -> String {
+        if let Some(sent) = self.sent.get(name) {
+            return sent.clone();
+        }
+        let mut sent = sanitize(name);
+        if self.original.contains_key(&sent) {
+            sent = hashed(name, &sent);
+        }
+        if sent != name {
+            tracing::debug!(target: "rig::bedrock", "Sending tool `{name}` as `{sent}`");
+        }
+        self.sent.insert(name.to_string(), sent.clone());
+        self.original.insert(sent.clone(), name.to_string());
+        sent
+    }
+
+    /// The original name of the tool sent as `name`.
+    pub(crate) fn restore(&self, name: String) -> String {
+        self.original.get(&name).cloned().unwrap_or(name)
+    }
+
+    /// Restore the original names of the tool calls of `choice`.
+    pub(crate) fn restore_choice(&self, choice: &mut OneOrMany<AssistantContent>) {
+        for content in choice.iter_mut() {
+            if let AssistantContent::ToolCall(tool_call) = content {
+                tool_call.function.name =
+                    self.restore(std::mem::take(&mut tool_call.function.name));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{AMAZON_NOVA_PRO, CompletionModel};
+    use crate::testing::{MockBedrock, MockResponse};
+    use rig::completion::{CompletionModel as _, ToolDefinition};
+    use serde_json::json;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("get_weather-v2"), "get_weather-v2");
+        assert_eq!(sanitize("search.web"), "search_web");
+        assert_eq!(sanitize("github/create issue"), "github_create_issue");
+
+        let long = "a".repeat(100);
+        let sanitized = sanitize(&long);
+        assert_eq!(sanitized.len(), MAX_TOOL_NAME_LENGTH);
+        assert_ne!(sanitized, sanitize(&"a".repeat(99)));
+        assert_eq!(sanitize("").len(), 13);
+    }
+
+    #[test]
+    fn test_colliding_names_are_told_apart() {
+        let mut names = ToolNames::default();
+        let dotted = names.send("search.web");
+        let underscored = names.send("search_web");
+
+        assert_eq!(dotted, "search_web");
+        assert_ne!(underscored, dotted);
+        assert_eq!(names.restore(dotted), "search.web");
+        assert_eq!(names.restore(underscored), "search_web");
+        assert_eq!(names.restore("unknown".into()), "unknown");
+    }
+
+    #[tokio::test]
+    async fn test_tool_names_are_restored_in_responses() {
+        let mock = MockBedrock::new().with_response(MockResponse::tool_use(
+            "tool_1",
+            "search_web",
+            json!({ "query": "rig" }),
+        ));
+        let model = CompletionModel::new(mock.client(), AMAZON_NOVA_PRO);
+        let request = model
+            .completion_request("Search for rig")
+            .tool(ToolDefinition {
+                name: "search.web".into(),
+                description: "Search the web".into(),
+                parameters: json!({ "type": "object" }),
+            })
+            .build();
+
+        let response = model.completion(request).await.unwrap();
+
+        let AssistantContent::ToolCall(tool_call) = response.choice.first() else {
+            panic!("expected a tool call");
+        };
+        assert_eq!(tool_call.function.name, "search.web");
+        let body = mock.requests()[0].json().unwrap();
+        assert_eq!(
+            body["toolConfig"]["tools"][0]["toolSpec"]["name"],
+            "search_web"
+        );
+    }
+}
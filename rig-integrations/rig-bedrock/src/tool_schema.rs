@@ -0,0 +1,236 @@
+//! Conversion of tool input schemas to the JSON Schema subset Bedrock accepts.
+//!
+//! Schemas derived with `schemars` use constructs that Bedrock, or the models behind it, reject:
+//! references to `$defs`, `oneOf` for enums, `const`, and formats such as `uint8` or `double`.
+//! Completion models send the tool parameters [`downconvert`]ed instead, so derived schemas work
+//! as they are:
+//! - `$ref`s to the schema's own definitions are inlined, and recursive ones accept any value
+//! - `oneOf` becomes `anyOf`, and alternatives of constants an `enum`
+//! - `const` becomes a single value `enum`
+//! - an `allOf` of a single schema is merged into its parent
+//! - formats other than the common string formats are removed, as are `$schema`, `$id`,
+//!   `$comment` and the definitions
+//!
+//! Tool calls are still validated against the original schema, see [`crate::tool_validation`].
+
+use serde_json::{Map, Value, json};
+
+/// The formats kept by [`downconvert`].
+const SUPPORTED_FORMATS: &[&str] = &[
+    "date-time",
+    "date",
+    "time",
+    "duration",
+    "email",
+    "hostname",
+    "uri",
+    "ipv4",
+    "ipv6",
+    "uuid",
+];
+
+/// `schema` converted to the JSON Schema subset Bedrock accepts, see [`crate::tool_schema`].
+pub fn downconvert(schema: &Value) -> Value {
+    convert(schema, schema, &mut Vec::new())
+}
+
+/// Convert `schema`, a part of `root`, with `refs` the references being inlined.
+fn convert(schema: &Value, root: &Value, refs: &mut Vec<String>) -> Value {
+    let Value::Object(object) = schema else {
+        return schema.clone();
+    };
+
+    if let Some(Value::String(reference)) = object.get("$ref") {
+        let target = reference
+            .strip_prefix('#')
+            .filter(|_| !refs.contains(reference))
+            .and_then(|pointer| root.pointer(pointer));
+        let mut inlined = match target {
+            Some(target) => {
+                refs.push(reference.clone());
+                let inlined = convert(target, root, refs);
+                refs.pop();
+                inlined
+            }
+            None => json!({}),
+        };
+        // Keywords next to the reference, e.g. a description, apply as well
+        let siblings = object
+            .iter()
+            .filter(|(key, _)| key.as_str() != "$ref")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Map<_, _>>();
+        if let (Value::Object(inlined), Value::Object(siblings)) =
+            (&mut inlined, convert(&Value::Object(siblings), root, refs))
+        {
+            inlined.extend(siblings);
+        }
+        return inlined;
+    }
+
+    let mut converted = Map::new();
+    for (key, value) in object {
+        match key.as_str() {
+            "$defs" | "definitions" | "$schema" | "$id" | "$comment" => {}
+            "format"
+                if !value
+                    .as_str()
+                    .is_some_and(|f| SUPPORTED_FORMATS.contains(&f)) => {}
+            "const" => {
+                converted.insert("enum".into(), json!([value]));
+            }
+            "properties" | "patternProperties" => {
+                let properties = match value {
+                    Value::Object(properties) => Value::Object(
+                        properties
+                            .iter()
+                            .map(|(name, schema)| (name.clone(), convert(schema, root, refs)))
+                            .collect(),
+                    ),
+                    value => value.clone(),
+                };
+                converted.insert(key.clone(), properties);
+            }
+            "oneOf" | "anyOf" | "allOf" | "prefixItems" => {
+                let key = if key == "oneOf" { "anyOf" } else { key };
+                let schemas = match value {
+                    Value::Array(schemas) => Value::Array(
+                        schemas
+                            .iter()
+                            .map(|schema| convert(schema, root, refs))
+                            .collect(),
+                    ),
+                    value => value.clone(),
+                };
+                converted.insert(key.into(), schemas);
+            }
+            "items" | "additionalProperties" | "not" | "contains" | "if" | "then" | "else" => {
+                converted.insert(key.clone(), convert(value, root, refs));
+            }
+            _ => {
+                converted.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    collapse_constants(&mut converted);
+    merge_single_all_of(&mut converted);
+    Value::Object(converted)
+}
+
+/// Replace an `anyOf` of constants, e.g. the variants of an enum, by an `enum`.
+fn collapse_constants(schema: &mut Map<String, Value>) {
+    let Some(Value::Array(variants)) = schema.get("anyOf") else {
+        return;
+    };
+    let mut values = Vec::with_capacity(variants.len());
+    let mut types = Vec::new();
+    for variant in variants {
+        match variant.get("enum") {
+            Some(Value::Array(value)) if value.len() == 1 => values.push(value[0].clone()),
+            _ => return,
+        }
+        if let Some(kind) = variant.get("type")
+            && !types.contains(kind)
+        {
+            types.push(kind.clone());
+        }
+    }
+
+    schema.remove("anyOf");
+    schema.insert("enum".into(), Value::Array(values));
+    if let [kind] = types.as_slice() {
+        schema.entry("type").or_insert_with(|| kind.clone());
+    }
+}
+
+/// Merge an `allOf` of a single schema into `schema`, as `schemars` writes references with a
+/// description.
+fn merge_single_all_of(schema: &mut Map<String, Value>) {
+    let Some(Value::Array(schemas)) = schema.get("allOf") else {
+        return;
+    };
+    let [Value::Object(single)] = schemas.as_slice() else {
+        return;
+    };
+    let single = single.clone();
+    schema.remove("allOf");
+    for (key, value) in single {
+        schema.entry(key).or_insert(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downconvert_derived_schema() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Order",
+            "type": "object",
+            "properties": {
+                "quantity": { "type": "integer", "format": "uint32", "minimum": 0 },
+                "placed_at": { "type": "string", "format": "date-time" },
+                "status": {
+                    "oneOf": [
+                        { "type": "string", "const": "pending", "description": "Not shipped" },
+                        { "type": "string", "const": "shipped" }
+                    ]
+                },
+                "address": { "$ref": "#/$defs/Address", "description": "Shipping address" },
+                "billing": { "allOf": [{ "$ref": "#/$defs/Address" }], "description": "Billing" }
+            },
+            "required": ["quantity", "status"],
+            "$defs": {
+                "Address": {
+                    "type": "object",
+                    "properties": {
+                        "city": { "type": "string" },
+                        "previous": { "$ref": "#/$defs/Address" }
+                    }
+                }
+            }
+        });
+
+        let address = |description: &str| {
+            json!({
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string" },
+                    "previous": {}
+                },
+                "description": description
+            })
+        };
+        assert_eq!(
+            downconvert(&schema),
+            json!({
+                "title": "Order",
+                "type": "object",
+                "properties": {
+                    "quantity": { "type": "integer", "minimum": 0 },
+                    "placed_at": { "type": "string", "format": "date-time" },
+                    "status": { "type": "string", "enum": ["pending", "shipped"] },
+                    "address": address("Shipping address"),
+                    "billing": address("Billing")
+                },
+                "required": ["quantity", "status"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_downconvert_keeps_supported_schemas() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "value": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                "tags": { "type": "array", "items": { "type": "string", "format": "email" } }
+            }
+        });
+
+        assert_eq!(downconvert(&schema), schema);
+    }
+}
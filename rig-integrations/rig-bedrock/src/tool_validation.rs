@@ -0,0 +1,489 @@
+//! Validation of the arguments of the tool calls made by a model against the JSON Schema of the
+//! called tool, so tools never receive structurally invalid input.
+//!
+//! ```rust,ignore
+//! let model = client
+//!     .completion_model(AMAZON_NOVA_PRO)
+//!     .with_tool_call_validation(ToolCallValidation::Reject);
+//! ```
+//!
+//! Invalid tool calls can also be sent back to the model, as tool results with an error status
+//! listing the violations, so it calls the tools again with fixed arguments, see
+//! [`CompletionModel::with_tool_call_repair`].
+//!
+//! The keywords used by tool schemas are checked: `type`, `enum`, `const`, `properties`,
+//! `required`, `additionalProperties`, `items`, the length and range bounds, `allOf`, `anyOf`,
+//! `oneOf` and local `$ref`s. Other keywords, such as `format` or `pattern`, are ignored.
+//!
+//! [`CompletionModel::with_tool_call_repair`]: crate::completion::CompletionModel::with_tool_call_repair
+
+use std::fmt;
+
+use rig::OneOrMany;
+use rig::completion::{CompletionError, ToolDefinition};
+use rig::message::{AssistantContent, ToolCall, ToolResultContent, UserContent};
+use serde_json::Value;
+
+/// What to do with tool calls whose arguments don't match the schema of the tool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolCallValidation {
+    /// Fail the completion with an [`InvalidToolCallError`].
+    Reject,
+    /// Log a warning and return the tool call anyway.
+    Flag,
+}
+
+/// A part of a value that doesn't match its schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// Where the violation is, e.g. `$.address.zip` or `$.items[2]`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// A tool call made by a model with arguments that don't match the schema of the tool, or to a
+/// tool that wasn't offered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidToolCallError {
+    pub tool: String,
+    pub id: String,
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl InvalidToolCallError {
+    /// The invalid tool call a completion failed with, if any.
+    pub fn from_completion_error(error: &CompletionError) -> Option<&Self> {
+        match error {
+            CompletionError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for InvalidToolCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid arguments for tool `{}`: ", self.tool)?;
+        for (index, violation) in self.violations.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidToolCallError {}
+
+impl From<InvalidToolCallError> for CompletionError {
+    fn from(error: InvalidToolCallError) -> Self {
+        CompletionError::RequestError(Box::new(error))
+    }
+}
+
+/// The parts of `value` that don't match `schema`, empty if it matches.
+pub fn validate(schema: &Value, value: &Value) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    validate_at(schema, schema, value, "$", &mut violations);
+    violations
+}
+
+/// Validate the arguments of `tool_call` against the schema of the tool it calls among `tools`.
+pub fn validate_tool_call(
+    tools: &[ToolDefinition],
+    tool_call: &ToolCall,
+) -> Result<(), InvalidToolCallError> {
+    let name = &tool_call.function.name;
+    let violations = match tools.iter().find(|tool| &tool.name == name) {
+        Some(tool) => validate(&tool.parameters, &tool_call.function.arguments),
+        None => vec![SchemaViolation {
+            path: "$".into(),
+            message: format!("there is no tool named `{name}`"),
+        }],
+    };
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(InvalidToolCallError {
+            tool: name.clone(),
+            id: tool_call.id.clone(),
+            violations,
+        })
+    }
+}
+
+impl ToolCallValidation {
+    /// Validate the tool calls of `choice`, failing or logging invalid ones.
+    pub(crate) fn check(
+        self,
+        tools: &[ToolDefinition],
+        choice: &OneOrMany<AssistantContent>,
+    ) -> Result<(), InvalidToolCallError> {
+        for content in choice.iter() {
+            if let AssistantContent::ToolCall(tool_call) = content {
+                self.check_tool_call(tools, tool_call)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_tool_call(
+        self,
+        tools: &[ToolDefinition],
+        tool_call: &ToolCall,
+    ) -> Result<(), InvalidToolCallError> {
+        match (self, validate_tool_call(tools, tool_call)) {
+            (Self::Flag, Err(error)) => {
+                tracing::warn!(target: "rig::bedrock", "{error}");
+                Ok(())
+            }
+            (_, result) => result,
+        }
+    }
+}
+
+/// The tool results asking the model to call the tools of `choice` again when some of them have
+/// invalid arguments, or `None` if they are all valid.
+///
+/// Bedrock expects a result for every tool call of a turn, so the valid tool calls of the turn
+/// get one too, telling the model they weren't run.
+pub(crate) fn repair_results(
+    tools: &[ToolDefinition],
+    choice: &OneOrMany<AssistantContent>,
+) -> Option<OneOrMany<UserContent>> {
+    let tool_calls = choice
+        .iter()
+        .filter_map(|content| match content {
+            AssistantContent::ToolCall(tool_call) => Some(tool_call),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let errors = tool_calls
+        .iter()
+        .map(|tool_call| validate_tool_call(tools, tool_call).err())
+        .collect::<Vec<_>>();
+    if errors.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let results = tool_calls.iter().zip(errors).map(|(tool_call, error)| {
+        let text = match error {
+            Some(error) => {
+                format!("{error}. Call the tool again with arguments matching its schema.")
+            }
+            None => "Not run because another tool call had invalid arguments. Call the tool \
+                     again if it is still needed."
+                .to_string(),
+        };
+        UserContent::tool_result(&tool_call.id, OneOrMany::one(ToolResultContent::text(text)))
+    });
+    OneOrMany::many(results).ok()
+}
+
+fn validate_at(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    let mut violation = |message: String| {
+        violations.push(SchemaViolation {
+            path: path.to_string(),
+            message,
+        })
+    };
+    let schema = match schema {
+        Value::Bool(false) => return violation("no value is allowed here".into()),
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str)
+        && let Some(target) = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+    {
+        validate_at(root, target, value, path, violations);
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let types = match expected {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            Value::String(name) => vec![name.as_str()],
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            return violation(format!(
+                "expected {}, found {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        violation(format!(
+            "{value} isn't one of {}",
+            Value::from(allowed.clone())
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        violation(format!("expected {expected}, found {value}"));
+    }
+
+    match value {
+        Value::Object(object) => {
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !object.contains_key(name) {
+                    violation(format!("missing required property `{name}`"));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, property) in object {
+                let property_path = format!("{path}.{name}");
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(property_schema) => {
+                        validate_at(root, property_schema, property, &property_path, violations)
+                    }
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => violations.push(SchemaViolation {
+                            path: property_path,
+                            message: "unknown property".into(),
+                        }),
+                        Some(additional) => {
+                            validate_at(root, additional, property, &property_path, violations)
+                        }
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                && (items.len() as u64) < min
+            {
+                violation(format!(
+                    "expected at least {min} items, found {}",
+                    items.len()
+                ));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+                && (items.len() as u64) > max
+            {
+                violation(format!(
+                    "expected at most {max} items, found {}",
+                    items.len()
+                ));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(
+                        root,
+                        item_schema,
+                        item,
+                        &format!("{path}[{index}]"),
+                        violations,
+                    );
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                && length < min
+            {
+                violation(format!(
+                    "expected at least {min} characters, found {length}"
+                ));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+                && length > max
+            {
+                violation(format!("expected at most {max} characters, found {length}"));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+            if let Some(minimum) = bound("minimum")
+                && number < minimum
+            {
+                violation(format!("expected at least {minimum}, found {number}"));
+            }
+            if let Some(maximum) = bound("maximum")
+                && number > maximum
+            {
+                violation(format!("expected at most {maximum}, found {number}"));
+            }
+            if let Some(minimum) = bound("exclusiveMinimum")
+                && number <= minimum
+            {
+                violation(format!("expected more than {minimum}, found {number}"));
+            }
+            if let Some(maximum) = bound("exclusiveMaximum")
+                && number >= maximum
+            {
+                violation(format!("expected less than {maximum}, found {number}"));
+            }
+        }
+        _ => {}
+    }
+
+    for sub_schema in schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        validate_at(root, sub_schema, value, path, violations);
+    }
+    let matching = |sub_schemas: &Vec<Value>| {
+        sub_schemas
+            .iter()
+            .filter(|sub_schema| {
+                let mut sub_violations = Vec::new();
+                validate_at(root, sub_schema, value, path, &mut sub_violations);
+                sub_violations.is_empty()
+            })
+            .count()
+    };
+    if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array)
+        && matching(any_of) == 0
+    {
+        violations.push(SchemaViolation {
+            path: path.to_string(),
+            message: "doesn't match any of the allowed schemas".into(),
+        });
+    }
+    if let Some(one_of) = schema.get("oneOf").and_then(Value::as_array) {
+        let count = matching(one_of);
+        if count != 1 {
+            violations.push(SchemaViolation {
+                path: path.to_string(),
+                message: format!("expected to match exactly one schema, matches {count}"),
+            });
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        "number" => value.is_number(),
+        name => type_name(value) == name,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn weather_tool() -> ToolDefinition {
+        ToolDefinition {
+            name: "get_weather".into(),
+            description: "Get the weather of a city".into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string", "minLength": 1 },
+                    "unit": { "$ref": "#/$defs/Unit" },
+                    "days": { "type": "integer", "minimum": 1, "maximum": 7 }
+                },
+                "required": ["city"],
+                "additionalProperties": false,
+                "$defs": { "Unit": { "enum": ["celsius", "fahrenheit"] } }
+            }),
+        }
+    }
+
+    fn tool_call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall::new(
+            "tool_1".into(),
+            rig::message::ToolFunction::new(name.into(), arguments),
+        )
+    }
+
+    #[test]
+    fn test_valid_arguments() {
+        let tools = [weather_tool()];
+        let call = tool_call(
+            "get_weather",
+            json!({ "city": "Lisbon", "unit": "celsius", "days": 3 }),
+        );
+
+        assert_eq!(validate_tool_call(&tools, &call), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let tools = [weather_tool()];
+        let call = tool_call(
+            "get_weather",
+            json!({ "unit": "kelvin", "days": 2.5, "country": "PT" }),
+        );
+
+        let error = validate_tool_call(&tools, &call).unwrap_err();
+        let violations = error
+            .violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            vec![
+                "$: missing required property `city`",
+                "$.country: unknown property",
+                "$.days: expected integer, found number",
+                "$.unit: \"kelvin\" isn't one of [\"celsius\",\"fahrenheit\"]",
+            ]
+        );
+
+        let unknown = validate_tool_call(&tools, &tool_call("get_time", json!({}))).unwrap_err();
+        assert_eq!(
+            unknown.violations[0].message,
+            "there is no tool named `get_time`"
+        );
+    }
+
+    #[test]
+    fn test_flag_keeps_invalid_tool_calls() {
+        let tools = [weather_tool()];
+        let choice = OneOrMany::one(AssistantContent::ToolCall(tool_call(
+            "get_weather",
+            json!({}),
+        )));
+
+        assert!(ToolCallValidation::Flag.check(&tools, &choice).is_ok());
+        assert!(ToolCallValidation::Reject.check(&tools, &choice).is_err());
+    }
+}
@@ -7,22 +7,34 @@ use rig::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::types::message::RigMessage;
-
-use super::{converse_output::InternalConverseOutput, json::AwsDocument};
+use super::{
+    converse_output::{
+        ContentBlock, ConversationRole, ConverseMetrics, ConverseOutput, ConverseTrace,
+        InternalConverseOutput, Message, StopReason, TokenUsage,
+    },
+    errors::{ContentBlockFailure, ContentConversionError},
+    json::AwsDocument,
+};
+use crate::pricing::CostEstimate;
 use rig::completion;
 
-#[derive(Clone, Deserialize, Serialize)]
+/// The response of a Converse call, as returned in
+/// [`CompletionResponse::raw_response`](completion::CompletionResponse::raw_response).
+///
+/// The output message, usage, metrics, stop reason, trace and additional fields are crate types
+/// rather than AWS SDK types, so responses can be serialized, e.g. to be cached or recorded, and
+/// read without depending on the SDK version.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct AwsConverseOutput(pub InternalConverseOutput);
 
 impl TryFrom<AwsConverseOutput> for completion::CompletionResponse<AwsConverseOutput> {
     type Error = CompletionError;
 
     fn try_from(value: AwsConverseOutput) -> Result<Self, Self::Error> {
-        let message: RigMessage = value
-            .to_owned()
+        let message = value
             .0
             .output
+            .as_ref()
             .ok_or(CompletionError::ProviderError(
                 "Model didn't return any output".into(),
             ))?
@@ -31,16 +43,38 @@ impl TryFrom<AwsConverseOutput> for completion::CompletionResponse<AwsConverseOu
                 CompletionError::ProviderError(
                     "Failed to extract message from converse output".into(),
                 )
-            })?
-            .to_owned()
-            .try_into()?;
-
-        let choice = match message.0 {
-            completion::Message::Assistant { content, .. } => Ok(content),
-            _ => Err(CompletionError::ResponseError(
-                "Response contained no message or tool call (empty)".to_owned(),
-            )),
-        }?;
+            })?;
+
+        if message.role != ConversationRole::Assistant {
+            return Err(CompletionError::ResponseError(format!(
+                "Response message has the {:?} role instead of the assistant role",
+                message.role
+            )));
+        }
+
+        // Blocks without a rig equivalent are skipped; they stay available through
+        // `AwsConverseOutput::unsupported_content`
+        let mut choice = Vec::with_capacity(message.content.len());
+        let mut failures = Vec::new();
+        for (index, block) in message.content.iter().enumerate() {
+            match assistant_content(block.clone()) {
+                Ok(content) => choice.push(content),
+                Err(e) => {
+                    let failure = ContentBlockFailure::new(index, block.kind(), e);
+                    tracing::warn!(
+                        target: "rig::bedrock",
+                        "Skipping response content block {index} ({}): {}",
+                        failure.kind,
+                        failure.reason
+                    );
+                    failures.push(failure);
+                }
+            }
+        }
+        let choice = OneOrMany::many(choice).map_err(|_| ContentConversionError {
+            role: "assistant".into(),
+            failures,
+        })?;
 
         let usage = value
             .0
@@ -74,6 +108,71 @@ impl TryFrom<AwsConverseOutput> for completion::CompletionResponse<AwsConverseOu
     }
 }
 
+impl AwsConverseOutput {
+    /// The message generated by the model, if the output is a message.
+    pub fn message(&self) -> Option<&Message> {
+        self.0.output.as_ref()?.as_message().ok()
+    }
+
+    pub fn stop_reason(&self) -> &StopReason {
+        &self.0.stop_reason
+    }
+
+    pub fn usage(&self) -> Option<&TokenUsage> {
+        self.0.usage.as_ref()
+    }
+
+    pub fn metrics(&self) -> Option<&ConverseMetrics> {
+        self.0.metrics.as_ref()
+    }
+
+    /// The guardrail and prompt router trace of the call.
+    pub fn trace(&self) -> Option<&ConverseTrace> {
+        self.0.trace.as_ref()
+    }
+
+    /// The fields of the response specific to the model, e.g. the `stop_sequence` of Anthropic
+    /// models, as JSON.
+    pub fn additional_fields(&self) -> Option<serde_json::Value> {
+        let fields = self.0.additional_model_response_fields.clone()?;
+        let fields = aws_smithy_types::Document::try_from(fields).ok()?;
+        Some(AwsDocument(fields).into())
+    }
+
+    /// The model id or inference profile that served the call, if known.
+    pub fn model(&self) -> Option<&str> {
+        self.0.model.as_deref()
+    }
+
+    pub fn estimated_cost(&self) -> Option<&CostEstimate> {
+        self.0.estimated_cost.as_ref()
+    }
+
+    /// The content blocks of the response that have no rig equivalent (e.g. citations or block
+    /// types added to Bedrock after this crate was released), serialized as JSON.
+    ///
+    /// Block types unknown to the AWS SDK are reported as `"Unknown"`, since the SDK doesn't
+    /// keep their data.
+    pub fn unsupported_content(&self) -> Vec<serde_json::Value> {
+        let Some(Ok(message)) = self.0.output.as_ref().map(ConverseOutput::as_message) else {
+            return Vec::new();
+        };
+
+        message
+            .content
+            .iter()
+            .filter(|block| assistant_content((*block).clone()).is_err())
+            .filter_map(|block| serde_json::to_value(block).ok())
+            .collect()
+    }
+}
+
+fn assistant_content(block: ContentBlock) -> Result<AssistantContent, CompletionError> {
+    let block = aws_bedrock::ContentBlock::try_from(block)
+        .map_err(|e| CompletionError::ProviderError(format!("Type conversion error: {e}")))?;
+    RigAssistantContent::try_from(block).map(|content| content.0)
+}
+
 pub struct RigAssistantContent(pub AssistantContent);
 
 impl TryFrom<aws_bedrock::ContentBlock> for RigAssistantContent {
@@ -155,7 +254,8 @@ impl TryFrom<RigAssistantContent> for aws_bedrock::ContentBlock {
 #[cfg(test)]
 mod tests {
     use crate::types::{
-        assistant_content::RigAssistantContent, converse_output::InternalConverseOutput,
+        assistant_content::RigAssistantContent,
+        converse_output::{ContentBlock, ConverseOutput, InternalConverseOutput, StopReason},
         errors::TypeConversionError,
     };
 
@@ -191,6 +291,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unknown_content_blocks_are_skipped() {
+        let message = aws_bedrock::Message::builder()
+            .role(aws_bedrock::ConversationRole::Assistant)
+            .content(aws_bedrock::ContentBlock::Text("txt".into()))
+            .build()
+            .unwrap();
+        let converse_output =
+            aws_sdk_bedrockruntime::operation::converse::ConverseOutput::builder()
+                .output(aws_bedrock::ConverseOutput::Message(message))
+                .stop_reason(aws_bedrock::StopReason::EndTurn)
+                .build()
+                .unwrap();
+        let mut converse_output: InternalConverseOutput = converse_output.try_into().unwrap();
+        // The SDK's own unknown variant can't be constructed, so add ours directly
+        if let Some(ConverseOutput::Message(message)) = &mut converse_output.output {
+            message.content.push(ContentBlock::Unknown);
+        }
+
+        let completion: completion::CompletionResponse<AwsConverseOutput> =
+            AwsConverseOutput(converse_output).try_into().unwrap();
+        assert_eq!(
+            completion.choice,
+            OneOrMany::one(AssistantContent::Text("txt".into()))
+        );
+        assert_eq!(
+            completion.raw_response.unsupported_content(),
+            vec![serde_json::json!("Unknown")]
+        );
+    }
+
+    #[test]
+    fn converse_output_accessors_survive_serialization() {
+        let message = aws_bedrock::Message::builder()
+            .role(aws_bedrock::ConversationRole::Assistant)
+            .content(aws_bedrock::ContentBlock::Text("txt".into()))
+            .build()
+            .unwrap();
+        let converse_output =
+            aws_sdk_bedrockruntime::operation::converse::ConverseOutput::builder()
+                .output(aws_bedrock::ConverseOutput::Message(message))
+                .stop_reason(aws_bedrock::StopReason::MaxTokens)
+                .usage(
+                    aws_bedrock::TokenUsage::builder()
+                        .input_tokens(3)
+                        .output_tokens(5)
+                        .total_tokens(8)
+                        .build()
+                        .unwrap(),
+                )
+                .additional_model_response_fields(
+                    crate::types::json::AwsDocument::from(
+                        serde_json::json!({ "stop_sequence": null }),
+                    )
+                    .0,
+                )
+                .build()
+                .unwrap();
+        let output = AwsConverseOutput(converse_output.try_into().unwrap());
+
+        let json = serde_json::to_string(&output).unwrap();
+        let output: AwsConverseOutput = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(output.stop_reason(), &StopReason::MaxTokens);
+        assert_eq!(output.usage().unwrap().total_tokens, 8);
+        assert_eq!(
+            output.message().unwrap().content,
+            vec![ContentBlock::Text("txt".into())]
+        );
+        assert_eq!(
+            output.additional_fields(),
+            Some(serde_json::json!({ "stop_sequence": null }))
+        );
+        assert!(output.trace().is_none());
+    }
+
     #[test]
     fn aws_content_block_to_assistant_content() {
         let content_block = aws_bedrock::ContentBlock::Text("text".into());
@@ -1,3 +1,4 @@
+use crate::tool_schema;
 use crate::types::json::AwsDocument;
 use crate::types::message::RigMessage;
 use aws_sdk_bedrockruntime::types as aws_bedrock;
@@ -37,9 +38,24 @@ impl AwsCompletionRequest {
     }
 
     pub fn tools_config(&self) -> Result<Option<ToolConfiguration>, CompletionError> {
+        self.tool_configuration(false)
+    }
+
+    /// Like [`AwsCompletionRequest::tools_config`], with a prompt cache point after the tool
+    /// definitions so they are cached across turns.
+    pub fn tools_config_with_cache_point(
+        &self,
+    ) -> Result<Option<ToolConfiguration>, CompletionError> {
+        self.tool_configuration(true)
+    }
+
+    fn tool_configuration(
+        &self,
+        cache_point: bool,
+    ) -> Result<Option<ToolConfiguration>, CompletionError> {
         let mut tools = vec![];
         for tool_definition in self.0.tools.iter() {
-            let doc: AwsDocument = tool_definition.parameters.clone().into();
+            let doc: AwsDocument = tool_schema::downconvert(&tool_definition.parameters).into();
             let schema = ToolInputSchema::Json(doc.0);
             let tool = Tool::ToolSpec(
                 ToolSpecification::builder()
@@ -53,33 +69,35 @@ impl AwsCompletionRequest {
         }
 
         if !tools.is_empty() {
+            if cache_point {
+                tools.push(Tool::CachePoint(cache_point_block()?));
+            }
+
             // Convert rig's ToolChoice to AWS Bedrock ToolChoice
             use aws_sdk_bedrockruntime::types as aws_bedrock;
-            let tool_choice = self.0.tool_choice.as_ref().and_then(|choice| {
-                match choice {
-                    rig::message::ToolChoice::Auto => Some(aws_bedrock::ToolChoice::Auto(
-                        aws_bedrock::AutoToolChoice::builder().build(),
-                    )),
-                    rig::message::ToolChoice::Required => Some(aws_bedrock::ToolChoice::Any(
-                        aws_bedrock::AnyToolChoice::builder().build(),
-                    )),
-                    rig::message::ToolChoice::None => {
-                        // Bedrock doesn't have a "None" option - just omit tool_choice
-                        None
-                    }
-                    rig::message::ToolChoice::Specific { function_names } => {
-                        // Use the first function name for Bedrock's specific tool choice
-                        function_names.first().map(|name| {
-                            aws_bedrock::ToolChoice::Tool(
-                                aws_bedrock::SpecificToolChoice::builder()
-                                    .name(name.clone())
-                                    .build()
-                                    .expect("Failed to build SpecificToolChoice"),
-                            )
+            let tool_choice = match self.0.tool_choice.as_ref() {
+                Some(rig::message::ToolChoice::Auto) => Some(aws_bedrock::ToolChoice::Auto(
+                    aws_bedrock::AutoToolChoice::builder().build(),
+                )),
+                Some(rig::message::ToolChoice::Required) => Some(aws_bedrock::ToolChoice::Any(
+                    aws_bedrock::AnyToolChoice::builder().build(),
+                )),
+                // Bedrock doesn't have a "None" option - just omit tool_choice
+                Some(rig::message::ToolChoice::None) | None => None,
+                Some(rig::message::ToolChoice::Specific { function_names }) => {
+                    // Use the first function name for Bedrock's specific tool choice
+                    function_names
+                        .first()
+                        .map(|name| {
+                            aws_bedrock::SpecificToolChoice::builder()
+                                .name(name.clone())
+                                .build()
+                                .map(aws_bedrock::ToolChoice::Tool)
+                                .map_err(|e| CompletionError::RequestError(e.into()))
                         })
-                    }
+                        .transpose()?
                 }
-            });
+            };
 
             let config = ToolConfiguration::builder()
                 .set_tools(Some(tools))
@@ -100,37 +118,79 @@ impl AwsCompletionRequest {
             .map(|system_prompt| vec![SystemContentBlock::Text(system_prompt)])
     }
 
-    pub fn messages(&self) -> Result<Vec<aws_bedrock::Message>, CompletionError> {
-        let mut full_history: Vec<Message> = Vec::new();
-
-        if !self.0.documents.is_empty() {
-            let messages = self
-                .0
-                .documents
-                .iter()
-                .map(|doc| doc.to_string())
-                .collect::<Vec<_>>()
-                .join(" | ");
-
-            let content = OneOrMany::one(UserContent::document(
-                messages,
-                Some(DocumentMediaType::TXT),
-            ));
-
-            full_history.push(Message::User { content });
-        }
+    /// Like [`Self::system_prompt`], with a prompt cache point after the preamble.
+    pub fn system_prompt_with_cache_point(
+        &self,
+    ) -> Result<Option<Vec<SystemContentBlock>>, CompletionError> {
+        let Some(mut system_prompt) = self.system_prompt() else {
+            return Ok(None);
+        };
+        system_prompt.push(SystemContentBlock::CachePoint(cache_point_block()?));
+        Ok(Some(system_prompt))
+    }
 
-        self.0.chat_history.iter().for_each(|message| {
-            full_history.push(message.clone());
-        });
+    pub fn messages(&self) -> Result<Vec<aws_bedrock::Message>, CompletionError> {
+        self.documents_message()
+            .into_iter()
+            .chain(self.0.chat_history.iter().cloned())
+            .map(|message| RigMessage(message).try_into())
+            .collect()
+    }
 
-        full_history
+    /// Like [`Self::messages`], but moves the images and documents of the chat history into the
+    /// Bedrock messages instead of copying them.
+    pub fn into_messages(self) -> Result<Vec<aws_bedrock::Message>, CompletionError> {
+        self.documents_message()
             .into_iter()
+            .chain(self.0.chat_history)
             .map(|message| RigMessage(message).try_into())
-            .collect::<Result<Vec<aws_bedrock::Message>, _>>()
+            .collect()
+    }
+
+    /// Like [`Self::into_messages`], with a prompt cache point after the documents of the
+    /// request.
+    pub fn into_messages_with_cache_point(
+        self,
+    ) -> Result<Vec<aws_bedrock::Message>, CompletionError> {
+        let has_documents = !self.0.documents.is_empty();
+        let mut messages = self.into_messages()?;
+        if has_documents {
+            messages[0]
+                .content
+                .push(aws_bedrock::ContentBlock::CachePoint(cache_point_block()?));
+        }
+        Ok(messages)
+    }
+
+    /// The documents of the request, as a single text document.
+    fn documents_message(&self) -> Option<Message> {
+        if self.0.documents.is_empty() {
+            return None;
+        }
+
+        let messages = self
+            .0
+            .documents
+            .iter()
+            .map(|doc| doc.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        let content = OneOrMany::one(UserContent::document(
+            messages,
+            Some(DocumentMediaType::TXT),
+        ));
+        Some(Message::User { content })
     }
 }
 
+fn cache_point_block() -> Result<aws_bedrock::CachePointBlock, CompletionError> {
+    aws_bedrock::CachePointBlock::builder()
+        .r#type(aws_bedrock::CachePointType::Default)
+        .build()
+        .map_err(|e| CompletionError::RequestError(e.into()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -384,4 +444,87 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_tool_cache_point() {
+        let request = CompletionRequest {
+            tools: vec![ToolDefinition {
+                name: "test_tool".to_string(),
+                description: "A test tool".to_string(),
+                parameters: serde_json::json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            }],
+            ..minimal_request()
+        };
+
+        let config = AwsCompletionRequest(request)
+            .tools_config_with_cache_point()
+            .expect("Should build tool config")
+            .unwrap();
+
+        assert_eq!(config.tools().len(), 2);
+        assert!(matches!(&config.tools()[0], aws_bedrock::Tool::ToolSpec(_)));
+        assert!(matches!(
+            &config.tools()[1],
+            aws_bedrock::Tool::CachePoint(_)
+        ));
+
+        // No cache point without tools
+        let config = AwsCompletionRequest(minimal_request())
+            .tools_config_with_cache_point()
+            .expect("Should build tool config");
+        assert!(config.is_none());
+    }
+
+    #[test]
+    fn test_prompt_cache_points() {
+        let request = CompletionRequest {
+            preamble: Some("You are a helpful assistant".to_string()),
+            documents: vec![rig::completion::Document {
+                id: "doc_1".to_string(),
+                text: "Rust is a systems programming language".to_string(),
+                additional_props: Default::default(),
+            }],
+            ..minimal_request()
+        };
+        let request = AwsCompletionRequest(request);
+
+        let system_prompt = request
+            .system_prompt_with_cache_point()
+            .expect("Should build system prompt")
+            .unwrap();
+        assert_eq!(system_prompt.len(), 2);
+        assert!(matches!(
+            &system_prompt[1],
+            SystemContentBlock::CachePoint(_)
+        ));
+
+        let messages = request
+            .into_messages_with_cache_point()
+            .expect("Should build messages");
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(
+            messages[0].content().last(),
+            Some(aws_bedrock::ContentBlock::CachePoint(_))
+        ));
+        assert!(!matches!(
+            messages[1].content().last(),
+            Some(aws_bedrock::ContentBlock::CachePoint(_))
+        ));
+
+        // No cache points without a preamble or documents
+        let request = AwsCompletionRequest(minimal_request());
+        assert!(
+            request
+                .system_prompt_with_cache_point()
+                .expect("Should build system prompt")
+                .is_none()
+        );
+        let messages = request
+            .into_messages_with_cache_point()
+            .expect("Should build messages");
+        assert_eq!(messages[0].content().len(), 1);
+    }
 }
@@ -5,6 +5,7 @@ use std::{collections::HashMap, fmt};
 use serde::{Deserialize, Serialize};
 
 use super::errors::TypeConversionError;
+use crate::pricing::CostEstimate;
 
 /// Our own implementation of the AWS Bedrock runtime "converse" operation output.
 /// The reason why we need to implement this is that we need to impl Deserialize/Serialize on top of this.
@@ -24,6 +25,13 @@ pub struct InternalConverseOutput {
     pub trace: Option<ConverseTrace>,
     /// <p>Model performance settings for the request.</p>
     pub performance_config: Option<PerformanceConfiguration>,
+    /// Cost of the call estimated from its usage, if the model's pricing is known.
+    #[serde(default)]
+    pub estimated_cost: Option<CostEstimate>,
+    /// The model id or inference profile that served the call, which differs from the requested
+    /// model when the request fell back to another model.
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 impl InternalConverseOutput {
@@ -61,6 +69,8 @@ impl TryFrom<aws_sdk_bedrockruntime::operation::converse::ConverseOutput>
                 .transpose()?,
             trace: trace.map(|x| x.try_into()).transpose()?,
             performance_config: performance_config.map(|x| x.try_into()).transpose()?,
+            estimated_cost: None,
+            model: None,
         };
 
         Ok(res)
@@ -91,6 +101,33 @@ impl fmt::Display for UnknownVariantValue {
     }
 }
 
+/// `as_str` and `From<&str>` for enums with an `Unknown` variant, using the Bedrock API values.
+macro_rules! api_values {
+    ($name:ident { $($variant:ident => $value:literal),+ $(,)? }) => {
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Unknown(value) => &value.0,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => Self::$variant,)+
+                    other => Self::Unknown($crate::types::converse_output::UnknownVariantValue(
+                        other.to_string(),
+                    )),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use api_values;
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct TokenUsage {
     pub input_tokens: i32,
@@ -424,6 +461,25 @@ pub enum ContentBlock {
     #[non_exhaustive]
     Unknown,
 }
+
+impl ContentBlock {
+    /// The Converse API name of the block type, e.g. `citationsContent`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::CachePoint(_) => "cachePoint",
+            Self::CitationsContent(_) => "citationsContent",
+            Self::Document(_) => "document",
+            Self::GuardContent(_) => "guardContent",
+            Self::Image(_) => "image",
+            Self::ReasoningContent(_) => "reasoningContent",
+            Self::Text(_) => "text",
+            Self::ToolResult(_) => "toolResult",
+            Self::ToolUse(_) => "toolUse",
+            Self::Video(_) => "video",
+            Self::Unknown => "unknown",
+        }
+    }
+}
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct CachePointBlock {
     #[serde(rename = "type")]
@@ -621,6 +677,7 @@ pub enum ToolResultContentBlock {
     #[non_exhaustive]
     Unknown,
 }
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct VideoBlock {
     pub format: VideoFormat,
@@ -754,6 +811,29 @@ impl TryFrom<aws_sdk_bedrockruntime::types::ConverseMetrics> for ConverseMetrics
     }
 }
 
+impl TryFrom<aws_sdk_bedrockruntime::types::ConverseStreamMetrics> for ConverseMetrics {
+    type Error = TypeConversionError;
+    fn try_from(
+        value: aws_sdk_bedrockruntime::types::ConverseStreamMetrics,
+    ) -> Result<Self, Self::Error> {
+        Ok(ConverseMetrics {
+            latency_ms: value.latency_ms(),
+        })
+    }
+}
+
+impl TryFrom<aws_sdk_bedrockruntime::types::ConverseStreamTrace> for ConverseTrace {
+    type Error = TypeConversionError;
+    fn try_from(
+        value: aws_sdk_bedrockruntime::types::ConverseStreamTrace,
+    ) -> Result<Self, Self::Error> {
+        Ok(ConverseTrace {
+            guardrail: value.guardrail().map(|v| v.try_into()).transpose()?,
+            prompt_router: value.prompt_router().map(|v| v.try_into()).transpose()?,
+        })
+    }
+}
+
 impl TryFrom<aws_sdk_bedrockruntime::types::ConverseTrace> for ConverseTrace {
     type Error = TypeConversionError;
     fn try_from(value: aws_sdk_bedrockruntime::types::ConverseTrace) -> Result<Self, Self::Error> {
@@ -1051,7 +1131,12 @@ impl TryFrom<aws_sdk_bedrockruntime::types::GuardrailContextualGroundingPolicyAs
         Ok(GuardrailContextualGroundingPolicyAssessment {
             filters: value
                 .filters
-                .map(|x| x.into_iter().map(|x| x.try_into().unwrap()).collect()),
+                .map(|x| {
+                    x.into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()
+                })
+                .transpose()?,
         })
     }
 }
@@ -1067,7 +1152,12 @@ impl TryFrom<&aws_sdk_bedrockruntime::types::GuardrailContextualGroundingPolicyA
             filters: value
                 .filters
                 .clone()
-                .map(|x| x.into_iter().map(|x| x.try_into().unwrap()).collect()),
+                .map(|x| {
+                    x.into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<_, _>>()
+                })
+                .transpose()?,
         })
     }
 }
@@ -1858,8 +1948,8 @@ impl TryFrom<aws_sdk_bedrockruntime::types::GuardrailCoverage> for GuardrailCove
         value: aws_sdk_bedrockruntime::types::GuardrailCoverage,
     ) -> Result<Self, Self::Error> {
         Ok(GuardrailCoverage {
-            text_characters: value.text_characters().map(|x| x.try_into().unwrap()),
-            images: value.images().map(|x| x.try_into().unwrap()),
+            text_characters: value.text_characters().map(TryInto::try_into).transpose()?,
+            images: value.images().map(TryInto::try_into).transpose()?,
         })
     }
 }
@@ -1870,8 +1960,8 @@ impl TryFrom<&aws_sdk_bedrockruntime::types::GuardrailCoverage> for GuardrailCov
         value: &aws_sdk_bedrockruntime::types::GuardrailCoverage,
     ) -> Result<Self, Self::Error> {
         Ok(GuardrailCoverage {
-            text_characters: value.text_characters().map(|x| x.try_into().unwrap()),
-            images: value.images().map(|x| x.try_into().unwrap()),
+            text_characters: value.text_characters().map(TryInto::try_into).transpose()?,
+            images: value.images().map(TryInto::try_into).transpose()?,
         })
     }
 }
@@ -2021,8 +2111,7 @@ impl TryFrom<Message> for aws_sdk_bedrockruntime::types::Message {
         let res = aws_sdk_bedrockruntime::types::Message::builder()
             .set_role(role)
             .set_content(content)
-            .build()
-            .expect("AWS SDK message conversion should never fail!");
+            .build()?;
 
         Ok(res)
     }
@@ -2111,9 +2200,9 @@ impl TryFrom<aws_sdk_bedrockruntime::types::ContentBlock> for ContentBlock {
             aws_sdk_bedrockruntime::types::ContentBlock::Video(value) => {
                 Ok(ContentBlock::Video(value.try_into()?))
             }
-            invalid => Err(TypeConversionError::new(&format!(
-                "Unknown variant for ContentBlock: {invalid:?}"
-            ))),
+            // Blocks added to Bedrock after this SDK version are kept as a marker instead of
+            // failing the whole response
+            _ => Ok(ContentBlock::Unknown),
         }
     }
 }
@@ -2618,8 +2707,7 @@ impl TryFrom<DocumentBlock> for aws_sdk_bedrockruntime::types::DocumentBlock {
             .set_source(source)
             .set_context(context)
             .set_citations(citations)
-            .build()
-            .expect("aws document block conversion should not fail");
+            .build()?;
 
         Ok(res)
     }
@@ -2819,8 +2907,7 @@ impl TryFrom<S3Location> for aws_sdk_bedrockruntime::types::S3Location {
         let res = aws_sdk_bedrockruntime::types::S3Location::builder()
             .set_uri(Some(value.uri))
             .set_bucket_owner(value.bucket_owner)
-            .build()
-            .expect("converting S3 bucket location should never fail");
+            .build()?;
 
         Ok(res)
     }
@@ -2889,8 +2976,7 @@ impl TryFrom<CitationsConfig> for aws_sdk_bedrockruntime::types::CitationsConfig
     fn try_from(value: CitationsConfig) -> Result<Self, Self::Error> {
         let res = aws_sdk_bedrockruntime::types::CitationsConfig::builder()
             .set_enabled(Some(value.enabled))
-            .build()
-            .expect("Citation config conversion should never fail!");
+            .build()?;
 
         Ok(res)
     }
@@ -2964,8 +3050,7 @@ impl TryFrom<GuardrailConverseImageBlock>
         let res = aws_sdk_bedrockruntime::types::GuardrailConverseImageBlock::builder()
             .set_format(format)
             .set_source(source)
-            .build()
-            .expect("GuardrailConverseImageBlock conversion should never fail!");
+            .build()?;
         Ok(res)
     }
 }
@@ -3118,8 +3203,7 @@ impl TryFrom<GuardrailConverseTextBlock>
         let res = aws_sdk_bedrockruntime::types::GuardrailConverseTextBlock::builder()
             .set_text(text)
             .set_qualifiers(qualifiers)
-            .build()
-            .expect("GuardrailConversionTextBlock conversion should never fail!");
+            .build()?;
 
         Ok(res)
     }
@@ -3213,8 +3297,7 @@ impl TryFrom<ImageBlock> for aws_sdk_bedrockruntime::types::ImageBlock {
         let res = aws_sdk_bedrockruntime::types::ImageBlock::builder()
             .set_format(format)
             .set_source(source)
-            .build()
-            .expect("ImageBlock conversion should never fail!");
+            .build()?;
         Ok(res)
     }
 }
@@ -3375,8 +3458,7 @@ impl TryFrom<ReasoningTextBlock> for aws_sdk_bedrockruntime::types::ReasoningTex
         let res = aws_sdk_bedrockruntime::types::ReasoningTextBlock::builder()
             .set_text(text)
             .set_signature(signature)
-            .build()
-            .expect("ReasoningTextBlock conversion should never fail!");
+            .build()?;
 
         Ok(res)
     }
@@ -3415,8 +3497,7 @@ impl TryFrom<ToolResultBlock> for aws_sdk_bedrockruntime::types::ToolResultBlock
             .set_tool_use_id(tool_use_id)
             .set_content(content)
             .set_status(status)
-            .build()
-            .expect("ToolResultBlock conversion should never fail!");
+            .build()?;
         Ok(res)
     }
 }
@@ -3494,8 +3575,7 @@ impl TryFrom<VideoBlock> for aws_sdk_bedrockruntime::types::VideoBlock {
         let res = aws_sdk_bedrockruntime::types::VideoBlock::builder()
             .set_format(format)
             .set_source(source)
-            .build()
-            .expect("VideoBlock conversion should never fail!");
+            .build()?;
 
         Ok(res)
     }
@@ -3634,8 +3714,7 @@ impl TryFrom<ToolUseBlock> for aws_sdk_bedrockruntime::types::ToolUseBlock {
             .set_tool_use_id(tool_use_id)
             .set_name(name)
             .set_input(input)
-            .build()
-            .expect("ToolUseBlock shouldn't panic!");
+            .build()?;
 
         Ok(res)
     }
@@ -5,6 +5,7 @@ use rig::{
 };
 
 pub(crate) use crate::types::media_types::RigDocumentMediaType;
+use crate::types::media_types::{DOCUMENT_FORMAT_KEY, office_document_format};
 use base64::{Engine, prelude::BASE64_STANDARD};
 use uuid::Uuid;
 
@@ -16,7 +17,9 @@ impl TryFrom<RigDocument> for aws_bedrock::DocumentBlock {
 
     fn try_from(
         RigDocument(Document {
-            data, media_type, ..
+            data,
+            media_type,
+            additional_params,
         }): RigDocument,
     ) -> Result<Self, Self::Error> {
         let document_media_type = media_type.map(|doc| RigDocumentMediaType(doc).try_into());
@@ -24,7 +27,17 @@ impl TryFrom<RigDocument> for aws_bedrock::DocumentBlock {
         let document_media_type = match document_media_type {
             Some(Ok(document_format)) => Ok(Some(document_format)),
             Some(Err(err)) => Err(err),
-            None => Ok(None),
+            // Office formats have no rig media type and are named in the additional params
+            None => additional_params
+                .as_ref()
+                .and_then(|params| params.get(DOCUMENT_FORMAT_KEY))
+                .and_then(|format| format.as_str())
+                .map(|format| {
+                    office_document_format(format).ok_or_else(|| {
+                        CompletionError::ProviderError(format!("Unsupported media type {format}"))
+                    })
+                })
+                .transpose(),
         }?;
 
         let document_source = match data {
@@ -35,11 +48,15 @@ impl TryFrom<RigDocument> for aws_bedrock::DocumentBlock {
 
                 aws_bedrock::DocumentSource::Bytes(aws_smithy_types::Blob::new(bytes))
             }
+            // Raw bytes are moved into the blob without copying
+            DocumentSourceKind::Raw(bytes) => {
+                aws_bedrock::DocumentSource::Bytes(aws_smithy_types::Blob::new(bytes))
+            }
             // NOTE: until [aws-sdk-bedrockruntime DocumentSource bug #1365](https://github.com/awslabs/aws-sdk-rust/issues/1365)
             // is resolved we will use this as a workaround
             // DocumentSourceKind::String(str) => aws_bedrock::DocumentSource::Text(str),
             DocumentSourceKind::String(str) => {
-                aws_bedrock::DocumentSource::Bytes(aws_smithy_types::Blob::new(str.as_bytes()))
+                aws_bedrock::DocumentSource::Bytes(aws_smithy_types::Blob::new(str.into_bytes()))
             }
             doc => {
                 return Err(CompletionError::RequestError(
@@ -64,8 +81,19 @@ impl TryFrom<aws_bedrock::DocumentBlock> for RigDocument {
     type Error = CompletionError;
 
     fn try_from(value: aws_bedrock::DocumentBlock) -> Result<Self, Self::Error> {
-        let media_type: RigDocumentMediaType = value.format.try_into()?;
-        let media_type = media_type.0;
+        let (media_type, additional_params) = match value.format {
+            format @ (aws_bedrock::DocumentFormat::Doc
+            | aws_bedrock::DocumentFormat::Docx
+            | aws_bedrock::DocumentFormat::Xls
+            | aws_bedrock::DocumentFormat::Xlsx) => (
+                None,
+                Some(serde_json::json!({ DOCUMENT_FORMAT_KEY: format.as_str() })),
+            ),
+            format => {
+                let media_type: RigDocumentMediaType = format.try_into()?;
+                (Some(media_type.0), None)
+            }
+        };
 
         let data = match value.source {
             Some(aws_bedrock::DocumentSource::Bytes(blob)) => {
@@ -80,8 +108,8 @@ impl TryFrom<aws_bedrock::DocumentBlock> for RigDocument {
 
         Ok(RigDocument(Document {
             data,
-            media_type: Some(media_type),
-            additional_params: None,
+            media_type,
+            additional_params,
         }))
     }
 }
@@ -186,7 +214,26 @@ mod tests {
         let rig_document: Result<RigDocument, _> = aws_document.clone().try_into();
         assert!(rig_document.is_ok());
         let rig_document = rig_document.unwrap().0;
-        assert_eq!(rig_document.media_type.unwrap(), DocumentMediaType::PDF)
+        assert_eq!(rig_document.media_type.unwrap(), DocumentMediaType::PDF);
+        assert_eq!(
+            rig_document.data,
+            DocumentSourceKind::Base64(BASE64_STANDARD.encode("document_data"))
+        );
+    }
+
+    #[test]
+    fn test_raw_document_to_aws_document() {
+        let rig_document = RigDocument(Document {
+            data: DocumentSourceKind::Raw(b"%PDF".to_vec()),
+            media_type: Some(DocumentMediaType::PDF),
+            additional_params: None,
+        });
+
+        let aws_document: aws_bedrock::DocumentBlock = rig_document.try_into().unwrap();
+        assert_eq!(
+            aws_document.source().unwrap().as_bytes().unwrap().as_ref(),
+            b"%PDF"
+        );
     }
 
     #[test]
@@ -194,7 +241,7 @@ mod tests {
         let data = aws_smithy_types::Blob::new("document_data");
         let document_source = aws_bedrock::DocumentSource::Bytes(data);
         let aws_document = aws_bedrock::DocumentBlock::builder()
-            .format(aws_bedrock::DocumentFormat::Xlsx)
+            .format(aws_bedrock::DocumentFormat::from("odt"))
             .name("Document")
             .source(document_source)
             .build()
@@ -203,7 +250,43 @@ mod tests {
         assert!(rig_document.is_err());
         assert_eq!(
             rig_document.err().unwrap().to_string(),
-            CompletionError::ProviderError("Unsupported media type xlsx".into()).to_string()
+            CompletionError::ProviderError("Unsupported media type odt".into()).to_string()
         )
     }
+
+    #[test]
+    fn test_office_document_round_trip() {
+        let aws_document = aws_bedrock::DocumentBlock::builder()
+            .format(aws_bedrock::DocumentFormat::Xlsx)
+            .name("Document")
+            .source(aws_bedrock::DocumentSource::Bytes(
+                aws_smithy_types::Blob::new("spreadsheet"),
+            ))
+            .build()
+            .unwrap();
+
+        let rig_document: RigDocument = aws_document.try_into().unwrap();
+        assert_eq!(rig_document.0.media_type, None);
+        assert_eq!(
+            rig_document.0.additional_params,
+            Some(serde_json::json!({ "format": "xlsx" }))
+        );
+
+        let aws_document: aws_bedrock::DocumentBlock = rig_document.try_into().unwrap();
+        assert_eq!(aws_document.format, aws_bedrock::DocumentFormat::Xlsx);
+    }
+
+    #[test]
+    fn test_office_document_from_mime_type() {
+        let rig_document = RigDocument(Document {
+            data: DocumentSourceKind::Base64("data".into()),
+            media_type: None,
+            additional_params: Some(serde_json::json!({
+                "format": "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            })),
+        });
+
+        let aws_document: aws_bedrock::DocumentBlock = rig_document.try_into().unwrap();
+        assert_eq!(aws_document.format, aws_bedrock::DocumentFormat::Docx);
+    }
 }
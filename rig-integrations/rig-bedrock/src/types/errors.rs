@@ -1,44 +1,429 @@
+use std::error::Error;
 use std::fmt;
 
 use aws_sdk_bedrockruntime::config::http::HttpResponse;
-use aws_sdk_bedrockruntime::error::SdkError;
+use aws_sdk_bedrockruntime::error::{
+    BuildError, DisplayErrorContext, ProvideErrorMetadata, SdkError,
+};
+use aws_sdk_bedrockruntime::operation::RequestId;
 use aws_sdk_bedrockruntime::operation::converse::ConverseError;
 use aws_sdk_bedrockruntime::operation::converse_stream::ConverseStreamError;
 use aws_sdk_bedrockruntime::operation::invoke_model::InvokeModelError;
+use aws_sdk_bedrockruntime::operation::invoke_model_with_response_stream::InvokeModelWithResponseStreamError;
+use aws_smithy_types::event_stream::RawMessage;
 use rig::completion::CompletionError;
 use rig::embeddings::EmbeddingError;
 use rig::image_generation::ImageGenerationError;
 
+use crate::embedding::EmbeddingOutputType;
+
+/// The kind of a failed Bedrock call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BedrockErrorKind {
+    /// The request exceeded the account's request or token quotas.
+    Throttled,
+    /// The caller isn't allowed to perform the action, or model access wasn't granted.
+    AccessDenied,
+    /// The model or resource ARN doesn't exist in this region.
+    ResourceNotFound,
+    /// The model isn't ready to serve requests yet.
+    ModelNotReady,
+    /// The model took too long to process the request.
+    ModelTimeout,
+    /// The model failed while processing the request.
+    ModelError,
+    /// The model failed while streaming its response.
+    ModelStreamError,
+    /// The request is invalid for the model, e.g. an unsupported parameter.
+    Validation,
+    /// The request exceeds a service quota that throttling won't resolve.
+    ServiceQuotaExceeded,
+    ServiceUnavailable,
+    InternalServer,
+    /// The request timed out on the client side.
+    Timeout,
+    /// The request couldn't be sent, e.g. a connection or credentials failure.
+    Dispatch,
+    Other,
+}
+
+impl BedrockErrorKind {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "ThrottlingException" => Self::Throttled,
+            "AccessDeniedException" => Self::AccessDenied,
+            "ResourceNotFoundException" => Self::ResourceNotFound,
+            "ModelNotReadyException" => Self::ModelNotReady,
+            "ModelTimeoutException" => Self::ModelTimeout,
+            "ModelErrorException" => Self::ModelError,
+            "ModelStreamErrorException" => Self::ModelStreamError,
+            "ValidationException" => Self::Validation,
+            "ServiceQuotaExceededException" => Self::ServiceQuotaExceeded,
+            "ServiceUnavailableException" => Self::ServiceUnavailable,
+            "InternalServerException" => Self::InternalServer,
+            _ => Self::Other,
+        }
+    }
+
+    /// Whether a request failing with this kind of error may succeed when retried later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Throttled
+                | Self::ModelNotReady
+                | Self::ModelTimeout
+                | Self::ServiceUnavailable
+                | Self::InternalServer
+                | Self::Timeout
+                | Self::Dispatch
+        )
+    }
+
+    fn default_message(&self) -> &'static str {
+        match self {
+            Self::Throttled => {
+                "Your request was denied due to exceeding the account quotas for AWS Bedrock."
+            }
+            Self::AccessDenied => {
+                "The request is denied because you do not have sufficient permissions to perform the requested action."
+            }
+            Self::ResourceNotFound => "The specified resource ARN was not found.",
+            Self::ModelNotReady => {
+                "The model specified in the request is not ready to serve inference requests. The AWS SDK will automatically retry the operation up to 5 times."
+            }
+            Self::ModelTimeout => {
+                "The request took too long to process. Processing time exceeded the model timeout length."
+            }
+            Self::ModelError => "The request failed due to an error while processing the model.",
+            Self::ModelStreamError => "An error occurred while streaming the response.",
+            Self::Validation => {
+                "The input fails to satisfy the constraints specified by AWS Bedrock."
+            }
+            Self::ServiceQuotaExceeded => {
+                "Your request exceeds the service quota for your account."
+            }
+            Self::ServiceUnavailable => "The service isn't currently available.",
+            Self::InternalServer => "An internal server error occurred.",
+            Self::Timeout => "The request timed out.",
+            Self::Dispatch | Self::Other => {
+                "An unexpected error occurred. Verify Internet connection or AWS keys"
+            }
+        }
+    }
+}
+
+/// A failed Bedrock call, keeping the original AWS SDK error as its source.
+///
+/// Its message includes the AWS request id, to reference in support tickets and to find the
+/// call in CloudTrail.
+///
+/// Completion errors carry it as a [`CompletionError::RequestError`], see
+/// [`BedrockError::from_completion_error`].
+#[derive(Debug)]
+pub struct BedrockError {
+    kind: BedrockErrorKind,
+    message: String,
+    request_id: Option<String>,
+    extended_request_id: Option<String>,
+    retry_after: Option<std::time::Duration>,
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl BedrockError {
+    pub fn kind(&self) -> BedrockErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The `x-amzn-RequestId` of the failed call, if it reached AWS.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// The `x-amz-id-2` extended request id of the failed call, if AWS returned one.
+    pub fn extended_request_id(&self) -> Option<&str> {
+        self.extended_request_id.as_deref()
+    }
+
+    /// How long Bedrock asked to wait before retrying, from the `Retry-After` header of a
+    /// throttled call.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        self.retry_after
+    }
+
+    /// Whether the request may succeed when retried later.
+    pub fn is_retryable(&self) -> bool {
+        self.kind.is_retryable()
+    }
+
+    /// The original AWS SDK error, to be downcast to e.g.
+    /// `SdkError<ConverseError, HttpResponse>`.
+    pub fn aws_error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self.source.as_ref()
+    }
+
+    /// The Bedrock error a completion failed with, if it failed calling Bedrock.
+    pub fn from_completion_error(error: &CompletionError) -> Option<&Self> {
+        match error {
+            CompletionError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// The Bedrock error an image generation failed with, if it failed calling Bedrock.
+    pub fn from_image_generation_error(error: &ImageGenerationError) -> Option<&Self> {
+        match error {
+            ImageGenerationError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl<E> From<SdkError<E, HttpResponse>> for BedrockError
+where
+    E: ProvideErrorMetadata + Error + Send + Sync + 'static,
+{
+    fn from(error: SdkError<E, HttpResponse>) -> Self {
+        let (kind, message) = kind_and_message(&error);
+
+        let request_id = error.request_id().map(str::to_string);
+        let extended_request_id = error
+            .raw_response()
+            .and_then(|response| response.headers().get("x-amz-id-2"))
+            .map(str::to_string);
+        let retry_after = error.raw_response().and_then(retry_after);
+
+        Self {
+            kind,
+            message,
+            request_id,
+            extended_request_id,
+            retry_after,
+            source: Box::new(error),
+        }
+    }
+}
+
+/// An exception sent by Bedrock in the middle of an event stream, e.g. a
+/// `ModelStreamErrorException` or a `ThrottlingException` of ConverseStream.
+impl<E> From<SdkError<E, RawMessage>> for BedrockError
+where
+    E: ProvideErrorMetadata + Error + Send + Sync + 'static,
+{
+    fn from(error: SdkError<E, RawMessage>) -> Self {
+        let (kind, message) = kind_and_message(&error);
+
+        Self {
+            kind,
+            message,
+            request_id: None,
+            extended_request_id: None,
+            retry_after: None,
+            source: Box::new(error),
+        }
+    }
+}
+
+/// The delay of the `Retry-After` header of `response`, in seconds.
+pub(crate) fn retry_after(response: &HttpResponse) -> Option<std::time::Duration> {
+    let seconds = response
+        .headers()
+        .get("retry-after")?
+        .trim()
+        .parse::<f64>()
+        .ok()?;
+    std::time::Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// The kind of a failed AWS SDK call, and its service message or a default one.
+fn kind_and_message<E, R>(error: &SdkError<E, R>) -> (BedrockErrorKind, String)
+where
+    E: ProvideErrorMetadata + Error + 'static,
+    R: fmt::Debug,
+{
+    match error {
+        SdkError::ServiceError(service_error) => {
+            let service_error = service_error.err();
+            let kind = service_error
+                .code()
+                .map(BedrockErrorKind::from_code)
+                .unwrap_or(BedrockErrorKind::Other);
+            let message = service_error
+                .message()
+                .map(str::to_string)
+                .unwrap_or_else(|| kind.default_message().to_string());
+            (kind, message)
+        }
+        SdkError::TimeoutError(_) => (
+            BedrockErrorKind::Timeout,
+            BedrockErrorKind::Timeout.default_message().to_string(),
+        ),
+        error => {
+            let kind = match error {
+                SdkError::DispatchFailure(_) => BedrockErrorKind::Dispatch,
+                _ => BedrockErrorKind::Other,
+            };
+            (
+                kind,
+                format!("{}: {}", kind.default_message(), DisplayErrorContext(error)),
+            )
+        }
+    }
+}
+
+impl fmt::Display for BedrockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        match (&self.request_id, &self.extended_request_id) {
+            (Some(request_id), Some(extended_request_id)) => write!(
+                f,
+                " (request id: {request_id}, extended request id: {extended_request_id})"
+            ),
+            (Some(request_id), None) => write!(f, " (request id: {request_id})"),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+impl Error for BedrockError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+impl From<BedrockError> for CompletionError {
+    fn from(value: BedrockError) -> Self {
+        CompletionError::RequestError(Box::new(value))
+    }
+}
+
+/// A call rejected without reaching Bedrock because its [`CircuitBreaker`] is open.
+///
+/// [`CircuitBreaker`]: crate::circuit_breaker::CircuitBreaker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitOpenError {
+    /// How long until the breaker lets a probe call through.
+    pub retry_after: std::time::Duration,
+}
+
+impl fmt::Display for CircuitOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Circuit breaker is open after repeated Bedrock failures, retry in {:?}",
+            self.retry_after
+        )
+    }
+}
+
+impl Error for CircuitOpenError {}
+
+impl From<CircuitOpenError> for CompletionError {
+    fn from(value: CircuitOpenError) -> Self {
+        CompletionError::RequestError(Box::new(value))
+    }
+}
+
+/// Only the message is kept, as `EmbeddingError` has no variant boxing a provider error.
+impl From<CircuitOpenError> for EmbeddingError {
+    fn from(value: CircuitOpenError) -> Self {
+        EmbeddingError::ProviderError(value.to_string())
+    }
+}
+
+/// A call rejected without reaching Bedrock because the spend of its [`BudgetGuard`] exceeds its
+/// limit.
+///
+/// [`BudgetGuard`]: crate::budget::BudgetGuard
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceededError {
+    /// Estimated spend, in US dollars.
+    pub spent: f64,
+    /// The budget, in US dollars.
+    pub limit: f64,
+}
+
+impl BudgetExceededError {
+    /// The budget error a completion failed with, if it was rejected over budget.
+    pub fn from_completion_error(error: &CompletionError) -> Option<&Self> {
+        match error {
+            CompletionError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BudgetExceededError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Budget of ${:.2} exceeded, ${:.2} spent",
+            self.limit, self.spent
+        )
+    }
+}
+
+impl Error for BudgetExceededError {}
+
+impl From<BudgetExceededError> for CompletionError {
+    fn from(value: BudgetExceededError) -> Self {
+        CompletionError::RequestError(Box::new(value))
+    }
+}
+
 pub struct AwsSdkInvokeModelError(pub SdkError<InvokeModelError, HttpResponse>);
 
 impl AwsSdkInvokeModelError {
     pub fn into_service_error(self) -> String {
-        let error: String = match self.0.into_service_error() {
-            InvokeModelError::ModelTimeoutException(e) => e.message.unwrap_or("The request took too long to process. Processing time exceeded the model timeout length.".into()),
-            InvokeModelError::AccessDeniedException(e) => e.message.unwrap_or("The request is denied because you do not have sufficient permissions to perform the requested action.".into()),
-            InvokeModelError::ResourceNotFoundException(e) => e.message.unwrap_or("The specified resource ARN was not found.".into()),
-            InvokeModelError::ThrottlingException(e) => e.message.unwrap_or("Your request was denied due to exceeding the account quotas for Amazon Bedrock.".into()),
-            InvokeModelError::ServiceUnavailableException(e) => e.message.unwrap_or("The service isn't currently available.".into()),
-            InvokeModelError::InternalServerException(e) => e.message.unwrap_or("An internal server error occurred.".into()),
-            InvokeModelError::ValidationException(e) => e.message.unwrap_or("The input fails to satisfy the constraints specified by Amazon Bedrock.".into()),
-            InvokeModelError::ModelNotReadyException(e) => e.message.unwrap_or("The model specified in the request is not ready to serve inference requests. The AWS SDK will automatically retry the operation up to 5 times.".into()),
-            InvokeModelError::ModelErrorException(e) => e.message.unwrap_or("The request failed due to an error while processing the model.".into()),
-            InvokeModelError::ServiceQuotaExceededException(e) => e.message.unwrap_or("Your request exceeds the service quota for your account.".into()),
-            _ => "An unexpected error occurred. Verify Internet connection or AWS keys".into(),
-        };
-        error
+        BedrockError::from(self.0).to_string()
     }
 }
 
+/// Whether an InvokeModel error is transient (throttling or temporary unavailability) and worth retrying.
+pub(crate) fn is_transient_invoke_model_error(
+    error: &SdkError<InvokeModelError, HttpResponse>,
+) -> bool {
+    matches!(
+        error.as_service_error(),
+        Some(
+            InvokeModelError::ThrottlingException(_)
+                | InvokeModelError::ServiceUnavailableException(_)
+        )
+    )
+}
+
 impl From<AwsSdkInvokeModelError> for ImageGenerationError {
     fn from(value: AwsSdkInvokeModelError) -> Self {
-        ImageGenerationError::ProviderError(value.into_service_error())
+        BedrockError::from(value.0).into()
     }
 }
 
 impl From<AwsSdkInvokeModelError> for EmbeddingError {
     fn from(value: AwsSdkInvokeModelError) -> Self {
-        EmbeddingError::ProviderError(value.into_service_error())
+        BedrockError::from(value.0).into()
+    }
+}
+
+impl From<AwsSdkInvokeModelError> for CompletionError {
+    fn from(value: AwsSdkInvokeModelError) -> Self {
+        BedrockError::from(value.0).into()
+    }
+}
+
+impl From<BedrockError> for ImageGenerationError {
+    fn from(value: BedrockError) -> Self {
+        ImageGenerationError::RequestError(Box::new(value))
+    }
+}
+
+/// `EmbeddingError` has no variant boxing a provider error, so only the message of the Bedrock
+/// error is kept, not the typed error nor its source.
+impl From<BedrockError> for EmbeddingError {
+    fn from(value: BedrockError) -> Self {
+        EmbeddingError::ProviderError(value.to_string())
     }
 }
 
@@ -46,56 +431,580 @@ pub struct AwsSdkConverseError(pub SdkError<ConverseError, HttpResponse>);
 
 impl From<AwsSdkConverseError> for CompletionError {
     fn from(value: AwsSdkConverseError) -> Self {
-        let error: String = match value.0.into_service_error() {
-            ConverseError::ModelTimeoutException(e) => e.message.unwrap_or("The request took too long to process. Processing time exceeded the model timeout length.".into()),
-            ConverseError::AccessDeniedException(e) => e.message.unwrap_or("The request is denied because you do not have sufficient permissions to perform the requested action.".into()),
-            ConverseError::ResourceNotFoundException(e) => e.message.unwrap_or("The specified resource ARN was not found.".into()),
-            ConverseError::ThrottlingException(e) => e.message.unwrap_or("Your request was denied due to exceeding the account quotas for AWS Bedrock.".into()),
-            ConverseError::ServiceUnavailableException(e) => e.message.unwrap_or("The service isn't currently available.".into()),
-            ConverseError::InternalServerException(e) => e.message.unwrap_or("An internal server error occurred.".into()),
-            ConverseError::ValidationException(e) => e.message.unwrap_or("The input fails to satisfy the constraints specified by AWS Bedrock.".into()),
-            ConverseError::ModelNotReadyException(e) => e.message.unwrap_or("The model specified in the request is not ready to serve inference requests. The AWS SDK will automatically retry the operation up to 5 times.".into()),
-            ConverseError::ModelErrorException(e) => e.message.unwrap_or("The request failed due to an error while processing the model.".into()),
-            _ => String::from("An unexpected error occurred. Verify Internet connection or AWS keys")
-        };
-        CompletionError::ProviderError(error)
+        BedrockError::from(value.0).into()
     }
 }
 
 pub struct AwsSdkConverseStreamError(pub SdkError<ConverseStreamError, HttpResponse>);
+
 impl From<AwsSdkConverseStreamError> for CompletionError {
     fn from(value: AwsSdkConverseStreamError) -> Self {
-        let error: String = match value.0.into_service_error() {
-            ConverseStreamError::ModelTimeoutException(e) => e.message.unwrap(),
-            ConverseStreamError::AccessDeniedException(e) => e.message.unwrap(),
-            ConverseStreamError::ResourceNotFoundException(e) => e.message.unwrap(),
-            ConverseStreamError::ThrottlingException(e) => e.message.unwrap(),
-            ConverseStreamError::ServiceUnavailableException(e) => e.message.unwrap(),
-            ConverseStreamError::InternalServerException(e) => e.message.unwrap(),
-            ConverseStreamError::ModelStreamErrorException(e) => e.message.unwrap(),
-            ConverseStreamError::ValidationException(e) => e.message.unwrap(),
-            ConverseStreamError::ModelNotReadyException(e) => e.message.unwrap(),
-            ConverseStreamError::ModelErrorException(e) => e.message.unwrap(),
-            _ => "An unexpected error occurred. Verify Internet connection or AWS keys".into(),
-        };
-        CompletionError::ProviderError(error)
+        BedrockError::from(value.0).into()
     }
 }
 
+pub struct AwsSdkInvokeModelWithResponseStreamError(
+    pub SdkError<InvokeModelWithResponseStreamError, HttpResponse>,
+);
+
+impl From<AwsSdkInvokeModelWithResponseStreamError> for CompletionError {
+    fn from(value: AwsSdkInvokeModelWithResponseStreamError) -> Self {
+        BedrockError::from(value.0).into()
+    }
+}
+
+/// Reason why a model can't be invoked, as reported by
+/// [`Client::verify_model_access`](crate::client::Client::verify_model_access).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModelAccessError {
+    /// The model exists, but access to it hasn't been granted for this account and region.
+    /// Request access in the Bedrock console under "Model access".
+    ModelNotEnabled { model: String, message: String },
+    /// The model id is invalid or the model isn't offered in the configured region.
+    ModelNotFound { model: String, message: String },
+    /// The model can't be invoked on-demand and must be called through an inference profile
+    /// (e.g. `us.anthropic.claude-sonnet-4-20250514-v1:0`).
+    InferenceProfileRequired { model: String, message: String },
+    /// The caller's IAM identity isn't allowed to invoke the model.
+    AccessDenied { model: String, message: String },
+    /// Any other failure (throttling, networking, credentials, ...).
+    Other(String),
+}
+
+impl ModelAccessError {
+    pub(crate) fn from_converse_error(model: &str, error: ConverseError) -> Self {
+        let model = model.to_string();
+        match error {
+            ConverseError::AccessDeniedException(e) => {
+                let message = e.message.unwrap_or_default();
+                if message.to_lowercase().contains("access to the model") {
+                    Self::ModelNotEnabled { model, message }
+                } else {
+                    Self::AccessDenied { model, message }
+                }
+            }
+            ConverseError::ResourceNotFoundException(e) => Self::ModelNotFound {
+                model,
+                message: e.message.unwrap_or_default(),
+            },
+            ConverseError::ValidationException(e) => {
+                let message = e.message.unwrap_or_default();
+                let lowercase = message.to_lowercase();
+                if lowercase.contains("inference profile") {
+                    Self::InferenceProfileRequired { model, message }
+                } else if lowercase.contains("model identifier is invalid") {
+                    Self::ModelNotFound { model, message }
+                } else {
+                    Self::Other(message)
+                }
+            }
+            e => Self::Other(e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ModelAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModelNotEnabled { model, message } => {
+                write!(
+                    f,
+                    "Model `{model}` is not enabled in this account/region: {message}"
+                )
+            }
+            Self::ModelNotFound { model, message } => {
+                write!(f, "Model `{model}` was not found in this region: {message}")
+            }
+            Self::InferenceProfileRequired { model, message } => write!(
+                f,
+                "Model `{model}` must be invoked through an inference profile: {message}"
+            ),
+            Self::AccessDenied { model, message } => {
+                write!(f, "Access denied when invoking model `{model}`: {message}")
+            }
+            Self::Other(message) => write!(f, "Failed to verify model access: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ModelAccessError {}
+
+/// An embedding dimension the model doesn't support.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidDimensionsError {
+    pub model: String,
+    pub ndims: usize,
+    pub supported: Vec<usize>,
+}
+
+impl fmt::Display for InvalidDimensionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Model `{}` does not support {} dimensional embeddings, supported dimensions: {:?}",
+            self.model, self.ndims, self.supported
+        )
+    }
+}
+
+impl std::error::Error for InvalidDimensionsError {}
+
+/// Only the message is kept, as `EmbeddingError` has no variant boxing a provider error.
+impl From<InvalidDimensionsError> for EmbeddingError {
+    fn from(value: InvalidDimensionsError) -> Self {
+        EmbeddingError::ProviderError(value.to_string())
+    }
+}
+
+/// Options of an [`EmbeddingModelBuilder`](crate::embedding::EmbeddingModelBuilder) that the
+/// model doesn't support.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EmbeddingOptionsError {
+    Dimensions(InvalidDimensionsError),
+    UnsupportedOutputType {
+        model: String,
+        output_type: EmbeddingOutputType,
+    },
+}
+
+impl fmt::Display for EmbeddingOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dimensions(error) => write!(f, "{error}"),
+            Self::UnsupportedOutputType { model, output_type } => write!(
+                f,
+                "Model `{model}` does not support {output_type:?} embeddings"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EmbeddingOptionsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Dimensions(error) => Some(error),
+            Self::UnsupportedOutputType { .. } => None,
+        }
+    }
+}
+
+impl From<InvalidDimensionsError> for EmbeddingOptionsError {
+    fn from(value: InvalidDimensionsError) -> Self {
+        Self::Dimensions(value)
+    }
+}
+
+/// A value that couldn't be converted between the AWS SDK and rig types.
 #[derive(Debug)]
-pub struct TypeConversionError(String);
+pub struct TypeConversionError {
+    message: String,
+    source: Option<BuildError>,
+}
 
 impl TypeConversionError {
     pub fn new(input: &str) -> Self {
-        Self(input.to_string())
+        Self {
+            message: input.to_string(),
+            source: None,
+        }
     }
 }
 
 impl fmt::Display for TypeConversionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let message = self.0.clone();
-        write!(f, "{message}")
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for TypeConversionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|error| error as &(dyn Error + 'static))
+    }
+}
+
+/// An AWS SDK type that couldn't be built, e.g. because a required field is missing.
+impl From<BuildError> for TypeConversionError {
+    fn from(error: BuildError) -> Self {
+        Self {
+            message: format!("Failed to build AWS SDK type: {error}"),
+            source: Some(error),
+        }
+    }
+}
+
+/// A Bedrock message whose content couldn't be converted to rig content, e.g. because Bedrock
+/// returned a block type this crate doesn't support yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentConversionError {
+    /// The role of the message, `assistant` or `user`.
+    pub role: String,
+    /// The blocks that failed to convert, empty if the message had no content at all.
+    pub failures: Vec<ContentBlockFailure>,
+}
+
+/// A content block that couldn't be converted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentBlockFailure {
+    /// The position of the block in its message.
+    pub index: usize,
+    /// The block type, as named by the Converse API, e.g. `citationsContent`.
+    pub kind: String,
+    pub reason: String,
+}
+
+impl ContentBlockFailure {
+    pub(crate) fn new(index: usize, kind: &str, error: CompletionError) -> Self {
+        let reason = match error {
+            CompletionError::ProviderError(reason) | CompletionError::ResponseError(reason) => {
+                reason
+            }
+            error => error.to_string(),
+        };
+        Self {
+            index,
+            kind: kind.to_string(),
+            reason,
+        }
+    }
+}
+
+impl ContentConversionError {
+    /// The content conversion error a completion failed with, if any.
+    pub fn from_completion_error(error: &CompletionError) -> Option<&Self> {
+        match error {
+            CompletionError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ContentConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.failures.is_empty() {
+            return write!(f, "The {} message has no content", self.role);
+        }
+        write!(
+            f,
+            "Failed to convert the content of the {} message: ",
+            self.role
+        )?;
+        for (i, failure) in self.failures.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(
+                f,
+                "block {} ({}): {}",
+                failure.index, failure.kind, failure.reason
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ContentConversionError {}
+
+impl From<ContentConversionError> for CompletionError {
+    fn from(value: ContentConversionError) -> Self {
+        CompletionError::RequestError(Box::new(value))
+    }
+}
+
+/// The Converse API name of the type of `block`.
+pub(crate) fn content_block_kind(
+    block: &aws_sdk_bedrockruntime::types::ContentBlock,
+) -> &'static str {
+    use aws_sdk_bedrockruntime::types::ContentBlock;
+    match block {
+        ContentBlock::CachePoint(_) => "cachePoint",
+        ContentBlock::CitationsContent(_) => "citationsContent",
+        ContentBlock::Document(_) => "document",
+        ContentBlock::GuardContent(_) => "guardContent",
+        ContentBlock::Image(_) => "image",
+        ContentBlock::ReasoningContent(_) => "reasoningContent",
+        ContentBlock::Text(_) => "text",
+        ContentBlock::ToolResult(_) => "toolResult",
+        ContentBlock::ToolUse(_) => "toolUse",
+        ContentBlock::Video(_) => "video",
+        _ => "unknown",
+    }
+}
+
+/// A completion request exceeding a Converse limit, rejected before being sent.
+/// See [`RequestLimits`](crate::request_limits::RequestLimits).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequestLimitError {
+    TooManyDocuments {
+        count: usize,
+        max: usize,
+    },
+    DocumentTooLarge {
+        bytes: usize,
+        max: usize,
+    },
+    TooManyImages {
+        count: usize,
+        max: usize,
+    },
+    ImageTooLarge {
+        bytes: usize,
+        max: usize,
+    },
+    TooManyTools {
+        count: usize,
+        max: usize,
+    },
+    /// The estimated size of the request body.
+    PayloadTooLarge {
+        bytes: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for RequestLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyDocuments { count, max } => write!(
+                f,
+                "The request has {count} documents, Bedrock accepts at most {max}"
+            ),
+            Self::DocumentTooLarge { bytes, max } => write!(
+                f,
+                "A document of the request is {bytes} bytes, Bedrock accepts at most {max}"
+            ),
+            Self::TooManyImages { count, max } => write!(
+                f,
+                "The request has {count} images, Bedrock accepts at most {max}"
+            ),
+            Self::ImageTooLarge { bytes, max } => write!(
+                f,
+                "An image of the request is {bytes} bytes, Bedrock accepts at most {max}"
+            ),
+            Self::TooManyTools { count, max } => {
+                write!(f, "The request has {count} tools, the limit is {max}")
+            }
+            Self::PayloadTooLarge { bytes, max } => write!(
+                f,
+                "The request is about {bytes} bytes, Bedrock accepts at most {max}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RequestLimitError {}
+
+impl From<RequestLimitError> for CompletionError {
+    fn from(value: RequestLimitError) -> Self {
+        CompletionError::RequestError(Box::new(value))
     }
 }
 
-impl std::error::Error for TypeConversionError {}
+/// A parameter of a completion request outside of the range the model accepts, rejected before
+/// being sent. See [`ParamRanges`](crate::param_ranges::ParamRanges).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamRangeError {
+    pub model: String,
+    /// `temperature`, `top_p` or `max_tokens`.
+    pub parameter: &'static str,
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ParamRangeError {
+    /// The parameter range error a completion failed with, if any.
+    pub fn from_completion_error(error: &CompletionError) -> Option<&Self> {
+        match error {
+            CompletionError::RequestError(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParamRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The {} {} is out of the range of {}, from {} to {}",
+            self.parameter, self.value, self.model, self.min, self.max
+        )
+    }
+}
+
+impl std::error::Error for ParamRangeError {}
+
+impl From<ParamRangeError> for CompletionError {
+    fn from(value: ParamRangeError) -> Self {
+        CompletionError::RequestError(Box::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_bedrockruntime::operation::converse::ConverseError;
+    use aws_sdk_bedrockruntime::types::error::{
+        AccessDeniedException, ThrottlingException, ValidationException,
+    };
+
+    use super::ModelAccessError;
+
+    const MODEL: &str = "amazon.nova-lite-v1:0";
+
+    #[test]
+    fn test_access_denied_for_disabled_model() {
+        let error = ConverseError::AccessDeniedException(
+            AccessDeniedException::builder()
+                .message("You don't have access to the model with the specified model ID.")
+                .build(),
+        );
+
+        assert!(matches!(
+            ModelAccessError::from_converse_error(MODEL, error),
+            ModelAccessError::ModelNotEnabled { .. }
+        ));
+    }
+
+    #[test]
+    fn test_access_denied_for_missing_iam_permission() {
+        let error = ConverseError::AccessDeniedException(
+            AccessDeniedException::builder()
+                .message("User: arn:aws:iam::123456789012:user/test is not authorized to perform: bedrock:InvokeModel")
+                .build(),
+        );
+
+        assert!(matches!(
+            ModelAccessError::from_converse_error(MODEL, error),
+            ModelAccessError::AccessDenied { .. }
+        ));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let invalid_model = ConverseError::ValidationException(
+            ValidationException::builder()
+                .message("The provided model identifier is invalid.")
+                .build(),
+        );
+        assert!(matches!(
+            ModelAccessError::from_converse_error(MODEL, invalid_model),
+            ModelAccessError::ModelNotFound { .. }
+        ));
+
+        let profile_required = ConverseError::ValidationException(
+            ValidationException::builder()
+                .message("Invocation of model ID anthropic.claude-sonnet-4-20250514-v1:0 with on-demand throughput isn't supported. Retry your request with the ID or ARN of an inference profile that contains this model.")
+                .build(),
+        );
+        assert!(matches!(
+            ModelAccessError::from_converse_error(MODEL, profile_required),
+            ModelAccessError::InferenceProfileRequired { .. }
+        ));
+    }
+
+    #[test]
+    fn test_other_errors() {
+        let error = ConverseError::ThrottlingException(
+            ThrottlingException::builder()
+                .message("Too many requests")
+                .build(),
+        );
+
+        assert!(matches!(
+            ModelAccessError::from_converse_error(MODEL, error),
+            ModelAccessError::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_bedrock_error_kind_and_retryability() {
+        use aws_sdk_bedrockruntime::config::http::HttpResponse;
+        use aws_sdk_bedrockruntime::error::{ErrorMetadata, SdkError};
+        use aws_smithy_types::body::SdkBody;
+        use rig::completion::CompletionError;
+
+        use super::{BedrockError, BedrockErrorKind};
+
+        let throttled = ConverseError::ThrottlingException(
+            ThrottlingException::builder()
+                .message("Too many requests")
+                .meta(
+                    ErrorMetadata::builder()
+                        .code("ThrottlingException")
+                        .message("Too many requests")
+                        .build(),
+                )
+                .build(),
+        );
+        let mut response = HttpResponse::new(429.try_into().unwrap(), SdkBody::empty());
+        response
+            .headers_mut()
+            .insert("x-amzn-RequestId", "4b0a1f1e-request-id");
+        let sdk_error = SdkError::service_error(throttled, response);
+
+        let completion_error: CompletionError = BedrockError::from(sdk_error).into();
+        let error = BedrockError::from_completion_error(&completion_error)
+            .expect("completion error should carry a Bedrock error");
+
+        assert_eq!(error.kind(), BedrockErrorKind::Throttled);
+        assert_eq!(error.message(), "Too many requests");
+        assert_eq!(error.request_id(), Some("4b0a1f1e-request-id"));
+        assert_eq!(error.extended_request_id(), None);
+        assert_eq!(
+            error.to_string(),
+            "Too many requests (request id: 4b0a1f1e-request-id)"
+        );
+        assert!(error.is_retryable());
+        assert!(
+            error
+                .aws_error()
+                .downcast_ref::<SdkError<ConverseError, HttpResponse>>()
+                .is_some()
+        );
+
+        assert!(!BedrockErrorKind::Validation.is_retryable());
+        assert!(!BedrockErrorKind::AccessDenied.is_retryable());
+    }
+
+    #[test]
+    fn test_type_conversion_error_source() {
+        use std::error::Error;
+
+        use aws_sdk_bedrockruntime::error::BuildError;
+
+        use super::TypeConversionError;
+
+        let error = TypeConversionError::from(BuildError::missing_field("role", "a message role"));
+        assert!(
+            error
+                .to_string()
+                .starts_with("Failed to build AWS SDK type: ")
+        );
+        assert!(
+            error
+                .source()
+                .and_then(|source| source.downcast_ref::<BuildError>())
+                .is_some()
+        );
+        assert!(TypeConversionError::new("invalid").source().is_none());
+    }
+
+    #[test]
+    fn test_image_generation_errors_keep_the_bedrock_error() {
+        use aws_sdk_bedrockruntime::config::http::HttpResponse;
+        use aws_sdk_bedrockruntime::error::{ErrorMetadata, SdkError};
+        use aws_sdk_bedrockruntime::operation::invoke_model::InvokeModelError;
+        use aws_smithy_types::body::SdkBody;
+
+        let service_error =
+            InvokeModelError::generic(ErrorMetadata::builder().code("ThrottlingException").build());
+        let response = HttpResponse::new(429.try_into().unwrap(), SdkBody::empty());
+        let error = rig::image_generation::ImageGenerationError::from(
+            super::AwsSdkInvokeModelError(SdkError::service_error(service_error, response)),
+        );
+
+        let bedrock_error = super::BedrockError::from_image_generation_error(&error).unwrap();
+        assert_eq!(bedrock_error.kind(), super::BedrockErrorKind::Throttled);
+    }
+}
@@ -14,8 +14,10 @@ impl TryFrom<RigImage> for aws_bedrock::ImageBlock {
     type Error = CompletionError;
 
     fn try_from(image: RigImage) -> Result<Self, Self::Error> {
-        let maybe_format: Option<Result<aws_bedrock::ImageFormat, CompletionError>> =
-            image.0.media_type.map(|f| match f {
+        let format = image
+            .0
+            .media_type
+            .map(|f| match f {
                 ImageMediaType::JPEG => Ok(aws_bedrock::ImageFormat::Jpeg),
                 ImageMediaType::PNG => Ok(aws_bedrock::ImageFormat::Png),
                 ImageMediaType::GIF => Ok(aws_bedrock::ImageFormat::Gif),
@@ -24,26 +26,43 @@ impl TryFrom<RigImage> for aws_bedrock::ImageBlock {
                     "Unsupported format {}",
                     e.to_mime_type()
                 ))),
-            });
+            })
+            .transpose()?;
 
-        let format = match maybe_format {
-            Some(Ok(image_format)) => Ok(Some(image_format)),
-            Some(Err(err)) => Err(err),
-            None => Ok(None),
-        }?;
+        let img_data = match image.0.data {
+            DocumentSourceKind::Base64(data) | DocumentSourceKind::String(data) => BASE64_STANDARD
+                .decode(data)
+                .map_err(|e| CompletionError::ProviderError(e.to_string()))?,
+            DocumentSourceKind::Raw(bytes) => bytes,
+            DocumentSourceKind::Url(url) => {
+                return Err(CompletionError::RequestError(
+                    format!("Image URL {url} wasn't fetched; image fetching is disabled, see `ImageFetch`")
+                        .into(),
+                ));
+            }
+            _ => {
+                return Err(CompletionError::RequestError(
+                    "Only base64 encoded strings, raw bytes and URLs are allowed for image input on AWS Bedrock".into(),
+                ));
+            }
+        };
 
-        let DocumentSourceKind::Base64(data) = image.0.data else {
-            return Err(CompletionError::RequestError(
-                "Only base64 encoded strings are allowed for image input on AWS Bedrock".into(),
-            ));
+        // Bedrock rejects images without a format, so it's detected from the data when missing
+        let format = match format {
+            Some(format) => format,
+            None => detect_format(&img_data).ok_or_else(|| {
+                CompletionError::ProviderError(
+                    "Image has no media type and its format couldn't be detected, only PNG, \
+                     JPEG, GIF and WebP images are supported"
+                        .into(),
+                )
+            })?,
         };
 
-        let img_data = BASE64_STANDARD
-            .decode(data)
-            .map_err(|e| CompletionError::ProviderError(e.to_string()))?;
+        // Raw bytes are moved into the blob without copying
         let blob = aws_smithy_types::Blob::new(img_data);
         let result = aws_bedrock::ImageBlock::builder()
-            .set_format(format)
+            .format(format)
             .source(aws_bedrock::ImageSource::Bytes(blob))
             .build()
             .map_err(|e| CompletionError::ProviderError(e.to_string()))?;
@@ -83,6 +102,31 @@ impl TryFrom<aws_bedrock::ImageBlock> for RigImage {
     }
 }
 
+/// The format of an image from the magic bytes its data starts with.
+fn detect_format(data: &[u8]) -> Option<aws_bedrock::ImageFormat> {
+    match data {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(aws_bedrock::ImageFormat::Png),
+        [0xFF, 0xD8, 0xFF, ..] => Some(aws_bedrock::ImageFormat::Jpeg),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(aws_bedrock::ImageFormat::Gif),
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => Some(aws_bedrock::ImageFormat::Webp),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use aws_sdk_bedrockruntime::types as aws_bedrock;
@@ -121,6 +165,29 @@ mod tests {
         assert_eq!(aws_image_bytes, img_data)
     }
 
+    #[test]
+    fn test_image_round_trip() {
+        let aws_image = aws_bedrock::ImageBlock::builder()
+            .format(aws_bedrock::ImageFormat::Png)
+            .source(aws_bedrock::ImageSource::Bytes(
+                aws_smithy_types::Blob::new(b"png".to_vec()),
+            ))
+            .build()
+            .unwrap();
+
+        let rig_image: RigImage = aws_image.try_into().unwrap();
+        assert_eq!(
+            rig_image.0.data,
+            DocumentSourceKind::Base64(BASE64_STANDARD.encode("png"))
+        );
+
+        let aws_image: aws_bedrock::ImageBlock = rig_image.try_into().unwrap();
+        assert_eq!(
+            aws_image.source().unwrap().as_bytes().unwrap().as_ref(),
+            b"png"
+        );
+    }
+
     #[test]
     fn test_unsupported_image_to_aws_image() {
         let encoded_str = BASE64_STANDARD.encode("img_data");
@@ -136,4 +203,34 @@ mod tests {
             CompletionError::ProviderError("Unsupported format image/heic".into()).to_string()
         )
     }
+
+    #[test]
+    fn test_image_format_is_detected() {
+        let image = |data: &[u8]| {
+            RigImage(Image {
+                data: DocumentSourceKind::Raw(data.to_vec()),
+                media_type: None,
+                detail: None,
+                additional_params: None,
+            })
+        };
+
+        for (data, format) in [
+            (&b"\x89PNG\r\n\x1a\n..."[..], aws_bedrock::ImageFormat::Png),
+            (b"\xff\xd8\xff\xe0...", aws_bedrock::ImageFormat::Jpeg),
+            (b"GIF89a...", aws_bedrock::ImageFormat::Gif),
+            (b"RIFF\x24\0\0\0WEBPVP8 ", aws_bedrock::ImageFormat::Webp),
+        ] {
+            let aws_image: aws_bedrock::ImageBlock = image(data).try_into().unwrap();
+            assert_eq!(aws_image.format, format);
+        }
+
+        let aws_image: Result<aws_bedrock::ImageBlock, _> = image(b"BM...").try_into();
+        assert!(
+            aws_image
+                .unwrap_err()
+                .to_string()
+                .contains("format couldn't be detected")
+        );
+    }
 }
@@ -44,11 +44,11 @@ impl From<Value> for AwsDocument {
             Value::Null => AwsDocument(Document::Null),
             Value::Bool(b) => AwsDocument(Document::Bool(b)),
             Value::Number(num) => {
-                if let Some(i) = num.as_i64() {
-                    match i > 0 {
-                        true => AwsDocument(Document::Number(Number::PosInt(i as u64))),
-                        false => AwsDocument(Document::Number(Number::NegInt(i))),
-                    }
+                // Unsigned first, so zero and values above `i64::MAX` are kept as `PosInt`
+                if let Some(u) = num.as_u64() {
+                    AwsDocument(Document::Number(Number::PosInt(u)))
+                } else if let Some(i) = num.as_i64() {
+                    AwsDocument(Document::Number(Number::NegInt(i)))
                 } else if let Some(f) = num.as_f64() {
                     AwsDocument(Document::Number(Number::Float(f)))
                 } else {
@@ -83,7 +83,8 @@ mod tests {
     use std::collections::HashMap;
 
     use aws_smithy_types::{Document, Number};
-    use serde_json::Value;
+    use quickcheck::quickcheck;
+    use serde_json::{Value, json};
 
     use crate::types::json::AwsDocument;
 
@@ -179,4 +180,50 @@ mod tests {
         let json: Value = document.into();
         println!("{json:?}");
     }
+
+    #[test]
+    fn test_integer_boundaries() {
+        let cases = [
+            (json!(0), Number::PosInt(0)),
+            (json!(1), Number::PosInt(1)),
+            (json!(-1), Number::NegInt(-1)),
+            (json!(i64::MAX), Number::PosInt(i64::MAX as u64)),
+            (
+                json!(i64::MAX as u64 + 1),
+                Number::PosInt(i64::MAX as u64 + 1),
+            ),
+            (json!(u64::MAX), Number::PosInt(u64::MAX)),
+            (json!(i64::MIN), Number::NegInt(i64::MIN)),
+        ];
+
+        for (value, number) in cases {
+            let document = AwsDocument::from(value.clone()).0;
+            assert_eq!(document, Document::Number(number));
+            assert_eq!(Value::from(AwsDocument(document)), value);
+        }
+    }
+
+    quickcheck! {
+        fn prop_u64_round_trips(n: u64) -> bool {
+            let value = json!(n);
+            AwsDocument::from(value.clone()).0 == Document::Number(Number::PosInt(n))
+                && Value::from(AwsDocument::from(value.clone())) == value
+        }
+
+        fn prop_i64_round_trips(n: i64) -> bool {
+            let value = json!(n);
+            Value::from(AwsDocument::from(value.clone())) == value
+        }
+
+        fn prop_finite_f64_round_trips(n: f64) -> bool {
+            let value = json!(n);
+            // Non finite floats aren't valid JSON and become null
+            !n.is_finite() || Value::from(AwsDocument::from(value.clone())) == value
+        }
+
+        fn prop_nested_numbers_round_trip(numbers: Vec<i64>, unsigned: Vec<u64>) -> bool {
+            let value = json!({ "signed": numbers, "unsigned": unsigned });
+            Value::from(AwsDocument::from(value.clone())) == value
+        }
+    }
 }
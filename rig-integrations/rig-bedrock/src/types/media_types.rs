@@ -6,6 +6,26 @@ use rig::{
 
 pub struct RigDocumentMediaType(pub DocumentMediaType);
 
+/// The `additional_params` key of a document holding a format rig has no media type for, e.g.
+/// `{ "format": "docx" }`.
+pub const DOCUMENT_FORMAT_KEY: &str = "format";
+
+/// The office format named by a file extension or MIME type, as found under
+/// [`DOCUMENT_FORMAT_KEY`]. Rig's [`DocumentMediaType`] has no variant for these formats.
+pub fn office_document_format(format: &str) -> Option<DocumentFormat> {
+    match format.to_ascii_lowercase().as_str() {
+        "doc" | "application/msword" => Some(DocumentFormat::Doc),
+        "docx" | "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            Some(DocumentFormat::Docx)
+        }
+        "xls" | "application/vnd.ms-excel" => Some(DocumentFormat::Xls),
+        "xlsx" | "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
+            Some(DocumentFormat::Xlsx)
+        }
+        _ => None,
+    }
+}
+
 impl TryFrom<RigDocumentMediaType> for DocumentFormat {
     type Error = CompletionError;
 
@@ -1,12 +1,12 @@
 use aws_sdk_bedrockruntime::types as aws_bedrock;
 
-use rig::{
-    OneOrMany,
-    completion::CompletionError,
-    message::{AssistantContent, Message, UserContent},
-};
+use rig::{OneOrMany, completion::CompletionError, message::Message};
 
-use super::{assistant_content::RigAssistantContent, user_content::RigUserContent};
+use super::{
+    assistant_content::RigAssistantContent,
+    errors::{ContentBlockFailure, ContentConversionError, content_block_kind},
+    user_content::RigUserContent,
+};
 
 pub struct RigMessage(pub Message);
 
@@ -50,41 +50,56 @@ impl TryFrom<aws_bedrock::Message> for RigMessage {
     fn try_from(message: aws_bedrock::Message) -> Result<Self, Self::Error> {
         match message.role {
             aws_bedrock::ConversationRole::Assistant => {
-                let assistant_content = message
-                    .content
-                    .into_iter()
-                    .map(|c| c.try_into())
-                    .collect::<Result<Vec<RigAssistantContent>, _>>()?
-                    .into_iter()
-                    .map(|rig_assistant_content| rig_assistant_content.0)
-                    .collect::<Vec<AssistantContent>>();
-
-                let content = OneOrMany::many(assistant_content)
-                    .map_err(|e| CompletionError::RequestError(Box::new(e)))?;
-
+                let content = convert_blocks("assistant", message.content, |block| {
+                    RigAssistantContent::try_from(block).map(|content| content.0)
+                })?;
                 Ok(RigMessage(Message::Assistant { content, id: None }))
             }
             aws_bedrock::ConversationRole::User => {
-                let user_content = message
-                    .content
-                    .into_iter()
-                    .map(|c| c.try_into())
-                    .collect::<Result<Vec<RigUserContent>, _>>()?
-                    .into_iter()
-                    .map(|user_content| user_content.0)
-                    .collect::<Vec<UserContent>>();
-
-                let content = OneOrMany::many(user_content)
-                    .map_err(|e| CompletionError::RequestError(Box::new(e)))?;
+                let content = convert_blocks("user", message.content, |block| {
+                    RigUserContent::try_from(block).map(|content| content.0)
+                })?;
                 Ok(RigMessage(Message::User { content }))
             }
-            _ => Err(CompletionError::ProviderError(
-                "AWS Bedrock returned unsupported ConversationRole".into(),
-            )),
+            role => Err(CompletionError::ProviderError(format!(
+                "AWS Bedrock returned unsupported ConversationRole {role}"
+            ))),
         }
     }
 }
 
+/// Convert the content `blocks` of a `role` message, failing with all the blocks that couldn't be
+/// converted.
+fn convert_blocks<T: Clone>(
+    role: &str,
+    blocks: Vec<aws_bedrock::ContentBlock>,
+    convert: impl Fn(aws_bedrock::ContentBlock) -> Result<T, CompletionError>,
+) -> Result<OneOrMany<T>, CompletionError> {
+    let mut content = Vec::with_capacity(blocks.len());
+    let mut failures = Vec::new();
+    for (index, block) in blocks.into_iter().enumerate() {
+        let kind = content_block_kind(&block);
+        match convert(block) {
+            Ok(converted) => content.push(converted),
+            Err(e) => failures.push(ContentBlockFailure::new(index, kind, e)),
+        }
+    }
+    if !failures.is_empty() {
+        return Err(ContentConversionError {
+            role: role.into(),
+            failures,
+        }
+        .into());
+    }
+    OneOrMany::many(content).map_err(|_| {
+        ContentConversionError {
+            role: role.into(),
+            failures,
+        }
+        .into()
+    })
+}
+
 impl TryFrom<super::converse_output::Message> for RigMessage {
     type Error = CompletionError;
 
@@ -98,7 +113,7 @@ impl TryFrom<super::converse_output::Message> for RigMessage {
 
 #[cfg(test)]
 mod tests {
-    use crate::types::message::RigMessage;
+    use crate::types::{errors::ContentConversionError, message::RigMessage};
     use aws_sdk_bedrockruntime::types as aws_bedrock;
     use rig::{
         OneOrMany,
@@ -119,4 +134,29 @@ mod tests {
             vec![aws_bedrock::ContentBlock::Text("text".into())]
         );
     }
+
+    #[test]
+    fn aws_message_conversion_reports_failed_blocks() {
+        let message = aws_bedrock::Message::builder()
+            .role(aws_bedrock::ConversationRole::User)
+            .content(aws_bedrock::ContentBlock::Text("text".into()))
+            .content(aws_bedrock::ContentBlock::CachePoint(
+                aws_bedrock::CachePointBlock::builder()
+                    .r#type(aws_bedrock::CachePointType::Default)
+                    .build()
+                    .unwrap(),
+            ))
+            .build()
+            .unwrap();
+
+        let Err(error) = RigMessage::try_from(message) else {
+            panic!("expected the conversion to fail");
+        };
+
+        let error = ContentConversionError::from_completion_error(&error).unwrap();
+        assert_eq!(error.role, "user");
+        assert_eq!(error.failures.len(), 1);
+        assert_eq!(error.failures[0].index, 1);
+        assert_eq!(error.failures[0].kind, "cachePoint");
+    }
 }
@@ -1,12 +1,23 @@
-pub(crate) mod assistant_content;
-pub(crate) mod completion_request;
-pub(crate) mod converse_output;
-pub(crate) mod document;
-pub(crate) mod errors;
-pub(crate) mod image;
-pub(crate) mod json;
-pub(crate) mod media_types;
-pub(crate) mod message;
+//! Conversions between rig and Bedrock types.
+//!
+//! Each rig type is wrapped in a newtype (e.g. [`message::RigMessage`]) implementing `TryFrom`
+//! into the matching Bedrock SDK type, and back, so conversions compose with `?`:
+//!
+//! ```rust,ignore
+//! let message: aws_bedrock::Message = RigMessage(message).try_into()?;
+//! let message: RigMessage = aws_message.try_into()?;
+//! ```
+
+pub mod assistant_content;
+pub mod completion_request;
+pub mod converse_output;
+pub mod document;
+pub mod errors;
+pub mod image;
+pub mod json;
+pub mod media_types;
+pub mod message;
+pub(crate) mod stability;
 pub(crate) mod text_to_image;
-pub(crate) mod tool;
-pub(crate) mod user_content;
+pub mod tool;
+pub mod user_content;
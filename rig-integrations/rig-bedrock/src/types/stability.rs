@@ -0,0 +1,270 @@
+//! Native request and response formats of Stability AI image models.
+//!
+//! SD3 and Stable Image models take an aspect ratio instead of a size, while the legacy SDXL
+//! model takes weighted prompts and returns "artifacts". Both responses are converted into a
+//! [`TextToImageResponse`] so all image models share the same response type.
+
+use rig::image_generation::ImageGenerationError;
+use serde::{Deserialize, Serialize};
+
+use crate::native::base_model_id;
+use crate::types::text_to_image::TextToImageResponse;
+
+/// Aspect ratios accepted by SD3 and Stable Image models.
+const ASPECT_RATIOS: [(&str, f64); 9] = [
+    ("21:9", 21.0 / 9.0),
+    ("16:9", 16.0 / 9.0),
+    ("3:2", 3.0 / 2.0),
+    ("5:4", 5.0 / 4.0),
+    ("1:1", 1.0),
+    ("4:5", 4.0 / 5.0),
+    ("2:3", 2.0 / 3.0),
+    ("9:16", 9.0 / 16.0),
+    ("9:21", 9.0 / 21.0),
+];
+
+/// Generation options shared by all Stability models.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StabilityParams {
+    pub prompt: String,
+    pub negative_prompt: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub seed: Option<u32>,
+    pub cfg_scale: Option<f32>,
+    pub samples: Option<u32>,
+}
+
+/// Whether `model` is the legacy SDXL model rather than an SD3 or Stable Image model.
+pub(crate) fn is_sdxl(model: &str) -> bool {
+    base_model_id(model).starts_with("stability.stable-diffusion-xl")
+}
+
+/// The supported aspect ratio closest to `width` / `height`.
+pub(crate) fn aspect_ratio(width: u32, height: u32) -> &'static str {
+    if width == 0 || height == 0 {
+        return "1:1";
+    }
+    let ratio = (width as f64 / height as f64).ln();
+
+    ASPECT_RATIOS
+        .iter()
+        .min_by(|(_, a), (_, b)| (a.ln() - ratio).abs().total_cmp(&(b.ln() - ratio).abs()))
+        .map(|(name, _)| *name)
+        .unwrap_or("1:1")
+}
+
+#[derive(Debug, Serialize)]
+struct Sd3Request {
+    prompt: String,
+    mode: &'static str,
+    aspect_ratio: &'static str,
+    output_format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    negative_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sd3Response {
+    images: Vec<String>,
+    #[serde(default)]
+    finish_reasons: Vec<Option<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SdxlTextPrompt {
+    text: String,
+    weight: f32,
+}
+
+#[derive(Debug, Serialize)]
+struct SdxlRequest {
+    text_prompts: Vec<SdxlTextPrompt>,
+    width: u32,
+    height: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cfg_scale: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    samples: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SdxlArtifact {
+    base64: Option<String>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SdxlResponse {
+    #[serde(default)]
+    artifacts: Vec<SdxlArtifact>,
+}
+
+/// The native request body of `model`.
+pub(crate) fn request_body(
+    model: &str,
+    params: StabilityParams,
+) -> Result<serde_json::Value, ImageGenerationError> {
+    let body = if is_sdxl(model) {
+        let mut text_prompts = vec![SdxlTextPrompt {
+            text: params.prompt,
+            weight: 1.0,
+        }];
+        // SDXL expresses negative prompts as negatively weighted prompts
+        if let Some(negative_prompt) = params.negative_prompt {
+            text_prompts.push(SdxlTextPrompt {
+                text: negative_prompt,
+                weight: -1.0,
+            });
+        }
+        serde_json::to_value(SdxlRequest {
+            text_prompts,
+            width: params.width,
+            height: params.height,
+            cfg_scale: params.cfg_scale,
+            seed: params.seed,
+            samples: params.samples,
+        })?
+    } else {
+        serde_json::to_value(Sd3Request {
+            prompt: params.prompt,
+            mode: "text-to-image",
+            aspect_ratio: aspect_ratio(params.width, params.height),
+            output_format: "png",
+            negative_prompt: params.negative_prompt,
+            seed: params.seed,
+        })?
+    };
+
+    Ok(body)
+}
+
+/// Parse the native response of `model`, failing if an image was filtered.
+pub(crate) fn parse_response(
+    model: &str,
+    response: &str,
+) -> Result<TextToImageResponse, ImageGenerationError> {
+    let parse_error = |e: serde_json::Error| ImageGenerationError::ResponseError(e.to_string());
+
+    let images = if is_sdxl(model) {
+        let response: SdxlResponse = serde_json::from_str(response).map_err(parse_error)?;
+        response
+            .artifacts
+            .into_iter()
+            .map(|artifact| match artifact.finish_reason.as_deref() {
+                None | Some("SUCCESS") => artifact.base64.ok_or_else(|| {
+                    ImageGenerationError::ResponseError("Missing image data".into())
+                }),
+                Some(reason) => Err(ImageGenerationError::ResponseError(format!(
+                    "Image generation finished with {reason}"
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        let response: Sd3Response = serde_json::from_str(response).map_err(parse_error)?;
+        if let Some(reason) = response.finish_reasons.into_iter().flatten().next() {
+            return Err(ImageGenerationError::ResponseError(reason));
+        }
+        response.images
+    };
+
+    Ok(TextToImageResponse {
+        images: Some(images),
+        error: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SD3: &str = "stability.sd3-large-v1:0";
+    const SDXL: &str = "stability.stable-diffusion-xl-v1";
+
+    fn params() -> StabilityParams {
+        StabilityParams {
+            prompt: "A fox in the snow".into(),
+            negative_prompt: Some("blurry".into()),
+            width: 1024,
+            height: 576,
+            seed: Some(42),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_aspect_ratio() {
+        assert_eq!(aspect_ratio(1024, 1024), "1:1");
+        assert_eq!(aspect_ratio(1024, 576), "16:9");
+        assert_eq!(aspect_ratio(576, 1024), "9:16");
+        assert_eq!(aspect_ratio(1200, 800), "3:2");
+        assert_eq!(aspect_ratio(0, 0), "1:1");
+    }
+
+    #[test]
+    fn test_sd3_request() {
+        assert_eq!(
+            request_body(SD3, params()).unwrap(),
+            json!({
+                "prompt": "A fox in the snow",
+                "mode": "text-to-image",
+                "aspect_ratio": "16:9",
+                "output_format": "png",
+                "negative_prompt": "blurry",
+                "seed": 42
+            })
+        );
+    }
+
+    #[test]
+    fn test_sdxl_request() {
+        assert!(is_sdxl(SDXL));
+        assert_eq!(
+            request_body(SDXL, params()).unwrap(),
+            json!({
+                "text_prompts": [
+                    { "text": "A fox in the snow", "weight": 1.0 },
+                    { "text": "blurry", "weight": -1.0 }
+                ],
+                "width": 1024,
+                "height": 576,
+                "seed": 42
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let sd3 = parse_response(
+            SD3,
+            r#"{"seeds":[42],"finish_reasons":[null],"images":["aW1hZ2U="]}"#,
+        )
+        .unwrap();
+        assert_eq!(sd3.images, Some(vec!["aW1hZ2U=".to_string()]));
+
+        let filtered = parse_response(
+            SD3,
+            r#"{"seeds":[42],"finish_reasons":["Filter reason: prompt"],"images":[]}"#,
+        );
+        assert!(filtered.is_err());
+
+        let sdxl = parse_response(
+            SDXL,
+            r#"{"result":"success","artifacts":[{"seed":1,"base64":"aW1hZ2U=","finishReason":"SUCCESS"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(sdxl.images, Some(vec!["aW1hZ2U=".to_string()]));
+
+        let sdxl_filtered = parse_response(
+            SDXL,
+            r#"{"result":"success","artifacts":[{"seed":1,"base64":"","finishReason":"CONTENT_FILTERED"}]}"#,
+        );
+        assert!(sdxl_filtered.is_err());
+    }
+}
@@ -3,8 +3,9 @@ use base64::prelude::BASE64_STANDARD;
 use rig::image_generation;
 use rig::image_generation::ImageGenerationError;
 use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ImageQuality {
     Standard,
@@ -22,8 +23,10 @@ pub struct ImageGenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub number_of_images: Option<u32>,
     // The height of the image in pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<u32>,
     // The width of the image in pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub width: Option<u32>,
     // Specifies how strongly the generated image should adhere to the prompt. Use a lower value to introduce more randomness in the generation.
     // Default: 8.0. Minimum: 1.1, Maximum: 10.0
@@ -57,43 +60,219 @@ pub struct TextToImageParams {
     pub negative_text: Option<String>,
 }
 
-impl TextToImageParams {
-    pub fn new(text: String) -> Self {
+/// Which image is masked by an inpainting or outpainting task.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageMask {
+    /// A description of the masked objects, e.g. "the car".
+    Prompt(String),
+    /// A black and white image, black pixels being masked. Same size as the input image.
+    Image(Vec<u8>),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OutPaintingMode {
+    /// Blend the masked area smoothly with the rest of the image.
+    #[default]
+    Default,
+    /// Keep the masked area exactly as is.
+    Precise,
+}
+
+/// How a condition image guides the layout of generated images.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ControlMode {
+    /// Follow the edges of the condition image.
+    #[default]
+    CannyEdge,
+    /// Follow the shapes and regions of the condition image.
+    Segmentation,
+}
+
+/// A reference image whose structure text-to-image generation follows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageConditioning {
+    /// Raw PNG or JPEG bytes.
+    pub image: Vec<u8>,
+    pub control_mode: ControlMode,
+    /// How closely the layout follows the condition image, between 0 and 1. Defaults to 0.7.
+    pub control_strength: Option<f32>,
+}
+
+impl ImageConditioning {
+    pub fn new(image: Vec<u8>, control_mode: ControlMode) -> Self {
         Self {
-            text,
-            negative_text: None,
+            image,
+            control_mode,
+            control_strength: None,
+        }
+    }
+
+    pub fn with_control_strength(mut self, control_strength: f32) -> Self {
+        self.control_strength = Some(control_strength);
+        self
+    }
+
+    /// Add the conditioning to the `textToImageParams` of a request.
+    pub(crate) fn insert_into(&self, params: &mut Value) {
+        params["conditionImage"] = json!(BASE64_STANDARD.encode(&self.image));
+        params["controlMode"] = json!(self.control_mode);
+        if let Some(control_strength) = self.control_strength {
+            params["controlStrength"] = json!(control_strength);
         }
     }
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TextToImageGeneration {
-    pub task_type: &'static str,
-    pub text_to_image_params: TextToImageParams,
-    pub image_generation_config: ImageGenerationConfig,
-}
-
-impl TextToImageGeneration {
-    pub(crate) fn new(text: String) -> TextToImageGeneration {
-        TextToImageGeneration {
-            task_type: "TEXT_IMAGE",
-            text_to_image_params: TextToImageParams::new(text),
-            image_generation_config: Default::default(),
+/// The image generation task of Titan Image Generator v2 and Nova Canvas.
+///
+/// The generation request's prompt is used as the task's text, except for
+/// [`ImageTask::BackgroundRemoval`] which has none. Images are raw PNG or JPEG bytes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ImageTask {
+    /// Generate images from the prompt.
+    #[default]
+    TextImage,
+    /// Generate variations of up to 5 reference images.
+    ImageVariation {
+        images: Vec<Vec<u8>>,
+        /// How similar the variations are to the references, between 0.2 and 1.0.
+        similarity_strength: Option<f32>,
+    },
+    /// Replace the masked area of an image.
+    Inpainting { image: Vec<u8>, mask: ImageMask },
+    /// Replace everything but the masked area of an image.
+    Outpainting {
+        image: Vec<u8>,
+        mask: ImageMask,
+        mode: OutPaintingMode,
+    },
+    /// Remove the background of an image.
+    BackgroundRemoval { image: Vec<u8> },
+    /// Generate images using a palette of up to 10 hex colors, e.g. `#ff8800`.
+    ColorGuidedGeneration {
+        colors: Vec<String>,
+        reference_image: Option<Vec<u8>>,
+    },
+}
+
+impl ImageTask {
+    pub fn task_type(&self) -> &'static str {
+        match self {
+            Self::TextImage => "TEXT_IMAGE",
+            Self::ImageVariation { .. } => "IMAGE_VARIATION",
+            Self::Inpainting { .. } => "INPAINTING",
+            Self::Outpainting { .. } => "OUTPAINTING",
+            Self::BackgroundRemoval { .. } => "BACKGROUND_REMOVAL",
+            Self::ColorGuidedGeneration { .. } => "COLOR_GUIDED_GENERATION",
         }
     }
 
-    pub fn height(&mut self, height: u32) -> &Self {
-        self.image_generation_config.height = Some(height);
-        self
+    /// The native request body of the task.
+    pub(crate) fn request_body(
+        &self,
+        text: String,
+        negative_text: Option<String>,
+        config: &ImageGenerationConfig,
+    ) -> Result<Value, ImageGenerationError> {
+        let encode = |image: &Vec<u8>| BASE64_STANDARD.encode(image);
+
+        let (params_key, params) = match self {
+            Self::TextImage => (
+                "textToImageParams",
+                serde_json::to_value(TextToImageParams {
+                    text,
+                    negative_text,
+                })?,
+            ),
+            Self::ImageVariation {
+                images,
+                similarity_strength,
+            } => (
+                "imageVariationParams",
+                json!({
+                    "text": text,
+                    "negativeText": negative_text,
+                    "images": images.iter().map(encode).collect::<Vec<_>>(),
+                    "similarityStrength": similarity_strength,
+                }),
+            ),
+            Self::Inpainting { image, mask } => {
+                let mut params = json!({
+                    "text": text,
+                    "negativeText": negative_text,
+                    "image": encode(image),
+                });
+                mask.insert_into(&mut params);
+                ("inPaintingParams", params)
+            }
+            Self::Outpainting { image, mask, mode } => {
+                let mut params = json!({
+                    "text": text,
+                    "negativeText": negative_text,
+                    "image": encode(image),
+                    "outPaintingMode": mode,
+                });
+                mask.insert_into(&mut params);
+                ("outPaintingParams", params)
+            }
+            Self::BackgroundRemoval { image } => {
+                return Ok(json!({
+                    "taskType": self.task_type(),
+                    "backgroundRemovalParams": { "image": encode(image) },
+                }));
+            }
+            Self::ColorGuidedGeneration {
+                colors,
+                reference_image,
+            } => (
+                "colorGuidedGenerationParams",
+                json!({
+                    "text": text,
+                    "negativeText": negative_text,
+                    "colors": colors,
+                    "referenceImage": reference_image.as_ref().map(encode),
+                }),
+            ),
+        };
+
+        let mut body = match self {
+            // Edited images keep the size of the source image
+            Self::Inpainting { .. } | Self::Outpainting { .. } => json!({
+                "taskType": self.task_type(),
+                "imageGenerationConfig": ImageGenerationConfig {
+                    width: None,
+                    height: None,
+                    ..config.clone()
+                },
+            }),
+            _ => json!({
+                "taskType": self.task_type(),
+                "imageGenerationConfig": config,
+            }),
+        };
+        body[params_key] = without_nulls(params);
+        Ok(body)
     }
+}
 
-    pub fn width(&mut self, width: u32) -> &Self {
-        self.image_generation_config.width = Some(width);
-        self
+impl ImageMask {
+    fn insert_into(&self, params: &mut Value) {
+        match self {
+            Self::Prompt(prompt) => params["maskPrompt"] = json!(prompt),
+            Self::Image(image) => params["maskImage"] = json!(BASE64_STANDARD.encode(image)),
+        }
     }
 }
 
+/// Drop the unset optional parameters, which the models reject as `null`.
+fn without_nulls(mut params: Value) -> Value {
+    if let Value::Object(params) = &mut params {
+        params.retain(|_, value| !value.is_null());
+    }
+    params
+}
+
 #[derive(Clone, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TextToImageResponse {
@@ -111,19 +290,146 @@ impl TryFrom<TextToImageResponse>
             return Err(ImageGenerationError::ResponseError(error));
         }
 
-        if let Some(images) = value.to_owned().images {
-            let data = BASE64_STANDARD
-                .decode(&images[0])
-                .expect("Could not decode image.");
+        let image = value.decoded_images()?.into_iter().next().ok_or_else(|| {
+            ImageGenerationError::ResponseError("Malformed response from model".to_string())
+        })?;
+
+        Ok(Self {
+            image,
+            response: value,
+        })
+    }
+}
+
+impl TextToImageResponse {
+    /// All generated images, decoded. The generation response only exposes the first one.
+    pub fn decoded_images(&self) -> Result<Vec<Vec<u8>>, ImageGenerationError> {
+        self.images
+            .iter()
+            .flatten()
+            .map(|image| {
+                BASE64_STANDARD
+                    .decode(image)
+                    .map_err(|e| ImageGenerationError::ResponseError(e.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_decodes_all_images() {
+        let response = TextToImageResponse {
+            images: Some(vec![
+                BASE64_STANDARD.encode(b"first"),
+                BASE64_STANDARD.encode(b"second"),
+            ]),
+            error: None,
+        };
+
+        assert_eq!(
+            response.decoded_images().unwrap(),
+            vec![b"first".to_vec(), b"second".to_vec()]
+        );
+
+        let response: image_generation::ImageGenerationResponse<TextToImageResponse> =
+            response.try_into().unwrap();
+        assert_eq!(response.image, b"first");
+    }
+
+    #[test]
+    fn test_image_task_request_bodies() {
+        let config = ImageGenerationConfig::default();
+
+        let text_image = ImageTask::TextImage
+            .request_body("a cat".into(), None, &config)
+            .unwrap();
+        assert_eq!(text_image["taskType"], "TEXT_IMAGE");
+        assert_eq!(text_image["textToImageParams"], json!({ "text": "a cat" }));
+        assert_eq!(text_image["imageGenerationConfig"]["width"], 512);
+
+        let variation = ImageTask::ImageVariation {
+            images: vec![b"png".to_vec()],
+            similarity_strength: Some(0.7),
+        }
+        .request_body("a cat".into(), Some("dogs".into()), &config)
+        .unwrap();
+        assert_eq!(
+            variation["imageVariationParams"],
+            json!({
+                "text": "a cat",
+                "negativeText": "dogs",
+                "images": [BASE64_STANDARD.encode(b"png")],
+                "similarityStrength": 0.699999988079071
+            })
+        );
+
+        let outpainting = ImageTask::Outpainting {
+            image: b"png".to_vec(),
+            mask: ImageMask::Prompt("the cat".into()),
+            mode: OutPaintingMode::Precise,
+        }
+        .request_body("a garden".into(), None, &config)
+        .unwrap();
+        assert_eq!(
+            outpainting["outPaintingParams"],
+            json!({
+                "text": "a garden",
+                "image": BASE64_STANDARD.encode(b"png"),
+                "maskPrompt": "the cat",
+                "outPaintingMode": "PRECISE"
+            })
+        );
 
-            return Ok(Self {
-                image: data,
-                response: value,
-            });
+        assert!(outpainting["imageGenerationConfig"].get("width").is_none());
+
+        let background_removal = ImageTask::BackgroundRemoval {
+            image: b"png".to_vec(),
         }
+        .request_body(String::new(), None, &config)
+        .unwrap();
+        assert_eq!(
+            background_removal,
+            json!({
+                "taskType": "BACKGROUND_REMOVAL",
+                "backgroundRemovalParams": { "image": BASE64_STANDARD.encode(b"png") }
+            })
+        );
+    }
+
+    #[test]
+    fn test_image_conditioning() {
+        let mut params = json!({ "text": "a house" });
+        ImageConditioning::new(b"png".to_vec(), ControlMode::Segmentation)
+            .with_control_strength(0.5)
+            .insert_into(&mut params);
+
+        assert_eq!(
+            params,
+            json!({
+                "text": "a house",
+                "conditionImage": BASE64_STANDARD.encode(b"png"),
+                "controlMode": "SEGMENTATION",
+                "controlStrength": 0.5
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_response() {
+        let invalid = TextToImageResponse {
+            images: Some(vec!["not base64!".into()]),
+            error: None,
+        };
+        assert!(invalid.decoded_images().is_err());
 
-        Err(ImageGenerationError::ResponseError(
-            "Malformed response from model".to_string(),
-        ))
+        let empty = TextToImageResponse {
+            images: Some(vec![]),
+            error: None,
+        };
+        assert!(image_generation::ImageGenerationResponse::try_from(empty).is_err());
     }
 }
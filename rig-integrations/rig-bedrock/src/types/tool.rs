@@ -15,9 +15,14 @@ impl TryFrom<RigToolResultContent> for aws_bedrock::ToolResultContentBlock {
 
     fn try_from(value: RigToolResultContent) -> Result<Self, Self::Error> {
         match value.0 {
-            ToolResultContent::Text(text) => {
-                Ok(aws_bedrock::ToolResultContentBlock::Text(text.text))
-            }
+            // Rig tool results are text, so JSON objects and arrays are detected to send them
+            // as structured results; incoming JSON results are turned back into text below
+            ToolResultContent::Text(text) => match serde_json::from_str::<Value>(&text.text) {
+                Ok(json @ (Value::Object(_) | Value::Array(_))) => Ok(
+                    aws_bedrock::ToolResultContentBlock::Json(AwsDocument::from(json).0),
+                ),
+                _ => Ok(aws_bedrock::ToolResultContentBlock::Text(text.text)),
+            },
             ToolResultContent::Image(image) => {
                 let image = RigImage(image).try_into()?;
                 Ok(aws_bedrock::ToolResultContentBlock::Image(image))
@@ -60,7 +65,7 @@ mod tests {
         message::{DocumentSourceKind, Image, ImageMediaType, Text, ToolResultContent},
     };
 
-    use crate::types::tool::RigToolResultContent;
+    use crate::types::{json::AwsDocument, tool::RigToolResultContent};
 
     #[test]
     fn rig_tool_text_to_aws_tool() {
@@ -73,6 +78,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rig_tool_json_to_aws_tool() {
+        let tool = RigToolResultContent(ToolResultContent::Text(Text {
+            text: r#"{"temperature":21.5,"unit":"celsius"}"#.into(),
+        }));
+        let aws_tool: aws_bedrock::ToolResultContentBlock = tool.try_into().unwrap();
+        let json: serde_json::Value = AwsDocument(aws_tool.as_json().unwrap().clone()).into();
+        assert_eq!(
+            json,
+            serde_json::json!({ "temperature": 21.5, "unit": "celsius" })
+        );
+
+        // Round trips back into the same JSON text
+        let tool: RigToolResultContent =
+            aws_bedrock::ToolResultContentBlock::Json(AwsDocument::from(json.clone()).0)
+                .try_into()
+                .unwrap();
+        let ToolResultContent::Text(text) = tool.0 else {
+            panic!("expected a text tool result");
+        };
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&text.text).unwrap(),
+            json
+        );
+    }
+
     #[test]
     fn rig_tool_image_to_aws_tool() {
         let encoded_str = BASE64_STANDARD.encode("img_data");
@@ -29,7 +29,10 @@ impl TryFrom<aws_bedrock::ContentBlock> for RigUserContent {
                     .collect::<Vec<ToolResultContent>>();
 
                 let tool_results = OneOrMany::many(tool_result_contents).map_err(|_| {
-                    CompletionError::ProviderError("ToolResult returned invalid response".into())
+                    CompletionError::ProviderError(format!(
+                        "Tool result {} has no content",
+                        tool_result.tool_use_id
+                    ))
                 })?;
                 Ok(RigUserContent(UserContent::ToolResult(ToolResult {
                     id: tool_result.tool_use_id,
@@ -0,0 +1,125 @@
+//! Accumulated token usage and estimated cost across many calls, e.g. a multi-turn agent
+//! session.
+//!
+//! A tracker attached to a [`Client`](crate::client::Client) records the calls of every model
+//! created from it afterwards, and one attached to a model records that model's calls:
+//!
+//! ```rust,ignore
+//! let usage = UsageTracker::new();
+//! let client = Client::from_env().with_usage_tracker(usage.clone());
+//!
+//! let agent = client.agent(AMAZON_NOVA_LITE).build();
+//! agent.prompt("Plan a trip to Lisbon").multi_turn(5).await?;
+//!
+//! let summary = usage.summary();
+//! println!("{} requests, ${:.4}", summary.requests, summary.estimated_cost.total());
+//! usage.reset();
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use crate::pricing::CostEstimate;
+
+/// Usage accumulated by a [`UsageTracker`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UsageSummary {
+    /// Number of successful calls.
+    pub requests: u64,
+    /// Input tokens, excluding the ones read from or written to the prompt cache.
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    pub cache_write_input_tokens: u64,
+    /// Estimated cost of the calls to models with a known pricing, see [`crate::pricing`].
+    pub estimated_cost: CostEstimate,
+}
+
+impl UsageSummary {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_read_input_tokens
+            + self.cache_write_input_tokens
+    }
+}
+
+/// Accumulates the usage of the calls of the clients and models it is attached to. Clones share
+/// the same totals.
+#[derive(Clone, Debug, Default)]
+pub struct UsageTracker {
+    summary: Arc<Mutex<UsageSummary>>,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The usage accumulated since the tracker was created or last reset.
+    pub fn summary(&self) -> UsageSummary {
+        *self.lock()
+    }
+
+    pub fn reset(&self) {
+        *self.lock() = UsageSummary::default();
+    }
+
+    /// The accumulated usage, resetting it at the same time so no call is missed in between.
+    pub fn take(&self) -> UsageSummary {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub(crate) fn record(
+        &self,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_write_tokens: u64,
+        estimated_cost: Option<CostEstimate>,
+    ) {
+        let mut summary = self.lock();
+        summary.requests += 1;
+        summary.input_tokens += input_tokens;
+        summary.output_tokens += output_tokens;
+        summary.cache_read_input_tokens += cache_read_tokens;
+        summary.cache_write_input_tokens += cache_write_tokens;
+        summary.estimated_cost += estimated_cost.unwrap_or_default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, UsageSummary> {
+        self.summary.lock().expect("usage tracker lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulates_and_resets() {
+        let tracker = UsageTracker::new();
+        let shared = tracker.clone();
+
+        tracker.record(100, 20, 0, 0, None);
+        shared.record(
+            50,
+            10,
+            1_000,
+            0,
+            Some(CostEstimate {
+                input: 0.01,
+                output: 0.02,
+            }),
+        );
+
+        let summary = tracker.summary();
+        assert_eq!(summary.requests, 2);
+        assert_eq!(summary.input_tokens, 150);
+        assert_eq!(summary.output_tokens, 30);
+        assert_eq!(summary.total_tokens(), 1_180);
+        assert!((summary.estimated_cost.total() - 0.03).abs() < 1e-12);
+
+        assert_eq!(shared.take(), summary);
+        assert_eq!(tracker.summary(), UsageSummary::default());
+    }
+}